@@ -1,2 +1,4 @@
 pub mod store;
-pub use store::{atomic_write, encode_filename, BlobMetadata, BlobStore};
+pub use store::{
+    atomic_write, encode_filename, BlobMetadata, BlobStats, BlobStore, BlobWriter, GcReport,
+};
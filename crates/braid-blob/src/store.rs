@@ -29,6 +29,30 @@ pub struct BlobMetadata {
     pub size: Option<u64>,
 }
 
+/// Storage summary returned by [`BlobStore::stats`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobStats {
+    /// Distinct content hashes stored.
+    pub unique_blobs: usize,
+    /// Bytes all references would take if each held its own copy.
+    pub logical_bytes: u64,
+    /// Bytes actually stored on disk.
+    pub physical_bytes: u64,
+    /// `logical_bytes - physical_bytes`.
+    pub bytes_saved: u64,
+}
+
+/// Result of a [`BlobStore::gc`] pass.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GcReport {
+    /// Metadata rows deleted.
+    pub rows_removed: usize,
+    /// Blob files deleted.
+    pub files_removed: usize,
+    /// Bytes freed on disk.
+    pub bytes_reclaimed: u64,
+}
+
 #[derive(Clone, Debug)]
 #[cfg(feature = "native")]
 pub struct BlobStore {
@@ -58,6 +82,27 @@ impl BlobStore {
             [],
         )
         .map_err(|e| BraidError::Fs(e.to_string()))?;
+        let has_refs = conn.prepare("SELECT hash FROM blob_refs LIMIT 0").is_ok();
+        if !has_refs {
+            conn.execute(
+                "CREATE TABLE blob_refs (
+                    hash TEXT NOT NULL,
+                    url TEXT NOT NULL,
+                    PRIMARY KEY (hash, url)
+                )",
+                [],
+            )
+            .map_err(|e| BraidError::Fs(e.to_string()))?;
+            // Blobs stored before reference tracking are kept alive by their
+            // key, content-addressed ones included: nothing recorded who uses them
+            conn.execute(
+                "INSERT OR IGNORE INTO blob_refs (hash, url)
+                 SELECT json_extract(value, '$.content_hash'), key FROM meta
+                 WHERE json_extract(value, '$.content_hash') IS NOT NULL",
+                [],
+            )
+            .map_err(|e| BraidError::Fs(e.to_string()))?;
+        }
 
         Ok(Self {
             db_path,
@@ -172,14 +217,17 @@ impl BlobStore {
         let temp_folder = self.db_path.join("tmp");
         atomic_write(&file_path, &data, &temp_folder).await?;
 
-        // Update metadata
-        {
+        self.write_meta(&new_meta).await?;
+
+        // A blob stored under a URL is referenced by that URL; content-addressed
+        // blobs only live as long as something else references them.
+        if key != content_hash {
             let conn = self.meta_conn.lock().await;
-            let val_str =
-                serde_json::to_string(&new_meta).map_err(|e| BraidError::Config(e.to_string()))?;
+            conn.execute("DELETE FROM blob_refs WHERE url = ?", params![key])
+                .map_err(|e| BraidError::Fs(e.to_string()))?;
             conn.execute(
-                "INSERT OR REPLACE INTO meta (key, value) VALUES (?, ?)",
-                params![key, val_str],
+                "INSERT OR IGNORE INTO blob_refs (hash, url) VALUES (?, ?)",
+                params![content_hash, key],
             )
             .map_err(|e| BraidError::Fs(e.to_string()))?;
         }
@@ -187,11 +235,55 @@ impl BlobStore {
         Ok(version)
     }
 
+    async fn write_meta(&self, meta: &BlobMetadata) -> Result<()> {
+        let conn = self.meta_conn.lock().await;
+        let val_str = serde_json::to_string(meta).map_err(|e| BraidError::Config(e.to_string()))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?, ?)",
+            params![meta.key, val_str],
+        )
+        .map_err(|e| BraidError::Fs(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Start a streaming, content-addressed write.
+    ///
+    /// Chunks are hashed and spooled to a temp file as they arrive, so large
+    /// blobs never have to fit in memory.
+    pub async fn writer(&self) -> Result<BlobWriter> {
+        let temp_folder = self.db_path.join("tmp");
+        fs::create_dir_all(&temp_folder)
+            .await
+            .map_err(BraidError::Io)?;
+        let temp_path = temp_folder.join(format!("tmp_{}", uuid::Uuid::new_v4()));
+        let file = fs::File::create(&temp_path).await.map_err(BraidError::Io)?;
+
+        Ok(BlobWriter {
+            store: self.clone(),
+            file: Some(file),
+            temp_path,
+            hasher: Sha256::new(),
+            size: 0,
+        })
+    }
+
+    /// Store `data` under its content hash, recording its MIME type.
+    ///
+    /// Returns the hash, which is also the blob's key and version.
+    pub async fn put_with_type(&self, data: Bytes, content_type: Option<String>) -> Result<String> {
+        let hash = format!("{:x}", Sha256::digest(&data));
+        let version = vec![Version::from(hash.clone())];
+        self.put(&hash, data, version, vec![], content_type).await?;
+        Ok(hash)
+    }
+
     pub async fn delete(&self, key: &str) -> Result<()> {
         {
             let conn = self.meta_conn.lock().await;
             conn.execute("DELETE FROM meta WHERE key = ?", params![key])
                 .map_err(|e| BraidError::Fs(e.to_string()))?;
+            conn.execute("DELETE FROM blob_refs WHERE url = ?", params![key])
+                .map_err(|e| BraidError::Fs(e.to_string()))?;
         }
 
         let file_path = self.get_file_path(key);
@@ -207,11 +299,213 @@ impl BlobStore {
         Ok(())
     }
 
+    /// Record that `url` references the blob with content hash `hash`.
+    pub async fn add_ref(&self, hash: &str, url: &str) -> Result<()> {
+        let conn = self.meta_conn.lock().await;
+        conn.execute(
+            "INSERT OR IGNORE INTO blob_refs (hash, url) VALUES (?, ?)",
+            params![hash, url],
+        )
+        .map_err(|e| BraidError::Fs(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Drop the reference from `url` to `hash`.
+    pub async fn remove_ref(&self, hash: &str, url: &str) -> Result<()> {
+        let conn = self.meta_conn.lock().await;
+        conn.execute(
+            "DELETE FROM blob_refs WHERE hash = ? AND url = ?",
+            params![hash, url],
+        )
+        .map_err(|e| BraidError::Fs(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Drop every reference held by `url`, e.g. when it stops syncing.
+    pub async fn release(&self, url: &str) -> Result<()> {
+        let conn = self.meta_conn.lock().await;
+        conn.execute("DELETE FROM blob_refs WHERE url = ?", params![url])
+            .map_err(|e| BraidError::Fs(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Number of URLs referencing `hash`.
+    pub async fn ref_count(&self, hash: &str) -> Result<usize> {
+        let conn = self.meta_conn.lock().await;
+        conn.query_row(
+            "SELECT COUNT(*) FROM blob_refs WHERE hash = ?",
+            params![hash],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|n| n as usize)
+        .map_err(|e| BraidError::Fs(e.to_string()))
+    }
+
+    /// Summarize storage use and how much deduplication saves.
+    pub async fn stats(&self) -> Result<BlobStats> {
+        let conn = self.meta_conn.lock().await;
+        let (unique_blobs, physical_bytes) = conn
+            .query_row(
+                "SELECT COUNT(DISTINCT json_extract(value, '$.content_hash')),
+                        COALESCE(SUM(json_extract(value, '$.size')), 0)
+                 FROM meta",
+                [],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+            )
+            .map_err(|e| BraidError::Fs(e.to_string()))?;
+        // Each reference counts the blob's full size once
+        let logical_bytes = conn
+            .query_row(
+                "SELECT COALESCE(SUM(size), 0) FROM blob_refs r JOIN (
+                     SELECT json_extract(value, '$.content_hash') AS hash,
+                            MAX(json_extract(value, '$.size')) AS size
+                     FROM meta GROUP BY hash
+                 ) m ON m.hash = r.hash",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .map_err(|e| BraidError::Fs(e.to_string()))?;
+
+        Ok(BlobStats {
+            unique_blobs: unique_blobs as usize,
+            logical_bytes: logical_bytes as u64,
+            physical_bytes: physical_bytes as u64,
+            bytes_saved: (logical_bytes - physical_bytes).max(0) as u64,
+        })
+    }
+
+    /// Delete every blob that no URL references.
+    ///
+    /// Blobs stored without a content hash are kept, since there's no way to
+    /// tell whether they're still in use.
+    pub async fn gc(&self) -> Result<GcReport> {
+        let conn = self.meta_conn.lock().await;
+        let unreferenced = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT value FROM meta WHERE NOT EXISTS (
+                        SELECT 1 FROM blob_refs
+                        WHERE blob_refs.hash = json_extract(meta.value, '$.content_hash')
+                    )",
+                )
+                .map_err(|e| BraidError::Fs(e.to_string()))?;
+            let rows = stmt
+                .query_map([], |row| row.get::<_, String>(0))
+                .map_err(|e| BraidError::Fs(e.to_string()))?;
+
+            let mut unreferenced = Vec::new();
+            for value in rows {
+                let value = value.map_err(|e| BraidError::Fs(e.to_string()))?;
+                let Ok(meta) = serde_json::from_str::<BlobMetadata>(&value) else {
+                    continue;
+                };
+                if meta.content_hash.is_some() {
+                    unreferenced.push(meta.key);
+                }
+            }
+            unreferenced
+        };
+
+        let mut report = GcReport::default();
+        for key in unreferenced {
+            conn.execute("DELETE FROM meta WHERE key = ?", params![key])
+                .map_err(|e| BraidError::Fs(e.to_string()))?;
+            report.rows_removed += 1;
+
+            let file_path = self.get_file_path(&key);
+            if let Ok(metadata) = fs::metadata(&file_path).await {
+                fs::remove_file(&file_path).await.map_err(BraidError::Io)?;
+                report.files_removed += 1;
+                report.bytes_reclaimed += metadata.len();
+            }
+        }
+
+        Ok(report)
+    }
+
     fn get_file_path(&self, key: &str) -> PathBuf {
         self.db_path.join(encode_filename(key))
     }
 }
 
+/// Incremental writer returned by [`BlobStore::writer`].
+///
+/// Dropping the writer without calling [`BlobWriter::finish`] discards the
+/// partial upload.
+#[cfg(feature = "native")]
+pub struct BlobWriter {
+    store: BlobStore,
+    file: Option<fs::File>,
+    temp_path: PathBuf,
+    hasher: Sha256,
+    size: u64,
+}
+
+#[cfg(feature = "native")]
+impl BlobWriter {
+    pub async fn write_chunk(&mut self, chunk: &[u8]) -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let file = self
+            .file
+            .as_mut()
+            .ok_or_else(|| BraidError::Fs("Blob writer already finished".to_string()))?;
+        file.write_all(chunk).await.map_err(BraidError::Io)?;
+        self.hasher.update(chunk);
+        self.size += chunk.len() as u64;
+        Ok(())
+    }
+
+    /// Bytes written so far.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Flush the blob into the store under its content hash.
+    ///
+    /// Returns the hash, which is also the blob's key and version.
+    pub async fn finish(mut self, content_type: Option<String>) -> Result<String> {
+        if let Some(file) = self.file.take() {
+            file.sync_all().await.map_err(BraidError::Io)?;
+        }
+        let hash = format!("{:x}", self.hasher.clone().finalize());
+
+        // Identical content is already stored
+        if self.store.get_meta(&hash).await?.is_some() {
+            let _ = fs::remove_file(&self.temp_path).await;
+            return Ok(hash);
+        }
+
+        let file_path = self.store.get_file_path(&hash);
+        fs::rename(&self.temp_path, &file_path)
+            .await
+            .map_err(BraidError::Io)?;
+
+        let version = vec![Version::from(hash.clone())];
+        self.store
+            .write_meta(&BlobMetadata {
+                key: hash.clone(),
+                version,
+                content_type,
+                parents: vec![],
+                content_hash: Some(hash.clone()),
+                size: Some(self.size),
+            })
+            .await?;
+        Ok(hash)
+    }
+}
+
+#[cfg(feature = "native")]
+impl Drop for BlobWriter {
+    fn drop(&mut self) {
+        // Close the handle first so the temp file can be removed on Windows
+        if self.file.take().is_some() {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
 #[cfg(feature = "native")]
 #[async_trait]
 impl BraidStorage for BlobStore {
@@ -427,3 +721,197 @@ pub async fn atomic_write(
     let metadata = std::fs::metadata(dest).map_err(|e| BraidError::Io(e))?;
     Ok(metadata)
 }
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+
+    fn hash_of(data: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(data))
+    }
+
+    #[tokio::test]
+    async fn test_put_with_type_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::new(dir.path().join("blobs"), dir.path().join("meta.sqlite"))
+            .await
+            .unwrap();
+
+        let png = Bytes::from_static(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR");
+        let hash = store
+            .put_with_type(png.clone(), Some("image/png".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(hash, hash_of(&png));
+
+        let (data, meta) = store.get(&hash).await.unwrap().unwrap();
+        assert_eq!(data, png);
+        assert_eq!(meta.content_type.as_deref(), Some("image/png"));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_write_matches_one_shot_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::new(dir.path().join("blobs"), dir.path().join("meta.sqlite"))
+            .await
+            .unwrap();
+
+        let data: Vec<u8> = (0..10 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let mut writer = store.writer().await.unwrap();
+        for chunk in data.chunks(64 * 1024 + 7) {
+            writer.write_chunk(chunk).await.unwrap();
+        }
+        assert_eq!(writer.size(), data.len() as u64);
+        let hash = writer.finish(None).await.unwrap();
+
+        assert_eq!(hash, hash_of(&data));
+        let (stored, meta) = store.get(&hash).await.unwrap().unwrap();
+        assert_eq!(stored.len(), data.len());
+        assert_eq!(meta.size, Some(data.len() as u64));
+        assert!(std::fs::read_dir(dir.path().join("blobs/tmp"))
+            .unwrap()
+            .next()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_gc_removes_unreferenced_blobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::new(dir.path().join("blobs"), dir.path().join("meta.sqlite"))
+            .await
+            .unwrap();
+
+        let mut hashes = Vec::new();
+        for data in ["alpha", "bravo", "charlie"] {
+            hashes.push(store.put_with_type(Bytes::from(data), None).await.unwrap());
+        }
+        store
+            .add_ref(&hashes[1], "https://example.org/doc")
+            .await
+            .unwrap();
+
+        let report = store.gc().await.unwrap();
+
+        assert_eq!(report.rows_removed, 2);
+        assert_eq!(report.files_removed, 2);
+        assert_eq!(
+            report.bytes_reclaimed,
+            ("alpha".len() + "charlie".len()) as u64
+        );
+        assert!(store.get(&hashes[0]).await.unwrap().is_none());
+        assert!(store.get(&hashes[2]).await.unwrap().is_none());
+        assert!(!store.get_file_path(&hashes[0]).exists());
+        assert_eq!(
+            store.get(&hashes[1]).await.unwrap().unwrap().0,
+            Bytes::from("bravo")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ref_counting_across_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::new(dir.path().join("blobs"), dir.path().join("meta.sqlite"))
+            .await
+            .unwrap();
+
+        let hash = store
+            .put_with_type(Bytes::from("shared image"), Some("image/png".to_string()))
+            .await
+            .unwrap();
+        store.add_ref(&hash, "https://example.org/a").await.unwrap();
+        store.add_ref(&hash, "https://example.org/b").await.unwrap();
+        // Adding the same reference twice doesn't double count
+        store.add_ref(&hash, "https://example.org/b").await.unwrap();
+        assert_eq!(store.ref_count(&hash).await.unwrap(), 2);
+
+        let stats = store.stats().await.unwrap();
+        assert_eq!(stats.unique_blobs, 1);
+        assert_eq!(stats.physical_bytes, 12);
+        assert_eq!(stats.logical_bytes, 24);
+        assert_eq!(stats.bytes_saved, 12);
+
+        store
+            .remove_ref(&hash, "https://example.org/a")
+            .await
+            .unwrap();
+        assert_eq!(store.gc().await.unwrap(), GcReport::default());
+        assert!(store.get(&hash).await.unwrap().is_some());
+
+        store.release("https://example.org/b").await.unwrap();
+        assert_eq!(store.ref_count(&hash).await.unwrap(), 0);
+        assert_eq!(store.gc().await.unwrap().rows_removed, 1);
+        assert!(store.get(&hash).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_keyed_blobs_reference_themselves() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::new(dir.path().join("blobs"), dir.path().join("meta.sqlite"))
+            .await
+            .unwrap();
+
+        store
+            .put(
+                "https://example.org/file.bin",
+                Bytes::from("v1"),
+                vec![Version::new("a-1")],
+                vec![],
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(store.gc().await.unwrap(), GcReport::default());
+
+        // Replacing the content moves the reference to the new hash
+        store
+            .put(
+                "https://example.org/file.bin",
+                Bytes::from("v2"),
+                vec![Version::new("a-2")],
+                vec![],
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(store.ref_count(&hash_of(b"v1")).await.unwrap(), 0);
+        assert_eq!(store.ref_count(&hash_of(b"v2")).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_blobs_from_before_ref_tracking_are_seeded() {
+        let dir = tempfile::tempdir().unwrap();
+        let meta_db = dir.path().join("meta.sqlite");
+        let store = BlobStore::new(dir.path().join("blobs"), meta_db.clone())
+            .await
+            .unwrap();
+        let hash = store
+            .put_with_type(Bytes::from("attachment"), None)
+            .await
+            .unwrap();
+        store
+            .put(
+                "https://example.org/file.bin",
+                Bytes::from("keyed"),
+                vec![Version::new("a-1")],
+                vec![],
+                None,
+            )
+            .await
+            .unwrap();
+        drop(store);
+
+        // An older database has no reference table
+        Connection::open(&meta_db)
+            .unwrap()
+            .execute("DROP TABLE blob_refs", [])
+            .unwrap();
+
+        let store = BlobStore::new(dir.path().join("blobs"), meta_db)
+            .await
+            .unwrap();
+        assert_eq!(store.ref_count(&hash).await.unwrap(), 1);
+        assert_eq!(store.ref_count(&hash_of(b"keyed")).await.unwrap(), 1);
+        assert_eq!(store.gc().await.unwrap(), GcReport::default());
+        assert!(store.get(&hash).await.unwrap().is_some());
+    }
+}
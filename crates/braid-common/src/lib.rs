@@ -9,15 +9,94 @@
 //! ├── braid.org/       # Synced wiki pages
 //! └── .braidfs/        # Internal blob storage (managed by daemon)
 //! ```
+//!
+//! Subdirectory names can be overridden with a `layout` section in `config.json`
+//! (see [`DirLayout`]).
 
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
 use tracing::{error, info, warn};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 struct BraidConfig {
     braid_root: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    layout: Option<DirLayout>,
+}
+
+/// Names of the directories under `braid_root()`.
+///
+/// Each entry is joined onto its parent directory, so an absolute path (e.g.
+/// `blobs: "/mnt/storage/blobs"`) relocates that directory entirely.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct DirLayout {
+    pub local: String,
+    pub peers: String,
+    pub ai: String,
+    /// Relative to `ai`
+    pub ai_context: String,
+    pub braid_org: String,
+    pub braidfs: String,
+    /// Relative to `braidfs`
+    pub blobs: String,
+    pub sync: String,
+}
+
+impl Default for DirLayout {
+    fn default() -> Self {
+        Self {
+            local: "local".to_string(),
+            peers: "peers".to_string(),
+            ai: "ai".to_string(),
+            ai_context: "context".to_string(),
+            braid_org: "braid.org".to_string(),
+            braidfs: ".braidfs".to_string(),
+            blobs: "blobs".to_string(),
+            sync: "sync".to_string(),
+        }
+    }
+}
+
+static DIR_LAYOUT: OnceLock<DirLayout> = OnceLock::new();
+
+/// Minimum free space required on the Braid volume (50 MiB)
+pub const MIN_FREE_SPACE: u64 = 50 * 1024 * 1024;
+
+/// `Merge-Type` for blob uploads. Blobs are immutable and content-addressed,
+/// so the arbitrary-writer-wins type used for binary sync is the only fit.
+pub const BLOB_MERGE_TYPE: &str = "aww";
+
+/// Errors from [`init_structure`].
+#[derive(Debug, thiserror::Error)]
+pub enum InitError {
+    #[error("Storage folder {0:?} is not writable")]
+    ReadOnly(PathBuf),
+    #[error(
+        "Not enough free disk space: {} MB available, at least {} MB required",
+        .0 / (1024 * 1024),
+        MIN_FREE_SPACE / (1024 * 1024)
+    )]
+    LowSpace(u64),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// Get the active directory layout, loading it from `config.json` on first use.
+pub fn dir_layout() -> &'static DirLayout {
+    DIR_LAYOUT.get_or_init(|| load_config().and_then(|c| c.layout).unwrap_or_default())
+}
+
+/// Override the directory layout for this process.
+///
+/// Must be called before any directory helper is used; fails if a layout is
+/// already active.
+pub fn set_dir_layout(layout: DirLayout) -> anyhow::Result<()> {
+    DIR_LAYOUT
+        .set(layout)
+        .map_err(|_| anyhow::anyhow!("Directory layout already initialized"))
 }
 
 /// Get the global configuration path
@@ -25,8 +104,8 @@ fn get_config_path() -> Option<PathBuf> {
     dirs::config_dir().map(|d| d.join("local_link").join("config.json"))
 }
 
-/// Load the persistent root from config file
-pub fn load_persistent_root() -> Option<PathBuf> {
+/// Load the config file, if present and valid
+fn load_config() -> Option<BraidConfig> {
     let path = get_config_path()?;
     if !path.exists() {
         return None;
@@ -34,7 +113,7 @@ pub fn load_persistent_root() -> Option<PathBuf> {
 
     match fs::read_to_string(&path) {
         Ok(content) => match serde_json::from_str::<BraidConfig>(&content) {
-            Ok(config) => config.braid_root,
+            Ok(config) => Some(config),
             Err(e) => {
                 warn!("Failed to parse config file at {:?}: {}", path, e);
                 None
@@ -47,6 +126,11 @@ pub fn load_persistent_root() -> Option<PathBuf> {
     }
 }
 
+/// Load the persistent root from config file
+pub fn load_persistent_root() -> Option<PathBuf> {
+    load_config()?.braid_root
+}
+
 /// Save a path as the persistent Braid root
 pub fn save_persistent_root(root: PathBuf) -> anyhow::Result<()> {
     let path = get_config_path().ok_or_else(|| anyhow::anyhow!("Could not determine config dir"))?;
@@ -54,9 +138,9 @@ pub fn save_persistent_root(root: PathBuf) -> anyhow::Result<()> {
         fs::create_dir_all(parent)?;
     }
 
-    let config = BraidConfig {
-        braid_root: Some(root),
-    };
+    // Keep any layout overrides already in the file
+    let mut config = load_config().unwrap_or_default();
+    config.braid_root = Some(root);
     let json = serde_json::to_string_pretty(&config)?;
     fs::write(path, json)?;
     Ok(())
@@ -88,37 +172,37 @@ pub fn set_braid_root(path: PathBuf) {
 
 /// Local data directory (SQLite, config)
 pub fn local_dir() -> PathBuf {
-    braid_root().join("local")
+    braid_root().join(&dir_layout().local)
 }
 
 /// Peer chat exports directory
 pub fn peers_dir() -> PathBuf {
-    braid_root().join("peers")
+    braid_root().join(&dir_layout().peers)
 }
 
 /// AI chat exports directory
 pub fn ai_dir() -> PathBuf {
-    braid_root().join("ai")
+    braid_root().join(&dir_layout().ai)
 }
 
 /// AI context directory for supplemental files
 pub fn ai_context_dir() -> PathBuf {
-    ai_dir().join("context")
+    ai_dir().join(&dir_layout().ai_context)
 }
 
 /// Braid.org synced wiki pages directory
 pub fn braid_org_dir() -> PathBuf {
-    braid_root().join("braid.org")
+    braid_root().join(&dir_layout().braid_org)
 }
 
 /// BraidFS internal blob storage directory
 pub fn braidfs_dir() -> PathBuf {
-    braid_root().join(".braidfs")
+    braid_root().join(&dir_layout().braidfs)
 }
 
 /// Blob storage subdirectory
 pub fn blobs_dir() -> PathBuf {
-    braidfs_dir().join("blobs")
+    braidfs_dir().join(&dir_layout().blobs)
 }
 
 /// Blob metadata database path
@@ -131,9 +215,84 @@ pub fn db_path() -> PathBuf {
     local_dir().join("xfmail.db")
 }
 
+/// SQLite connection URL for a database file
+///
+/// Every module that opens a sqlite database by URL should go through this so
+/// they agree on the same file. On Windows backslashes become `/`; anything
+/// outside a small safe set (spaces, `?`, `#`, `%`, non-ASCII, and backslashes
+/// elsewhere) is percent-encoded, which sqlx decodes back to the original path.
+pub fn sqlite_url(path: &Path) -> String {
+    encode_sqlite_path(&path.to_string_lossy(), cfg!(windows))
+}
+
+fn encode_sqlite_path(path: &str, backslash_is_separator: bool) -> String {
+    let mut url = String::from("sqlite://");
+    for byte in path.bytes() {
+        match byte {
+            b'\\' if backslash_is_separator => url.push('/'),
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b':' | b'.' | b'-' | b'_' | b'~' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url
+}
+
 /// Sync directory (for filesystem watcher)
 pub fn sync_dir() -> PathBuf {
-    braid_root().join("sync")
+    braid_root().join(&dir_layout().sync)
+}
+
+/// Join a user-supplied relative path onto `root` without escaping it.
+///
+/// Rejects absolute paths and `..` components that would climb above `root`,
+/// then resolves symlinks on the deepest existing ancestor to make sure the
+/// result still lives under the canonical root. The target itself need not exist.
+pub fn safe_join(root: &Path, rel: &str) -> anyhow::Result<PathBuf> {
+    let rel_path = Path::new(rel);
+    if rel_path.has_root() || rel.starts_with('/') || rel.starts_with('\\') {
+        anyhow::bail!("Absolute paths are not allowed: {}", rel);
+    }
+
+    let mut normalized = PathBuf::new();
+    for component in rel_path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    anyhow::bail!("Path escapes root: {}", rel);
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => {
+                anyhow::bail!("Absolute paths are not allowed: {}", rel);
+            }
+        }
+    }
+
+    let canonical_root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let joined = canonical_root.join(&normalized);
+
+    // Canonicalize the deepest ancestor that exists so symlinks can't escape
+    let mut existing = joined.as_path();
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => break,
+        }
+    }
+    let mut resolved = fs::canonicalize(existing).unwrap_or_else(|_| existing.to_path_buf());
+    resolved.extend(rest.iter().rev());
+
+    if !resolved.starts_with(&canonical_root) {
+        anyhow::bail!("Path escapes root: {}", rel);
+    }
+    Ok(resolved)
 }
 
 /// Ensure a single directory exists
@@ -145,22 +304,48 @@ pub fn ensure_dir(path: &PathBuf) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Check that `root` is writable and has enough free space.
+fn preflight(root: &Path) -> Result<(), InitError> {
+    let probe = root.join(format!(".braid_write_test_{}", uuid::Uuid::new_v4()));
+    if let Err(e) = fs::write(&probe, b"ok") {
+        warn!("Write probe failed in {:?}: {}", root, e);
+        return Err(InitError::ReadOnly(root.to_path_buf()));
+    }
+    let _ = fs::remove_file(&probe);
+
+    match fs2::available_space(root) {
+        Ok(free) if free < MIN_FREE_SPACE => return Err(InitError::LowSpace(free)),
+        Ok(_) => {}
+        Err(e) => warn!("Could not query free space for {:?}: {}", root, e),
+    }
+    Ok(())
+}
+
 /// Initialize the complete directory structure
 /// Call this once at app startup before any other operations
-pub fn init_structure() -> anyhow::Result<PathBuf> {
-    let root = braid_root();
+pub fn init_structure() -> Result<PathBuf, InitError> {
+    init_structure_at(&braid_root())
+}
+
+/// Initialize the directory structure under an explicit root.
+pub fn init_structure_at(root: &Path) -> Result<PathBuf, InitError> {
+    let root = root.to_path_buf();
+    let layout = dir_layout();
 
-    // Ensure root exists first
+    // Ensure root exists first, then make sure we can actually use it
     ensure_dir(&root)?;
+    preflight(&root)?;
 
     // Create all subdirectories
-    ensure_dir(&local_dir())?;
-    ensure_dir(&peers_dir())?;
-    ensure_dir(&ai_dir())?;
-    ensure_dir(&ai_context_dir())?;
-    ensure_dir(&braid_org_dir())?;
-    ensure_dir(&braidfs_dir())?;
-    ensure_dir(&blobs_dir())?;
+    let ai = root.join(&layout.ai);
+    let braidfs = root.join(&layout.braidfs);
+    ensure_dir(&root.join(&layout.local))?;
+    ensure_dir(&root.join(&layout.peers))?;
+    ensure_dir(&ai)?;
+    ensure_dir(&ai.join(&layout.ai_context))?;
+    ensure_dir(&root.join(&layout.braid_org))?;
+    ensure_dir(&braidfs)?;
+    ensure_dir(&braidfs.join(&layout.blobs))?;
 
     // Canonicalize for absolute path
     let canonical = std::fs::canonicalize(&root).unwrap_or_else(|_| root.clone());
@@ -197,35 +382,96 @@ pub fn chat_export_path(conversation_id: &str, participants: &[String]) -> PathB
     chat_export_dir(participants).join(format!("{}.md", conversation_id))
 }
 
+/// Outcome of [`migrate_legacy_paths`].
+#[derive(Debug, Default, Clone)]
+pub struct MigrationSummary {
+    /// Files moved to their new location (source paths)
+    pub moved: Vec<PathBuf>,
+    /// Files left in place because the destination already exists
+    pub skipped: Vec<PathBuf>,
+    /// Files that could not be moved, with the error
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Recursively move the contents of `src` into `dst`.
+///
+/// Existing destination files are never overwritten; the source copy is left
+/// in place and recorded as skipped. Source directories are removed once empty.
+fn merge_dir(src: &Path, dst: &Path, summary: &mut MigrationSummary) {
+    if let Err(e) = fs::create_dir_all(dst) {
+        summary.failed.push((src.to_path_buf(), e.to_string()));
+        return;
+    }
+    let entries = match fs::read_dir(src) {
+        Ok(entries) => entries,
+        Err(e) => {
+            summary.failed.push((src.to_path_buf(), e.to_string()));
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let from = entry.path();
+        let to = dst.join(entry.file_name());
+        if from.is_dir() {
+            merge_dir(&from, &to, summary);
+        } else if to.exists() {
+            warn!("Not migrating {:?}: {:?} already exists", from, to);
+            summary.skipped.push(from);
+        } else {
+            match fs::rename(&from, &to) {
+                Ok(()) => summary.moved.push(from),
+                Err(e) => {
+                    error!("Failed to migrate {:?}: {}", from, e);
+                    summary.failed.push((from, e.to_string()));
+                }
+            }
+        }
+    }
+
+    // Only succeeds if everything was moved out
+    let _ = fs::remove_dir(src);
+}
+
 /// Legacy path migration: Move old data to new locations if present
 /// This can be called optionally during startup to migrate old structures
-pub fn migrate_legacy_paths() -> anyhow::Result<()> {
-    let root = braid_root();
+pub fn migrate_legacy_paths() -> anyhow::Result<MigrationSummary> {
+    migrate_legacy_paths_in(&braid_root())
+}
+
+/// Migrate legacy directories under an explicit root.
+///
+/// Directories are merged into their new location, so a partially migrated
+/// tree from an earlier run can be completed safely.
+pub fn migrate_legacy_paths_in(root: &Path) -> anyhow::Result<MigrationSummary> {
+    let layout = dir_layout();
+    let mut summary = MigrationSummary::default();
 
     // Migrate: Messages/AI -> ai/
     let old_ai = root.join("Messages").join("AI");
     if old_ai.exists() {
         info!("Migrating legacy AI chats from {:?}", old_ai);
-        let new_ai = ai_dir();
-        for entry in std::fs::read_dir(&old_ai)? {
-            let entry = entry?;
-            let new_path = new_ai.join(entry.file_name());
-            if let Err(e) = std::fs::rename(entry.path(), new_path) {
-                error!("Failed to migrate {:?}: {}", entry.path(), e);
-            }
-        }
-        let _ = std::fs::remove_dir(&old_ai);
-        let _ = std::fs::remove_dir(root.join("Messages"));
+        merge_dir(&old_ai, &root.join(&layout.ai), &mut summary);
+        let _ = fs::remove_dir(root.join("Messages"));
     }
 
     // Migrate: data/ -> local/
     let old_data = root.join("data");
-    if old_data.exists() && !local_dir().exists() {
-        info!("Migrating legacy data directory to local/");
-        std::fs::rename(&old_data, local_dir())?;
+    if old_data.exists() {
+        info!("Migrating legacy data directory to {}/", layout.local);
+        merge_dir(&old_data, &root.join(&layout.local), &mut summary);
     }
 
-    Ok(())
+    if !summary.skipped.is_empty() || !summary.failed.is_empty() {
+        warn!(
+            "Legacy migration incomplete: {} moved, {} skipped, {} failed",
+            summary.moved.len(),
+            summary.skipped.len(),
+            summary.failed.len()
+        );
+    }
+
+    Ok(summary)
 }
 
 #[cfg(test)]
@@ -246,6 +492,114 @@ mod tests {
             .ends_with("peers"));
     }
 
+    #[test]
+    fn test_sqlite_url_normalizes_windows_paths() {
+        let backslashes = encode_sqlite_path(r"C:\Users\Jane Doe\braid\users.sqlite", true);
+        let slashes = encode_sqlite_path("C:/Users/Jane Doe/braid/users.sqlite", true);
+        assert_eq!(backslashes, "sqlite://C:/Users/Jane%20Doe/braid/users.sqlite");
+        assert_eq!(backslashes, slashes);
+    }
+
+    #[test]
+    fn test_sqlite_url_escapes_reserved_characters() {
+        assert_eq!(
+            encode_sqlite_path("/data/a b\\c?#%/users.sqlite", false),
+            "sqlite:///data/a%20b%5Cc%3F%23%25/users.sqlite"
+        );
+        assert_eq!(encode_sqlite_path("/tmp/é.db", false), "sqlite:///tmp/%C3%A9.db");
+    }
+
+    #[test]
+    fn test_safe_join_rejects_escapes() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(safe_join(root.path(), "../etc/passwd").is_err());
+        assert!(safe_join(root.path(), "braid.org/../../secret").is_err());
+        assert!(safe_join(root.path(), "/etc/passwd").is_err());
+        assert!(safe_join(root.path(), "\\etc\\passwd").is_err());
+    }
+
+    #[test]
+    fn test_safe_join_allows_nested_paths() {
+        let root = tempfile::tempdir().unwrap();
+        let canonical = fs::canonicalize(root.path()).unwrap();
+
+        let path = safe_join(root.path(), "braid.org/sub/page.md").unwrap();
+        assert_eq!(path, canonical.join("braid.org").join("sub").join("page.md"));
+
+        // `..` that stays inside the root is fine
+        let path = safe_join(root.path(), "braid.org/sub/../page.md").unwrap();
+        assert_eq!(path, canonical.join("braid.org").join("page.md"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_safe_join_rejects_symlink_escape() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("link")).unwrap();
+        assert!(safe_join(root.path(), "link/file.md").is_err());
+    }
+
+    #[test]
+    fn test_migrate_merges_into_existing_local() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        fs::create_dir_all(root.join("data").join("nested")).unwrap();
+        fs::write(root.join("data").join("xfmail.db"), "old").unwrap();
+        fs::write(root.join("data").join("notes.txt"), "notes").unwrap();
+        fs::write(root.join("data").join("nested").join("a.md"), "a").unwrap();
+        fs::create_dir_all(root.join("local")).unwrap();
+        fs::write(root.join("local").join("xfmail.db"), "new").unwrap();
+
+        let summary = migrate_legacy_paths_in(root).unwrap();
+
+        // The conflicting file is left alone on both sides
+        assert_eq!(summary.skipped, vec![root.join("data").join("xfmail.db")]);
+        assert_eq!(fs::read_to_string(root.join("local").join("xfmail.db")).unwrap(), "new");
+        assert!(root.join("data").join("xfmail.db").exists());
+
+        // Everything else is merged in
+        assert_eq!(summary.moved.len(), 2);
+        assert!(summary.failed.is_empty());
+        assert_eq!(fs::read_to_string(root.join("local").join("notes.txt")).unwrap(), "notes");
+        assert!(root.join("local").join("nested").join("a.md").exists());
+        assert!(!root.join("data").join("nested").exists());
+    }
+
+    #[test]
+    fn test_migrate_legacy_ai() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let old_ai = root.join("Messages").join("AI");
+        fs::create_dir_all(&old_ai).unwrap();
+        fs::write(old_ai.join("chat.md"), "hi").unwrap();
+
+        let summary = migrate_legacy_paths_in(root).unwrap();
+        assert_eq!(summary.moved.len(), 1);
+        assert!(root.join("ai").join("chat.md").exists());
+        assert!(!root.join("Messages").exists());
+    }
+
+    #[test]
+    fn test_init_structure_creates_layout() {
+        let root = tempfile::tempdir().unwrap();
+        let canonical = init_structure_at(root.path()).unwrap();
+        assert!(canonical.join("local").is_dir());
+        assert!(canonical.join("ai").join("context").is_dir());
+        assert!(canonical.join(".braidfs").join("blobs").is_dir());
+    }
+
+    #[test]
+    fn test_init_structure_unwritable_root() {
+        // Nothing can be written under a file, even by privileged users
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        fs::write(&root, b"").unwrap();
+
+        let result = init_structure_at(&root);
+        assert!(matches!(result, Err(InitError::ReadOnly(_))));
+    }
+
     #[test]
     fn test_paths_are_absolute_when_canonicalized() {
         let _root = braid_root();
@@ -0,0 +1,18 @@
+//! Runs in its own process so the global layout can be set before first use.
+
+use braid_common::{braid_org_dir, braid_root, dir_layout, set_dir_layout, DirLayout};
+
+#[test]
+fn test_dir_layout_override() {
+    let layout = DirLayout {
+        braid_org: "wiki".to_string(),
+        ..DirLayout::default()
+    };
+    set_dir_layout(layout.clone()).unwrap();
+
+    assert_eq!(dir_layout(), &layout);
+    assert_eq!(braid_org_dir(), braid_root().join("wiki"));
+
+    // The layout can only be chosen once
+    assert!(set_dir_layout(DirLayout::default()).is_err());
+}
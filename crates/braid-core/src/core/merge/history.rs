@@ -0,0 +1,169 @@
+//! Revision history for text documents.
+//!
+//! Each revision keeps the splice that turned the previous content into its
+//! own, so any recorded version can be rebuilt by replaying splices through
+//! a simpleton merge. The first revision holds the whole document, which lets
+//! history start on a page that already had content.
+
+use super::merge_type::{MergePatch, MergeType};
+use super::simpleton::SimpletonMergeType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One recorded version of a document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    pub version: String,
+    pub parents: Vec<String>,
+    /// Unix time in milliseconds when the revision was recorded
+    pub timestamp: u64,
+    /// Splices from the previous revision's content, in `char` offsets
+    pub patches: Vec<MergePatch>,
+}
+
+impl Revision {
+    /// `version` as the change from `before` to `after`.
+    ///
+    /// The `first` revision of a document holds the whole of `after`.
+    pub fn new(
+        version: &str,
+        parents: Vec<String>,
+        before: &str,
+        after: &str,
+        first: bool,
+    ) -> Self {
+        let patch = if first {
+            MergePatch::new("everything", Value::String(after.to_string()))
+        } else {
+            let (start, end, text) = splice_between(before, after);
+            MergePatch::new(&format!("[{}:{}]", start, end), Value::String(text))
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        Self {
+            version: version.to_string(),
+            parents,
+            timestamp,
+            patches: vec![patch],
+        }
+    }
+}
+
+/// A revision without its patches, as listed to clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevisionInfo {
+    pub version: String,
+    pub parents: Vec<String>,
+    pub timestamp: u64,
+}
+
+/// Every recorded revision of a document, oldest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    pub revisions: Vec<Revision>,
+}
+
+impl History {
+    /// Record `version` as the change from `before` to `after`.
+    ///
+    /// Returns false without recording if `version` is already known.
+    pub fn record(
+        &mut self,
+        version: &str,
+        parents: Vec<String>,
+        before: &str,
+        after: &str,
+    ) -> bool {
+        if self.revisions.iter().any(|r| r.version == version) {
+            return false;
+        }
+        let first = self.revisions.is_empty();
+        self.revisions
+            .push(Revision::new(version, parents, before, after, first));
+        true
+    }
+
+    /// Versions with their parents and timestamps, oldest first.
+    pub fn list(&self) -> Vec<RevisionInfo> {
+        self.revisions
+            .iter()
+            .map(|r| RevisionInfo {
+                version: r.version.clone(),
+                parents: r.parents.clone(),
+                timestamp: r.timestamp,
+            })
+            .collect()
+    }
+
+    /// The document as it was at `version`, or `None` if it wasn't recorded.
+    pub fn content_at(&self, version: &str) -> Option<String> {
+        let end = self.revisions.iter().position(|r| r.version == version)?;
+        let mut merge = SimpletonMergeType::new("history");
+        for patch in self.revisions[..=end].iter().flat_map(|r| &r.patches) {
+            let result = merge.apply_patch(patch.clone());
+            if !result.success {
+                return None;
+            }
+        }
+        Some(merge.get_content())
+    }
+}
+
+/// The single `[start:end]` splice, in `char` offsets, turning `old` into `new`.
+fn splice_between(old: &str, new: &str) -> (usize, usize, String) {
+    let a: Vec<char> = old.chars().collect();
+    let b: Vec<char> = new.chars().collect();
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let text = b[prefix..b.len() - suffix].iter().collect();
+    (prefix, a.len() - suffix, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_past_versions_replay() {
+        let mut history = History::default();
+        assert!(history.record("v1", vec![], "old text", "# Page\n"));
+        assert!(history.record("v2", vec!["v1".into()], "# Page\n", "# Page\nHello 👋\n"));
+        assert!(history.record(
+            "v3",
+            vec!["v2".into()],
+            "# Page\nHello 👋\n",
+            "# Page\nBye 👋\n"
+        ));
+        assert!(!history.record("v2", vec![], "ignored", "ignored"));
+
+        let versions: Vec<_> = history.list().into_iter().map(|r| r.version).collect();
+        assert_eq!(versions, ["v1", "v2", "v3"]);
+        assert_eq!(history.list()[2].parents, ["v2"]);
+        assert_eq!(history.content_at("v1").as_deref(), Some("# Page\n"));
+        assert_eq!(
+            history.content_at("v2").as_deref(),
+            Some("# Page\nHello 👋\n")
+        );
+        assert_eq!(
+            history.content_at("v3").as_deref(),
+            Some("# Page\nBye 👋\n")
+        );
+        assert_eq!(history.content_at("v4"), None);
+    }
+
+    #[test]
+    fn test_splice_between() {
+        assert_eq!(splice_between("abc", "abc"), (3, 3, String::new()));
+        assert_eq!(splice_between("abc", "abXc"), (2, 2, "X".to_string()));
+        assert_eq!(splice_between("aaa", "aa"), (2, 3, String::new()));
+        assert_eq!(splice_between("", "hi"), (0, 0, "hi".to_string()));
+    }
+}
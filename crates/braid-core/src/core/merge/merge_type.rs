@@ -8,7 +8,7 @@
 //!
 //! | Name | Description |
 //! |------|-------------|
-//! | `\"diamond\"` | Diamond-types CRDT for text |
+//! | `"simpleton"` / `"braid-text"` | braid-text compatible range patches |
 //! | `"diamond"` | Diamond-types CRDT for text |
 //! | `"antimatter"` | Antimatter CRDT with pruning |
 //! | Custom | Application-defined algorithms |
@@ -122,6 +122,11 @@ pub trait MergeType: Debug + Send + Sync {
     /// Get the current version frontier.
     fn get_version(&self) -> Vec<braid_http::types::Version>;
 
+    /// Get the version of the latest applied edit, if any.
+    fn current_version(&self) -> Option<braid_http::types::Version> {
+        self.get_version().first().cloned()
+    }
+
     /// Get all known versions (for sync).
     fn get_all_versions(&self) -> HashMap<String, Vec<braid_http::types::Version>>;
 
@@ -135,6 +140,13 @@ pub trait MergeType: Debug + Send + Sync {
         false
     }
 
+    /// Serialize the merge state for persistence.
+    ///
+    /// Returns `None` if this merge type can't be snapshotted.
+    fn snapshot(&self) -> Option<Value> {
+        None
+    }
+
     /// Clone this merge type instance.
     fn clone_box(&self) -> Box<dyn MergeType>;
 }
@@ -145,6 +157,16 @@ impl Clone for Box<dyn MergeType> {
     }
 }
 
+/// A merge type name that isn't registered.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown merge type {name:?} (supported: {})", supported.join(", "))]
+pub struct UnknownMergeType {
+    /// The requested name.
+    pub name: String,
+    /// Registered names, sorted.
+    pub supported: Vec<String>,
+}
+
 /// Registry for available merge types.
 pub struct MergeTypeRegistry {
     factories: HashMap<String, Box<dyn Fn(&str) -> Box<dyn MergeType> + Send + Sync>>,
@@ -174,10 +196,17 @@ impl MergeTypeRegistry {
     pub fn new() -> Self {
         let mut registry = Self::default();
 
-        // Register built-in types
+        // Register built-in types. "braid-text" is an alias for simpleton with
+        // its own peer-id seed so versions minted under the two names never collide.
         registry.register("simpleton", |peer_id| {
             Box::new(super::simpleton::SimpletonMergeType::new(peer_id))
         });
+        registry.register("braid-text", |peer_id| {
+            Box::new(super::simpleton::SimpletonMergeType::new(&format!(
+                "{}.bt",
+                peer_id
+            )))
+        });
 
         // Register Diamond Types CRDT for true collaborative editing
         #[cfg(not(target_arch = "wasm32"))]
@@ -198,17 +227,70 @@ impl MergeTypeRegistry {
 
     /// Create an instance of a merge type by name.
     pub fn create(&self, name: &str, peer_id: &str) -> Option<Box<dyn MergeType>> {
-        self.factories.get(name).map(|f| f(peer_id))
+        self.try_create(name, peer_id).ok()
+    }
+
+    /// Create an instance of a merge type by name, or say which names are supported.
+    pub fn try_create(
+        &self,
+        name: &str,
+        peer_id: &str,
+    ) -> Result<Box<dyn MergeType>, UnknownMergeType> {
+        match self.factories.get(name) {
+            Some(factory) => Ok(factory(peer_id)),
+            None => Err(UnknownMergeType {
+                name: name.to_string(),
+                supported: self.list(),
+            }),
+        }
     }
 
-    /// List available merge types.
+    /// List available merge types, sorted by name.
     pub fn list(&self) -> Vec<String> {
-        self.factories.keys().cloned().collect()
+        let mut names: Vec<String> = self.factories.keys().cloned().collect();
+        names.sort();
+        names
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::merge::SimpletonMergeType;
     use serde_json::json;
+
+    #[test]
+    fn test_registry_braid_text_matches_simpleton() {
+        let registry = MergeTypeRegistry::new();
+        let mut via_registry = registry.create("braid-text", "peer").unwrap();
+        let mut direct = SimpletonMergeType::new("peer");
+
+        for merge in [via_registry.as_mut(), &mut direct as &mut dyn MergeType] {
+            merge.initialize("hello world");
+            let res = merge.apply_patch(MergePatch::new("[6:11]", json!("braid")));
+            assert!(res.success);
+        }
+        assert_eq!(via_registry.name(), direct.name());
+        assert_eq!(via_registry.get_content(), direct.get_content());
+        assert_eq!(via_registry.get_content(), "hello braid");
+
+        // Same behavior, distinct version namespace
+        let simpleton = registry.create("simpleton", "peer").unwrap();
+        let a = via_registry.current_version().unwrap().to_string();
+        let b = direct.current_version().unwrap().to_string();
+        assert_ne!(a, b);
+        assert_eq!(a, b.replacen("peer", "peer.bt", 1));
+        assert!(simpleton.current_version().is_none());
+    }
+
+    #[test]
+    fn test_registry_unknown_merge_type() {
+        let registry = MergeTypeRegistry::new();
+        let err = registry.try_create("sync9", "peer").unwrap_err();
+        assert_eq!(err.name, "sync9");
+        assert_eq!(err.supported, registry.list());
+        assert!(err.supported.contains(&"simpleton".to_string()));
+        assert!(err.to_string().starts_with("Unknown merge type \"sync9\" (supported: "));
+        assert!(registry.create("sync9", "peer").is_none());
+    }
 }
@@ -26,6 +26,7 @@
 //!
 //! [draft-toomim-httpbis-braid-http-04]: https://datatracker.ietf.org/doc/html/draft-toomim-httpbis-braid-http
 
+pub mod history;
 pub mod merge_type;
 pub mod simpleton;
 
@@ -35,8 +36,9 @@ pub mod simpleton;
 pub mod diamond;
 
 // Re-exports
-pub use merge_type::{MergePatch, MergeResult, MergeType, MergeTypeRegistry};
-pub use simpleton::SimpletonMergeType;
+pub use history::{History, Revision, RevisionInfo};
+pub use merge_type::{MergePatch, MergeResult, MergeType, MergeTypeRegistry, UnknownMergeType};
+pub use simpleton::{OffsetUnit, SimpletonMergeType, SimpletonSnapshot};
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use diamond::{DiamondCRDT, DiamondMergeType};
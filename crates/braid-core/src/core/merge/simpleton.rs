@@ -8,12 +8,112 @@ use serde_json::Value;
 use std::collections::HashMap;
 use tracing::warn;
 
+/// Maximum number of applied splices kept for rebasing stale patches.
+const MAX_HISTORY: usize = 1024;
+
+/// Unit in which `[start:end]` ranges are expressed.
+///
+/// braid-text and simpleton-client.js index strings by UTF-16 code units, so
+/// astral-plane characters (e.g. emoji) occupy two positions there but one
+/// Rust `char`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OffsetUnit {
+    /// Unicode scalar values (Rust `char`s).
+    #[default]
+    Char,
+    /// UTF-16 code units, as used by JavaScript strings.
+    Utf16,
+}
+
+/// A splice that has been applied to the content, in the configured offset unit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AppliedSplice {
+    version: Option<braid_http::types::Version>,
+    start: usize,
+    end: usize,
+    inserted: usize,
+}
+
+impl AppliedSplice {
+    /// Map a position from before this splice to after it.
+    ///
+    /// Positions inside the replaced region collapse to its start (for range
+    /// starts) or to the end of the inserted text (for range ends). Inserts at
+    /// the same position as ours are ordered after it.
+    fn transform(&self, pos: usize, is_end: bool) -> usize {
+        if pos < self.start {
+            pos
+        } else if pos >= self.end {
+            pos - (self.end - self.start) + self.inserted
+        } else if is_end {
+            self.start + self.inserted
+        } else {
+            self.start
+        }
+    }
+}
+
+/// Why a `[start:end]` range was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RangeError {
+    /// Not of the form `[start:end]` with non-negative integer offsets.
+    Syntax(String),
+    /// A unit prefix other than `text`, e.g. `json .text [2:5]`.
+    UnsupportedUnit(String),
+    /// `start` is after `end`.
+    Reversed { start: usize, end: usize },
+    /// An offset is past the end of the document.
+    OutOfBounds { start: usize, end: usize, len: usize },
+    /// An offset falls inside a character (e.g. between a UTF-16 surrogate pair).
+    Misaligned { start: usize, end: usize },
+}
+
+impl std::fmt::Display for RangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(range) => write!(f, "Invalid range syntax: {:?}", range),
+            Self::UnsupportedUnit(unit) => {
+                write!(f, "Unsupported range unit {:?} (expected \"text\")", unit)
+            }
+            Self::Reversed { start, end } => {
+                write!(f, "Invalid range [{}:{}]: start is after end", start, end)
+            }
+            Self::OutOfBounds { start, end, len } => write!(
+                f,
+                "Range [{}:{}] out of bounds for document of length {}",
+                start, end, len
+            ),
+            Self::Misaligned { start, end } => {
+                write!(f, "Range [{}:{}] splits a character", start, end)
+            }
+        }
+    }
+}
+
+/// Serializable merge state, used to rehydrate a document after a restart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimpletonSnapshot {
+    pub peer_id: String,
+    pub content: String,
+    pub version: Vec<braid_http::types::Version>,
+    pub char_counter: i64,
+    #[serde(default)]
+    pub offset_unit: OffsetUnit,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SimpletonMergeType {
     pub peer_id: String,
     pub content: String,
     pub version: Vec<braid_http::types::Version>,
     pub char_counter: i64,
+    #[serde(default)]
+    pub offset_unit: OffsetUnit,
+    /// Recent splices, kept with the merge state so stale patches can still be
+    /// rebased after it is reloaded
+    #[serde(default)]
+    history: Vec<AppliedSplice>,
 }
 
 impl SimpletonMergeType {
@@ -29,9 +129,99 @@ impl SimpletonMergeType {
             content: String::new(),
             version: Vec::new(),
             char_counter: initial_counter,
+            offset_unit: OffsetUnit::Char,
+            history: Vec::new(),
+        }
+    }
+
+    /// Capture the state needed to resume merging after a restart.
+    pub fn snapshot(&self) -> SimpletonSnapshot {
+        SimpletonSnapshot {
+            peer_id: self.peer_id.clone(),
+            content: self.content.clone(),
+            version: self.version.clone(),
+            char_counter: self.char_counter,
+            offset_unit: self.offset_unit,
         }
     }
 
+    /// Rebuild a merge type from a snapshot.
+    ///
+    /// The rebase history is not persisted, so patches based on versions older
+    /// than the snapshot are applied without rebasing.
+    pub fn restore(snapshot: SimpletonSnapshot) -> Self {
+        Self {
+            peer_id: snapshot.peer_id,
+            content: snapshot.content,
+            version: snapshot.version,
+            char_counter: snapshot.char_counter,
+            offset_unit: snapshot.offset_unit,
+            history: Vec::new(),
+        }
+    }
+
+    /// Set the unit used for patch ranges.
+    pub fn with_offset_unit(mut self, unit: OffsetUnit) -> Self {
+        self.offset_unit = unit;
+        self
+    }
+
+    /// Current document text.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Current version ID, if there is one and it is string-based.
+    pub fn version(&self) -> Option<&str> {
+        match self.version.first()? {
+            braid_http::types::Version::String(v) => Some(v),
+            braid_http::types::Version::Integer(_) => None,
+        }
+    }
+
+    pub fn peer_id(&self) -> &str {
+        &self.peer_id
+    }
+
+    /// Document length in chars (Unicode scalar values).
+    pub fn len_chars(&self) -> usize {
+        self.content.chars().count()
+    }
+
+    /// Seed the document from a full snapshot, e.g. the first response of a
+    /// fresh subscription. Later local edits are diffed against `content`.
+    pub fn set_initial(&mut self, content: &str, version: Option<braid_http::types::Version>) {
+        self.content = content.to_string();
+        self.version = version.into_iter().collect();
+        // Ranges from before the snapshot can't be rebased
+        self.history.clear();
+    }
+
+    /// Width of a single char in the configured offset unit.
+    fn unit_len(&self, c: char) -> usize {
+        match self.offset_unit {
+            OffsetUnit::Char => 1,
+            OffsetUnit::Utf16 => c.len_utf16(),
+        }
+    }
+
+    /// Convert an offset in the configured unit to a char index.
+    ///
+    /// Returns `None` if the offset is past the end or splits a surrogate pair.
+    fn to_char_index(&self, chars: &[char], offset: usize) -> Option<usize> {
+        let mut units = 0;
+        for (i, c) in chars.iter().enumerate() {
+            if units == offset {
+                return Some(i);
+            }
+            if units > offset {
+                return None;
+            }
+            units += self.unit_len(*c);
+        }
+        (units == offset).then_some(chars.len())
+    }
+
     fn simple_diff(&self, old_text: &str, new_text: &str) -> (usize, usize, String) {
         let a: Vec<char> = old_text.chars().collect();
         let b: Vec<char> = new_text.chars().collect();
@@ -50,13 +240,184 @@ impl SimpletonMergeType {
             s += 1;
         }
 
-        let range_start = p;
-        let range_end = a.len() - s;
+        let range_start: usize = a[..p].iter().map(|c| self.unit_len(*c)).sum();
+        let range_end: usize =
+            range_start + a[p..a.len() - s].iter().map(|c| self.unit_len(*c)).sum::<usize>();
         let content: String = b[p..b.len() - s].iter().collect();
 
         (range_start, range_end, content)
     }
 
+    /// The single `[start:end]` patch turning `old_text` into `new_text`, with
+    /// offsets in the configured unit.
+    pub fn diff(&self, old_text: &str, new_text: &str) -> MergePatch {
+        let (start, end, text) = self.simple_diff(old_text, new_text);
+        MergePatch::new(&format!("[{}:{}]", start, end), Value::String(text))
+    }
+
+    /// Replace the `[start:end]` range (in the configured unit) with `text`.
+    ///
+    /// Nothing is modified if the range is invalid.
+    fn splice(
+        &mut self,
+        start: usize,
+        end: usize,
+        text: &str,
+        version: Option<&braid_http::types::Version>,
+    ) -> Result<(), RangeError> {
+        if start > end {
+            return Err(RangeError::Reversed { start, end });
+        }
+        let chars: Vec<char> = self.content.chars().collect();
+        let (Some(char_start), Some(char_end)) = (
+            self.to_char_index(&chars, start),
+            self.to_char_index(&chars, end),
+        ) else {
+            let len: usize = chars.iter().map(|c| self.unit_len(*c)).sum();
+            return Err(if end > len {
+                RangeError::OutOfBounds { start, end, len }
+            } else {
+                RangeError::Misaligned { start, end }
+            });
+        };
+
+        let mut new_chars = chars[..char_start].to_vec();
+        new_chars.extend(text.chars());
+        new_chars.extend(&chars[char_end..]);
+        self.content = new_chars.into_iter().collect();
+
+        if let Some(v) = version {
+            self.version = vec![v.clone()];
+        }
+
+        if self.history.len() >= MAX_HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(AppliedSplice {
+            version: self.version.first().cloned(),
+            start,
+            end,
+            inserted: text.chars().map(|c| self.unit_len(c)).sum(),
+        });
+        Ok(())
+    }
+
+    /// Transform a range based on `parents` against everything applied since.
+    ///
+    /// Returns the range unchanged if `parents` is current, empty, or no longer
+    /// in the retained history.
+    fn rebase_range(
+        &self,
+        parents: &[braid_http::types::Version],
+        start: usize,
+        end: usize,
+    ) -> (usize, usize) {
+        if parents.is_empty() || parents == self.version.as_slice() {
+            return (start, end);
+        }
+
+        let Some(base) = self
+            .history
+            .iter()
+            .rposition(|h| h.version.as_ref().is_some_and(|v| parents.contains(v)))
+        else {
+            warn!("Simpleton: Parents {:?} not in history, applying unrebased", parents);
+            return (start, end);
+        };
+
+        self.history[base + 1..]
+            .iter()
+            .fold((start, end), |(start, end), h| {
+                (h.transform(start, false), h.transform(end, true))
+            })
+    }
+
+    /// Parse a `[start:end]` range string, rejecting reversed ranges.
+    ///
+    /// The range may carry a braid-text unit prefix (`text [2:5]`, `text[2:5]`);
+    /// any other unit or key path, such as `json .text [2:5]`, is rejected.
+    fn parse_range(range: &str) -> Result<(usize, usize), RangeError> {
+        let syntax = || RangeError::Syntax(range.to_string());
+        let bracket = range.find('[').ok_or_else(syntax)?;
+        let (prefix, bare) = range.split_at(bracket);
+        match prefix.split_whitespace().collect::<Vec<_>>()[..] {
+            [] | ["text"] => {}
+            [unit, ..] => return Err(RangeError::UnsupportedUnit(unit.to_string())),
+        }
+        let (start, end) = bare
+            .strip_prefix('[')
+            .and_then(|r| r.strip_suffix(']'))
+            .and_then(|r| r.split_once(':'))
+            .ok_or_else(syntax)?;
+        let start: usize = start.parse().map_err(|_| syntax())?;
+        let end: usize = end.parse().map_err(|_| syntax())?;
+        if start > end {
+            return Err(RangeError::Reversed { start, end });
+        }
+        Ok((start, end))
+    }
+
+    /// Apply several patches from a single update atomically.
+    ///
+    /// Patches are applied in descending order of start offset so that earlier
+    /// splices don't shift later ones. If any range is malformed, out of bounds,
+    /// or overlaps another patch in the batch, nothing is applied.
+    pub fn apply_patches(&mut self, patches: Vec<MergePatch>) -> MergeResult {
+        let mut ranged = Vec::with_capacity(patches.len());
+        for patch in patches {
+            match Self::parse_range(&patch.range) {
+                Ok((start, end)) => ranged.push((start, end, patch)),
+                Err(e) => {
+                    warn!("Simpleton: {} in batch", e);
+                    return MergeResult::failure(&e.to_string());
+                }
+            }
+        }
+
+        ranged.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+        if ranged.windows(2).any(|w| w[1].1 > w[0].0) {
+            warn!("Simpleton: Overlapping ranges in batch");
+            return MergeResult::failure("Overlapping ranges");
+        }
+
+        let saved_content = self.content.clone();
+        let saved_version = self.version.clone();
+        let saved_history = self.history.len();
+        let versioned = ranged.iter().any(|(_, _, p)| p.version.is_some());
+        let mut delta = 0;
+        for (start, end, patch) in ranged {
+            let content_str = match &patch.content {
+                Value::String(s) => s.clone(),
+                v => v.to_string(),
+            };
+            if let Err(e) = self.splice(start, end, &content_str, patch.version.as_ref()) {
+                warn!("Simpleton: {} in batch", e);
+                self.content = saved_content;
+                self.version = saved_version;
+                self.history.truncate(saved_history);
+                return MergeResult::failure(&e.to_string());
+            }
+            delta += (end - start) as i64 + self.count_code_points(&content_str);
+        }
+
+        if !versioned {
+            let version = self.next_version(delta);
+            self.version = vec![version.clone()];
+            let applied = self.history.len() - saved_history;
+            for h in self.history.iter_mut().rev().take(applied) {
+                h.version = Some(version.clone());
+            }
+        }
+
+        MergeResult::success(self.version.first().cloned(), vec![])
+    }
+
+    /// Advance `char_counter` by `delta` and mint a `{peer_id}-{char_counter}` version.
+    fn next_version(&mut self, delta: i64) -> braid_http::types::Version {
+        self.char_counter += delta;
+        braid_http::types::Version::String(format!("{}-{}", self.peer_id, self.char_counter))
+    }
+
     fn count_code_points(&self, s: &str) -> i64 {
         s.chars().count() as i64
     }
@@ -87,51 +448,64 @@ impl MergeType for SimpletonMergeType {
             };
 
             self.content = content_str;
-            
-            if let Some(ref v) = patch.version {
-                self.version = vec![v.clone()];
-            }
+            // Ranges from before a full replacement can't be rebased
+            self.history.clear();
+
+            let version = match patch.version {
+                Some(v) => v,
+                None => {
+                    let delta = self.count_code_points(&self.content);
+                    self.next_version(delta)
+                }
+            };
+            self.version = vec![version];
 
             return MergeResult::success(self.version.first().cloned(), vec![]);
         }
 
         // Range format: [start:end]
-        if let Some(range) = patch
-            .range
-            .strip_prefix('[')
-            .and_then(|s| s.strip_suffix(']'))
-        {
-            let parts: Vec<&str> = range.split(':').collect();
-            if parts.len() == 2 {
-                if let (Ok(start), Ok(end)) = (parts[0].parse::<usize>(), parts[1].parse::<usize>())
-                {
-                    let content_str = match &patch.content {
-                        Value::String(s) => s.clone(),
-                        v => v.to_string(),
-                    };
-
-                    let chars: Vec<char> = self.content.chars().collect();
-                    if start <= chars.len() && end <= chars.len() && start <= end {
-                        let mut new_chars = chars[..start].to_vec();
-                        new_chars.extend(content_str.chars());
-                        new_chars.extend(&chars[end..]);
-                        self.content = new_chars.into_iter().collect();
-
-                        if let Some(ref v) = patch.version {
-                            self.version = vec![v.clone()];
-                        }
- 
-                        return MergeResult::success(self.version.first().cloned(), vec![]);
-                    }
-                }
+        let (start, end) = match Self::parse_range(&patch.range) {
+            Ok(range) => range,
+            Err(e) => {
+                warn!("Simpleton: {}", e);
+                return MergeResult::failure(&e.to_string());
             }
+        };
+        let content_str = match &patch.content {
+            Value::String(s) => s.clone(),
+            v => v.to_string(),
+        };
+
+        let (new_start, new_end) = self.rebase_range(&patch.parents, start, end);
+        let current = self.version.clone();
+        let counter = self.char_counter;
+        let version = match patch.version {
+            Some(v) => v,
+            None if new_start <= new_end => self.next_version(
+                (new_end - new_start) as i64 + self.count_code_points(&content_str),
+            ),
+            None => {
+                let e = RangeError::Reversed { start: new_start, end: new_end };
+                return MergeResult::failure(&e.to_string());
+            }
+        };
+        if let Err(e) = self.splice(new_start, new_end, &content_str, Some(&version)) {
+            self.char_counter = counter;
+            warn!("Simpleton: {} (patch range {})", e, patch.range);
+            return MergeResult::failure(&e.to_string());
         }
 
-        warn!(
-            "Simpleton: Invalid range format or indices: {}",
-            patch.range
-        );
-        MergeResult::failure("Invalid range")
+        let rebased = if (new_start, new_end) != (start, end) {
+            vec![MergePatch {
+                range: format!("[{}:{}]", new_start, new_end),
+                content: patch.content,
+                version: Some(version),
+                parents: current,
+            }]
+        } else {
+            vec![]
+        };
+        MergeResult::success(self.version.first().cloned(), rebased)
     }
 
     fn local_edit(&mut self, patch: MergePatch) -> MergeResult {
@@ -168,9 +542,6 @@ impl MergeType for SimpletonMergeType {
              return MergeResult::failure("Custom range patches not yet supported in local_edit - use 'everything' to trigger diffing");
         };
 
-        // Apply the change locally
-        let patch_obj = MergePatch::new(&range, Value::String(content.clone()));
-
         // Guard: If nothing changed, return success with no patches to signal sync should skip
         if delete_count == 0 && content.is_empty() && !self.version.is_empty() {
             return MergeResult {
@@ -181,17 +552,19 @@ impl MergeType for SimpletonMergeType {
             };
         }
 
-        // Apply state update
-        self.apply_patch(patch_obj.clone());
-
         // Update version using monotonic counter
         let insert_count = self.count_code_points(&content);
-        self.char_counter += delete_count + insert_count;
-        
-        let version_id = format!("{}-{}", self.peer_id, self.char_counter);
-        let version = braid_http::types::Version::String(version_id);
-        self.version = vec![version.clone()];
- 
+        let version = self.next_version(delete_count + insert_count);
+
+        // Apply the change locally
+        let patch_obj = MergePatch::with_version(
+            &range,
+            Value::String(content),
+            version.clone(),
+            self.version.clone(),
+        );
+        self.apply_patch(patch_obj.clone());
+
         MergeResult::success(Some(version), vec![patch_obj])
     }
 
@@ -212,8 +585,14 @@ impl MergeType for SimpletonMergeType {
     }
 
     fn prune(&mut self) -> bool {
-        // Simpleton doesn't really have history to prune
-        false
+        // Only the splice log used for rebasing is kept
+        let pruned = !self.history.is_empty();
+        self.history.clear();
+        pruned
+    }
+
+    fn snapshot(&self) -> Option<Value> {
+        serde_json::to_value(SimpletonMergeType::snapshot(self)).ok()
     }
 
     fn clone_box(&self) -> Box<dyn MergeType> {
@@ -317,4 +696,311 @@ mod tests {
         assert_eq!(res4.rebased_patches[0].content, Value::String("\r".to_string()));
         assert_eq!(simpleton.get_content(), "abc\r\n");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_simpleton_utf16_offsets() {
+        let mut simpleton = SimpletonMergeType::new("peer3").with_offset_unit(OffsetUnit::Utf16);
+        simpleton.initialize("a\u{1F600}b");
+
+        // braid-text sees "a😀b" as 4 UTF-16 code units, so the emoji spans [1:3]
+        let res = simpleton.local_edit(MergePatch::new("everything", Value::String("aXb".to_string())));
+        assert_eq!(res.rebased_patches[0].range, "[1:3]");
+        assert_eq!(res.rebased_patches[0].content, Value::String("X".to_string()));
+        assert_eq!(simpleton.get_content(), "aXb");
+
+        // Remote patches are interpreted in the same unit
+        let mut remote = SimpletonMergeType::new("peer4").with_offset_unit(OffsetUnit::Utf16);
+        remote.initialize("a\u{1F600}b");
+        assert!(remote.apply_patch(MergePatch::new("[3:4]", Value::String("c".to_string()))).success);
+        assert_eq!(remote.get_content(), "a\u{1F600}c");
+
+        // Offsets that split a surrogate pair are rejected
+        assert!(!remote.apply_patch(MergePatch::new("[2:2]", Value::String("!".to_string()))).success);
+
+        // Char mode keeps counting scalar values
+        let mut chars = SimpletonMergeType::new("peer5");
+        chars.initialize("a\u{1F600}b");
+        let res = chars.local_edit(MergePatch::new("everything", Value::String("aXb".to_string())));
+        assert_eq!(res.rebased_patches[0].range, "[1:2]");
+    }
+
+    #[test]
+    fn test_simpleton_apply_patches_disjoint() {
+        let mut simpleton = SimpletonMergeType::new("peer6");
+        simpleton.initialize("foo bar foo baz foo");
+
+        // Replace every "foo" with "qux", listed in ascending order
+        let patches = vec![
+            MergePatch::new("[0:3]", Value::String("qux".to_string())),
+            MergePatch::new("[8:11]", Value::String("qux".to_string())),
+            MergePatch::new("[16:19]", Value::String("qux".to_string())),
+        ];
+        let res = simpleton.apply_patches(patches);
+        assert!(res.success);
+        assert_eq!(simpleton.get_content(), "qux bar qux baz qux");
+
+        // Adjacent ranges and pure inserts don't count as overlapping
+        let patches = vec![
+            MergePatch::new("[0:0]", Value::String(">".to_string())),
+            MergePatch::new("[0:3]", Value::String("Q".to_string())),
+            MergePatch::new("[3:4]", Value::String("_".to_string())),
+        ];
+        assert!(simpleton.apply_patches(patches).success);
+        assert_eq!(simpleton.get_content(), ">Q_bar qux baz qux");
+    }
+
+    #[test]
+    fn test_simpleton_apply_patches_rollback() {
+        let mut simpleton = SimpletonMergeType::new("peer7");
+        simpleton.initialize("hello world");
+        let version = braid_http::types::Version::String("v1".to_string());
+        simpleton.version = vec![version.clone()];
+
+        // Overlapping ranges fail the whole batch
+        let patches = vec![
+            MergePatch::new("[0:5]", Value::String("HELLO".to_string())),
+            MergePatch::new("[3:8]", Value::String("xx".to_string())),
+        ];
+        assert!(!simpleton.apply_patches(patches).success);
+        assert_eq!(simpleton.get_content(), "hello world");
+
+        // A later splice that fails rolls back the ones already applied
+        simpleton.offset_unit = OffsetUnit::Utf16;
+        simpleton.initialize("a\u{1F600}b world");
+        let patches = vec![
+            MergePatch::with_version(
+                "[5:10]",
+                Value::String("there".to_string()),
+                braid_http::types::Version::String("v2".to_string()),
+                vec![version.clone()],
+            ),
+            MergePatch::new("[2:2]", Value::String("!".to_string())),
+        ];
+        assert!(!simpleton.apply_patches(patches).success);
+        assert_eq!(simpleton.get_content(), "a\u{1F600}b world");
+        assert_eq!(simpleton.get_version(), vec![version]);
+    }
+
+    #[test]
+    fn test_simpleton_malformed_ranges() {
+        let mut simpleton = SimpletonMergeType::new("peer8").with_offset_unit(OffsetUnit::Utf16);
+        simpleton.initialize("a\u{1F600}b");
+        let x = || Value::String("x".to_string());
+
+        let cases = [
+            ("[abc:def]", "Invalid range syntax: \"[abc:def]\""),
+            ("[-1:2]", "Invalid range syntax: \"[-1:2]\""),
+            ("1:2", "Invalid range syntax: \"1:2\""),
+            ("[1:2", "Invalid range syntax: \"[1:2\""),
+            ("[12]", "Invalid range syntax: \"[12]\""),
+            ("", "Invalid range syntax: \"\""),
+            ("[3:1]", "Invalid range [3:1]: start is after end"),
+            ("[3:99]", "Range [3:99] out of bounds for document of length 4"),
+            ("[5:5]", "Range [5:5] out of bounds for document of length 4"),
+            ("[2:2]", "Range [2:2] splits a character"),
+        ];
+        for (range, expected) in cases {
+            let res = simpleton.apply_patch(MergePatch::new(range, x()));
+            assert!(!res.success, "{}", range);
+            assert_eq!(res.error.as_deref(), Some(expected), "{}", range);
+            assert_eq!(simpleton.get_content(), "a\u{1F600}b");
+        }
+
+        // Batches report the same errors and leave the document untouched
+        let patches = vec![
+            MergePatch::new("[0:1]", x()),
+            MergePatch::new("[4:9]", x()),
+        ];
+        let res = simpleton.apply_patches(patches);
+        assert_eq!(
+            res.error.as_deref(),
+            Some("Range [4:9] out of bounds for document of length 4")
+        );
+        let res = simpleton.apply_patches(vec![MergePatch::new("[0:x]", x())]);
+        assert_eq!(res.error.as_deref(), Some("Invalid range syntax: \"[0:x]\""));
+        assert_eq!(simpleton.get_content(), "a\u{1F600}b");
+    }
+
+    #[test]
+    fn test_simpleton_unit_prefixed_ranges() {
+        let mut simpleton = SimpletonMergeType::new("peer9");
+        simpleton.initialize("hello world");
+
+        let res = simpleton.apply_patch(MergePatch::new("text[2:5]", Value::String("LLO".into())));
+        assert!(res.success);
+        assert_eq!(simpleton.get_content(), "heLLO world");
+
+        let res = simpleton.apply_patch(MergePatch::new("text [0:2]", Value::String("HE".into())));
+        assert!(res.success);
+        assert_eq!(simpleton.get_content(), "HELLO world");
+
+        // Bare ranges keep working
+        assert!(simpleton.apply_patch(MergePatch::new("[5:6]", Value::String("_".into()))).success);
+        assert_eq!(simpleton.get_content(), "HELLO_world");
+
+        for range in ["json[0:2]", "json .text [0:2]", "bytes [0:2]"] {
+            let res = simpleton.apply_patch(MergePatch::new(range, Value::String("x".into())));
+            let unit = range.split(['[', ' ']).next().unwrap();
+            let expected = format!("Unsupported range unit {:?} (expected \"text\")", unit);
+            assert_eq!(res.error, Some(expected), "{}", range);
+        }
+        let res = simpleton.apply_patches(vec![MergePatch::new("json[0:2]", Value::Null)]);
+        assert!(!res.success);
+        assert_eq!(simpleton.get_content(), "HELLO_world");
+    }
+
+    #[test]
+    fn test_simpleton_set_initial() {
+        let mut simpleton = SimpletonMergeType::new("peer10");
+        assert_eq!(simpleton.peer_id(), "peer10");
+        assert_eq!(simpleton.version(), None);
+
+        let seeded = braid_http::types::Version::String("server-42".to_string());
+        simpleton.set_initial("h\u{E9}llo world", Some(seeded.clone()));
+        assert_eq!(simpleton.content(), "h\u{E9}llo world");
+        assert_eq!(simpleton.version(), Some("server-42"));
+        assert_eq!(simpleton.len_chars(), 11);
+
+        // The next diff is computed against the seeded text and version
+        let res = simpleton.local_edit(MergePatch::new(
+            "everything",
+            Value::String("h\u{E9}llo there".to_string()),
+        ));
+        assert_eq!(res.rebased_patches.len(), 1);
+        assert_eq!(res.rebased_patches[0].range, "[6:11]");
+        assert_eq!(res.rebased_patches[0].content, Value::String("there".to_string()));
+        assert_eq!(res.rebased_patches[0].parents, vec![seeded]);
+        assert_eq!(simpleton.content(), "h\u{E9}llo there");
+        assert_eq!(simpleton.version(), res.version.as_ref().map(|v| v.to_string()).as_deref());
+    }
+
+    #[test]
+    fn test_simpleton_rebase_concurrent() {
+        let v = |s: &str| braid_http::types::Version::String(s.to_string());
+        let mut simpleton = SimpletonMergeType::new("server");
+        simpleton.apply_patch(MergePatch::with_version(
+            "[0:0]",
+            Value::String("hello world".to_string()),
+            v("v1"),
+            vec![],
+        ));
+
+        // Two clients branch from v1
+        let res_a = simpleton.apply_patch(MergePatch::with_version(
+            "[6:6]",
+            Value::String("big ".to_string()),
+            v("v2"),
+            vec![v("v1")],
+        ));
+        assert!(res_a.success);
+        assert!(res_a.rebased_patches.is_empty());
+
+        let res_b = simpleton.apply_patch(MergePatch::with_version(
+            "[6:11]",
+            Value::String("there".to_string()),
+            v("v3"),
+            vec![v("v1")],
+        ));
+        assert!(res_b.success);
+        assert_eq!(simpleton.get_content(), "hello big there");
+
+        // The second patch is shifted past the concurrent insert
+        assert_eq!(res_b.rebased_patches.len(), 1);
+        assert_eq!(res_b.rebased_patches[0].range, "[10:15]");
+        assert_eq!(res_b.rebased_patches[0].parents, vec![v("v2")]);
+        assert_eq!(simpleton.get_version(), vec![v("v3")]);
+    }
+
+    #[test]
+    fn test_simpleton_auto_version() {
+        let mut simpleton = SimpletonMergeType::new("peer8");
+
+        let res = simpleton.apply_patch(MergePatch::new("[0:0]", Value::String("abc".to_string())));
+        assert!(res.success);
+        assert_eq!(res.version.unwrap().to_string(), "peer8-3");
+        assert_eq!(simpleton.char_counter, 3);
+
+        let res = simpleton.apply_patch(MergePatch::new("[3:3]", Value::String("de".to_string())));
+        assert_eq!(res.version.unwrap().to_string(), "peer8-5");
+        assert_eq!(simpleton.get_version()[0].to_string(), "peer8-5");
+
+        // Explicit versions are kept and don't advance the counter
+        let explicit = braid_http::types::Version::String("remote-1".to_string());
+        let res = simpleton.apply_patch(MergePatch::with_version(
+            "[0:0]",
+            Value::String("x".to_string()),
+            explicit.clone(),
+            vec![],
+        ));
+        assert_eq!(res.version, Some(explicit));
+        assert_eq!(simpleton.char_counter, 5);
+    }
+
+    #[test]
+    fn test_simpleton_deletion() {
+        let mut simpleton = SimpletonMergeType::new("peer9");
+        simpleton.initialize("hello world");
+
+        let res = simpleton.apply_patch(MergePatch::new("[5:11]", Value::String(String::new())));
+        assert!(res.success);
+        assert_eq!(simpleton.get_content(), "hello");
+
+        // Pure deletions still produce a fresh version
+        assert_eq!(res.version.unwrap().to_string(), "peer9-6");
+
+        let res = simpleton.apply_patch(MergePatch::new("[0:1]", Value::String(String::new())));
+        assert_eq!(simpleton.get_content(), "ello");
+        assert_eq!(res.version.unwrap().to_string(), "peer9-7");
+    }
+
+    #[test]
+    fn test_simpleton_snapshot_roundtrip() {
+        let mut original = SimpletonMergeType::new("peer10");
+        original.local_edit(MergePatch::new("everything", Value::String("hello".to_string())));
+        original.local_edit(MergePatch::new("everything", Value::String("hello world".to_string())));
+        original.apply_patch(MergePatch::new("[0:1]", Value::String("J".to_string())));
+
+        let json = serde_json::to_string(&original.snapshot()).unwrap();
+        let mut restored = SimpletonMergeType::restore(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.snapshot(), original.snapshot());
+
+        // The next edit produces identical content and versions on both
+        let next = MergePatch::new("everything", Value::String("Jello, world".to_string()));
+        let res_a = original.local_edit(next.clone());
+        let res_b = restored.local_edit(next);
+        assert_eq!(res_a.rebased_patches[0].range, res_b.rebased_patches[0].range);
+        assert_eq!(res_a.version, res_b.version);
+        assert_eq!(restored.get_content(), "Jello, world");
+    }
+
+    #[test]
+    fn test_simpleton_serde_keeps_rebase_history() {
+        let v = |s: &str| braid_http::types::Version::String(s.to_string());
+        let mut original = SimpletonMergeType::new("server");
+        original.apply_patch(MergePatch::with_version(
+            "[0:0]",
+            Value::String("hello world".to_string()),
+            v("v1"),
+            vec![],
+        ));
+        original.apply_patch(MergePatch::with_version(
+            "[6:6]",
+            Value::String("big ".to_string()),
+            v("v2"),
+            vec![v("v1")],
+        ));
+
+        let json = serde_json::to_string(&original).unwrap();
+        let mut restored: SimpletonMergeType = serde_json::from_str(&json).unwrap();
+
+        // A patch based on v1 is still rebased past the insert
+        let res = restored.apply_patch(MergePatch::with_version(
+            "[6:11]",
+            Value::String("there".to_string()),
+            v("v3"),
+            vec![v("v1")],
+        ));
+        assert!(res.success);
+        assert_eq!(restored.get_content(), "hello big there");
+    }
+}
@@ -9,8 +9,9 @@ use axum::{
     routing::{delete, put},
     Json, Router,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 #[derive(Deserialize)]
 pub struct SyncParams {
@@ -36,6 +37,33 @@ pub struct IdentityParams {
     pub email: String,
 }
 
+#[derive(Deserialize)]
+pub struct StatusParams {
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryParams {
+    url: String,
+    version: Option<String>,
+}
+
+/// Sync state of one URL, as returned by `GET /api/status`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SyncStatus {
+    pub url: String,
+    /// Listed in `config.sync` and switched on
+    pub enabled: bool,
+    /// Enabled and not paused, so a subscription is kept open
+    pub subscribed: bool,
+    /// Unix time in milliseconds of the last update pushed or received
+    pub last_synced: Option<u64>,
+    /// Why the last push failed, cleared by the next successful one
+    pub last_error: Option<String>,
+    /// A local change is queued or being retried
+    pub pending: bool,
+}
+
 #[derive(Deserialize)]
 pub struct MountParams {
     pub port: Option<u16>,
@@ -49,7 +77,9 @@ pub async fn run_server(port: u16, state: DaemonState) -> Result<()> {
         .route("/api/push", put(handle_push))
         .route("/api/get", axum::routing::get(handle_get_file_api))
         .route("/api/cookie", put(handle_cookie))
-        .route("/api/identity", put(handle_identity));
+        .route("/api/identity", put(handle_identity))
+        .route("/api/status", axum::routing::get(handle_status))
+        .route("/api/history", axum::routing::get(handle_history));
 
     #[cfg(feature = "nfs")]
     let app = app
@@ -278,6 +308,86 @@ async fn handle_identity(
     Json(serde_json::json!({ "status": "ok", "domain": params.domain }))
 }
 
+/// Handle GET /api/status - one URL with `?url=`, otherwise every synced URL.
+async fn handle_status(
+    State(state): State<DaemonState>,
+    axum::extract::Query(params): axum::extract::Query<StatusParams>,
+) -> Json<serde_json::Value> {
+    if let Some(url) = params.url {
+        return Json(serde_json::json!(sync_status(&state, &url).await));
+    }
+
+    let mut urls: Vec<String> = state.config.read().await.sync.keys().cloned().collect();
+    urls.sort();
+    let mut statuses = Vec::with_capacity(urls.len());
+    for url in urls {
+        statuses.push(sync_status(&state, &url).await);
+    }
+    Json(serde_json::json!(statuses))
+}
+
+/// Handle GET /api/history - the versions recorded for `?url=`, oldest first,
+/// or with `&version=` the content at that version.
+async fn handle_history(
+    State(state): State<DaemonState>,
+    axum::extract::Query(params): axum::extract::Query<HistoryParams>,
+) -> axum::response::Response {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    let history = match state.merge_store.history(&params.url) {
+        Ok(history) => history,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    match params.version {
+        Some(version) => match history.content_at(&version) {
+            Some(content) => content.into_response(),
+            None => (
+                StatusCode::NOT_FOUND,
+                format!("Unknown version: {}", version),
+            )
+                .into_response(),
+        },
+        None => Json(history.list()).into_response(),
+    }
+}
+
+/// Assemble the status of `url` from the config, failed syncs and activity.
+pub async fn sync_status(state: &DaemonState, url: &str) -> SyncStatus {
+    let (enabled, paused) = {
+        let config = state.config.read().await;
+        (
+            config.sync.get(url).copied().unwrap_or(false),
+            config.paused.contains(url),
+        )
+    };
+    let last_error = state
+        .failed_syncs
+        .read()
+        .await
+        .get(url)
+        .map(|(status, _)| format!("HTTP {}", status));
+    let last_synced = state.tracker.last_activity(url).and_then(unix_millis);
+    let pending =
+        state.debouncer.is_pending(url) || state.binary_sync.has_pending_write_retry(url).await;
+
+    SyncStatus {
+        url: url.to_string(),
+        enabled,
+        subscribed: enabled && !paused,
+        last_synced,
+        last_error,
+        pending,
+    }
+}
+
+/// Convert a monotonic instant in the past to Unix milliseconds.
+fn unix_millis(at: Instant) -> Option<u64> {
+    let wall = SystemTime::now().checked_sub(at.elapsed())?;
+    let millis = wall.duration_since(UNIX_EPOCH).ok()?.as_millis();
+    u64::try_from(millis).ok()
+}
+
 /// Handle /.braidfs/config - returns the current configuration.
 async fn handle_braidfs_config(State(state): State<DaemonState>) -> Json<serde_json::Value> {
     let config = state.config.read().await;
@@ -286,6 +396,8 @@ async fn handle_braidfs_config(State(state): State<DaemonState>) -> Json<serde_j
         "cookies": config.cookies,
         "port": config.port,
         "debounce_ms": config.debounce_ms,
+        "coalesce_ms": config.coalesce_ms,
+        "pending_write_ms": config.pending_write_ms,
         "ignore_patterns": config.ignore_patterns,
     }))
 }
@@ -526,22 +638,130 @@ async fn handle_push_binary(
     .await
     {
         Ok(()) => {
-            // 4. Commit to local disk atomically
-            let temp_folder = path
-                .parent()
-                .unwrap_or(std::path::Path::new("."))
-                .join(".braid_tmp");
-            if let Err(e) = crate::blob::atomic_write(&path, &body, &temp_folder).await {
-                return Json(
+            // 4. Keep the blob so a failed local write can be retried from it
+            let hash = match state
+                .binary_sync
+                .blob_store()
+                .put_with_type(body.clone(), None)
+                .await
+            {
+                Ok(hash) => hash,
+                Err(e) => {
+                    return Json(
+                        serde_json::json!({ "status": "error", "message": format!("Server accepted but blob store write failed: {}", e) }),
+                    );
+                }
+            };
+
+            // 5. Commit to local disk atomically and verify it
+            state.pending.add(path.clone());
+            match state
+                .binary_sync
+                .write_verified(&query.url, &path, &body, &hash)
+                .await
+            {
+                Ok(true) => Json(serde_json::json!({ "status": "ok", "url": query.url })),
+                Ok(false) => Json(
+                    serde_json::json!({ "status": "error", "message": "Server accepted but local write failed verification; retrying" }),
+                ),
+                Err(e) => Json(
                     serde_json::json!({ "status": "error", "message": format!("Server accepted but local atomic write failed: {}", e) }),
-                );
+                ),
             }
-            state.pending.add(path);
-
-            Json(serde_json::json!({ "status": "ok", "url": query.url }))
         }
         Err(e) => Json(
             serde_json::json!({ "status": "error", "message": format!("Binary push failed: {}", e) }),
         ),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::state::test_support::test_state;
+    use axum::extract::Query;
+
+    #[tokio::test]
+    async fn test_status_reports_injected_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, _rx_cmd) = test_state(dir.path()).await;
+        let url = "https://braid.org/status_test";
+
+        state
+            .config
+            .write()
+            .await
+            .sync
+            .insert(url.to_string(), true);
+        state.tracker.mark(url);
+
+        let status = sync_status(&state, url).await;
+        assert!(status.enabled && status.subscribed);
+        assert!(status.last_synced.is_some());
+        assert_eq!(status.last_error, None);
+        assert!(!status.pending);
+
+        state
+            .failed_syncs
+            .write()
+            .await
+            .insert(url.to_string(), (503, Instant::now()));
+        state.config.write().await.paused.insert(url.to_string());
+
+        let Json(body) = handle_status(
+            State(state.clone()),
+            Query(StatusParams {
+                url: Some(url.to_string()),
+            }),
+        )
+        .await;
+        let status: SyncStatus = serde_json::from_value(body).unwrap();
+        assert_eq!(status.last_error.as_deref(), Some("HTTP 503"));
+        assert!(status.enabled);
+        assert!(!status.subscribed);
+
+        let Json(body) = handle_status(State(state), Query(StatusParams { url: None })).await;
+        let all: Vec<SyncStatus> = serde_json::from_value(body).unwrap();
+        assert_eq!(all, vec![status]);
+    }
+
+    #[tokio::test]
+    async fn test_history_lists_versions_and_serves_past_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, _rx_cmd) = test_state(dir.path()).await;
+        let url = "https://braid.org/history_test";
+        let store = &state.merge_store;
+        store.record_revision(url, "a-1", vec![], "", "hi").unwrap();
+        store
+            .record_revision(url, "a-3", vec!["a-1".into()], "hi", "hi!!")
+            .unwrap();
+        store
+            .record_revision(url, "a-4", vec!["a-3".into()], "hi!!", "oh hi!!")
+            .unwrap();
+
+        let query = |version: Option<&str>| {
+            Query(HistoryParams {
+                url: url.to_string(),
+                version: version.map(str::to_string),
+            })
+        };
+        let body = |resp: axum::response::Response| async move {
+            axum::body::to_bytes(resp.into_body(), usize::MAX)
+                .await
+                .unwrap()
+        };
+
+        let resp = handle_history(State(state.clone()), query(None)).await;
+        let list: Vec<crate::core::merge::RevisionInfo> =
+            serde_json::from_slice(&body(resp).await).unwrap();
+        let versions: Vec<_> = list.iter().map(|r| r.version.as_str()).collect();
+        assert_eq!(versions, ["a-1", "a-3", "a-4"]);
+        assert!(list.iter().all(|r| r.timestamp > 0));
+
+        let resp = handle_history(State(state.clone()), query(Some("a-3"))).await;
+        assert_eq!(&body(resp).await[..], b"hi!!");
+
+        let resp = handle_history(State(state), query(Some("a-9"))).await;
+        assert_eq!(resp.status(), axum::http::StatusCode::NOT_FOUND);
+    }
+}
@@ -5,13 +5,21 @@
 
 use crate::core::Result;
 use crate::fs::config::{get_root_dir, is_binary};
-use crate::fs::rate_limiter::ReconnectRateLimiter;
+use crate::fs::rate_limiter::{ReconnectRateLimiter, BACKOFF_INITIAL, BACKOFF_MAX};
 use braid_blob::BlobStore;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Bytes read from the start of a file to decide whether it's binary.
+pub const SNIFF_LEN: usize = 8192;
+
+/// Attempts at writing a file whose content doesn't match its blob hash
+/// before giving up.
+pub const MAX_WRITE_RETRIES: u32 = 5;
+
 /// State for a binary sync operation.
 #[derive(Debug)]
 pub struct BinarySyncState {
@@ -55,6 +63,10 @@ pub struct BinarySyncManager {
     temp_folder: PathBuf,
     /// Meta folder for sync metadata.
     meta_folder: PathBuf,
+    /// URLs whose last write failed verification -> attempts so far.
+    write_retries: Arc<RwLock<HashMap<String, u32>>>,
+    /// Sniffed binary (true) or text (false) decision per file.
+    file_kinds: Arc<RwLock<HashMap<PathBuf, bool>>>,
 }
 
 impl BinarySyncManager {
@@ -64,15 +76,28 @@ impl BinarySyncManager {
         blob_store: Arc<BlobStore>,
     ) -> Result<Self> {
         let root = get_root_dir().map_err(|e| crate::core::BraidError::Config(e.to_string()))?;
-        let braidfs_dir = root.join(".braidfs");
+        Ok(Self::with_braidfs_dir(
+            rate_limiter,
+            blob_store,
+            &root.join(".braidfs"),
+        ))
+    }
 
-        Ok(Self {
+    /// Create a manager keeping its temp and meta folders under `braidfs_dir`.
+    pub fn with_braidfs_dir(
+        rate_limiter: Arc<ReconnectRateLimiter>,
+        blob_store: Arc<BlobStore>,
+        braidfs_dir: &Path,
+    ) -> Self {
+        Self {
             syncs: Arc::new(RwLock::new(HashMap::new())),
             rate_limiter,
             blob_store: Some(blob_store),
             temp_folder: braidfs_dir.join("temp"),
             meta_folder: braidfs_dir.join("braid-blob-meta"),
-        })
+            write_retries: Arc::new(RwLock::new(HashMap::new())),
+            file_kinds: Arc::new(RwLock::new(HashMap::new())),
+        }
     }
 
     /// Initialize a binary sync for a URL.
@@ -160,6 +185,173 @@ impl BinarySyncManager {
         Ok(())
     }
 
+    /// Write a synced binary file, then check it against the blob it came from.
+    ///
+    /// The file is re-read and hashed; if that doesn't match `expected_hash`
+    /// the partial file is deleted and the write is retried from the blob
+    /// store with backoff, up to [`MAX_WRITE_RETRIES`] times, rather than the
+    /// sync being marked complete. Returns whether the written file verified.
+    pub async fn write_verified(
+        self: &Arc<Self>,
+        url: &str,
+        fullpath: &Path,
+        data: &[u8],
+        expected_hash: &str,
+    ) -> Result<bool> {
+        if self
+            .write_checked(url, fullpath, data, expected_hash)
+            .await?
+        {
+            return Ok(true);
+        }
+
+        // A retry already queued for this URL will pick up from here
+        if self
+            .write_retries
+            .write()
+            .await
+            .insert(url.to_string(), 0)
+            .is_none()
+        {
+            let manager = self.clone();
+            let url = url.to_string();
+            let fullpath = fullpath.to_path_buf();
+            let expected_hash = expected_hash.to_string();
+            tokio::spawn(async move {
+                manager.retry_write(&url, &fullpath, &expected_hash).await;
+                manager.write_retries.write().await.remove(&url);
+            });
+        }
+        Ok(false)
+    }
+
+    /// Write `data` and hash it back, deleting the file if it doesn't match.
+    async fn write_checked(
+        &self,
+        url: &str,
+        fullpath: &Path,
+        data: &[u8],
+        expected_hash: &str,
+    ) -> Result<bool> {
+        let metadata = braid_blob::store::atomic_write(fullpath, data, &self.temp_folder)
+            .await
+            .map_err(crate::core::BraidError::Client)?;
+        let written = tokio::fs::read(fullpath).await?;
+        let actual_hash = format!("{:x}", Sha256::digest(&written));
+
+        if actual_hash != expected_hash {
+            tracing::error!(
+                "Binary write for {} failed verification: expected {}, got {}",
+                url,
+                expected_hash,
+                actual_hash
+            );
+            // Don't leave a corrupt file around to be uploaded again
+            match tokio::fs::remove_file(fullpath).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(crate::core::BraidError::Io(e)),
+            }
+            return Ok(false);
+        }
+
+        // Record the mtime so the file isn't uploaded straight back
+        let mtime = metadata
+            .modified()
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let mut syncs = self.syncs.write().await;
+        if let Some(state) = syncs.get_mut(url) {
+            state.file_mtime_ns_str = Some(mtime.to_string());
+            self.save_meta(url, state).await?;
+        }
+        Ok(true)
+    }
+
+    /// Rewrite `url` from the blob `expected_hash` until it verifies,
+    /// backing off exponentially between attempts.
+    async fn retry_write(&self, url: &str, fullpath: &Path, expected_hash: &str) {
+        let mut delay = BACKOFF_INITIAL;
+        for attempt in 1..=MAX_WRITE_RETRIES {
+            self.write_retries
+                .write()
+                .await
+                .insert(url.to_string(), attempt);
+            tracing::warn!(
+                "Retrying write of {} in {:?} (attempt {})",
+                url,
+                delay,
+                attempt
+            );
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(BACKOFF_MAX);
+
+            let data = match self.blob_store().get(expected_hash).await {
+                Ok(Some((data, _))) => data,
+                Ok(None) => {
+                    tracing::error!("Blob {} for {} is gone, not retrying", expected_hash, url);
+                    return;
+                }
+                Err(e) => {
+                    tracing::error!("Failed to read blob {} for {}: {}", expected_hash, url, e);
+                    continue;
+                }
+            };
+            match self
+                .write_checked(url, fullpath, &data, expected_hash)
+                .await
+            {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(e) => tracing::error!("Retried write of {} failed: {}", url, e),
+            }
+        }
+        tracing::error!(
+            "Giving up on writing {} after {} attempts",
+            url,
+            MAX_WRITE_RETRIES
+        );
+    }
+
+    /// Whether a verification-failed write of `url` is waiting to be retried.
+    pub async fn has_pending_write_retry(&self, url: &str) -> bool {
+        self.write_retries.read().await.contains_key(url)
+    }
+
+    /// Whether the file at `fullpath` should sync as binary.
+    ///
+    /// A binary extension on the URL decides outright. Otherwise the first
+    /// [`SNIFF_LEN`] bytes are checked, and the result cached per path until
+    /// the file changes.
+    pub async fn is_binary_file(&self, url: &str, fullpath: &Path) -> bool {
+        if should_use_binary_sync(url) {
+            return true;
+        }
+        if let Some(&binary) = self.file_kinds.read().await.get(fullpath) {
+            return binary;
+        }
+        let sample = match read_sample(fullpath).await {
+            Ok(sample) if !sample.is_empty() => sample,
+            _ => return false,
+        };
+        let binary = looks_binary(&sample);
+        if binary {
+            tracing::debug!("{} sniffed as binary despite its extension", url);
+        }
+        self.file_kinds
+            .write()
+            .await
+            .insert(fullpath.to_path_buf(), binary);
+        binary
+    }
+
+    /// Drop the cached binary/text decision for `fullpath` once it changes.
+    pub async fn forget_file_kind(&self, fullpath: &Path) {
+        self.file_kinds.write().await.remove(fullpath);
+    }
+
     /// Save metadata for a sync.
     async fn save_meta(&self, url: &str, state: &BinarySyncState) -> Result<()> {
         tokio::fs::create_dir_all(&self.meta_folder).await?;
@@ -213,15 +405,17 @@ impl BinarySyncManager {
 
 /// Database interface for binary sync (matches JS `db` object).
 pub struct BinarySyncDb {
+    url: String,
     fullpath: PathBuf,
-    temp_folder: PathBuf,
+    manager: Arc<BinarySyncManager>,
 }
 
 impl BinarySyncDb {
-    pub fn new(fullpath: PathBuf, temp_folder: PathBuf) -> Self {
+    pub fn new(url: String, fullpath: PathBuf, manager: Arc<BinarySyncManager>) -> Self {
         Self {
+            url,
             fullpath,
-            temp_folder,
+            manager,
         }
     }
 
@@ -234,11 +428,12 @@ impl BinarySyncDb {
         }
     }
 
-    /// Write file content atomically.
-    pub async fn write(&self, _key: &str, data: &[u8]) -> Result<std::fs::Metadata> {
-        braid_blob::store::atomic_write(&self.fullpath, data, &self.temp_folder)
+    /// Write file content atomically and verify it against `key`, the
+    /// blob's content hash. See [`BinarySyncManager::write_verified`].
+    pub async fn write(&self, key: &str, data: &[u8]) -> Result<bool> {
+        self.manager
+            .write_verified(&self.url, &self.fullpath, data, key)
             .await
-            .map_err(crate::core::BraidError::Client)
     }
 
     /// Delete the file.
@@ -256,9 +451,166 @@ pub fn should_use_binary_sync(path: &str) -> bool {
     is_binary(path)
 }
 
+/// Whether `sample`, the start of a file, looks binary: it contains a NUL or
+/// isn't UTF-8. A character cut off by the end of the sample doesn't count.
+pub fn looks_binary(sample: &[u8]) -> bool {
+    if sample.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => false,
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+/// Read up to [`SNIFF_LEN`] bytes from the start of `path`.
+async fn read_sample(path: &Path) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+
+    let file = tokio::fs::File::open(path).await?;
+    let mut sample = Vec::with_capacity(SNIFF_LEN);
+    file.take(SNIFF_LEN as u64).read_to_end(&mut sample).await?;
+    Ok(sample)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bytes::Bytes;
+
+    async fn manager(dir: &Path) -> Arc<BinarySyncManager> {
+        let blob_store = Arc::new(
+            BlobStore::new(dir.join("blobs"), dir.join("meta.sqlite"))
+                .await
+                .unwrap(),
+        );
+        Arc::new(BinarySyncManager::with_braidfs_dir(
+            Arc::new(ReconnectRateLimiter::new(100)),
+            blob_store,
+            &dir.join(".braidfs"),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_verified_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(dir.path()).await;
+        let path = dir.path().join("image.png");
+        let data = b"\x89PNG\r\n\x1a\n";
+        let hash = format!("{:x}", Sha256::digest(data));
+
+        assert!(manager
+            .write_verified("https://braid.org/image.png", &path, data, &hash)
+            .await
+            .unwrap());
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        assert!(
+            !manager
+                .has_pending_write_retry("https://braid.org/image.png")
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_hash_removes_file_and_queues_retry() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(dir.path()).await;
+        let path = dir.path().join("image.png");
+        let url = "https://braid.org/image.png";
+
+        let verified = manager
+            .write_verified(url, &path, b"truncated", &"0".repeat(64))
+            .await
+            .unwrap();
+        assert!(!verified);
+        assert!(!path.exists());
+        assert!(manager.has_pending_write_retry(url).await);
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_is_retried_from_blob_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(dir.path()).await;
+        let path = dir.path().join("image.png");
+        let url = "https://braid.org/image.png";
+        let data = b"\x89PNG\r\n\x1a\n";
+        let hash = manager
+            .blob_store()
+            .put_with_type(Bytes::from_static(data), None)
+            .await
+            .unwrap();
+        let db = BinarySyncDb::new(url.to_string(), path.clone(), manager.clone());
+
+        assert!(!db.write(&hash, b"truncated").await.unwrap());
+        assert!(manager.has_pending_write_retry(url).await);
+
+        // The first retry runs after BACKOFF_INITIAL
+        for _ in 0..50 {
+            if !manager.has_pending_write_retry(url).await {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert!(!manager.has_pending_write_retry(url).await);
+        assert_eq!(db.read(&hash).await.unwrap().unwrap(), data);
+    }
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary(b"plain text\n"));
+        assert!(looks_binary(b"PK\x03\x04\0\0"));
+        assert!(looks_binary(b"caf\xe9 in latin-1"));
+        // "é" cut in half by the sample boundary
+        assert!(!looks_binary(&"café".as_bytes()[..4]));
+    }
+
+    #[tokio::test]
+    async fn test_binary_content_with_txt_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(dir.path()).await;
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+
+        assert!(
+            manager
+                .is_binary_file("https://braid.org/notes.txt", &path)
+                .await
+        );
+        // The decision is cached until the file is forgotten
+        std::fs::write(&path, "now text").unwrap();
+        assert!(
+            manager
+                .is_binary_file("https://braid.org/notes.txt", &path)
+                .await
+        );
+        manager.forget_file_kind(&path).await;
+        assert!(
+            !manager
+                .is_binary_file("https://braid.org/notes.txt", &path)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_binary_extension_overrides_sniffing() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(dir.path()).await;
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, "héllo wörld\n").unwrap();
+
+        assert!(
+            manager
+                .is_binary_file("https://braid.org/data.bin", &path)
+                .await
+        );
+        // Nothing to sniff yet: text unless the extension says otherwise
+        let missing = dir.path().join("new.md");
+        assert!(
+            !manager
+                .is_binary_file("https://braid.org/new.md", &missing)
+                .await
+        );
+    }
 
     #[test]
     fn test_should_use_binary_sync() {
@@ -8,14 +8,15 @@ pub async fn handle_get_blob(
     let key = format!("blob:{}", hash);
     if let Ok(Some((bytes, meta))) = state.binary_sync.blob_store().get(&key).await {
         let mut headers = axum::http::HeaderMap::new();
-        if let Some(ct) = meta.content_type {
-            headers.insert(
-                axum::http::header::CONTENT_TYPE,
-                ct.parse().unwrap_or(axum::http::HeaderValue::from_static(
-                    "application/octet-stream",
-                )),
-            );
-        }
+        let ct = meta
+            .content_type
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        headers.insert(
+            axum::http::header::CONTENT_TYPE,
+            ct.parse().unwrap_or(axum::http::HeaderValue::from_static(
+                "application/octet-stream",
+            )),
+        );
         (headers, bytes).into_response()
     } else {
         axum::http::StatusCode::NOT_FOUND.into_response()
@@ -1,7 +1,9 @@
 use crate::core::{BraidError, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -10,6 +12,16 @@ pub struct Config {
     pub peer_id: String,
     #[serde(default)]
     pub sync: HashMap<String, bool>,
+    /// Synced URLs whose subscription is temporarily suspended
+    #[serde(default)]
+    pub paused: HashSet<String>,
+    /// Subscriptions dropped from `sync` at startup. An entry matches a URL
+    /// exactly, or as a prefix when it ends in `*`.
+    #[serde(default)]
+    pub sync_denylist: Vec<String>,
+    /// If non-empty, only subscriptions matching an entry are kept at startup
+    #[serde(default)]
+    pub sync_allowlist: Vec<String>,
     #[serde(default)]
     pub cookies: HashMap<String, String>,
     #[serde(default)]
@@ -22,17 +34,129 @@ pub struct Config {
     /// Debounce delay in milliseconds for file changes
     #[serde(default = "default_debounce_ms")]
     pub debounce_ms: u64,
+    /// Window in milliseconds over which raw watcher events for a path are
+    /// merged into one change before syncing
+    #[serde(default = "default_coalesce_ms")]
+    pub coalesce_ms: u64,
+    /// How long in milliseconds the watcher ignores a path after the daemon
+    /// writes it, so our own writes aren't synced back
+    #[serde(default = "default_pending_write_ms")]
+    pub pending_write_ms: u64,
+    /// Gitignore-style globs for paths the watcher and scanner never sync
+    #[serde(default = "default_exclude_patterns")]
+    pub exclude: Vec<String>,
+    /// Seconds between full rescans that catch changes the watcher missed
+    #[serde(default = "default_scan_interval_secs")]
+    pub scan_interval_secs: u64,
+    /// Rely solely on the filesystem watcher.
+    ///
+    /// Network drives (SMB/NFS mounts) often don't deliver change events, so
+    /// leave the scanner enabled when the BraidFS root lives on one.
+    #[serde(default)]
+    pub disable_scanner: bool,
+    /// What to do with a local edit when the server has diverged from it
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
 }
 
+/// How `sync_local_to_remote` resolves a local edit that conflicts with
+/// different content already on the server.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Accept the server content and drop the local edit
+    Overwrite,
+    /// Accept the server content, saving the local edit to a
+    /// `{name}.conflict-{timestamp}` file next to it
+    #[default]
+    KeepBackup,
+    /// Push the local edit on top of the server version
+    PreferLocal,
+}
+
+fn default_scan_interval_secs() -> u64 {
+    60
+}
+
+/// Whether `notify` delivers reliable recursive events on this platform.
+const RELIABLE_RECURSIVE_WATCH: bool =
+    cfg!(any(target_os = "linux", target_os = "macos", target_os = "windows"));
+
 fn default_debounce_ms() -> u64 {
     10  // Live sync: 10ms debounce for near-instant collaboration
 }
 
+/// Default for [`Config::coalesce_ms`]
+pub const DEFAULT_COALESCE_WINDOW: Duration = Duration::from_millis(150);
+
+fn default_coalesce_ms() -> u64 {
+    DEFAULT_COALESCE_WINDOW.as_millis() as u64
+}
+
+/// Default for [`Config::pending_write_ms`]
+pub const DEFAULT_PENDING_WRITE_WINDOW: Duration = Duration::from_millis(100);
+
+fn default_pending_write_ms() -> u64 {
+    DEFAULT_PENDING_WRITE_WINDOW.as_millis() as u64
+}
+
 fn default_port() -> u16 {
     45678
 }
 
 impl Config {
+    /// Interval for the periodic scanner, or `None` if it should not run.
+    ///
+    /// `disable_scanner` is ignored on platforms without reliable recursive
+    /// watch events, since the scanner is the only way to see changes there.
+    pub fn scan_interval(&self) -> Option<Duration> {
+        if self.disable_scanner {
+            if RELIABLE_RECURSIVE_WATCH {
+                return None;
+            }
+            tracing::warn!("disable_scanner ignored: recursive watching is unreliable here");
+        }
+        Some(Duration::from_secs(self.scan_interval_secs.max(1)))
+    }
+
+    /// How long the watcher waits for a path to go quiet before syncing it
+    pub fn coalesce_window(&self) -> Duration {
+        Duration::from_millis(self.coalesce_ms)
+    }
+
+    /// How long our own writes are hidden from the watcher
+    pub fn pending_write_window(&self) -> Duration {
+        Duration::from_millis(self.pending_write_ms)
+    }
+
+    /// Drop subscriptions the deny/allow lists rule out, returning how many
+    pub fn prune_sync(&mut self) -> usize {
+        let mut removed = Vec::new();
+        let denylist = &self.sync_denylist;
+        let allowlist = &self.sync_allowlist;
+
+        self.sync.retain(|url, _| {
+            if let Some(pattern) = denylist.iter().find(|p| url_matches(p, url)) {
+                tracing::warn!(
+                    "[Config] Removing subscription {} (denylisted by {})",
+                    url,
+                    pattern
+                );
+            } else if !allowlist.is_empty() && !allowlist.iter().any(|p| url_matches(p, url)) {
+                tracing::warn!("[Config] Removing subscription {} (not in sync_allowlist)", url);
+            } else {
+                return true;
+            }
+            removed.push(url.clone());
+            false
+        });
+
+        for url in &removed {
+            self.paused.remove(url);
+        }
+        removed.len()
+    }
+
     pub async fn load() -> Result<Self> {
         let config_path = get_config_path()?;
 
@@ -102,12 +226,78 @@ impl Default for Config {
         Self {
             peer_id: format!("braidfs_{}", &uuid::Uuid::new_v4().to_string()[..8]),
             sync: HashMap::new(),
+            paused: HashSet::new(),
+            sync_denylist: Vec::new(),
+            sync_allowlist: Vec::new(),
             cookies: HashMap::new(),
             identities: HashMap::new(),
             port: default_port(),
             ignore_patterns: default_ignore_patterns(),
             debounce_ms: default_debounce_ms(),
+            coalesce_ms: default_coalesce_ms(),
+            pending_write_ms: default_pending_write_ms(),
+            exclude: default_exclude_patterns(),
+            scan_interval_secs: default_scan_interval_secs(),
+            disable_scanner: false,
+            conflict_policy: ConflictPolicy::default(),
+        }
+    }
+}
+
+/// Default exclude patterns (editor swap files, VCS metadata, OS junk)
+fn default_exclude_patterns() -> Vec<String> {
+    vec![
+        "*.swp".to_string(),
+        "*~".to_string(),
+        ".git/**".to_string(),
+        ".DS_Store".to_string(),
+    ]
+}
+
+/// Compiled form of [`Config::exclude`].
+///
+/// Patterns follow gitignore conventions: a leading `/` anchors the pattern to
+/// the root, otherwise it may match at any depth. `*` never crosses a `/`.
+#[derive(Debug, Clone)]
+pub struct ExcludeMatcher {
+    root: PathBuf,
+    set: GlobSet,
+}
+
+impl ExcludeMatcher {
+    pub fn new(root: PathBuf, patterns: &[String]) -> Self {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let globs = match pattern.strip_prefix('/') {
+                Some(anchored) => vec![anchored.to_string()],
+                None => vec![pattern.clone(), format!("**/{}", pattern)],
+            };
+            for glob in globs {
+                match GlobBuilder::new(&glob).literal_separator(true).build() {
+                    Ok(g) => {
+                        builder.add(g);
+                    }
+                    Err(e) => tracing::warn!("Invalid exclude pattern '{}': {}", pattern, e),
+                }
+            }
         }
+        let set = builder.build().unwrap_or_else(|e| {
+            tracing::warn!("Failed to compile exclude patterns: {}", e);
+            GlobSet::empty()
+        });
+        Self { root, set }
+    }
+
+    /// The BraidFS root that patterns are matched relative to.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Check whether `path` (absolute, or relative to the root) is excluded.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let rel = path.strip_prefix(&self.root).unwrap_or(path);
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        self.set.is_match(rel.trim_start_matches('/'))
     }
 }
 
@@ -168,11 +358,41 @@ pub fn is_binary(filename: &str) -> bool {
         .any(|ext| filename_lower.ends_with(ext))
 }
 
+/// Marker in the names of files holding the losing side of a conflict.
+const CONFLICT_MARKER: &str = ".conflict-";
+
+/// Sibling of `path` to save a conflicting local edit in:
+/// `page.md` becomes `page.conflict-{timestamp}.md`.
+pub fn conflict_backup_path(path: &Path, timestamp: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}{}{}.{}", stem, CONFLICT_MARKER, timestamp, ext.to_string_lossy()),
+        None => format!("{}{}{}", stem, CONFLICT_MARKER, timestamp),
+    };
+    path.with_file_name(name)
+}
+
+/// Match a deny/allow list entry: exact, or a prefix if it ends in `*`.
+fn url_matches(pattern: &str, url: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => url.starts_with(prefix),
+        None => url == pattern,
+    }
+}
+
+/// Whether `name` is a conflict backup, which is never synced.
+pub fn is_conflict_backup(name: &str) -> bool {
+    name.contains(CONFLICT_MARKER)
+}
+
 /// Check if a path should be skipped during sync.
 pub fn skip_file(path: &str) -> bool {
     if path.contains('#') {
         return true;
     }
+    if is_conflict_backup(path) {
+        return true;
+    }
     if path.ends_with(".DS_Store") {
         return true;
     }
@@ -207,3 +427,98 @@ pub async fn trash_file(fullpath: &std::path::Path, path: &str) -> Result<PathBu
 
     Ok(dest)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclude_matcher_defaults() {
+        let root = PathBuf::from("/sync");
+        let matcher = ExcludeMatcher::new(root.clone(), &default_exclude_patterns());
+
+        assert!(matcher.is_excluded(&root.join("braid.org/.page.md.swp")));
+        assert!(matcher.is_excluded(&root.join("notes.md~")));
+        assert!(matcher.is_excluded(&root.join("repo/.git/HEAD")));
+        assert!(matcher.is_excluded(&root.join(".git/objects/ab/cdef")));
+        assert!(matcher.is_excluded(&root.join("a/b/.DS_Store")));
+        assert!(!matcher.is_excluded(&root.join("braid.org/page.md")));
+        assert!(!matcher.is_excluded(&root.join("braid.org/gitignore")));
+    }
+
+    #[test]
+    fn test_scan_interval() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.scan_interval(), Some(Duration::from_secs(60)));
+
+        let config: Config = serde_json::from_str(r#"{"scan_interval_secs": 5}"#).unwrap();
+        assert_eq!(config.scan_interval(), Some(Duration::from_secs(5)));
+
+        let config: Config = serde_json::from_str(r#"{"disable_scanner": true}"#).unwrap();
+        assert_eq!(config.scan_interval().is_none(), RELIABLE_RECURSIVE_WATCH);
+    }
+
+    #[test]
+    fn test_conflict_backup_path() {
+        let backup = conflict_backup_path(Path::new("/sync/braid.org/page.md"), "20260101T000000");
+        assert_eq!(backup, Path::new("/sync/braid.org/page.conflict-20260101T000000.md"));
+        assert!(skip_file("braid.org/page.conflict-20260101T000000.md"));
+
+        let backup = conflict_backup_path(Path::new("/sync/braid.org/notes"), "1");
+        assert_eq!(backup, Path::new("/sync/braid.org/notes.conflict-1"));
+
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.conflict_policy, ConflictPolicy::KeepBackup);
+        let config: Config =
+            serde_json::from_str(r#"{"conflict_policy": "prefer_local"}"#).unwrap();
+        assert_eq!(config.conflict_policy, ConflictPolicy::PreferLocal);
+    }
+
+    #[test]
+    fn test_exclude_matcher_anchored() {
+        let root = PathBuf::from("/sync");
+        let matcher = ExcludeMatcher::new(root.clone(), &["/build/**".to_string()]);
+
+        assert!(matcher.is_excluded(&root.join("build/out.txt")));
+        assert!(!matcher.is_excluded(&root.join("src/build/out.txt")));
+    }
+
+    #[test]
+    fn test_prune_sync_keeps_user_subscriptions() {
+        let mut config: Config = serde_json::from_str(
+            r#"{
+                "sync": {
+                    "https://braid.org/tino": true,
+                    "https://mail.braid.org/feed": true,
+                    "https://example.org/old/page": true,
+                    "https://example.org/notes": true
+                },
+                "paused": ["https://example.org/old/page"],
+                "sync_denylist": ["https://example.org/old/*"]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.prune_sync(), 1);
+        assert!(config.sync.contains_key("https://braid.org/tino"));
+        assert!(config.sync.contains_key("https://mail.braid.org/feed"));
+        assert!(config.sync.contains_key("https://example.org/notes"));
+        assert!(!config.sync.contains_key("https://example.org/old/page"));
+        assert!(config.paused.is_empty());
+
+        config.sync_allowlist = vec!["https://braid.org/*".to_string()];
+        assert_eq!(config.prune_sync(), 2);
+        let urls: Vec<_> = config.sync.keys().collect();
+        assert_eq!(urls, vec!["https://braid.org/tino"]);
+    }
+
+    #[test]
+    fn test_prune_sync_is_a_no_op_by_default() {
+        let mut config = Config::default();
+        config.sync.insert("https://braid.org/tino_test".to_string(), true);
+        config.sync.insert("https://braid.org/".to_string(), false);
+
+        assert_eq!(config.prune_sync(), 0);
+        assert_eq!(config.sync.len(), 2);
+    }
+}
@@ -1,59 +1,326 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 use tokio::sync::RwLock;
 use tokio::time::{self, Duration, Instant};
 use tracing::{error, info};
 
 use crate::fs::state::DaemonState;
-use crate::fs::sync::sync_local_to_remote;
+use crate::fs::sync::{sync_binary_to_remote, sync_local_to_remote};
+
+/// A raw filesystem change, before coalescing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawChange {
+    Create,
+    Modify,
+    Remove,
+    /// The old name of a renamed path
+    RenameFrom,
+    /// The new name of a renamed path
+    RenameTo,
+}
+
+/// One logical change produced by [`DebouncedSyncManager::drain_changes`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Moved { from: PathBuf },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoalescedChange {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+}
+
+impl CoalescedChange {
+    /// Events to replay through the watcher; a move becomes a remove and a create
+    pub fn into_events(self) -> Vec<notify::Event> {
+        use notify::event::{CreateKind, DataChange, EventKind, ModifyKind, RemoveKind};
+
+        let event = |kind: EventKind, path: PathBuf| notify::Event::new(kind).add_path(path);
+        match self.kind {
+            ChangeKind::Created => vec![event(EventKind::Create(CreateKind::Any), self.path)],
+            ChangeKind::Modified => vec![event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Any)),
+                self.path,
+            )],
+            ChangeKind::Removed => vec![event(EventKind::Remove(RemoveKind::Any), self.path)],
+            ChangeKind::Moved { from } => vec![
+                event(EventKind::Remove(RemoveKind::Any), from),
+                event(EventKind::Create(CreateKind::Any), self.path),
+            ],
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PendingChange {
+    /// `None` for a path created and removed within the window
+    kind: Option<ChangeKind>,
+    deadline: Instant,
+    /// Removed by a rename whose new name hasn't been seen yet
+    rename_source: bool,
+}
+
+/// Raw events per path, waiting out the coalescing window
+#[derive(Debug)]
+struct PendingChanges {
+    window: Duration,
+    pending: HashMap<PathBuf, PendingChange>,
+}
+
+impl PendingChanges {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: HashMap::new(),
+        }
+    }
+
+    fn push_event(&mut self, event: &notify::Event, now: Instant) {
+        use notify::event::{EventKind, ModifyKind, RenameMode};
+
+        let raw = match event.kind {
+            EventKind::Access(_) | EventKind::Other => return,
+            EventKind::Create(_) => RawChange::Create,
+            EventKind::Remove(_) => RawChange::Remove,
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => RawChange::RenameFrom,
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => RawChange::RenameTo,
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                self.push(&event.paths[0], RawChange::RenameFrom, now);
+                self.push(&event.paths[1], RawChange::RenameTo, now);
+                return;
+            }
+            // Some platforms don't say which side of a rename this is
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                for path in &event.paths {
+                    let raw = if path.exists() {
+                        RawChange::RenameTo
+                    } else {
+                        RawChange::RenameFrom
+                    };
+                    self.push(path, raw, now);
+                }
+                return;
+            }
+            EventKind::Modify(_) | EventKind::Any => RawChange::Modify,
+        };
+
+        for path in &event.paths {
+            self.push(path, raw, now);
+        }
+    }
+
+    fn push(&mut self, path: &Path, raw: RawChange, now: Instant) {
+        let deadline = now + self.window;
+        let existing = self.pending.remove(path);
+        let prior = existing.as_ref().map(|p| p.kind.clone());
+
+        let kind = match raw {
+            RawChange::Modify => match prior {
+                Some(Some(kind @ (ChangeKind::Created | ChangeKind::Moved { .. }))) => Some(kind),
+                _ => Some(ChangeKind::Modified),
+            },
+            RawChange::Create | RawChange::RenameTo => match prior {
+                // Deleted and written again, e.g. an editor replacing the file
+                Some(Some(ChangeKind::Removed)) => Some(ChangeKind::Modified),
+                Some(Some(kind)) => Some(kind),
+                _ => match self.take_move_source(path, raw == RawChange::RenameTo) {
+                    Some(from) => Some(ChangeKind::Moved { from }),
+                    None => Some(ChangeKind::Created),
+                },
+            },
+            RawChange::Remove | RawChange::RenameFrom => match prior {
+                Some(Some(ChangeKind::Created)) | Some(None) => None,
+                Some(Some(ChangeKind::Moved { from })) => {
+                    // Moved and then deleted: the original path is what went away
+                    self.pending.insert(
+                        from,
+                        PendingChange {
+                            kind: Some(ChangeKind::Removed),
+                            deadline,
+                            rename_source: raw == RawChange::RenameFrom,
+                        },
+                    );
+                    return;
+                }
+                _ => Some(ChangeKind::Removed),
+            },
+        };
+
+        self.pending.insert(
+            path.to_path_buf(),
+            PendingChange {
+                kind,
+                deadline,
+                rename_source: raw == RawChange::RenameFrom,
+            },
+        );
+    }
+
+    /// Claim a pending removal as the source of a move to `path`
+    ///
+    /// A source that was itself created in the window is consumed but not
+    /// returned, so the destination reads as newly created.
+    fn take_move_source(&mut self, path: &Path, renamed: bool) -> Option<PathBuf> {
+        let is_candidate = |candidate: &Path, change: &PendingChange| {
+            let removed = matches!(change.kind, None | Some(ChangeKind::Removed));
+            if renamed {
+                removed && change.rename_source
+            } else {
+                removed && candidate.file_name() == path.file_name()
+            }
+        };
+
+        let source = self
+            .pending
+            .iter()
+            .filter(|(candidate, change)| is_candidate(candidate, change))
+            .min_by_key(|(_, change)| change.deadline)
+            .map(|(candidate, _)| candidate.clone())?;
+        let change = self.pending.remove(&source)?;
+        change.kind.map(|_| source)
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().map(|p| p.deadline).min()
+    }
+
+    fn drain_ready(&mut self, now: Instant) -> Vec<CoalescedChange> {
+        let ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, change)| change.deadline <= now)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        let mut changes: Vec<CoalescedChange> = ready
+            .into_iter()
+            .filter_map(|path| {
+                let kind = self.pending.remove(&path)?.kind?;
+                Some(CoalescedChange { path, kind })
+            })
+            .collect();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes
+    }
+}
 
 /// A request to sync a specific URL from a specific local path.
 #[derive(Debug, Clone)]
-struct DebounceRequest {
-    url: String,
-    path: PathBuf,
+pub(crate) struct DebounceRequest {
+    pub(crate) url: String,
+    pub(crate) path: PathBuf,
 }
 
 /// Manages debouncing of sync requests to prevent network flooding
 /// while maintaining high responsiveness for "sync-as-you-type".
+///
+/// Raw watcher events go through it first: they are collected per path and
+/// come out as one logical change once the path has been quiet for the
+/// coalescing window. A single editor save often fires modify+create+modify;
+/// those collapse into one change. A remove followed by a create elsewhere
+/// (a rename, or a move between directories keeping the file name)
+/// collapses into a move.
 pub struct DebouncedSyncManager {
     tx: mpsc::Sender<DebounceRequest>,
+    /// URLs requested but not yet done syncing
+    queued: Arc<Mutex<HashSet<String>>>,
+    /// Raw events not yet coalesced into changes
+    changes: Mutex<PendingChanges>,
 }
 
 impl DebouncedSyncManager {
     /// Create a placeholder manager (used for circular initialization)
     pub fn new_placeholder() -> Self {
+        Self::events_only(crate::fs::config::DEFAULT_COALESCE_WINDOW)
+    }
+
+    /// Create a manager that only coalesces events, for watchers that
+    /// report changes rather than sync them
+    pub fn events_only(coalesce_window: Duration) -> Self {
         let (tx, _) = mpsc::channel(1);
-        Self { tx }
+        Self {
+            tx,
+            queued: Arc::new(Mutex::new(HashSet::new())),
+            changes: Mutex::new(PendingChanges::new(coalesce_window)),
+        }
+    }
+
+    /// Create a manager that hands requests to the returned receiver
+    /// instead of syncing them
+    #[cfg(test)]
+    pub(crate) fn recording() -> (Self, mpsc::Receiver<DebounceRequest>) {
+        let (tx, rx) = mpsc::channel(100);
+        let manager = Self {
+            tx,
+            ..Self::new_placeholder()
+        };
+        (manager, rx)
     }
 
     /// Create a new manager and spawn its processing loop.
-    pub fn new(state: DaemonState, debounce_ms: u64) -> Arc<Self> {
+    pub fn new(state: DaemonState, debounce_ms: u64, coalesce_window: Duration) -> Arc<Self> {
         let (tx, rx) = mpsc::channel(100);
-        let manager = Arc::new(Self { tx });
+        let queued = Arc::new(Mutex::new(HashSet::new()));
+        let manager = Arc::new(Self {
+            tx,
+            queued: queued.clone(),
+            changes: Mutex::new(PendingChanges::new(coalesce_window)),
+        });
 
         // Spawn the background processing task
         let state_clone = state.clone();
         tokio::spawn(async move {
-            Self::process_loop(rx, state_clone, Duration::from_millis(debounce_ms)).await;
+            Self::process_loop(rx, state_clone, Duration::from_millis(debounce_ms), queued).await;
         });
 
         manager
     }
 
+    /// Record every path of a raw `notify` event, restarting their windows
+    pub fn push_event(&self, event: &notify::Event, now: Instant) {
+        self.changes.lock().unwrap().push_event(event, now);
+    }
+
+    /// Record one raw change to `path`, restarting its window
+    pub fn push_change(&self, path: &Path, raw: RawChange, now: Instant) {
+        self.changes.lock().unwrap().push(path, raw, now);
+    }
+
+    /// Earliest time a recorded change becomes ready
+    pub fn next_change_deadline(&self) -> Option<Instant> {
+        self.changes.lock().unwrap().next_deadline()
+    }
+
+    /// Remove and return changes whose window has passed, ordered by path
+    pub fn drain_changes(&self, now: Instant) -> Vec<CoalescedChange> {
+        self.changes.lock().unwrap().drain_ready(now)
+    }
+
     /// Request a sync for a given URL and path.
     pub async fn request_sync(&self, url: String, path: PathBuf) {
+        self.queued.lock().unwrap().insert(url.clone());
         if let Err(e) = self.tx.send(DebounceRequest { url, path }).await {
             error!("[Debouncer] Failed to send sync request: {}", e);
+            self.queued.lock().unwrap().remove(&e.0.url);
         }
     }
 
+    /// Whether a sync for `url` has been requested and hasn't finished yet.
+    pub fn is_pending(&self, url: &str) -> bool {
+        self.queued.lock().unwrap().contains(url)
+    }
+
     async fn process_loop(
         mut rx: mpsc::Receiver<DebounceRequest>,
         state: DaemonState,
         debounce_duration: Duration,
+        queued: Arc<Mutex<HashSet<String>>>,
     ) {
         // Track the latest path and the next scheduled sync time for each URL
         let pending: Arc<RwLock<HashMap<String, (PathBuf, Instant)>>> =
@@ -105,11 +372,17 @@ impl DebouncedSyncManager {
 
             for (url, path) in to_sync {
                 let state_inner = state_sync.clone();
+                let queued = queued.clone();
+                let pending = pending.clone();
                 info!("[Debouncer] Deadline expired for {}. Triggering sync.", url);
                 tokio::spawn(async move {
                     if let Err(e) = Self::perform_sync(&path, &url, state_inner).await {
                         error!("[Debouncer] Sync failed for {}: {}", url, e);
                     }
+                    // Still queued if another change arrived while syncing
+                    if !pending.read().await.contains_key(&url) {
+                        queued.lock().unwrap().remove(&url);
+                    }
                 });
             }
         }
@@ -120,6 +393,25 @@ impl DebouncedSyncManager {
         url: &str,
         state: DaemonState,
     ) -> crate::core::Result<()> {
+        if state.binary_sync.is_binary_file(url, path).await {
+            match tokio::fs::read(path).await {
+                Ok(data) => {
+                    let parents = {
+                        let store = state.version_store.read().await;
+                        store
+                            .get(url)
+                            .map(|v| v.current_version.clone())
+                            .unwrap_or_default()
+                    };
+                    return sync_binary_to_remote(path, url, &parents, data.into(), None, state)
+                        .await;
+                }
+                // Fall through to propagate the deletion
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(crate::core::BraidError::Io(e)),
+            }
+        }
+
         let mut attempts = 0;
         let mut content = None;
         while attempts < 3 {
@@ -166,3 +458,173 @@ impl DebouncedSyncManager {
         sync_local_to_remote(path, url, &parents, original_content, content, None, state).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, EventKind, ModifyKind, RemoveKind, RenameMode};
+
+    const WINDOW: Duration = Duration::from_millis(150);
+
+    fn event(kind: EventKind, paths: &[&str]) -> notify::Event {
+        paths
+            .iter()
+            .fold(notify::Event::new(kind), |e, p| e.add_path(PathBuf::from(p)))
+    }
+
+    #[test]
+    fn test_save_burst_coalesces_into_one_sync() {
+        let coalescer = DebouncedSyncManager::events_only(WINDOW);
+        let start = Instant::now();
+        let modify = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+
+        coalescer.push_event(&event(modify, &["/root/page.md"]), start);
+        coalescer.push_event(
+            &event(EventKind::Create(CreateKind::File), &["/root/page.md"]),
+            start + Duration::from_millis(5),
+        );
+        coalescer.push_event(
+            &event(modify, &["/root/page.md"]),
+            start + Duration::from_millis(40),
+        );
+
+        // Still inside the window of the last event
+        assert!(coalescer
+            .drain_changes(start + Duration::from_millis(100))
+            .is_empty());
+
+        let changes = coalescer.drain_changes(start + Duration::from_millis(40) + WINDOW);
+        assert_eq!(
+            changes,
+            vec![CoalescedChange {
+                path: PathBuf::from("/root/page.md"),
+                kind: ChangeKind::Modified,
+            }]
+        );
+        assert!(coalescer.next_change_deadline().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_burst_triggers_single_downstream_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (mut state, _rx_cmd) = crate::fs::state::test_support::test_state(&root).await;
+        let (debouncer, mut requests) = DebouncedSyncManager::recording();
+        state.debouncer = Arc::new(debouncer);
+        let page = root.join("braid.org").join("page.md");
+        std::fs::create_dir_all(page.parent().unwrap()).unwrap();
+        std::fs::write(&page, "page").unwrap();
+
+        let start = Instant::now();
+        for i in 0..10 {
+            let kind = if i % 2 == 0 {
+                EventKind::Modify(ModifyKind::Any)
+            } else {
+                EventKind::Create(CreateKind::Any)
+            };
+            let event = notify::Event::new(kind).add_path(page.clone());
+            state
+                .debouncer
+                .push_event(&event, start + Duration::from_millis(i * 10));
+        }
+
+        let deadline = state.debouncer.next_change_deadline().unwrap();
+        for change in state.debouncer.drain_changes(deadline) {
+            for event in change.into_events() {
+                crate::fs::watcher::handle_fs_event(event, state.clone()).await;
+            }
+        }
+        assert_eq!(requests.try_recv().unwrap().path, page);
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_rename_collapses_into_move() {
+        let coalescer = DebouncedSyncManager::events_only(WINDOW);
+        let now = Instant::now();
+        coalescer.push_event(
+            &event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)),
+                &["/root/old.md"],
+            ),
+            now,
+        );
+        coalescer.push_event(
+            &event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::To)),
+                &["/root/new.md"],
+            ),
+            now,
+        );
+
+        let changes = coalescer.drain_changes(now + WINDOW);
+        assert_eq!(
+            changes,
+            vec![CoalescedChange {
+                path: PathBuf::from("/root/new.md"),
+                kind: ChangeKind::Moved {
+                    from: PathBuf::from("/root/old.md"),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_remove_create_pair_collapses_into_move() {
+        let coalescer = DebouncedSyncManager::events_only(WINDOW);
+        let now = Instant::now();
+        coalescer.push_event(
+            &event(EventKind::Remove(RemoveKind::File), &["/root/a/page.md"]),
+            now,
+        );
+        coalescer.push_event(
+            &event(EventKind::Create(CreateKind::File), &["/root/b/page.md"]),
+            now,
+        );
+
+        let changes = coalescer.drain_changes(now + WINDOW);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0].kind,
+            ChangeKind::Moved {
+                from: PathBuf::from("/root/a/page.md"),
+            }
+        );
+
+        let events = changes[0].clone().into_events();
+        assert!(events[0].kind.is_remove());
+        assert_eq!(events[0].paths, vec![PathBuf::from("/root/a/page.md")]);
+        assert!(events[1].kind.is_create());
+        assert_eq!(events[1].paths, vec![PathBuf::from("/root/b/page.md")]);
+    }
+
+    #[test]
+    fn test_atomic_save_through_temp_file() {
+        let coalescer = DebouncedSyncManager::events_only(WINDOW);
+        let now = Instant::now();
+        coalescer.push_change(Path::new("/root/page.md.tmp"), RawChange::Create, now);
+        coalescer.push_change(Path::new("/root/page.md.tmp"), RawChange::Modify, now);
+        coalescer.push_change(Path::new("/root/page.md.tmp"), RawChange::RenameFrom, now);
+        coalescer.push_change(Path::new("/root/page.md"), RawChange::RenameTo, now);
+
+        let changes = coalescer.drain_changes(now + WINDOW);
+        assert_eq!(
+            changes,
+            vec![CoalescedChange {
+                path: PathBuf::from("/root/page.md"),
+                kind: ChangeKind::Created,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_created_then_removed_emits_nothing() {
+        let coalescer = DebouncedSyncManager::events_only(WINDOW);
+        let now = Instant::now();
+        coalescer.push_change(Path::new("/root/scratch.md"), RawChange::Create, now);
+        coalescer.push_change(Path::new("/root/scratch.md"), RawChange::Remove, now);
+
+        assert!(coalescer.drain_changes(now + WINDOW).is_empty());
+        assert!(coalescer.next_change_deadline().is_none());
+    }
+}
@@ -81,6 +81,8 @@ impl LocalBraidServer {
     }
 
     /// Poll loop - only polls when there are active subscribers
+    ///
+    /// Only URLs the activity tracker considers active are polled.
     async fn poll_loop(&self) {
         let mut interval = interval(self.poll_interval);
         let grace_period = Duration::from_secs(30); // Keep managing for 30s after disconnect
@@ -90,13 +92,16 @@ impl LocalBraidServer {
 
             // Find URLs with active subscribers and update last_active
             let urls_to_poll: Vec<String> = {
+                let active = self.daemon_state.tracker.active_urls();
                 let mut subs = self.subscriptions.write().await;
                 let mut active_urls = Vec::new();
 
                 for (url, state) in subs.iter_mut() {
                     if state.tx.receiver_count() > 0 {
                         state.last_active = std::time::Instant::now();
-                        active_urls.push(url.clone());
+                        if active.binary_search(url).is_ok() {
+                            active_urls.push(url.clone());
+                        }
                     }
                 }
                 active_urls
@@ -184,6 +189,7 @@ impl LocalBraidServer {
                 };
 
                 let _ = state.tx.send(update);
+                self.daemon_state.tracker.mark(url);
 
                 // Update stored state
                 state.last_version = current_version.clone();
@@ -277,6 +283,7 @@ async fn subscribe_handler(
     axum::extract::State(state): axum::extract::State<Arc<LocalBraidServer>>,
 ) -> Response {
     info!("[LocalBraidServer] New subscription request for: {}", url);
+    state.daemon_state.tracker.mark(&url);
 
     // Ensure URL is registered
     {
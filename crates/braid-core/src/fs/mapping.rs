@@ -41,10 +41,13 @@ pub fn url_to_path(url_str: &str) -> Result<PathBuf> {
 }
 
 pub fn path_to_url(path: &Path) -> Result<String> {
-    let root = get_root_dir()?;
+    path_to_url_in(&get_root_dir()?, path)
+}
 
+/// Map `path` to its URL, for a BraidFS rooted at `root`.
+pub fn path_to_url_in(root: &Path, path: &Path) -> Result<String> {
     // Canonicalize both paths to ensure matching prefix format (e.g. \\?\ prefix and casing)
-    let root_abs = std::fs::canonicalize(&root).unwrap_or_else(|_| root.clone());
+    let root_abs = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
     let path_abs = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
 
     let relative = path_abs.strip_prefix(&root_abs).map_err(|_| {
@@ -0,0 +1,224 @@
+//! Persistent merge state for open documents.
+//!
+//! Snapshots of each URL's merge type are kept in a small sqlite table so the
+//! daemon can rehydrate `active_merges` on startup instead of re-subscribing
+//! from scratch. Every version applied to a URL is also kept, so past
+//! versions can be listed and rebuilt.
+
+use crate::core::merge::{History, MergeType, Revision, SimpletonMergeType};
+use crate::core::{BraidError, Result};
+use parking_lot::Mutex as PMutex;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct MergeStore {
+    conn: Arc<PMutex<Connection>>,
+}
+
+impl MergeStore {
+    /// Open (or create) the snapshot database at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .map_err(|e| BraidError::Fs(format!("Failed to open merge DB: {}", e)))?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS merge_snapshots (
+            url TEXT PRIMARY KEY,
+            merge_type TEXT NOT NULL,
+            snapshot TEXT NOT NULL
+        )",
+            [],
+        )
+        .map_err(|e| BraidError::Fs(format!("Failed to create merge_snapshots table: {}", e)))?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS revisions (
+            url TEXT NOT NULL,
+            version TEXT NOT NULL,
+            revision TEXT NOT NULL,
+            PRIMARY KEY (url, version)
+        )",
+            [],
+        )
+        .map_err(|e| BraidError::Fs(format!("Failed to create revisions table: {}", e)))?;
+        Ok(Self {
+            conn: Arc::new(PMutex::new(conn)),
+        })
+    }
+
+    /// Persist the current state of `merge` for `url`.
+    ///
+    /// Merge types that don't support snapshots are skipped.
+    pub fn save(&self, url: &str, merge: &dyn MergeType) -> Result<()> {
+        let Some(snapshot) = merge.snapshot() else {
+            return Ok(());
+        };
+        self.conn
+            .lock()
+            .execute(
+                "INSERT OR REPLACE INTO merge_snapshots (url, merge_type, snapshot) VALUES (?1, ?2, ?3)",
+                params![url, merge.name(), snapshot.to_string()],
+            )
+            .map_err(|e| BraidError::Fs(format!("Failed to save merge snapshot: {}", e)))?;
+        Ok(())
+    }
+
+    /// Forget the persisted state and history for `url`.
+    pub fn remove(&self, url: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM merge_snapshots WHERE url = ?1", params![url])
+            .map_err(|e| BraidError::Fs(format!("Failed to remove merge snapshot: {}", e)))?;
+        conn.execute("DELETE FROM revisions WHERE url = ?1", params![url])
+            .map_err(|e| BraidError::Fs(format!("Failed to remove history: {}", e)))?;
+        Ok(())
+    }
+
+    /// Record `version` of `url` as the change from `before` to `after`.
+    ///
+    /// Versions already in the history are ignored.
+    pub fn record_revision(
+        &self,
+        url: &str,
+        version: &str,
+        parents: Vec<String>,
+        before: &str,
+        after: &str,
+    ) -> Result<()> {
+        let mut history = self.history(url)?;
+        if !history.record(version, parents, before, after) {
+            return Ok(());
+        }
+        let Some(revision) = history.revisions.last() else {
+            return Ok(());
+        };
+        let revision = serde_json::to_string(revision)
+            .map_err(|e| BraidError::Fs(format!("Failed to encode revision: {}", e)))?;
+        self.conn
+            .lock()
+            .execute(
+                "INSERT OR IGNORE INTO revisions (url, version, revision) VALUES (?1, ?2, ?3)",
+                params![url, version, revision],
+            )
+            .map_err(|e| BraidError::Fs(format!("Failed to save revision: {}", e)))?;
+        Ok(())
+    }
+
+    /// Every recorded revision of `url`, oldest first.
+    pub fn history(&self, url: &str) -> Result<History> {
+        let conn = self.conn.lock();
+        let mut stmt = conn
+            .prepare("SELECT revision FROM revisions WHERE url = ?1 ORDER BY rowid")
+            .map_err(|e| BraidError::Fs(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![url], |row| row.get::<_, String>(0))
+            .map_err(|e| BraidError::Fs(e.to_string()))?;
+
+        let mut history = History::default();
+        for json in rows.flatten() {
+            match serde_json::from_str::<Revision>(&json) {
+                Ok(revision) => history.revisions.push(revision),
+                Err(e) => tracing::warn!("[MergeStore] Corrupt revision for {}: {}", url, e),
+            }
+        }
+        Ok(history)
+    }
+
+    /// Load every persisted merge, keyed by URL.
+    ///
+    /// Rows that fail to deserialize or use an unknown merge type are skipped.
+    pub fn load_all(&self) -> Result<HashMap<String, Box<dyn MergeType>>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn
+            .prepare("SELECT url, merge_type, snapshot FROM merge_snapshots")
+            .map_err(|e| BraidError::Fs(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| BraidError::Fs(e.to_string()))?;
+
+        let mut merges: HashMap<String, Box<dyn MergeType>> = HashMap::new();
+        for (url, merge_type, snapshot) in rows.flatten() {
+            match merge_type.as_str() {
+                "simpleton" => match serde_json::from_str(&snapshot) {
+                    Ok(snapshot) => {
+                        merges.insert(url, Box::new(SimpletonMergeType::restore(snapshot)));
+                    }
+                    Err(e) => {
+                        tracing::warn!("[MergeStore] Corrupt snapshot for {}: {}", url, e);
+                    }
+                },
+                other => {
+                    tracing::warn!(
+                        "[MergeStore] Cannot restore merge type {} for {}",
+                        other,
+                        url
+                    );
+                }
+            }
+        }
+        Ok(merges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::merge::MergePatch;
+    use serde_json::Value;
+
+    #[test]
+    fn test_merge_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MergeStore::open(&dir.path().join("merges.sqlite")).unwrap();
+
+        let mut merge = SimpletonMergeType::new("peer");
+        merge.local_edit(MergePatch::new(
+            "everything",
+            Value::String("abc".to_string()),
+        ));
+        store.save("https://example.org/doc", &merge).unwrap();
+
+        let reopened = MergeStore::open(&dir.path().join("merges.sqlite")).unwrap();
+        let loaded = reopened.load_all().unwrap();
+        let restored = loaded.get("https://example.org/doc").unwrap();
+        assert_eq!(restored.get_content(), "abc");
+        assert_eq!(restored.get_version(), merge.get_version());
+
+        reopened.remove("https://example.org/doc").unwrap();
+        assert!(reopened.load_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_history_lists_and_rebuilds_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MergeStore::open(&dir.path().join("merges.sqlite")).unwrap();
+        let url = "https://braid.org/page";
+
+        let edits = ["one", "one two", "one 2"];
+        let mut before = "";
+        for (i, after) in edits.iter().enumerate() {
+            let parents = (i > 0).then(|| format!("v{}", i)).into_iter().collect();
+            store
+                .record_revision(url, &format!("v{}", i + 1), parents, before, after)
+                .unwrap();
+            before = after;
+        }
+        store.record_revision(url, "v2", vec![], "x", "y").unwrap();
+
+        let reopened = MergeStore::open(&dir.path().join("merges.sqlite")).unwrap();
+        let history = reopened.history(url).unwrap();
+        let versions: Vec<_> = history.list().into_iter().map(|r| r.version).collect();
+        assert_eq!(versions, ["v1", "v2", "v3"]);
+        assert_eq!(history.content_at("v2").as_deref(), Some("one two"));
+        assert_eq!(history.content_at("v3").as_deref(), Some("one 2"));
+
+        reopened.remove(url).unwrap();
+        assert!(reopened.history(url).unwrap().revisions.is_empty());
+    }
+}
@@ -25,6 +25,7 @@ pub mod debouncer;
 pub mod diff;
 pub mod local_server;
 pub mod mapping;
+pub mod merge_store;
 #[cfg(feature = "nfs")]
 pub mod mount;
 #[cfg(feature = "nfs")]
@@ -39,7 +40,7 @@ pub mod versions;
 pub mod watcher;
 
 use state::{Command, DaemonState};
-use subscription::spawn_subscription;
+use subscription::{pause_subscription, resume_subscription, spawn_subscription};
 use watcher::handle_fs_event;
 
 lazy_static::lazy_static! {
@@ -50,22 +51,34 @@ lazy_static::lazy_static! {
 pub struct PendingWrites {
     // Map path -> Expiration Time (when we stop ignoring it)
     paths: Arc<Mutex<HashMap<String, std::time::Instant>>>,
+    window: Duration,
 }
 
 impl PendingWrites {
     pub fn new() -> Self {
+        Self::with_window(config::DEFAULT_PENDING_WRITE_WINDOW)
+    }
+
+    /// Ignore watcher events for a written path for `window`
+    pub fn with_window(window: Duration) -> Self {
         Self {
             paths: Arc::new(Mutex::new(HashMap::new())),
+            window,
         }
     }
 
     fn normalize(path: &std::path::Path) -> String {
-        path.to_string_lossy().to_lowercase().replace('\\', "/")
+        // Only Windows paths are case-insensitive; elsewhere `Foo.md` and
+        // `foo.md` are different files
+        if cfg!(windows) {
+            path.to_string_lossy().to_lowercase().replace('\\', "/")
+        } else {
+            path.to_string_lossy().into_owned()
+        }
     }
 
     pub fn add(&self, path: PathBuf) {
-        // Ignore events for this path for 100ms
-        let expiry = std::time::Instant::now() + Duration::from_millis(100);
+        let expiry = std::time::Instant::now() + self.window;
         self.paths
             .lock()
             .unwrap()
@@ -92,69 +105,75 @@ impl PendingWrites {
     }
 }
 
+/// How long a URL counts as active after its last activity by default
+pub const DEFAULT_ACTIVITY_WINDOW: Duration = Duration::from_secs(600);
+
 #[derive(Clone)]
 pub struct ActivityTracker {
     // Map URL -> Last Activity Time
     activity: Arc<Mutex<HashMap<String, std::time::Instant>>>,
+    window: Duration,
 }
 
 impl ActivityTracker {
-    pub fn new() -> Self {
+    /// Track URLs that count as active for `window` after each `mark`
+    pub fn new(window: Duration) -> Self {
         Self {
             activity: Arc::new(Mutex::new(HashMap::new())),
+            window,
         }
     }
 
     pub fn mark(&self, url: &str) {
+        self.mark_at(url, std::time::Instant::now());
+    }
+
+    fn mark_at(&self, url: &str, at: std::time::Instant) {
         let mut activity = self.activity.lock().unwrap();
-        activity.insert(url.to_string(), std::time::Instant::now());
+        activity.insert(url.to_string(), at);
+    }
+
+    /// When `url` last saw activity, if ever
+    pub fn last_activity(&self, url: &str) -> Option<std::time::Instant> {
+        self.activity.lock().unwrap().get(url).copied()
     }
 
     pub fn is_active(&self, url: &str) -> bool {
-        // Log is active if there was activity in the last 10 minutes
+        let now = std::time::Instant::now();
+        self.last_activity(url)
+            .is_some_and(|last_time| now.saturating_duration_since(last_time) < self.window)
+    }
+
+    /// URLs with activity inside the window, sorted
+    pub fn active_urls(&self) -> Vec<String> {
+        self.active_urls_at(std::time::Instant::now())
+    }
+
+    fn active_urls_at(&self, now: std::time::Instant) -> Vec<String> {
         let activity = self.activity.lock().unwrap();
-        if let Some(&last_time) = activity.get(url) {
-            std::time::Instant::now().duration_since(last_time) < Duration::from_secs(600)
-        } else {
-            false
-        }
+        let mut urls: Vec<String> = activity
+            .iter()
+            .filter(|(_, &last_time)| now.saturating_duration_since(last_time) < self.window)
+            .map(|(url, _)| url.clone())
+            .collect();
+        urls.sort();
+        urls
     }
 }
 
+/// How often unreferenced blobs are purged from the blob store.
+const BLOB_GC_INTERVAL: Duration = Duration::from_secs(3600);
+
 pub async fn run_daemon(port: u16) -> Result<()> {
     let mut config = Config::load().await?;
     config.port = port;
 
-    // Filter out dead domains
-    // Force remove known problematic URLs that might persist in config
-    let bad_urls = vec![
-        "https://braid.org/Braid",
-        "https://braid.org/Main",
-        "https://braid.org/Welcome",
-        "https://braid.org/Protocol",
-        "https://braid.org/wiki",
-        "https://braid.org/about",
-        "https://braid.org/editing",
-    ];
-
-    for url in bad_urls {
-        if config.sync.contains_key(url) {
-            tracing::warn!("[Config] Purging deprecated subscription: {}", url);
-            config.sync.remove(url);
-        }
+    // Drop subscriptions ruled out by the configured deny/allow lists
+    let pruned = config.prune_sync();
+    if pruned > 0 {
+        tracing::info!("[Config] Removed {} subscription(s) at startup", pruned);
     }
 
-    // Filter out other dead/test domains
-    config.sync.retain(|url, _| {
-        !url.contains("mail.braid.org")
-            && !url.contains("braid.org/tino")
-            && !url.contains("braid.org/tino_test")
-            && !url.contains("braid.org/xfmail")
-            && !url.contains("braid.org/127_xfmail")
-            && url != "https://braid.org/"
-            && url != "https://braid.org"
-    });
-
     config.save().await?;
     let config = Arc::new(RwLock::new(config));
 
@@ -168,17 +187,8 @@ pub async fn run_daemon(port: u16) -> Result<()> {
     let content_cache = Arc::new(RwLock::new(std::collections::HashMap::new()));
 
     // Initialize Merge Registry
-    let mut merge_registry = crate::core::merge::MergeTypeRegistry::new();
-    // Antimatter removed.
     // Simpleton (braid-text) is the primary merge type for text documents
-    merge_registry.register("simpleton", |id| {
-        Box::new(crate::core::merge::simpleton::SimpletonMergeType::new(id))
-    });
-    merge_registry.register("braid-text", |id| {
-        Box::new(crate::core::merge::simpleton::SimpletonMergeType::new(id))
-    });
-    let merge_registry = Arc::new(merge_registry);
-    let active_merges = Arc::new(RwLock::new(HashMap::new()));
+    let merge_registry = Arc::new(crate::core::merge::MergeTypeRegistry::new());
 
     // Cache Warming AND Metadata Stubbing
     {
@@ -219,8 +229,12 @@ pub async fn run_daemon(port: u16) -> Result<()> {
 
     tracing::info!("BraidFS root: {:?}", root_dir);
 
-    let pending_writes = PendingWrites::new();
-    let activity_tracker = ActivityTracker::new();
+    let pending_writes = PendingWrites::with_window(config.read().await.pending_write_window());
+    let activity_tracker = ActivityTracker::new(DEFAULT_ACTIVITY_WINDOW);
+    let exclude = Arc::new(config::ExcludeMatcher::new(
+        root_dir.clone(),
+        &config.read().await.exclude,
+    ));
 
     // Setup file watcher
     let (tx_fs, mut rx_fs) = tokio::sync::mpsc::channel(100);
@@ -263,6 +277,15 @@ pub async fn run_daemon(port: u16) -> Result<()> {
         })?;
     let inode_db = Arc::new(parking_lot::Mutex::new(inode_conn));
 
+    // Rehydrate merge state persisted by the previous run
+    let merge_store = merge_store::MergeStore::open(&braidfs_dir.join("merges.sqlite"))?;
+    let restored_merges = merge_store.load_all().unwrap_or_else(|e| {
+        tracing::warn!("[BraidFS] Failed to restore merge state: {}", e);
+        HashMap::new()
+    });
+    tracing::info!("[BraidFS] Restored merge state for {} documents", restored_merges.len());
+    let active_merges = Arc::new(RwLock::new(restored_merges));
+
     let binary_sync_manager = BinarySyncManager::new(rate_limiter.clone(), blob_store.clone())
         .map_err(|e| crate::core::BraidError::Anyhow(e.to_string()))?;
     let binary_sync_manager = Arc::new(binary_sync_manager);
@@ -279,25 +302,23 @@ pub async fn run_daemon(port: u16) -> Result<()> {
             .collect::<HashMap<String, bool>>()
     }));
 
-    // Start scan loop
+    // Start scan loop (unless configured to rely on the watcher alone)
     let scan_state_clone = scan_state.clone();
     let sync_urls_clone = sync_urls_map.clone();
     let tx_fs_clone = tx_fs.clone();
-    tokio::spawn(async move {
-        start_scan_loop(
-            scan_state_clone,
-            sync_urls_clone,
-            Duration::from_secs(10), // Scan every 10s for more responsiveness during testing
-            move |path| {
-                tracing::info!("Scanner detected change in {:?}, triggering sync", path);
-                // Send a fake event to the FS watcher channel to trigger standard sync logic
-                let mut event =
-                    notify::Event::new(notify::EventKind::Modify(notify::event::ModifyKind::Any));
-                event.paths.push(path);
-                let _ = tx_fs_clone.blocking_send(event);
-            },
-        )
-        .await;
+    let exclude_scan = exclude.clone();
+    spawn_scanner(&*config.read().await, move |scan_interval| {
+        start_scan_loop(scan_state_clone, sync_urls_clone, scan_interval, move |path| {
+            if exclude_scan.is_excluded(&path) {
+                return;
+            }
+            tracing::info!("Scanner detected change in {:?}, triggering sync", path);
+            // Send a fake event to the FS watcher channel to trigger standard sync logic
+            let mut event =
+                notify::Event::new(notify::EventKind::Modify(notify::event::ModifyKind::Any));
+            event.paths.push(path);
+            let _ = tx_fs_clone.blocking_send(event);
+        })
     });
 
     let braid_client = BraidClient::new()?;
@@ -309,10 +330,13 @@ pub async fn run_daemon(port: u16) -> Result<()> {
         tracker: activity_tracker,
         merge_registry,
         active_merges,
+        merge_store,
         pending: pending_writes,
+        exclude,
         client: braid_client,
         failed_syncs,
         binary_sync: binary_sync_manager,
+        rate_limiter: rate_limiter.clone(),
         inode_db,
         tx_cmd: tx_cmd.clone(),
         debouncer: Arc::new(debouncer::DebouncedSyncManager::new_placeholder()), // Placeholder to fix circularity
@@ -320,12 +344,31 @@ pub async fn run_daemon(port: u16) -> Result<()> {
     };
 
     // Initialize the real debouncer with the state
-    let debouncer = debouncer::DebouncedSyncManager::new(state.clone(), 100);
+    let coalesce_window = state.config.read().await.coalesce_window();
+    let debouncer = debouncer::DebouncedSyncManager::new(state.clone(), 100, coalesce_window);
 
     // Update state with the real debouncer
     let mut state = state;
     state.debouncer = debouncer;
 
+    let state_gc = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(BLOB_GC_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            match state_gc.binary_sync.blob_store().gc().await {
+                Ok(report) if report.rows_removed > 0 => tracing::info!(
+                    "[BraidFS] Blob GC removed {} blobs, reclaimed {} bytes",
+                    report.rows_removed,
+                    report.bytes_reclaimed
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("[BraidFS] Blob GC failed: {}", e),
+            }
+        }
+    });
+
     let state_server = state.clone();
     tokio::spawn(async move {
         if let Err(e) = run_server(port, state_server).await {
@@ -344,6 +387,7 @@ pub async fn run_daemon(port: u16) -> Result<()> {
         println!("\n[BraidFS CONSOLE] Ready for commands.");
         println!("Available: token <domain> <value>  (e.g. token braid.org ud8zp...)");
         println!("           sync <url>               (e.g. sync https://braid.org/tino)");
+        println!("           pause <url> / resume <url>");
 
         while let Ok(Some(line)) = reader.next_line().await {
             let parts: Vec<&str> = line.split_whitespace().collect();
@@ -372,8 +416,18 @@ pub async fn run_daemon(port: u16) -> Result<()> {
                     let _ = state_console.tx_cmd.send(Command::Sync { url: url_str.clone() }).await;
                     println!("[BraidFS] Sync triggered for {}", url_str);
                 }
+                "pause" if parts.len() >= 2 => {
+                    let url = parts[1].to_string();
+                    let _ = state_console.tx_cmd.send(Command::Pause { url }).await;
+                    println!("[BraidFS] Pause requested for {}", parts[1]);
+                }
+                "resume" if parts.len() >= 2 => {
+                    let url = parts[1].to_string();
+                    let _ = state_console.tx_cmd.send(Command::Resume { url }).await;
+                    println!("[BraidFS] Resume requested for {}", parts[1]);
+                }
                 "help" => {
-                    println!("Commands: token <domain> <value>, sync <url>");
+                    println!("Commands: token <domain> <value>, sync <url>, pause <url>, resume <url>");
                 }
                 _ => {
                     println!(
@@ -393,7 +447,9 @@ pub async fn run_daemon(port: u16) -> Result<()> {
     {
         let cfg = state.config.read().await;
         for (url, enabled) in &cfg.sync {
-            if *enabled {
+            if cfg.paused.contains(url) {
+                tracing::info!("[BraidFS] Sync paused for {}, not subscribing", url);
+            } else if *enabled {
                 tracing::info!("[BraidFS] Starting subscription for {}", url);
                 spawn_subscription(url.clone(), &mut subscriptions, state.clone()).await;
             }
@@ -432,8 +488,21 @@ pub async fn run_daemon(port: u16) -> Result<()> {
                 break;
             }
 
-            Some(event) = rx_fs.recv() => {
-                handle_fs_event(event, state.clone()).await;
+            Some(mut event) = rx_fs.recv() => {
+                // Drop echoes of our own writes now, while their ignore window is open
+                event.paths.retain(|path| !state.pending.should_ignore(path));
+                // Raw events are merged per path before they are synced
+                state.debouncer.push_event(&event, tokio::time::Instant::now());
+            }
+
+            _ = tokio::time::sleep_until(
+                state.debouncer.next_change_deadline().unwrap_or_else(tokio::time::Instant::now)
+            ), if state.debouncer.next_change_deadline().is_some() => {
+                for change in state.debouncer.drain_changes(tokio::time::Instant::now()) {
+                    for event in change.into_events() {
+                        handle_fs_event(event, state.clone()).await;
+                    }
+                }
             }
 
             Ok(cmd) = rx_cmd.recv() => {
@@ -443,21 +512,22 @@ pub async fn run_daemon(port: u16) -> Result<()> {
                         {
                             let mut cfg = state.config.write().await;
                             cfg.sync.insert(url.clone(), true);
+                            cfg.paused.remove(&url);
                             let _ = cfg.save().await;
                         }
                         tracing::info!("[DEBUG] About to call spawn_subscription for {}", url);
                         spawn_subscription(url.clone(), &mut subscriptions, state.clone()).await;
                         tracing::info!("[DEBUG] spawn_subscription completed for {}", url);
 
-                        if binary_sync::should_use_binary_sync(&url) {
-                            let bsm = state.binary_sync.clone();
-                            let url_clone = url.clone();
-                            let root = config::get_root_dir()?;
-                            let fullpath = root.join(url.trim_start_matches('/'));
-                            tokio::spawn(async move {
+                        let bsm = state.binary_sync.clone();
+                        let url_clone = url.clone();
+                        let root = config::get_root_dir()?;
+                        let fullpath = root.join(url.trim_start_matches('/'));
+                        tokio::spawn(async move {
+                            if bsm.is_binary_file(&url_clone, &fullpath).await {
                                 let _ = bsm.init_binary_sync(&url_clone, &fullpath).await;
-                            });
-                        }
+                            }
+                        });
                         sync_urls_map.write().await.insert(url, true);
                     }
                     Command::Unsync { url } => {
@@ -465,13 +535,38 @@ pub async fn run_daemon(port: u16) -> Result<()> {
                         {
                             let mut cfg = state.config.write().await;
                             cfg.sync.remove(&url);
+                            cfg.paused.remove(&url);
                             let _ = cfg.save().await;
                         }
                         if let Some(handle) = subscriptions.remove(&url) {
                             handle.abort();
                         }
+                        state.active_merges.write().await.remove(&url);
+                        if let Err(e) = state.merge_store.remove(&url) {
+                            tracing::warn!("Failed to drop merge state for {}: {}", url, e);
+                        }
+                        // Let blob GC reclaim whatever this URL was holding
+                        if let Err(e) = state.binary_sync.blob_store().release(&url).await {
+                            tracing::warn!("Failed to release blobs for {}: {}", url, e);
+                        }
                         sync_urls_map.write().await.remove(&url);
                     }
+                    Command::Pause { url } => {
+                        if pause_subscription(&url, &mut subscriptions, &state).await {
+                            let _ = state.config.read().await.save().await;
+                            sync_urls_map.write().await.insert(url, false);
+                        } else {
+                            tracing::warn!("Cannot pause {}: not synced", url);
+                        }
+                    }
+                    Command::Resume { url } => {
+                        if resume_subscription(&url, &mut subscriptions, &state).await {
+                            let _ = state.config.read().await.save().await;
+                            sync_urls_map.write().await.insert(url, true);
+                        } else {
+                            tracing::warn!("Cannot resume {}: not paused", url);
+                        }
+                    }
                     Command::SetCookie { domain, value } => {
                         tracing::info!("Set Cookie: {} for {}", value, domain);
                         let mut cfg = state.config.write().await;
@@ -491,7 +586,7 @@ pub async fn run_daemon(port: u16) -> Result<()> {
                         } else {
                             let state_nfs = state.clone();
                             let handle = tokio::spawn(async move {
-                                let backend = nfs::BraidNfsBackend::new(state_nfs.clone(), state_nfs.binary_sync.blob_store());
+                                let backend = nfs::BraidNfsBackend::new(state_nfs.clone(), state_nfs.binary_sync.blob_store(), false);
                                 tracing::info!("Starting NFS server on port {}", port);
                                  match nfsserve::tcp::NFSTcpListener::bind(&format!("127.0.0.1:{}", port), backend).await {
                                      Ok(listener) => {
@@ -535,3 +630,105 @@ pub async fn run_daemon(port: u16) -> Result<()> {
     }
     Ok(())
 }
+
+/// Spawn the scan loop built by `start` with the configured interval, unless
+/// the config relies on the watcher alone
+fn spawn_scanner<F, Fut>(config: &Config, start: F) -> Option<tokio::task::JoinHandle<()>>
+where
+    F: FnOnce(Duration) -> Fut,
+    Fut: std::future::Future<Output = ()> + Send + 'static,
+{
+    match config.scan_interval() {
+        Some(scan_interval) => {
+            tracing::info!("[BraidFS] Scanner running every {:?}", scan_interval);
+            Some(tokio::spawn(start(scan_interval)))
+        }
+        None => {
+            tracing::info!("[BraidFS] Scanner disabled, relying on filesystem watcher");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_pending_writes_keep_case_distinct_paths_apart() {
+        let pending = PendingWrites::new();
+        pending.add(PathBuf::from("/root/braid.org/Foo.md"));
+
+        assert!(pending.should_ignore(&PathBuf::from("/root/braid.org/Foo.md")));
+        assert!(!pending.should_ignore(&PathBuf::from("/root/braid.org/foo.md")));
+
+        pending.add(PathBuf::from("/root/braid.org/foo.md"));
+        pending.remove(&PathBuf::from("/root/braid.org/Foo.md"));
+        assert!(!pending.should_ignore(&PathBuf::from("/root/braid.org/Foo.md")));
+        assert!(pending.should_ignore(&PathBuf::from("/root/braid.org/foo.md")));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_pending_writes_ignore_case_on_windows() {
+        let pending = PendingWrites::new();
+        pending.add(PathBuf::from(r"C:\braid\Foo.md"));
+        assert!(pending.should_ignore(&PathBuf::from("c:/braid/foo.md")));
+    }
+
+    #[test]
+    fn test_active_urls_drop_out_after_window() {
+        let window = Duration::from_secs(600);
+        let tracker = ActivityTracker::new(window);
+        let start = std::time::Instant::now();
+
+        tracker.mark_at("https://braid.org/old", start);
+        tracker.mark_at("https://braid.org/recent", start + Duration::from_secs(300));
+        assert_eq!(
+            tracker.active_urls_at(start + Duration::from_secs(301)),
+            vec!["https://braid.org/old", "https://braid.org/recent"]
+        );
+
+        let later = start + window + Duration::from_secs(1);
+        assert_eq!(tracker.active_urls_at(later), vec!["https://braid.org/recent"]);
+    }
+
+    #[test]
+    fn test_pending_writes_custom_window_expires() {
+        let path = PathBuf::from("/root/braid.org/page.md");
+
+        let short = PendingWrites::with_window(Duration::from_millis(20));
+        short.add(path.clone());
+        assert!(short.should_ignore(&path));
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(!short.should_ignore(&path));
+
+        let long = PendingWrites::with_window(Duration::from_secs(60));
+        long.add(path.clone());
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(long.should_ignore(&path));
+    }
+
+    #[tokio::test]
+    async fn test_scanner_gets_configured_interval() {
+        let config: Config = serde_json::from_str(r#"{"scan_interval_secs": 5}"#).unwrap();
+        let mut received = None;
+        let handle = spawn_scanner(&config, |scan_interval| {
+            received = Some(scan_interval);
+            async {}
+        });
+        handle.unwrap().await.unwrap();
+        assert_eq!(received, Some(Duration::from_secs(5)));
+
+        // Watcher-only mode starts no scanner where the watcher can be trusted
+        let config: Config = serde_json::from_str(r#"{"disable_scanner": true}"#).unwrap();
+        let mut started = false;
+        let handle = spawn_scanner(&config, |_| {
+            started = true;
+            async {}
+        });
+        assert_eq!(handle.is_some(), started);
+        assert_eq!(started, config.scan_interval().is_some());
+    }
+}
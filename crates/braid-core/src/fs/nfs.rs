@@ -19,10 +19,16 @@ pub struct BraidNfsBackend {
     id_to_path: Arc<PRwLock<HashMap<u64, String>>>,
     path_to_id: Arc<PRwLock<HashMap<String, u64>>>,
     next_id: Arc<PRwLock<u64>>,
+    /// Reject every operation that would modify the tree
+    read_only: bool,
 }
 
 impl BraidNfsBackend {
-    pub fn new(state: DaemonState, blob_store: Arc<braid_blob::BlobStore>) -> Self {
+    pub fn new(
+        state: DaemonState,
+        blob_store: Arc<braid_blob::BlobStore>,
+        read_only: bool,
+    ) -> Self {
         let mut id_to_path = HashMap::new();
         let mut path_to_id = HashMap::new();
         let mut max_id = 1;
@@ -65,6 +71,16 @@ impl BraidNfsBackend {
             id_to_path: Arc::new(PRwLock::new(id_to_path)),
             path_to_id: Arc::new(PRwLock::new(path_to_id)),
             next_id: Arc::new(PRwLock::new(max_id + 1)),
+            read_only,
+        }
+    }
+
+    /// EROFS for mutating operations on a read-only mount
+    fn check_writable(&self) -> std::result::Result<(), nfsstat3> {
+        if self.read_only {
+            Err(nfsstat3::NFS3ERR_ROFS)
+        } else {
+            Ok(())
         }
     }
 
@@ -158,7 +174,11 @@ impl BraidNfsBackend {
 #[async_trait]
 impl NFSFileSystem for BraidNfsBackend {
     fn capabilities(&self) -> VFSCapabilities {
-        VFSCapabilities::ReadWrite
+        if self.read_only {
+            VFSCapabilities::ReadOnly
+        } else {
+            VFSCapabilities::ReadWrite
+        }
     }
 
     fn root_dir(&self) -> u64 {
@@ -233,6 +253,7 @@ impl NFSFileSystem for BraidNfsBackend {
     }
 
     async fn setattr(&self, id: u64, _attr: sattr3) -> std::result::Result<fattr3, nfsstat3> {
+        self.check_writable()?;
         self.getattr(id).await
     }
 
@@ -329,6 +350,7 @@ impl NFSFileSystem for BraidNfsBackend {
         offset: u64,
         data: &[u8],
     ) -> std::result::Result<fattr3, nfsstat3> {
+        self.check_writable()?;
         let vpath = self.get_path(id).ok_or(nfsstat3::NFS3ERR_STALE)?;
         let root = crate::fs::config::get_root_dir().map_err(|_| nfsstat3::NFS3ERR_IO)?;
         let path = root.join(vpath.trim_start_matches('/'));
@@ -415,6 +437,7 @@ impl NFSFileSystem for BraidNfsBackend {
         name: &nfsstring,
         _attr: sattr3,
     ) -> std::result::Result<(u64, fattr3), nfsstat3> {
+        self.check_writable()?;
         let dir_path = self.get_path(dir_id).ok_or(nfsstat3::NFS3ERR_STALE)?;
         let name_str = String::from_utf8_lossy(&name.0).to_string();
         let full_path = mapping::path_join(&dir_path, &name_str);
@@ -443,6 +466,7 @@ impl NFSFileSystem for BraidNfsBackend {
         _dir_id: u64,
         _name: &nfsstring,
     ) -> std::result::Result<u64, nfsstat3> {
+        self.check_writable()?;
         Err(nfsstat3::NFS3ERR_NOTSUPP)
     }
 
@@ -451,6 +475,7 @@ impl NFSFileSystem for BraidNfsBackend {
         dir_id: u64,
         name: &nfsstring,
     ) -> std::result::Result<(u64, fattr3), nfsstat3> {
+        self.check_writable()?;
         let dir_path = self.get_path(dir_id).ok_or(nfsstat3::NFS3ERR_STALE)?;
         let name_str = String::from_utf8_lossy(&name.0).to_string();
         let full_path = mapping::path_join(&dir_path, &name_str);
@@ -469,6 +494,7 @@ impl NFSFileSystem for BraidNfsBackend {
     }
 
     async fn remove(&self, dir_id: u64, name: &nfsstring) -> std::result::Result<(), nfsstat3> {
+        self.check_writable()?;
         let dir_path = self.get_path(dir_id).ok_or(nfsstat3::NFS3ERR_STALE)?;
         let name_str = String::from_utf8_lossy(&name.0).to_string();
         let full_path = mapping::path_join(&dir_path, &name_str);
@@ -490,6 +516,7 @@ impl NFSFileSystem for BraidNfsBackend {
         new_dir: u64,
         new_name: &nfsstring,
     ) -> std::result::Result<(), nfsstat3> {
+        self.check_writable()?;
         let old_dir_path = self.get_path(old_dir).ok_or(nfsstat3::NFS3ERR_STALE)?;
         let old_name_str = String::from_utf8_lossy(&old_name.0).to_string();
         let old_full_path = mapping::path_join(&old_dir_path, &old_name_str);
@@ -633,6 +660,7 @@ impl NFSFileSystem for BraidNfsBackend {
         _target: &nfsstring,
         _attr: &sattr3,
     ) -> std::result::Result<(u64, fattr3), nfsstat3> {
+        self.check_writable()?;
         Err(nfsstat3::NFS3ERR_NOTSUPP)
     }
 
@@ -640,3 +668,62 @@ impl NFSFileSystem for BraidNfsBackend {
         Err(nfsstat3::NFS3ERR_NOTSUPP)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::state::test_support::test_state;
+
+    async fn backend(root: &std::path::Path, read_only: bool) -> BraidNfsBackend {
+        let (state, _rx_cmd) = test_state(root).await;
+        state
+            .inode_db
+            .lock()
+            .execute(
+                "CREATE TABLE inodes (id INTEGER PRIMARY KEY, path TEXT UNIQUE NOT NULL)",
+                [],
+            )
+            .unwrap();
+        let blob_store = state.binary_sync.blob_store();
+        BraidNfsBackend::new(state, blob_store, read_only)
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let nfs = backend(dir.path(), true).await;
+        let name = nfsstring(b"page.md".to_vec());
+        let root = nfs.root_dir();
+
+        assert!(matches!(nfs.capabilities(), VFSCapabilities::ReadOnly));
+        assert!(matches!(
+            nfs.write(root, 0, b"clobbered").await,
+            Err(nfsstat3::NFS3ERR_ROFS)
+        ));
+        assert!(matches!(
+            nfs.create(root, &name, sattr3::default()).await,
+            Err(nfsstat3::NFS3ERR_ROFS)
+        ));
+        assert!(matches!(
+            nfs.mkdir(root, &name).await,
+            Err(nfsstat3::NFS3ERR_ROFS)
+        ));
+        assert!(matches!(
+            nfs.remove(root, &name).await,
+            Err(nfsstat3::NFS3ERR_ROFS)
+        ));
+        assert!(matches!(
+            nfs.rename(root, &name, root, &nfsstring(b"moved.md".to_vec()))
+                .await,
+            Err(nfsstat3::NFS3ERR_ROFS)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_write_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let nfs = backend(dir.path(), false).await;
+        assert!(matches!(nfs.capabilities(), VFSCapabilities::ReadWrite));
+        assert!(nfs.check_writable().is_ok());
+    }
+}
@@ -8,6 +8,15 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+/// First delay after a subscription drops.
+pub const BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+/// Upper bound for the reconnect delay.
+pub const BACKOFF_MAX: Duration = Duration::from_secs(300);
+/// A connection that stays up this long resets the backoff.
+pub const BACKOFF_STABLE: Duration = Duration::from_secs(30);
+/// Random jitter applied to each delay, as a fraction of the delay.
+const BACKOFF_JITTER: f64 = 0.2;
+
 /// Rate limiter for reconnection attempts.
 #[derive(Debug)]
 pub struct ReconnectRateLimiter {
@@ -27,6 +36,10 @@ struct ConnectionState {
     failure_count: u32,
     /// Queue of pending connection requests.
     pending_turns: u32,
+    /// When the current connection was established.
+    connected_since: Option<Instant>,
+    /// Un-jittered delay for the next reconnect.
+    backoff: Duration,
 }
 
 impl Default for ConnectionState {
@@ -36,6 +49,8 @@ impl Default for ConnectionState {
             last_attempt: Instant::now(),
             failure_count: 0,
             pending_turns: 0,
+            connected_since: None,
+            backoff: BACKOFF_INITIAL,
         }
     }
 }
@@ -83,6 +98,7 @@ impl ReconnectRateLimiter {
         state.connected = true;
         state.failure_count = 0;
         state.last_attempt = Instant::now();
+        state.connected_since = Some(Instant::now());
 
         tracing::debug!("on_conn: {} - connected", url);
     }
@@ -95,6 +111,13 @@ impl ReconnectRateLimiter {
         state.connected = false;
         state.failure_count += 1;
         state.last_attempt = Instant::now();
+        if state
+            .connected_since
+            .take()
+            .is_some_and(|since| since.elapsed() >= BACKOFF_STABLE)
+        {
+            state.backoff = BACKOFF_INITIAL;
+        }
 
         tracing::debug!(
             "on_diss: {} - disconnected (failures: {})",
@@ -103,6 +126,22 @@ impl ReconnectRateLimiter {
         );
     }
 
+    /// Delay before the next reconnect attempt for `url`, with jitter.
+    ///
+    /// Each call doubles the stored delay, starting at [`BACKOFF_INITIAL`] and
+    /// capped at [`BACKOFF_MAX`]. [`Self::on_diss`] resets the delay if the
+    /// connection had stayed up for [`BACKOFF_STABLE`].
+    pub async fn next_backoff(&self, url: &str) -> Duration {
+        let base = {
+            let mut conns = self.connections.lock().await;
+            let state = conns.entry(url.to_string()).or_default();
+            let base = state.backoff;
+            state.backoff = (base * 2).min(BACKOFF_MAX);
+            base
+        };
+        jitter(base, rand::random::<f64>() * 2.0 - 1.0)
+    }
+
     /// Check if a URL is currently connected.
     pub async fn is_connected(&self, url: &str) -> bool {
         let conns = self.connections.lock().await;
@@ -122,6 +161,11 @@ impl ReconnectRateLimiter {
     }
 }
 
+/// Scale `delay` by `1 + BACKOFF_JITTER * factor`, where `factor` is in `[-1, 1]`.
+fn jitter(delay: Duration, factor: f64) -> Duration {
+    delay.mul_f64(1.0 + BACKOFF_JITTER * factor.clamp(-1.0, 1.0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,4 +202,58 @@ mod tests {
 
         assert_eq!(limiter.failure_count("http://example.com").await, 5);
     }
+
+    #[tokio::test]
+    async fn test_backoff_grows_and_caps() {
+        let limiter = ReconnectRateLimiter::new(100);
+        let url = "http://example.com";
+
+        let mut expected = BACKOFF_INITIAL;
+        for _ in 0..12 {
+            limiter.on_diss(url).await;
+            let delay = limiter.next_backoff(url).await;
+            assert!(
+                delay >= expected.mul_f64(0.8),
+                "{:?} < {:?}",
+                delay,
+                expected
+            );
+            assert!(
+                delay <= expected.mul_f64(1.2),
+                "{:?} > {:?}",
+                delay,
+                expected
+            );
+            expected = (expected * 2).min(BACKOFF_MAX);
+        }
+        // 1s doubled eleven times is well past the cap
+        assert_eq!(expected, BACKOFF_MAX);
+
+        // A short-lived connection doesn't reset the delay
+        limiter.on_conn(url).await;
+        limiter.on_diss(url).await;
+        assert!(limiter.next_backoff(url).await >= BACKOFF_MAX.mul_f64(0.8));
+
+        // A connection that stayed up resets it
+        limiter.on_conn(url).await;
+        if let Some(since) = Instant::now().checked_sub(BACKOFF_STABLE) {
+            limiter
+                .connections
+                .lock()
+                .await
+                .get_mut(url)
+                .unwrap()
+                .connected_since = Some(since);
+            limiter.on_diss(url).await;
+            assert!(limiter.next_backoff(url).await <= BACKOFF_INITIAL.mul_f64(1.2));
+        }
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let base = Duration::from_secs(10);
+        assert_eq!(jitter(base, -1.0), Duration::from_secs(8));
+        assert_eq!(jitter(base, 1.0), Duration::from_secs(12));
+        assert_eq!(jitter(base, 5.0), Duration::from_secs(12));
+    }
 }
@@ -6,7 +6,7 @@
 use crate::core::{BraidError, Result};
 use crate::fs::config::{get_root_dir, skip_file};
 use crate::fs::mapping;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
@@ -21,6 +21,11 @@ pub struct ScanState {
     pub running: bool,
     /// Number of watcher misses detected.
     pub watcher_misses: u32,
+    /// Descend into symlinked directories and sync symlinked files.
+    ///
+    /// Each directory is visited once by canonical path either way, so a
+    /// link back to an ancestor can't recurse forever.
+    pub follow_symlinks: bool,
 }
 
 impl ScanState {
@@ -50,7 +55,19 @@ pub async fn scan_files(
     let mut changed_files = Vec::new();
 
     // Recursively scan directory
-    let result = scan_directory(root_dir, root_dir, state, sync_urls, &mut changed_files).await;
+    let mut visited = HashSet::new();
+    if let Ok(canonical) = tokio::fs::canonicalize(root_dir).await {
+        visited.insert(canonical);
+    }
+    let result = scan_directory(
+        root_dir,
+        root_dir,
+        state,
+        sync_urls,
+        &mut visited,
+        &mut changed_files,
+    )
+    .await;
 
     // Mark as done
     {
@@ -78,8 +95,10 @@ async fn scan_directory(
     root: &Path,
     state: &Arc<RwLock<ScanState>>,
     sync_urls: &HashMap<String, bool>,
+    visited: &mut HashSet<PathBuf>,
     changed: &mut Vec<PathBuf>,
 ) -> Result<()> {
+    let follow_symlinks = state.read().await.follow_symlinks;
     let mut entries = tokio::fs::read_dir(dir)
         .await
         .map_err(|e| BraidError::Io(e))?;
@@ -95,12 +114,32 @@ async fn scan_directory(
         }
 
         let metadata = entry.metadata().await.map_err(|e| BraidError::Io(e))?;
+        let metadata = if metadata.is_symlink() {
+            if !follow_symlinks {
+                continue;
+            }
+            // Dangling links have nothing to sync
+            let Ok(target) = tokio::fs::metadata(&path).await else {
+                continue;
+            };
+            target
+        } else {
+            metadata
+        };
 
         if metadata.is_dir() {
+            // Skip directories already scanned, e.g. reached again via a symlink
+            let Ok(canonical) = tokio::fs::canonicalize(&path).await else {
+                continue;
+            };
+            if !visited.insert(canonical) {
+                continue;
+            }
             // Recurse into subdirectories
-            Box::pin(scan_directory(&path, root, state, sync_urls, changed)).await?;
-        } else if metadata.is_file() {
-            // Check if this file is being synced
+            Box::pin(scan_directory(
+                &path, root, state, sync_urls, visited, changed,
+            ))
+            .await?;
         } else if metadata.is_file() {
             // Check if this file is being synced
             if let Ok(url) = mapping::path_to_url(&path) {
@@ -214,6 +253,36 @@ pub async fn on_watcher_miss(state: &Arc<RwLock<ScanState>>, message: &str, trig
 mod tests {
     use super::*;
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_to_ancestor_terminates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let nested = root.join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::os::unix::fs::symlink(root.join("a"), nested.join("loop")).unwrap();
+
+        let sync_urls = HashMap::new();
+        let expected: HashSet<PathBuf> = [root.clone(), root.join("a"), nested.clone()]
+            .into_iter()
+            .collect();
+        for follow_symlinks in [false, true] {
+            let state = Arc::new(RwLock::new(ScanState {
+                follow_symlinks,
+                ..ScanState::default()
+            }));
+            let mut visited = HashSet::from([root.clone()]);
+            let mut changed = Vec::new();
+            let scan = scan_directory(&root, &root, &state, &sync_urls, &mut visited, &mut changed);
+            tokio::time::timeout(Duration::from_secs(5), scan)
+                .await
+                .expect("scan should terminate")
+                .unwrap();
+            // Each real directory once; the link back to `a` isn't a new one
+            assert_eq!(visited, expected, "follow_symlinks={}", follow_symlinks);
+        }
+    }
+
     #[test]
     fn test_normalize_url() {
         assert_eq!(
@@ -2,7 +2,9 @@ use super::{debouncer::DebouncedSyncManager, ActivityTracker, PendingWrites};
 use crate::core::merge::{MergeType, MergeTypeRegistry};
 use crate::core::BraidClient;
 use crate::fs::binary_sync::BinarySyncManager;
-use crate::fs::config::Config;
+use crate::fs::config::{Config, ExcludeMatcher};
+use crate::fs::merge_store::MergeStore;
+use crate::fs::rate_limiter::ReconnectRateLimiter;
 use crate::fs::versions::VersionStore;
 use parking_lot::Mutex as PMutex;
 use rusqlite::Connection;
@@ -18,6 +20,13 @@ pub enum Command {
     Unsync {
         url: String,
     },
+    /// Stop syncing a URL but keep its config entry
+    Pause {
+        url: String,
+    },
+    Resume {
+        url: String,
+    },
     SetCookie {
         domain: String,
         value: String,
@@ -44,13 +53,65 @@ pub struct DaemonState {
     pub tracker: ActivityTracker,
     pub merge_registry: Arc<MergeTypeRegistry>,
     pub active_merges: Arc<RwLock<HashMap<String, Box<dyn MergeType>>>>,
+    /// Persisted snapshots of `active_merges`, restored on startup
+    pub merge_store: MergeStore,
     pub pending: PendingWrites,
+    /// Compiled `Config::exclude` patterns
+    pub exclude: Arc<ExcludeMatcher>,
     pub client: BraidClient,
     pub failed_syncs: Arc<RwLock<HashMap<String, (u16, std::time::Instant)>>>,
     pub binary_sync: Arc<BinarySyncManager>,
+    /// Per-URL reconnect backoff for subscriptions
+    pub rate_limiter: Arc<ReconnectRateLimiter>,
     pub inode_db: Arc<PMutex<Connection>>,
     pub tx_cmd: async_channel::Sender<Command>,
     pub debouncer: Arc<DebouncedSyncManager>,
     /// URLs being managed by the local HTTP 209 server (polling)
     pub local_server_managed: Arc<RwLock<std::collections::HashSet<String>>>,
 }
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+    use crate::fs::debouncer::DebouncedSyncManager;
+    use crate::fs::merge_store::MergeStore;
+    use std::path::Path;
+
+    /// Build a daemon state rooted at `root`, returning the command receiver.
+    pub(crate) async fn test_state(root: &Path) -> (DaemonState, async_channel::Receiver<Command>) {
+        let braidfs_dir = root.join(".braidfs");
+        std::fs::create_dir_all(&braidfs_dir).unwrap();
+        let config = Config::default();
+        let exclude = Arc::new(ExcludeMatcher::new(root.to_path_buf(), &config.exclude));
+        let blob_store = Arc::new(
+            crate::blob::BlobStore::new(braidfs_dir.join("blobs"), braidfs_dir.join("meta.sqlite"))
+                .await
+                .unwrap(),
+        );
+        let rate_limiter = Arc::new(ReconnectRateLimiter::new(100));
+        let binary_sync =
+            BinarySyncManager::with_braidfs_dir(rate_limiter.clone(), blob_store, &braidfs_dir);
+        let (tx_cmd, rx_cmd) = async_channel::unbounded();
+
+        let state = DaemonState {
+            config: Arc::new(RwLock::new(config)),
+            content_cache: Arc::new(RwLock::new(HashMap::new())),
+            version_store: Arc::new(RwLock::new(VersionStore::default())),
+            tracker: ActivityTracker::new(crate::fs::DEFAULT_ACTIVITY_WINDOW),
+            merge_registry: Arc::new(MergeTypeRegistry::new()),
+            active_merges: Arc::new(RwLock::new(HashMap::new())),
+            merge_store: MergeStore::open(&braidfs_dir.join("merges.sqlite")).unwrap(),
+            pending: PendingWrites::new(),
+            exclude,
+            client: BraidClient::new().unwrap(),
+            failed_syncs: Arc::new(RwLock::new(HashMap::new())),
+            binary_sync: Arc::new(binary_sync),
+            rate_limiter,
+            inode_db: Arc::new(PMutex::new(Connection::open_in_memory().unwrap())),
+            tx_cmd,
+            debouncer: Arc::new(DebouncedSyncManager::new_placeholder()),
+            local_server_managed: Arc::new(RwLock::new(std::collections::HashSet::new())),
+        };
+        (state, rx_cmd)
+    }
+}
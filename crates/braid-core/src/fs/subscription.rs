@@ -33,41 +33,89 @@ pub async fn spawn_subscription(
         return;
     }
 
+    if state.config.read().await.paused.contains(&url) {
+        tracing::info!("[BraidFS] Sync paused for {}, skipping subscription", url);
+        return;
+    }
+
     let url_capture = url.clone();
     let state_capture = state.clone();
     let handle = tokio::spawn(async move {
         loop {
             match subscribe_loop(url_capture.clone(), state_capture.clone()).await {
                 Ok(_) => {
-                    tracing::info!(
-                        "Subscription for {} ended normally. Reconnecting in 1s...",
-                        url_capture
-                    );
+                    tracing::info!("Subscription for {} ended normally", url_capture);
                 }
                 Err(e) => {
                     // Stream errors are usually just idle timeouts - not real errors
                     let error_str = format!("{}", e);
                     if error_str.contains("decode") || error_str.contains("timeout") || error_str.contains("closed") {
                         tracing::info!(
-                            "Subscription for {} idle timeout (normal). Reconnecting in 1s...",
+                            "Subscription for {} idle timeout (normal)",
                             url_capture
                         );
                     } else {
-                        tracing::error!(
-                            "Subscription error for {}: {}. Reconnecting in 1s...",
-                            url_capture,
-                            e
-                        );
+                        tracing::error!("Subscription error for {}: {}", url_capture, e);
                     }
                 }
             }
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            state_capture.rate_limiter.on_diss(&url_capture).await;
+            let delay = state_capture.rate_limiter.next_backoff(&url_capture).await;
+            tracing::info!("Reconnecting to {} in {:.1}s", url_capture, delay.as_secs_f64());
+            tokio::time::sleep(delay).await;
         }
     });
 
     subscriptions.insert(url, handle);
 }
 
+/// Suspend syncing `url`, keeping its config entry, cookies and local file.
+///
+/// Returns `false` if the URL isn't configured for sync. The caller is
+/// responsible for persisting the config.
+pub async fn pause_subscription(
+    url: &str,
+    subscriptions: &mut HashMap<String, tokio::task::JoinHandle<()>>,
+    state: &DaemonState,
+) -> bool {
+    {
+        let mut cfg = state.config.write().await;
+        if !cfg.sync.contains_key(url) {
+            return false;
+        }
+        cfg.paused.insert(url.to_string());
+    }
+    if let Some(handle) = subscriptions.remove(url) {
+        handle.abort();
+    }
+    tracing::info!("[BraidFS] Paused sync for {}", url);
+    true
+}
+
+/// Resume a paused URL, respawning its subscription.
+///
+/// Returns `false` if the URL wasn't paused. The caller is responsible for
+/// persisting the config.
+pub async fn resume_subscription(
+    url: &str,
+    subscriptions: &mut HashMap<String, tokio::task::JoinHandle<()>>,
+    state: &DaemonState,
+) -> bool {
+    if !state.config.write().await.paused.remove(url) {
+        return false;
+    }
+    tracing::info!("[BraidFS] Resuming sync for {}", url);
+    spawn_subscription(url.to_string(), subscriptions, state.clone()).await;
+
+    // Push any local edits made while paused
+    if let Ok(path) = mapping::url_to_path(url) {
+        if path.exists() {
+            state.debouncer.request_sync(url.to_string(), path).await;
+        }
+    }
+    true
+}
+
 pub async fn subscribe_loop(url: String, state: DaemonState) -> Result<()> {
     tracing::info!("[DEBUG] === subscribe_loop START for {}", url);
 
@@ -224,6 +272,7 @@ pub async fn subscribe_loop(url: String, state: DaemonState) -> Result<()> {
     }
 
     let mut sub = state.client.subscribe(&url, sub_req).await?;
+    state.rate_limiter.on_conn(&url).await;
     let mut is_first = true;
     
     tracing::info!("[BraidFS-Sub] Subscription stream started for {}", url);
@@ -301,6 +350,7 @@ pub async fn subscribe_loop(url: String, state: DaemonState) -> Result<()> {
                             m
                         });
 
+                        let before = merge.get_content();
                         let patch = crate::core::merge::MergePatch {
                             range: "".to_string(),
                             content: serde_json::Value::String(body.to_string()),
@@ -308,6 +358,10 @@ pub async fn subscribe_loop(url: String, state: DaemonState) -> Result<()> {
                             parents: update.parents.clone(),
                         };
                         merge.apply_patch(patch);
+                        if let Err(e) = state.merge_store.save(&url, merge.as_ref()) {
+                            tracing::warn!("[BraidFS] Failed to persist merge state for {}: {}", url, e);
+                        }
+                        record_revision(&state, &url, &update, &before, &merge.get_content());
                         merge.get_content()
                     };
 
@@ -403,6 +457,7 @@ pub async fn subscribe_loop(url: String, state: DaemonState) -> Result<()> {
                 m.initialize(&content);
                 m
             });
+            let before = merge.get_content();
 
             for patch in patches {
                 let patch_content = std::str::from_utf8(&patch.content).unwrap_or("");
@@ -420,6 +475,10 @@ pub async fn subscribe_loop(url: String, state: DaemonState) -> Result<()> {
                     return Err(crate::core::BraidError::Internal(format!("Merge failed: {}", error_msg)));
                 }
             }
+            if let Err(e) = state.merge_store.save(&url, merge.as_ref()) {
+                tracing::warn!("[BraidFS] Failed to persist merge state for {}: {}", url, e);
+            }
+            record_revision(&state, &url, &update, &before, &merge.get_content());
             merge.get_content()
         };
 
@@ -460,6 +519,26 @@ pub async fn subscribe_loop(url: String, state: DaemonState) -> Result<()> {
     Ok(())
 }
 
+/// Add `update` to the URL's history, if it carries a version
+fn record_revision(
+    state: &DaemonState,
+    url: &str,
+    update: &crate::core::Update,
+    before: &str,
+    after: &str,
+) {
+    let Some(version) = update.primary_version() else {
+        return;
+    };
+    let parents = update.parents.iter().map(|p| p.to_string()).collect();
+    let recorded = state
+        .merge_store
+        .record_revision(url, &version.to_string(), parents, before, after);
+    if let Err(e) = recorded {
+        tracing::warn!("[BraidFS] Failed to record history for {}: {}", url, e);
+    }
+}
+
 async fn ensure_dir_path(path: &std::path::Path) {
     let mut current = std::path::PathBuf::new();
     for component in path.components() {
@@ -504,3 +583,35 @@ async fn ensure_dir_path(path: &std::path::Path) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::state::test_support::test_state;
+
+    #[tokio::test]
+    async fn test_pause_keeps_config_and_drops_subscription() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, _rx) = test_state(dir.path()).await;
+        let url = "https://braid.org/paused".to_string();
+        state.config.write().await.sync.insert(url.clone(), true);
+
+        let mut subscriptions = HashMap::new();
+        subscriptions.insert(url.clone(), tokio::spawn(std::future::pending::<()>()));
+
+        assert!(pause_subscription(&url, &mut subscriptions, &state).await);
+        assert!(!subscriptions.contains_key(&url));
+        {
+            let cfg = state.config.read().await;
+            assert_eq!(cfg.sync.get(&url), Some(&true));
+            assert!(cfg.paused.contains(&url));
+        }
+
+        // Paused URLs are not respawned
+        spawn_subscription(url.clone(), &mut subscriptions, state.clone()).await;
+        assert!(!subscriptions.contains_key(&url));
+
+        // Unknown URLs can't be paused
+        assert!(!pause_subscription("https://braid.org/other", &mut subscriptions, &state).await);
+    }
+}
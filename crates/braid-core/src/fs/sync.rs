@@ -1,13 +1,14 @@
 use crate::core::{protocol_mod as protocol, BraidError, Result};
+use crate::fs::config::{conflict_backup_path, ConflictPolicy};
 use crate::fs::state::DaemonState;
 use crate::fs::PEER_ID;
 use braid_http::types::{BraidRequest, Version as BraidVersion, Patch};
 use std::path::PathBuf;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// Logic for syncing a local file to a remote Braid URL.
 pub async fn sync_local_to_remote(
-    _path: &PathBuf,
+    path: &PathBuf,
     url_in: &str,
     parents: &[BraidVersion],
     _original_content: Option<String>,
@@ -77,8 +78,31 @@ pub async fn sync_local_to_remote(
         }
     }
     
+    // Server content differs from the local edit: resolve per the conflict policy
+    let conflict_policy = state.config.read().await.conflict_policy;
+    if server_content.is_some() && conflict_policy == ConflictPolicy::PreferLocal {
+        info!("[BraidFS-Sync] Conflict on {} - keeping local edit (prefer_local)", url_str);
+        server_content = None;
+    }
+
     // If server has different content, update local file instead of pushing
     if let Some(server_body) = server_content {
+        if conflict_policy == ConflictPolicy::KeepBackup {
+            let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S").to_string();
+            let backup = conflict_backup_path(path, &timestamp);
+            match tokio::fs::write(&backup, &new_content).await {
+                Ok(()) => warn!(
+                    "[BraidFS-Sync] Conflict on {} - local edit saved to {:?}",
+                    url_str, backup
+                ),
+                // Don't clobber the only copy of the local edit
+                Err(e) => {
+                    error!("[BraidFS-Sync] Failed to write conflict backup {:?}: {}", backup, e);
+                    return Err(BraidError::Io(e));
+                }
+            }
+        }
+
         info!("[BraidFS-Sync] Updating local file with server content (server is newer/different)");
         
         // Update content cache
@@ -95,13 +119,11 @@ pub async fn sync_local_to_remote(
         }
         
         // Write to file
-        if let Ok(path) = crate::fs::mapping::url_to_path(&url_str) {
-            state.pending.add(path.clone());
-            let tmp_path = path.with_extension("tmp");
-            if tokio::fs::write(&tmp_path, &server_body).await.is_ok() {
-                let _ = tokio::fs::rename(&tmp_path, &path).await;
-                info!("[BraidFS-Sync] Local file updated with server content");
-            }
+        state.pending.add(path.clone());
+        let tmp_path = path.with_extension("tmp");
+        if tokio::fs::write(&tmp_path, &server_body).await.is_ok() {
+            let _ = tokio::fs::rename(&tmp_path, path).await;
+            info!("[BraidFS-Sync] Local file updated with server content");
         }
         
         return Ok(()); // Don't push - we accepted server content
@@ -156,8 +178,24 @@ pub async fn sync_local_to_remote(
         // Capture the *current* version (which will become the parent) BEFORE applying the edit
         let current_ver_before_edit = merge.get_version().first().cloned();
 
+        let before = merge.get_content();
         let patch = crate::core::merge::MergePatch::new("everything", serde_json::Value::String(new_content.clone()));
         let res = merge.local_edit(patch);
+        if let Err(e) = state.merge_store.save(&url_str, merge.as_ref()) {
+            warn!("[BraidFS-Sync] Failed to persist merge state for {}: {}", url_str, e);
+        }
+        if let Some(version) = &res.version {
+            let parents = current_ver_before_edit.iter().map(|p| p.to_string()).collect();
+            if let Err(e) = state.merge_store.record_revision(
+                &url_str,
+                &version.to_string(),
+                parents,
+                &before,
+                &merge.get_content(),
+            ) {
+                warn!("[BraidFS-Sync] Failed to record history for {}: {}", url_str, e);
+            }
+        }
         
         let ver = res.version.unwrap_or_else(|| BraidVersion::new(format!("{}-{}", peer_id, 0)));
 
@@ -268,6 +306,7 @@ pub async fn sync_local_to_remote(
             info!("[BraidFS-Sync] PUT response status: {}", res.status);
             if (200..300).contains(&res.status) {
                 state.failed_syncs.write().await.remove(&url_str);
+                state.tracker.mark(&url_str);
                 info!("[BraidFS] Sync success (braid) status: {}", res.status);
                 state
                     .content_cache
@@ -403,3 +442,54 @@ pub async fn sync_binary_to_remote(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::config::is_conflict_backup;
+    use crate::fs::state::test_support::test_state;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serve `body` at version "remote-1" to every request
+    async fn remote_server(body: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/page.md", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = vec![0u8; 4096];
+                let _ = socket.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nversion: \"remote-1\"\r\ncontent-length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_divergent_edit_keeps_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, _rx) = test_state(dir.path()).await;
+        state.config.write().await.conflict_policy = ConflictPolicy::KeepBackup;
+        let url = remote_server("remote edit").await;
+        let path = dir.path().join("page.md");
+        std::fs::write(&path, "local edit").unwrap();
+
+        sync_local_to_remote(&path, &url, &[], None, "local edit".to_string(), None, state)
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "remote edit");
+        let backups: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|p| is_conflict_backup(&p.file_name().unwrap().to_string_lossy()))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].extension().unwrap(), "md");
+        assert_eq!(std::fs::read_to_string(&backups[0]).unwrap(), "local edit");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::fs::mapping;
+use crate::fs::{config, mapping};
 use crate::fs::state::{Command, DaemonState};
 use notify::Event;
 
@@ -14,28 +14,51 @@ pub async fn handle_fs_event(event: Event, state: DaemonState) {
         let is_removal = event.kind.is_remove() || !path.exists();
         let is_create = event.kind.is_create();
 
+        // Its content changed, so sniff it again next time
+        state.binary_sync.forget_file_kind(&path).await;
+
         // Skip non-files if it's NOT a removal (e.g. it's a new directory)
         if !is_removal && !path.is_file() {
             tracing::trace!("[BraidFS] Skipping non-file: {:?}", path);
             continue;
         }
 
-        // Skip if this is a dotfile or inside a hidden directory (like .braidfs) or a .tmp file
-        if path.components().any(|c| {
+        // Skip if this is a dotfile or inside a hidden directory (like .braidfs) or a .tmp file.
+        // Only the part below the root counts, which may itself be under a hidden directory.
+        let relative = path.strip_prefix(state.exclude.root()).unwrap_or(&path);
+        if relative.components().any(|c| {
             let s = c.as_os_str().to_string_lossy();
             s.starts_with('.') || s.ends_with(".tmp") || s.ends_with(".sqlite") || s.ends_with("-journal") || s.ends_with(".db")
         }) {
             continue;
         }
 
+        // Conflict backups stay local
+        if path
+            .file_name()
+            .is_some_and(|name| config::is_conflict_backup(&name.to_string_lossy()))
+        {
+            continue;
+        }
+
+        // Skip user-configured excludes (swap files, VCS metadata, ...)
+        if state.exclude.is_excluded(&path) {
+            tracing::trace!("[BraidFS] Skipping excluded path: {:?}", path);
+            continue;
+        }
+
         // Skip if this was a pending write from us (to avoid echo loops)
         if state.pending.should_ignore(&path) {
             tracing::trace!("[BraidFS] Skipping pending write: {:?}", path);
             continue;
         }
 
-        match mapping::path_to_url(&path) {
+        match mapping::path_to_url_in(state.exclude.root(), &path) {
             Ok(url) => {
+                if state.config.read().await.paused.contains(&url) {
+                    tracing::debug!("[BraidFS] Sync paused for {}, not pushing", url);
+                    continue;
+                }
                 tracing::info!("[BraidFS] File changed: {:?} -> {}", path, url);
 
                 // Auto-add new files to config.sync (IDE sync feature)
@@ -67,3 +90,63 @@ pub async fn handle_fs_event(event: Event, state: DaemonState) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::debouncer::DebouncedSyncManager;
+    use crate::fs::state::test_support::test_state;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_excluded_path_does_not_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (mut state, _rx_cmd) = test_state(&root).await;
+        let (debouncer, mut requests) = DebouncedSyncManager::recording();
+        state.debouncer = Arc::new(debouncer);
+
+        let page = root.join("braid.org").join("page.md");
+        let swap = root.join("braid.org").join("page.md.swp");
+        std::fs::create_dir_all(page.parent().unwrap()).unwrap();
+        std::fs::write(&page, "page").unwrap();
+        std::fs::write(&swap, "swap").unwrap();
+
+        for path in [&swap, &page] {
+            let kind = notify::EventKind::Modify(notify::event::ModifyKind::Any);
+            let mut event = Event::new(kind);
+            event.paths.push(path.clone());
+            handle_fs_event(event, state.clone()).await;
+        }
+
+        // Only the page, not its swap file, is handed on to sync
+        let request = requests.try_recv().unwrap();
+        assert_eq!(request.url, "https://braid.org/page.md");
+        assert_eq!(request.path, page);
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_modify_resniffs_file_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (mut state, _rx_cmd) = test_state(&root).await;
+        let (debouncer, _requests) = DebouncedSyncManager::recording();
+        state.debouncer = Arc::new(debouncer);
+        let url = "https://braid.org/notes.txt";
+        let path = root.join("braid.org").join("notes.txt");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        std::fs::write(&path, b"\0\x01binary").unwrap();
+        assert!(state.binary_sync.is_binary_file(url, &path).await);
+
+        std::fs::write(&path, "now text").unwrap();
+        let mut event = Event::new(notify::EventKind::Modify(
+            notify::event::ModifyKind::Any,
+        ));
+        event.paths.push(path.clone());
+        handle_fs_event(event, state.clone()).await;
+
+        assert!(!state.binary_sync.is_binary_file(url, &path).await);
+    }
+}
@@ -22,7 +22,18 @@ pub struct ClientConfig {
     /// Request timeout in milliseconds.
     pub request_timeout_ms: u64,
     /// Maximum total connections in the pool.
+    ///
+    /// Not enforced by the native client, which only bounds idle
+    /// connections; see `pool_max_idle_per_host`.
     pub max_total_connections: u32,
+    /// Idle keep-alive connections kept open per host.
+    pub pool_max_idle_per_host: usize,
+    /// How long an idle pooled connection is kept before closing, in seconds.
+    pub pool_idle_timeout_secs: u64,
+    /// Advertise gzip/deflate/br and decode compressed response bodies.
+    pub enable_compression: bool,
+    /// Largest body, in bytes, a compressed response may decode to.
+    pub max_decoded_body: usize,
 }
 
 impl Default for ClientConfig {
@@ -38,6 +49,10 @@ impl Default for ClientConfig {
             proxy_url: String::new(),
             request_timeout_ms: 30000,
             max_total_connections: 100,
+            pool_max_idle_per_host: 16,
+            pool_idle_timeout_secs: 90,
+            enable_compression: true,
+            max_decoded_body: 50 * 1024 * 1024,
         }
     }
 }
@@ -59,6 +74,10 @@ mod tests {
         assert_eq!(config.proxy_url, "");
         assert_eq!(config.request_timeout_ms, 30000);
         assert_eq!(config.max_total_connections, 100);
+        assert_eq!(config.pool_max_idle_per_host, 16);
+        assert_eq!(config.pool_idle_timeout_secs, 90);
+        assert!(config.enable_compression);
+        assert_eq!(config.max_decoded_body, 50 * 1024 * 1024);
     }
 
     #[test]
@@ -74,6 +93,10 @@ mod tests {
             proxy_url: "http://proxy".to_string(),
             request_timeout_ms: 1000,
             max_total_connections: 40,
+            pool_max_idle_per_host: 4,
+            pool_idle_timeout_secs: 10,
+            enable_compression: false,
+            max_decoded_body: 1024,
         };
         assert_eq!(config.max_retries, 5);
         assert_eq!(config.retry_delay_ms, 2000);
@@ -85,6 +108,10 @@ mod tests {
         assert_eq!(config.proxy_url, "http://proxy");
         assert_eq!(config.request_timeout_ms, 1000);
         assert_eq!(config.max_total_connections, 40);
+        assert_eq!(config.pool_max_idle_per_host, 4);
+        assert_eq!(config.pool_idle_timeout_secs, 10);
+        assert!(!config.enable_compression);
+        assert_eq!(config.max_decoded_body, 1024);
     }
 
     #[test]
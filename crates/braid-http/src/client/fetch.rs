@@ -3,11 +3,14 @@
 use crate::client::config::ClientConfig;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::client::native_network::NativeNetwork;
+use crate::client::subscription::{json_updates, text_updates};
 #[cfg(target_arch = "wasm32")]
 use crate::client::wasm_network::WasmNetwork;
 use crate::error::{BraidError, Result};
 use crate::traits::BraidNetwork;
 use crate::types::{BraidRequest, BraidResponse};
+use futures::Stream;
+use serde::de::DeserializeOwned;
 use std::sync::Arc;
 
 /// The main Braid HTTP client
@@ -52,9 +55,15 @@ impl BraidClient {
         {
             let mut builder = reqwest::Client::builder()
                 .http1_only()
-                .timeout(std::time::Duration::from_millis(config.request_timeout_ms))
-                .pool_idle_timeout(std::time::Duration::from_secs(90))
-                .pool_max_idle_per_host(config.max_total_connections as usize);
+                // Whole-request timeouts are applied per request in `fetch_internal`;
+                // a client-wide one would also cut off long-lived subscriptions
+                .connect_timeout(std::time::Duration::from_secs(
+                    config.connection_timeout_secs,
+                ))
+                .pool_idle_timeout(std::time::Duration::from_secs(
+                    config.pool_idle_timeout_secs,
+                ))
+                .pool_max_idle_per_host(config.pool_max_idle_per_host);
 
             if !config.proxy_url.is_empty() {
                 if let Ok(proxy) = reqwest::Proxy::all(&config.proxy_url) {
@@ -66,7 +75,11 @@ impl BraidClient {
                 .user_agent("curl/7.81.0")
                 .build()
                 .map_err(|e| BraidError::Config(e.to_string()))?;
-            let network = Arc::new(NativeNetwork::new(client));
+            let network = Arc::new(
+                NativeNetwork::new(client)
+                    .with_compression(config.enable_compression)
+                    .with_max_decoded_body(config.max_decoded_body),
+            );
 
             Ok(BraidClient {
                 network,
@@ -144,13 +157,39 @@ impl BraidClient {
     pub async fn subscribe(
         &self,
         url: &str,
-        request: BraidRequest,
+        mut request: BraidRequest,
     ) -> Result<crate::client::Subscription> {
+        request.timeout.get_or_insert(self.default_timeout());
         self.log_request(url, &request);
         let rx = self.network.subscribe(url, request).await?;
         Ok(crate::client::Subscription::new(rx))
     }
 
+    /// Subscribe and deserialize each update as JSON
+    ///
+    /// A multi-patch update yields one item per patch; heartbeats and other
+    /// empty updates are skipped.
+    pub async fn subscribe_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        request: BraidRequest,
+    ) -> Result<impl Stream<Item = Result<T>>> {
+        let subscription = self.subscribe(url, request).await?;
+        Ok(json_updates(subscription.into_updates()))
+    }
+
+    /// Subscribe and decode each update as UTF-8 text
+    ///
+    /// Skips empty updates like [`BraidClient::subscribe_json`].
+    pub async fn subscribe_text(
+        &self,
+        url: &str,
+        request: BraidRequest,
+    ) -> Result<impl Stream<Item = Result<String>>> {
+        let subscription = self.subscribe(url, request).await?;
+        Ok(text_updates(subscription.into_updates()))
+    }
+
     async fn fetch_with_retries(&self, url: &str, request: BraidRequest) -> Result<BraidResponse> {
         let retry_config = request.retry.clone().unwrap_or_else(|| {
             if self.config.max_retries == 0 {
@@ -227,7 +266,13 @@ impl BraidClient {
     }
 
     async fn fetch_internal(&self, url: &str, request: &BraidRequest) -> Result<BraidResponse> {
-        self.network.fetch(url, request.clone()).await
+        let timeout = request.timeout.unwrap_or_else(|| self.default_timeout());
+        crate::client::utils::timeout(timeout, self.network.fetch(url, request.clone())).await?
+    }
+
+    /// Timeout for requests that don't set their own
+    fn default_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.config.request_timeout_ms)
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -376,6 +421,9 @@ impl Default for BraidClient {
 mod tests {
     use super::*;
     use crate::types::BraidRequest;
+    use futures::StreamExt;
+    use serde::Deserialize;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     #[test]
     fn test_client_init() {
@@ -418,4 +466,196 @@ mod tests {
             "http://example.com/post"
         );
     }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Post {
+        n: u32,
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_json_yields_typed_items() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/feed", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await.unwrap();
+            let mut response = String::from(
+                "HTTP/1.1 209 Subscription\r\nsubscribe: true\r\nconnection: close\r\n\r\n",
+            );
+            for (version, body) in [("1", r#"{"n": 1}"#), ("2", r#"{"n": 2}"#)] {
+                response.push_str(&format!(
+                    "Version: \"{}\"\r\nContent-Length: {}\r\n\r\n{}\r\n\r\n",
+                    version,
+                    body.len(),
+                    body
+                ));
+            }
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let client = BraidClient::new().unwrap();
+        let posts: Vec<Post> = client
+            .subscribe_json(&url, BraidRequest::new())
+            .await
+            .unwrap()
+            .map(|post| post.unwrap())
+            .collect()
+            .await;
+        assert_eq!(posts, vec![Post { n: 1 }, Post { n: 2 }]);
+    }
+
+    /// Accepts connections and never answers
+    async fn silent_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hang", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                open.push(socket);
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_fetch_times_out() {
+        let url = silent_server().await;
+        let client = BraidClient::new().unwrap();
+        let request = BraidRequest::new()
+            .with_timeout(std::time::Duration::from_millis(200))
+            .with_retry(crate::client::retry::RetryConfig::no_retry());
+
+        let started = std::time::Instant::now();
+        let result = client.fetch(&url, request).await;
+        assert!(matches!(result, Err(BraidError::Timeout)), "{:?}", result);
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_defaults_to_client_config() {
+        let url = silent_server().await;
+        let client = BraidClient::with_config(ClientConfig {
+            request_timeout_ms: 200,
+            max_retries: 0,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let started = std::time::Instant::now();
+        assert!(matches!(client.get(&url).await, Err(BraidError::Timeout)));
+        assert!(matches!(
+            client.subscribe(&url, BraidRequest::new()).await,
+            Err(BraidError::Timeout)
+        ));
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    /// Serves one gzip-encoded response, returning the request it received
+    async fn gzip_server(body: &str) -> (String, tokio::task::JoinHandle<String>) {
+        use std::io::Write;
+
+        let body = body.to_string();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/page", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 4096];
+            let n = socket.read(&mut request).await.unwrap();
+
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(body.as_bytes()).unwrap();
+            let gzipped = encoder.finish().unwrap();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\ncontent-encoding: gzip\r\ncontent-length: {}\r\n\r\n",
+                gzipped.len()
+            );
+            socket.write_all(head.as_bytes()).await.unwrap();
+            socket.write_all(&gzipped).await.unwrap();
+            String::from_utf8_lossy(&request[..n]).to_lowercase()
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn test_gzip_response_is_decoded() {
+        let page = "# Wiki page\n".repeat(100);
+        let (url, server) = gzip_server(&page).await;
+
+        let response = BraidClient::new().unwrap().get(&url).await.unwrap();
+        assert_eq!(response.body_str(), Some(page.as_str()));
+        assert_eq!(response.header("content-encoding"), None);
+        assert_eq!(
+            response.header("content-length"),
+            Some(page.len().to_string().as_str())
+        );
+        assert!(server
+            .await
+            .unwrap()
+            .contains("accept-encoding: gzip, deflate, br"));
+    }
+
+    #[tokio::test]
+    async fn test_decoded_body_is_capped() {
+        // Compresses to a few hundred bytes
+        let page = "x".repeat(64 * 1024);
+        let (url, _server) = gzip_server(&page).await;
+        let client = BraidClient::with_config(ClientConfig {
+            max_decoded_body: 1024,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(matches!(client.get(&url).await, Err(BraidError::BodyParse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_compression_opt_out_keeps_raw_bytes() {
+        let (url, server) = gzip_server("raw").await;
+        let client = BraidClient::with_config(ClientConfig {
+            enable_compression: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let response = client.get(&url).await.unwrap();
+        assert_eq!(response.header("content-encoding"), Some("gzip"));
+        assert_eq!(&response.body[..2], &[0x1f, 0x8b]);
+        assert!(!server.await.unwrap().contains("accept-encoding"));
+    }
+
+    #[tokio::test]
+    async fn test_sequential_requests_reuse_connection() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/page", listener.local_addr().unwrap());
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut request = vec![0u8; 4096];
+                    while let Ok(n) = socket.read(&mut request).await {
+                        if n == 0 {
+                            break;
+                        }
+                        let response = "HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok";
+                        if socket.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let client = BraidClient::new().unwrap();
+        for _ in 0..2 {
+            let response = client.get(&url).await.unwrap();
+            assert_eq!(response.body_str(), Some("ok"));
+        }
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    }
 }
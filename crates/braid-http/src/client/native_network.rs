@@ -1,4 +1,5 @@
 use crate::client::parser::MessageParser;
+use crate::client::HeartbeatConfig;
 use crate::error::{BraidError, Result};
 use crate::protocol;
 use crate::traits::BraidNetwork;
@@ -6,14 +7,44 @@ use crate::types::{BraidRequest, BraidResponse, Update};
 use async_trait::async_trait;
 use futures::StreamExt;
 use reqwest::Client;
+use std::time::Duration;
+
+/// How long a subscription may go without data when no heartbeat
+/// interval is known
+const SUBSCRIPTION_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Encodings `decode_body` understands
+const ACCEPT_ENCODING: &str = "gzip, deflate, br";
 
 pub struct NativeNetwork {
     client: Client,
+    compression: bool,
+    max_decoded_body: usize,
 }
 
 impl NativeNetwork {
     pub fn new(client: Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            compression: true,
+            max_decoded_body: usize::MAX,
+        }
+    }
+
+    /// Whether fetches advertise and decode compressed bodies.
+    ///
+    /// Subscriptions are never compressed: their updates are framed by
+    /// `Content-Length` on the wire.
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Fail fetches whose compressed body decodes to more than `limit` bytes,
+    /// so a small response can't expand without bound.
+    pub fn with_max_decoded_body(mut self, limit: usize) -> Self {
+        self.max_decoded_body = limit;
+        self
     }
 
     pub fn client(&self) -> &Client {
@@ -38,6 +69,14 @@ impl BraidNetwork for NativeNetwork {
             req_builder = req_builder.header(k, v);
         }
 
+        let accepts_encoding = request
+            .extra_headers
+            .keys()
+            .any(|k| k.eq_ignore_ascii_case("accept-encoding"));
+        if self.compression && !request.subscribe && !accepts_encoding {
+            req_builder = req_builder.header(reqwest::header::ACCEPT_ENCODING, ACCEPT_ENCODING);
+        }
+
         if !request.body.is_empty() {
             let ct = request
                 .content_type
@@ -64,7 +103,11 @@ impl BraidNetwork for NativeNetwork {
             req_builder = req_builder.header("Parents", header_val);
         }
         if request.subscribe {
-            req_builder = req_builder.header("subscribe", "true");
+            // Force a new connection for subscriptions by disabling connection reuse;
+            // plain fetches go back to the pool
+            req_builder = req_builder
+                .header("subscribe", "true")
+                .header("Connection", "close");
         }
         if let Some(peer) = &request.peer {
             // Only add quotes if not already present
@@ -86,9 +129,7 @@ impl BraidNetwork for NativeNetwork {
             request.extra_headers
         );
 
-        // Force a new connection for subscriptions by disabling connection reuse
         let response = req_builder
-            .header("Connection", "close")
             .send()
             .await
             .map_err(|e| BraidError::Http(e.to_string()))?;
@@ -101,11 +142,20 @@ impl BraidNetwork for NativeNetwork {
             }
         }
 
-        let body = response
+        let mut body = response
             .bytes()
             .await
             .map_err(|e| BraidError::Http(e.to_string()))?;
 
+        if self.compression {
+            if let Some(encoding) = headers.remove("content-encoding") {
+                body = decode_body(&encoding, body, self.max_decoded_body)?;
+                if headers.contains_key("content-length") {
+                    headers.insert("content-length".to_string(), body.len().to_string());
+                }
+            }
+        }
+
         Ok(BraidResponse {
             status,
             headers,
@@ -160,13 +210,14 @@ impl BraidNetwork for NativeNetwork {
             request.extra_headers
         );
 
-        // For subscriptions, disable timeout (or set very long) since we're waiting for heartbeats/updates
-        // Heartbeats are every 30s, so we need timeout > 30s. Using 5 minutes for safety.
-        let response = req_builder
-            .timeout(std::time::Duration::from_secs(300))
-            .send()
-            .await
-            .map_err(|e| BraidError::Http(e.to_string()))?;
+        // Only the wait for headers is bounded here; once streaming, the
+        // subscription is cut off by the idle timeout below instead
+        let send = req_builder.send();
+        let response = match request.timeout {
+            Some(timeout) => crate::client::utils::timeout(timeout, send).await?,
+            None => send.await,
+        }
+        .map_err(|e| BraidError::Http(e.to_string()))?;
 
         let status = response.status();
         tracing::info!("[BraidHTTP-Sub] Response status: {}", status);
@@ -227,6 +278,13 @@ impl BraidNetwork for NativeNetwork {
             }
         }
 
+        let idle_timeout = headers
+            .get("heartbeats")
+            .and_then(|v| HeartbeatConfig::from_header(v))
+            .or_else(|| request.heartbeat_interval.map(|s| HeartbeatConfig::new(s as f64)))
+            .map(|config| config.timeout)
+            .unwrap_or(SUBSCRIPTION_IDLE_TIMEOUT);
+
         let (tx, rx) = async_channel::bounded(100);
         let mut stream = response.bytes_stream();
 
@@ -236,7 +294,19 @@ impl BraidNetwork for NativeNetwork {
             let mut parser = MessageParser::new_with_state(headers, content_length);
             tracing::debug!("[BraidHTTP-Parser] Started with content_length={}", content_length);
 
-            while let Some(chunk_res) = stream.next().await {
+            loop {
+                let chunk_res = match tokio::time::timeout(idle_timeout, stream.next()).await {
+                    Ok(Some(chunk_res)) => chunk_res,
+                    Ok(None) => break,
+                    Err(_) => {
+                        tracing::warn!(
+                            "[BraidHTTP-Parser] No data for {:?}, dropping subscription",
+                            idle_timeout
+                        );
+                        let _ = tx.send(Err(BraidError::Timeout)).await;
+                        break;
+                    }
+                };
                 match chunk_res {
                     Ok(chunk) => {
                         tracing::trace!("[BraidHTTP-Parser] Received chunk of {} bytes: {:?}", chunk.len(), 
@@ -272,3 +342,50 @@ impl BraidNetwork for NativeNetwork {
         Ok(rx)
     }
 }
+
+/// Undo a `Content-Encoding`, applied codings last-first
+///
+/// Fails once any stage decodes to more than `limit` bytes.
+fn decode_body(encoding: &str, mut body: bytes::Bytes, limit: usize) -> Result<bytes::Bytes> {
+    use std::io::Read;
+
+    // One byte past the limit is enough to tell it was exceeded
+    let cap = (limit as u64).saturating_add(1);
+    for coding in encoding.rsplit(',').map(str::trim) {
+        let mut decoded = Vec::new();
+        let read = match coding.to_ascii_lowercase().as_str() {
+            "" | "identity" => continue,
+            "gzip" | "x-gzip" => flate2::read::GzDecoder::new(&body[..])
+                .take(cap)
+                .read_to_end(&mut decoded),
+            // Per the RFC this is zlib-wrapped, but some servers send raw deflate
+            "deflate" => flate2::read::ZlibDecoder::new(&body[..])
+                .take(cap)
+                .read_to_end(&mut decoded)
+                .or_else(|_| {
+                    decoded.clear();
+                    flate2::read::DeflateDecoder::new(&body[..])
+                        .take(cap)
+                        .read_to_end(&mut decoded)
+                }),
+            "br" => brotli_decompressor::Decompressor::new(&body[..], 4096)
+                .take(cap)
+                .read_to_end(&mut decoded),
+            other => {
+                return Err(BraidError::BodyParse(format!(
+                    "Unsupported content encoding: {}",
+                    other
+                )))
+            }
+        };
+        read.map_err(|e| BraidError::BodyParse(format!("Failed to decode {}: {}", coding, e)))?;
+        if decoded.len() > limit {
+            return Err(BraidError::BodyParse(format!(
+                "Decoded {} body exceeds {} bytes",
+                coding, limit
+            )));
+        }
+        body = decoded.into();
+    }
+    Ok(body)
+}
@@ -2,7 +2,9 @@
 
 use crate::error::{BraidError, Result};
 use crate::types::Update;
-use futures::Stream;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
@@ -205,3 +207,105 @@ impl Stream for SubscriptionStream {
         unsafe { Pin::new_unchecked(&mut this.receiver) }.poll_next(cx)
     }
 }
+
+impl Subscription {
+    /// Consume the subscription as a stream, keeping heartbeat timeouts
+    pub(crate) fn into_updates(self) -> impl Stream<Item = Result<Update>> {
+        futures::stream::unfold(self, |mut sub| async move {
+            sub.next().await.map(|update| (update, sub))
+        })
+    }
+}
+
+/// The non-empty payloads of an update: its body, or else each patch
+fn update_payloads(update: Update) -> Vec<Bytes> {
+    match update.body {
+        Some(body) if !body.is_empty() => vec![body],
+        _ => update
+            .patches
+            .unwrap_or_default()
+            .into_iter()
+            .map(|patch| patch.content)
+            .filter(|content| !content.is_empty())
+            .collect(),
+    }
+}
+
+/// Decode every payload in `updates`, skipping heartbeats and empty updates
+fn decode_payloads<S, T>(
+    updates: S,
+    decode: fn(Bytes) -> Result<T>,
+) -> impl Stream<Item = Result<T>>
+where
+    S: Stream<Item = Result<Update>>,
+{
+    updates.flat_map(move |update| {
+        let items: Vec<Result<T>> = match update {
+            Ok(update) => update_payloads(update).into_iter().map(decode).collect(),
+            Err(e) => vec![Err(e)],
+        };
+        futures::stream::iter(items)
+    })
+}
+
+/// Deserialize each payload in `updates` as JSON
+pub(crate) fn json_updates<S, T>(updates: S) -> impl Stream<Item = Result<T>>
+where
+    S: Stream<Item = Result<Update>>,
+    T: DeserializeOwned,
+{
+    decode_payloads(updates, |payload| Ok(serde_json::from_slice(&payload)?))
+}
+
+/// Decode each payload in `updates` as UTF-8 text
+pub(crate) fn text_updates<S>(updates: S) -> impl Stream<Item = Result<String>>
+where
+    S: Stream<Item = Result<Update>>,
+{
+    decode_payloads(updates, |payload| Ok(String::from_utf8(payload.to_vec())?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Patch, Version};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Post {
+        n: u32,
+    }
+
+    #[tokio::test]
+    async fn test_json_updates_skip_heartbeats() {
+        let updates = vec![
+            Ok(Update::snapshot(Version::new("1"), r#"{"n": 1}"#)),
+            // Heartbeat
+            Ok(Update::snapshot(Version::new("2"), "")),
+            Ok(Update::patched(
+                Version::new("3"),
+                vec![
+                    Patch::json(".a", r#"{"n": 2}"#),
+                    Patch::json(".b", r#"{"n": 3}"#),
+                ],
+            )),
+        ];
+        let posts: Vec<Post> = json_updates(futures::stream::iter(updates))
+            .map(|post| post.unwrap())
+            .collect()
+            .await;
+        assert_eq!(posts, vec![Post { n: 1 }, Post { n: 2 }, Post { n: 3 }]);
+    }
+
+    #[tokio::test]
+    async fn test_json_updates_surface_errors() {
+        let updates = vec![
+            Ok(Update::snapshot(Version::new("1"), "not json")),
+            Err(BraidError::Timeout),
+        ];
+        let results: Vec<Result<Post>> =
+            json_updates(futures::stream::iter(updates)).collect().await;
+        assert!(matches!(results[0], Err(BraidError::Json(_))));
+        assert!(matches!(results[1], Err(BraidError::Timeout)));
+    }
+}
@@ -157,3 +157,21 @@ pub async fn sleep(duration: Duration) {
 pub async fn sleep(duration: Duration) {
     gloo_timers::future::sleep(duration).await;
 }
+
+/// Run `future`, failing with [`BraidError::Timeout`] if it takes longer than `duration`
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn timeout<F: std::future::Future>(duration: Duration, future: F) -> Result<F::Output> {
+    tokio::time::timeout(duration, future)
+        .await
+        .map_err(|_| BraidError::Timeout)
+}
+
+/// Run `future`, failing with [`BraidError::Timeout`] if it takes longer than `duration`
+#[cfg(target_arch = "wasm32")]
+pub async fn timeout<F: std::future::Future>(duration: Duration, future: F) -> Result<F::Output> {
+    use futures::future::{select, Either};
+    match select(std::pin::pin!(future), std::pin::pin!(sleep(duration))).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(BraidError::Timeout),
+    }
+}
@@ -6,7 +6,7 @@
 use crate::error::Result;
 use crate::protocol::constants::headers;
 use crate::protocol;
-use crate::types::{Update, Patch};
+use crate::types::{Update, Patch, Version};
 use bytes::{Bytes, BytesMut};
 
 /// Format an Update into Braid protocol message bytes.
@@ -71,6 +71,25 @@ pub fn format_update(update: &Update) -> Result<Bytes> {
     Ok(buffer.freeze())
 }
 
+/// Frame one full-body update for a 209 subscription stream.
+///
+/// `Version` and `Parents` are only written when non-empty. The trailing
+/// blank line separates this update from the next one on the wire.
+pub fn write_update_frame(version: &[Version], parents: &[Version], body: &[u8]) -> Bytes {
+    let mut buffer = BytesMut::new();
+    if !version.is_empty() {
+        write_header(&mut buffer, "Version", &protocol::format_version_header(version));
+    }
+    if !parents.is_empty() {
+        write_header(&mut buffer, "Parents", &protocol::format_version_header(parents));
+    }
+    write_header(&mut buffer, "Content-Length", &body.len().to_string());
+    buffer.extend_from_slice(b"\r\n");
+    buffer.extend_from_slice(body);
+    buffer.extend_from_slice(b"\r\n\r\n");
+    buffer.freeze()
+}
+
 fn write_header(buffer: &mut BytesMut, key: &str, value: &str) {
     buffer.extend_from_slice(key.as_bytes());
     buffer.extend_from_slice(b": ");
@@ -93,7 +112,8 @@ fn format_patch(buffer: &mut BytesMut, patch: &Patch) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::Version;
+    use crate::client::MessageParser;
+    use crate::protocol::parse_version_header;
 
     #[test]
     fn test_format_snapshot() {
@@ -105,4 +125,28 @@ mod tests {
         assert!(s.contains("content-length: 4"));
         assert!(s.ends_with("\r\ndata"));
     }
+
+    #[test]
+    fn test_update_frame_round_trips_through_parser() {
+        let mut frames = write_update_frame(&[Version::new("v1")], &[], b"[]").to_vec();
+        frames.extend_from_slice(&write_update_frame(
+            &[Version::new("v2")],
+            &[Version::new("v1")],
+            b"{\"a\":1}",
+        ));
+
+        let messages = MessageParser::new().feed(&frames).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].body_text().as_deref(), Some("[]"));
+        assert!(messages[0].parents().is_none());
+        assert_eq!(
+            parse_version_header(messages[1].version().unwrap()).unwrap(),
+            vec![Version::new("v2")]
+        );
+        assert_eq!(
+            parse_version_header(messages[1].parents().unwrap()).unwrap(),
+            vec![Version::new("v1")]
+        );
+        assert_eq!(messages[1].body_text().as_deref(), Some("{\"a\":1}"));
+    }
 }
@@ -19,6 +19,9 @@ pub struct BraidRequest {
     pub body: bytes::Bytes,
     pub extra_headers: std::collections::BTreeMap<String, String>,
     pub retry: Option<crate::client::retry::RetryConfig>,
+    /// Overrides `ClientConfig::request_timeout_ms`; for subscriptions this
+    /// only bounds the wait for response headers
+    pub timeout: Option<std::time::Duration>,
 }
 
 impl BraidRequest {
@@ -125,6 +128,11 @@ impl BraidRequest {
     pub fn retry(self) -> Self {
         self.with_retry(crate::client::retry::RetryConfig::default())
     }
+
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 #[cfg(test)]
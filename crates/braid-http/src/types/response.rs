@@ -59,6 +59,29 @@ impl BraidResponse {
         self.header("merge-type").map(|s| s.to_string())
     }
 
+    /// The `Version` header, falling back to `Current-Version`
+    ///
+    /// Header names match case-insensitively and quotes are stripped, so
+    /// `Version: "abc"` gives `abc`. Empty values count as absent.
+    pub fn version(&self) -> Option<Version> {
+        ["version", "current-version"].iter().find_map(|name| {
+            protocol::parse_version_header(self.header(name)?)
+                .ok()?
+                .into_iter()
+                .find(|v| !matches!(v, Version::String(s) if s.is_empty()))
+        })
+    }
+
+    /// The `Parents` header, empty when absent
+    pub fn parents(&self) -> Vec<Version> {
+        self.get_parents().unwrap_or_default()
+    }
+
+    /// The `Merge-Type` header
+    pub fn merge_type(&self) -> Option<String> {
+        self.header("merge-type").map(|s| s.trim().to_string())
+    }
+
     pub fn get_content_range(&self) -> Option<ContentRange> {
         self.header("content-range")
             .and_then(|v| ContentRange::from_header_value(v).ok())
@@ -112,4 +135,46 @@ mod tests {
         assert_eq!(res.body_str(), Some("hello"));
         assert_eq!(res.header("version"), Some("\"v1\""));
     }
+
+    #[test]
+    fn test_version_header_casing() {
+        for name in ["version", "Version", "VERSION"] {
+            let res = BraidResponse::new(200, "").with_header(name, "\"v1\"");
+            assert_eq!(res.version(), Some(Version::new("v1")), "{}", name);
+        }
+        assert_eq!(BraidResponse::new(200, "").version(), None);
+    }
+
+    #[test]
+    fn test_version_falls_back_to_current_version() {
+        let res = BraidResponse::new(200, "").with_header("Current-Version", "\"cv\"");
+        assert_eq!(res.version(), Some(Version::new("cv")));
+
+        let res = res.with_header("version", "\"v\"");
+        assert_eq!(res.version(), Some(Version::new("v")));
+
+        let empty = BraidResponse::new(200, "")
+            .with_header("Version", "\"\"")
+            .with_header("current-version", "\"cv\"");
+        assert_eq!(empty.version(), Some(Version::new("cv")));
+    }
+
+    #[test]
+    fn test_parents_and_merge_type() {
+        let res = BraidResponse::new(200, "")
+            .with_header("Parents", "\"a\", \"b\"")
+            .with_header("Merge-Type", "sync9");
+        assert_eq!(res.parents(), vec![Version::new("a"), Version::new("b")]);
+        assert_eq!(res.merge_type(), Some("sync9".to_string()));
+
+        let lower = BraidResponse::new(200, "")
+            .with_header("parents", "\"a\"")
+            .with_header("merge-type", "dt");
+        assert_eq!(lower.parents(), vec![Version::new("a")]);
+        assert_eq!(lower.merge_type(), Some("dt".to_string()));
+
+        let bare = BraidResponse::new(200, "");
+        assert!(bare.parents().is_empty());
+        assert_eq!(bare.merge_type(), None);
+    }
 }
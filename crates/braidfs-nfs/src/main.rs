@@ -17,6 +17,9 @@ struct Cli {
     daemon_port: u16,
     #[arg(short, long)]
     mount_point: Option<String>,
+    /// Serve the drive read-only; writes fail with EROFS
+    #[arg(long)]
+    read_only: bool,
 }
 
 #[tokio::main]
@@ -27,11 +30,18 @@ async fn main() -> anyhow::Result<()> {
     info!("=== BraidFS NFS Monitor [crate: braidfs-nfs] ===");
     info!("Manages the network drive bridge and discovery.");
     info!("Mode: Standalone Server (Port {})", cli.nfs_port);
+    if cli.read_only {
+        info!("Serving read-only: writes will be rejected");
+    }
 
     // 1. Load Config (Shared with Daemon)
     let config = config::Config::load().await?;
     let root_dir = config::get_root_dir()?;
     let braidfs_dir = root_dir.join(".braidfs");
+    let exclude = Arc::new(config::ExcludeMatcher::new(
+        root_dir.clone(),
+        &config.exclude,
+    ));
     let config = Arc::new(RwLock::new(config));
 
     // 2. Initialize Stores (Shared DBs)
@@ -57,11 +67,15 @@ async fn main() -> anyhow::Result<()> {
     // We provide valid handles to the stores, and dummy/new instances for the rest.
 
     let content_cache = Arc::new(RwLock::new(HashMap::new()));
-    let activity_tracker = ActivityTracker::new();
+    let activity_tracker = ActivityTracker::new(fs::DEFAULT_ACTIVITY_WINDOW);
     let merge_registry = Arc::new(braid_core::core::merge::MergeTypeRegistry::new());
     let active_merges = Arc::new(RwLock::new(HashMap::new()));
+    let merge_store = fs::merge_store::MergeStore::open(&braidfs_dir.join("merges.sqlite"))?;
     let pending_writes = PendingWrites::new();
-    let client = BraidClient::new()?; // Standalone client, separate from Daemon's
+    // Standalone client, separate from the daemon's: it keeps its own
+    // keep-alive pool (sized by `ClientConfig::default()`), so NFS traffic
+    // never shares sockets with daemon syncs.
+    let client = BraidClient::new()?;
     let failed_syncs = Arc::new(RwLock::new(HashMap::new()));
     let debouncer = Arc::new(fs::debouncer::DebouncedSyncManager::new_placeholder());
 
@@ -71,7 +85,7 @@ async fn main() -> anyhow::Result<()> {
     // Binary Sync Manager needed for state struct, but NFS might not use it directly for syncing
     let rate_limiter = Arc::new(fs::rate_limiter::ReconnectRateLimiter::new(100));
     let binary_sync_manager =
-        fs::binary_sync::BinarySyncManager::new(rate_limiter, blob_store.clone())
+        fs::binary_sync::BinarySyncManager::new(rate_limiter.clone(), blob_store.clone())
             .map_err(|e| anyhow::anyhow!(e))?;
     let binary_sync_manager = Arc::new(binary_sync_manager);
 
@@ -82,10 +96,13 @@ async fn main() -> anyhow::Result<()> {
         tracker: activity_tracker,
         merge_registry,
         active_merges,
+        merge_store,
         pending: pending_writes,
+        exclude,
         client,
         failed_syncs,
         binary_sync: binary_sync_manager,
+        rate_limiter,
         inode_db,
         tx_cmd,
         debouncer,
@@ -93,7 +110,7 @@ async fn main() -> anyhow::Result<()> {
     };
 
     // 4. Start NFS Server
-    let backend = fs::nfs::BraidNfsBackend::new(state.clone(), blob_store);
+    let backend = fs::nfs::BraidNfsBackend::new(state.clone(), blob_store, cli.read_only);
 
     // Trigger OS Mount if requested
     if let Some(mp) = &cli.mount_point {
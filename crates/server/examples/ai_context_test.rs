@@ -27,7 +27,7 @@ async fn main() -> anyhow::Result<()> {
         Arc::new(AiChatManager::new(ai_config, store.clone(), &config.storage_dir).await?);
 
     let room_id = "test-ai-room";
-    ai_manager.register_ai_room(room_id).await?;
+    ai_manager.register_ai_room(room_id, None).await?;
 
     // 3. Create a mock context file
     let context_dir = braid_common::ai_context_dir();
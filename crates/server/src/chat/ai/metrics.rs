@@ -0,0 +1,93 @@
+//! AI Usage Metrics
+//!
+//! Token counts and generation latency, per room and in total.
+
+use genai::chat::Usage;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Accumulated usage for a room (or for all rooms)
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct UsageTotals {
+    /// Completed generations
+    pub responses: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Summed wall-clock generation time
+    pub total_latency_ms: u64,
+    pub avg_latency_ms: u64,
+    /// Slowest single generation
+    pub max_latency_ms: u64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, usage: Option<&Usage>, elapsed: Duration) {
+        let tokens = |count: Option<i32>| count.unwrap_or(0).max(0) as u64;
+        let elapsed_ms = elapsed.as_millis() as u64;
+
+        self.responses += 1;
+        if let Some(usage) = usage {
+            self.prompt_tokens += tokens(usage.prompt_tokens);
+            self.completion_tokens += tokens(usage.completion_tokens);
+        }
+        self.total_latency_ms += elapsed_ms;
+        self.avg_latency_ms = self.total_latency_ms / self.responses;
+        self.max_latency_ms = self.max_latency_ms.max(elapsed_ms);
+    }
+}
+
+/// Usage across all AI rooms
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct AiMetrics {
+    pub total: UsageTotals,
+    pub rooms: HashMap<String, UsageTotals>,
+}
+
+impl AiMetrics {
+    /// Record one completed generation in `room_id`
+    ///
+    /// `usage` is `None` when the provider didn't report token counts; the
+    /// response and its latency are still counted.
+    pub fn record(&mut self, room_id: &str, usage: Option<&Usage>, elapsed: Duration) {
+        self.total.add(usage, elapsed);
+        self.rooms
+            .entry(room_id.to_string())
+            .or_default()
+            .add(usage, elapsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt: i32, completion: i32) -> Usage {
+        Usage {
+            prompt_tokens: Some(prompt),
+            completion_tokens: Some(completion),
+            total_tokens: Some(prompt + completion),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_aggregates_per_room_and_total() {
+        let mut metrics = AiMetrics::default();
+        metrics.record("a", Some(&usage(100, 20)), Duration::from_millis(300));
+        metrics.record("a", Some(&usage(50, 10)), Duration::from_millis(900));
+        metrics.record("b", None, Duration::from_millis(600));
+
+        let room_a = &metrics.rooms["a"];
+        assert_eq!(room_a.responses, 2);
+        assert_eq!(room_a.prompt_tokens, 150);
+        assert_eq!(room_a.completion_tokens, 30);
+        assert_eq!(room_a.avg_latency_ms, 600);
+        assert_eq!(room_a.max_latency_ms, 900);
+
+        assert_eq!(metrics.rooms["b"].prompt_tokens, 0);
+        assert_eq!(metrics.total.responses, 3);
+        assert_eq!(metrics.total.prompt_tokens, 150);
+        assert_eq!(metrics.total.total_latency_ms, 1800);
+    }
+}
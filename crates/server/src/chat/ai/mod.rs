@@ -7,20 +7,45 @@
 //! - Braid protocol integration for sync
 //! - Thinking indicator support
 
+mod metrics;
+
+pub use metrics::{AiMetrics, UsageTotals};
+
 use crate::core::models::{Message, MessageType};
+use crate::core::shutdown::Shutdown;
 use crate::core::store::json_store::JsonChatStore;
 use anyhow::Result;
+use futures::{Stream, StreamExt};
 use notify::{Event, RecursiveMode, Watcher};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
+use tokio::time::Instant;
 use tracing::{debug, error, info, warn};
 
 // GenAI imports
-use genai::chat::{ChatMessage, ChatRequest};
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest, ChatStreamEvent, Usage};
 use genai::Client as GenAIClient;
 
+/// Minimum time between live edits while a response streams in
+const STREAM_EDIT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Placeholder posted while a response is generated
+const THINKING_MARKER: &str = "🤔 *Thinking...*";
+/// Prefix of the message shown when generation fails
+const ERROR_MARKER: &str = "❌ *Error";
+/// Reply to a mention that arrives while the room is still busy
+const BUSY_MARKER: &str = "⏳ *Still thinking about the previous message...*";
+
+/// Whether `content` is one of the bot's own status messages
+fn is_bot_status(content: &str) -> bool {
+    [THINKING_MARKER, ERROR_MARKER, BUSY_MARKER]
+        .iter()
+        .any(|marker| content.starts_with(marker))
+}
+
 /// AI Assistant configuration
 #[derive(Clone, Debug)]
 pub struct AiConfig {
@@ -45,6 +70,103 @@ impl Default for AiConfig {
     }
 }
 
+impl AiConfig {
+    /// This config with a room's overrides applied on top
+    pub fn merged(&self, overrides: &AiRoomOverride) -> AiConfig {
+        AiConfig {
+            model: overrides
+                .model
+                .clone()
+                .unwrap_or_else(|| self.model.clone()),
+            system_prompt: overrides
+                .system_prompt
+                .clone()
+                .unwrap_or_else(|| self.system_prompt.clone()),
+            enable_context: overrides.enable_context.unwrap_or(self.enable_context),
+            max_context_files: self.max_context_files,
+        }
+    }
+}
+
+/// Per-room settings layered over the global `AiConfig`
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AiRoomOverride {
+    pub model: Option<String>,
+    pub system_prompt: Option<String>,
+    pub enable_context: Option<bool>,
+}
+
+impl AiRoomOverride {
+    /// Apply a `/model`, `/prompt` or `/context` control message.
+    ///
+    /// Returns a confirmation to post, or `None` if `content` isn't a command.
+    fn apply_command(&mut self, content: &str) -> Option<String> {
+        let (command, arg) = content
+            .trim()
+            .split_once(' ')
+            .unwrap_or((content.trim(), ""));
+        let arg = arg.trim();
+
+        match command {
+            "/model" if arg.is_empty() || arg == "default" => {
+                self.model = None;
+                Some("⚙️ Using the default model in this room".to_string())
+            }
+            "/model" => {
+                self.model = Some(arg.to_string());
+                Some(format!("⚙️ Model for this room set to `{}`", arg))
+            }
+            "/prompt" if arg.is_empty() || arg == "default" => {
+                self.system_prompt = None;
+                Some("⚙️ Using the default system prompt in this room".to_string())
+            }
+            "/prompt" => {
+                self.system_prompt = Some(arg.to_string());
+                Some("⚙️ System prompt for this room updated".to_string())
+            }
+            "/context" => {
+                let enabled = match arg {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Some("⚙️ Usage: `/context on` or `/context off`".to_string()),
+                };
+                self.enable_context = Some(enabled);
+                Some(format!(
+                    "⚙️ Folder context {}",
+                    if enabled { "enabled" } else { "disabled" }
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A file in the AI context directory
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ContextFileInfo {
+    pub name: String,
+    pub size: u64,
+}
+
+/// Whether `name` is a plain file name that stays inside the context dir
+///
+/// Separators and `.`/`..` are rejected so callers can't escape it.
+pub fn is_valid_context_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\'])
+        && Path::new(name).file_name() == Some(name.as_ref())
+}
+
+/// Resolve a context file name inside `context_dir`
+fn context_file_path(context_dir: &Path, name: &str) -> Result<PathBuf> {
+    if !is_valid_context_file_name(name) {
+        anyhow::bail!("Invalid context file name: {}", name);
+    }
+    Ok(context_dir.join(name))
+}
+
 /// AI Chat Manager
 pub struct AiChatManager {
     config: AiConfig,
@@ -54,10 +176,19 @@ pub struct AiChatManager {
     ai_rooms: Arc<RwLock<HashMap<String, AiRoomState>>>,
     /// GenAI client for API calls
     genai_client: GenAIClient,
-    /// Track pending AI responses (room_id -> thinking_message_id)
-    _pending_responses: Arc<RwLock<HashMap<String, String>>>,
+    /// Rooms with a response in progress (room_id -> thinking_message_id)
+    pending_responses: Arc<RwLock<HashMap<String, String>>>,
     /// System context directory
     context_dir: PathBuf,
+    /// Token and latency totals
+    metrics: Arc<RwLock<AiMetrics>>,
+}
+
+/// A finished generation
+struct Generation {
+    text: String,
+    usage: Option<Usage>,
+    elapsed: Duration,
 }
 
 #[derive(Clone, Debug)]
@@ -65,6 +196,8 @@ struct AiRoomState {
     room_id: String,
     last_processed_version: Option<String>,
     pending_mentions: Vec<String>,
+    /// Room-specific model/prompt settings
+    overrides: AiRoomOverride,
 }
 
 impl AiChatManager {
@@ -95,17 +228,23 @@ impl AiChatManager {
             ai_chats_dir,
             ai_rooms: Arc::new(RwLock::new(HashMap::new())),
             genai_client,
-            _pending_responses: Arc::new(RwLock::new(HashMap::new())),
+            pending_responses: Arc::new(RwLock::new(HashMap::new())),
             context_dir,
+            metrics: Arc::new(RwLock::new(AiMetrics::default())),
         })
     }
 
-    /// Register a room as an AI chat
-    pub async fn register_ai_room(&self, room_id: &str) -> Result<()> {
+    /// Register a room as an AI chat, optionally with its own model/prompt
+    pub async fn register_ai_room(
+        &self,
+        room_id: &str,
+        overrides: Option<AiRoomOverride>,
+    ) -> Result<()> {
         let state = AiRoomState {
             room_id: room_id.to_string(),
             last_processed_version: None,
             pending_mentions: Vec::new(),
+            overrides: overrides.unwrap_or_default(),
         };
 
         self.ai_rooms
@@ -121,6 +260,19 @@ impl AiChatManager {
         Ok(())
     }
 
+    /// Register every stored room created as an AI chat, e.g. after a
+    /// restart. Returns how many were registered.
+    pub async fn register_saved_rooms(&self) -> Result<usize> {
+        let mut registered = 0;
+        for room in self._store.list_rooms().await {
+            if room.is_ai_chat && !self.ai_rooms.read().await.contains_key(&room.id) {
+                self.register_ai_room(&room.id, None).await?;
+                registered += 1;
+            }
+        }
+        Ok(registered)
+    }
+
     /// Create the markdown file for an AI chat
     async fn create_ai_chat_file(&self, room_id: &str) -> Result<PathBuf> {
         let path = self.ai_chats_dir.join(format!("{}.md", room_id));
@@ -134,6 +286,14 @@ impl AiChatManager {
         Ok(path)
     }
 
+    /// Effective config for a room: its overrides merged over the global config
+    pub async fn room_config(&self, room_id: &str) -> AiConfig {
+        match self.ai_rooms.read().await.get(room_id) {
+            Some(room) => self.config.merged(&room.overrides),
+            None => self.config.clone(),
+        }
+    }
+
     /// Process incoming message for AI mentions
     /// Returns immediately with a "thinking" message, then spawns async task for AI response
     pub async fn process_message(
@@ -148,37 +308,104 @@ impl AiChatManager {
             return Ok(None);
         }
 
+        // Room settings commands (`/model`, `/prompt`, `/context`)
+        if message.sender != "@BraidBot" && message.content.trim_start().starts_with('/') {
+            let reply = self
+                .ai_rooms
+                .write()
+                .await
+                .get_mut(room_id)
+                .and_then(|room| room.overrides.apply_command(&message.content));
+            if let Some(reply) = reply {
+                info!(
+                    "[@BraidBot] Room {} settings changed by {}",
+                    room_id, message.sender
+                );
+                let msg = self
+                    ._store
+                    .add_message(
+                        room_id,
+                        "@BraidBot",
+                        &reply,
+                        MessageType::Text,
+                        Some(message.id.clone()),
+                        vec![],
+                    )
+                    .await?;
+                return Ok(Some(msg));
+            }
+        }
+
         // Check for @BraidBot mention
         if !message.content.contains("@BraidBot") && !message.content.contains("@BraidBot!") {
             return Ok(None);
         }
 
-        // Check if we've already responded to this message
-        if message.sender == "@BraidBot" {
+        // Never respond to ourselves, including edits of our status messages
+        if message.sender == "@BraidBot" || is_bot_status(&message.content) {
             return Ok(None);
         }
 
+        // One response per room at a time; reserve the slot before any await
+        // so a second mention can't slip in behind this one
+        {
+            let mut pending = self.pending_responses.write().await;
+            if pending.contains_key(room_id) {
+                drop(pending);
+                info!(
+                    "[@BraidBot] Ignoring mention from {} in {}: response in progress",
+                    message.sender, room_id
+                );
+                let msg = self
+                    ._store
+                    .add_message(
+                        room_id,
+                        "@BraidBot",
+                        BUSY_MARKER,
+                        MessageType::Text,
+                        Some(message.id.clone()),
+                        vec![],
+                    )
+                    .await?;
+                return Ok(Some(msg));
+            }
+            pending.insert(room_id.to_string(), String::new());
+        }
+
         info!(
             "[@BraidBot] Triggered in room {} by {}",
             room_id, message.sender
         );
 
         // Add "thinking..." message immediately so user sees feedback
-        let thinking_msg = self
+        let thinking_msg = match self
             ._store
             .add_message(
                 room_id,
                 "@BraidBot",
-                "🤔 *Thinking...*",
+                THINKING_MARKER,
                 MessageType::Text,
                 Some(message.id.clone()),
                 vec![],
             )
-            .await?;
+            .await
+        {
+            Ok(msg) => msg,
+            Err(e) => {
+                self.pending_responses.write().await.remove(room_id);
+                return Err(e);
+            }
+        };
 
         let thinking_id = thinking_msg.id.clone();
+        self.pending_responses
+            .write()
+            .await
+            .insert(room_id.to_string(), thinking_id.clone());
+        let pending_responses = self.pending_responses.clone();
+        let metrics = self.metrics.clone();
         let _store = self._store.clone();
-        let config = self.config.clone();
+        let config = self.room_config(room_id).await;
         let genai_client = self.genai_client.clone();
         let user_msg = message.clone();
         let room_id_owned = room_id.to_string();
@@ -194,10 +421,18 @@ impl AiChatManager {
                 &room_id_owned,
                 &user_msg,
                 &context_dir,
+                &thinking_id,
             )
             .await
             {
-                Ok(response_text) => {
+                Ok(generation) => {
+                    metrics.write().await.record(
+                        &room_id_owned,
+                        generation.usage.as_ref(),
+                        generation.elapsed,
+                    );
+                    let response_text = generation.text;
+
                     // Edit the thinking message with the actual response
                     if let Err(e) = _store
                         .edit_message(&room_id_owned, &thinking_id, &response_text)
@@ -235,8 +470,10 @@ impl AiChatManager {
                 }
                 Err(e) => {
                     // Edit thinking message to show error
-                    let error_msg =
-                        format!("❌ *Error: Could not generate response. Please try again.*");
+                    let error_msg = format!(
+                        "{}: Could not generate response. Please try again.*",
+                        ERROR_MARKER
+                    );
                     if let Err(e2) = _store
                         .edit_message(&room_id_owned, &thinking_id, &error_msg)
                         .await
@@ -249,6 +486,8 @@ impl AiChatManager {
                     warn!("[@BraidBot] Failed to generate response: {}", e);
                 }
             }
+
+            pending_responses.write().await.remove(&room_id_owned);
         });
 
         // Return the thinking message immediately
@@ -256,6 +495,9 @@ impl AiChatManager {
     }
 
     /// Generate AI response using GenAI - static method for spawned task
+    ///
+    /// The response is streamed into `message_id` as it arrives; the complete
+    /// text is returned for the final edit, along with token usage and timing.
     async fn generate_ai_response(
         client: &GenAIClient,
         config: &AiConfig,
@@ -263,10 +505,65 @@ impl AiChatManager {
         room_id: &str,
         trigger_message: &Message,
         context_dir: &Path,
-    ) -> Result<String> {
+        message_id: &str,
+    ) -> Result<Generation> {
         // Get chat history for context
         let history = store.get_messages(room_id, None).await?;
 
+        let chat_messages =
+            Self::build_chat_messages(config, &history, trigger_message, context_dir).await;
+        let chat_req = ChatRequest::new(chat_messages);
+
+        // Call the AI API
+        info!("[@BraidBot] Calling {} for response...", config.model);
+        let started = Instant::now();
+
+        let options = ChatOptions::default().with_capture_usage(true);
+        let response = client
+            .exec_chat_stream(&config.model, chat_req, Some(&options))
+            .await
+            .map_err(|e| anyhow::anyhow!("GenAI error: {}", e))?;
+
+        // Usage only arrives with the final stream event
+        let usage = Arc::new(std::sync::Mutex::new(None));
+        let chunks = response.stream.filter_map(|event| {
+            let usage = usage.clone();
+            async move {
+                match event {
+                    Ok(ChatStreamEvent::Chunk(chunk)) => Some(Ok(chunk.content)),
+                    Ok(ChatStreamEvent::End(end)) => {
+                        *usage.lock().unwrap() = end.captured_usage;
+                        None
+                    }
+                    Ok(_) => None,
+                    Err(e) => Some(Err(anyhow::anyhow!("GenAI error: {}", e))),
+                }
+            }
+        });
+
+        let mut text =
+            Self::stream_into_message(store, room_id, message_id, chunks, STREAM_EDIT_INTERVAL)
+                .await?;
+        if text.is_empty() {
+            text = "*No response generated*".to_string();
+        }
+
+        let usage = usage.lock().unwrap().take();
+        Ok(Generation {
+            text,
+            usage,
+            elapsed: started.elapsed(),
+        })
+    }
+
+    /// Assemble the system prompt, any requested context file, recent
+    /// history and the trigger message into chat messages
+    async fn build_chat_messages(
+        config: &AiConfig,
+        history: &[Message],
+        trigger_message: &Message,
+        context_dir: &Path,
+    ) -> Vec<ChatMessage> {
         // Build chat request with history
         let mut chat_messages = vec![ChatMessage::system(&config.system_prompt)];
 
@@ -281,30 +578,39 @@ impl AiChatManager {
                 let after = &trigger_message.content[idx + "ai read context".len()..];
                 let filename = after.trim().trim_matches('"');
                 if !filename.is_empty() {
-                    let context_path = context_dir.join(filename);
-                    info!(
-                        "[@BraidBot] Attempting to read context file: {:?}",
-                        context_path
-                    );
-
-                    if context_path.exists() {
-                        match tokio::fs::read_to_string(&context_path).await {
-                            Ok(content) => {
-                                info!("[@BraidBot] Successfully read context from {}", filename);
-                                chat_messages.push(ChatMessage::system(&format!(
-                                    "DOCKER CONTEXT FILE ({}):\n\n{}",
-                                    filename, content
-                                )));
-                            }
-                            Err(e) => {
-                                warn!(
-                                    "[@BraidBot] Failed to read context file {}: {}",
-                                    filename, e
-                                );
+                    match context_file_path(context_dir, filename) {
+                        Err(e) => {
+                            warn!("[@BraidBot] Rejected context file {}: {}", filename, e);
+                        }
+                        Ok(context_path) => {
+                            info!(
+                                "[@BraidBot] Attempting to read context file: {:?}",
+                                context_path
+                            );
+
+                            if context_path.exists() {
+                                match tokio::fs::read_to_string(&context_path).await {
+                                    Ok(content) => {
+                                        info!(
+                                            "[@BraidBot] Successfully read context from {}",
+                                            filename
+                                        );
+                                        chat_messages.push(ChatMessage::system(format!(
+                                            "DOCKER CONTEXT FILE ({}):\n\n{}",
+                                            filename, content
+                                        )));
+                                    }
+                                    Err(e) => {
+                                        warn!(
+                                            "[@BraidBot] Failed to read context file {}: {}",
+                                            filename, e
+                                        );
+                                    }
+                                }
+                            } else {
+                                warn!("[@BraidBot] Context file not found: {:?}", context_path);
                             }
                         }
-                    } else {
-                        warn!("[@BraidBot] Context file not found: {:?}", context_path);
                     }
                 }
             }
@@ -330,22 +636,41 @@ impl AiChatManager {
             )));
         }
 
-        let chat_req = ChatRequest::new(chat_messages);
-
-        // Call the AI API
-        info!("[@BraidBot] Calling {} for response...", config.model);
+        chat_messages
+    }
 
-        let response = client
-            .exec_chat(&config.model, chat_req, None)
-            .await
-            .map_err(|e| anyhow::anyhow!("GenAI error: {}", e))?;
+    /// Accumulate streamed text, editing `message_id` at most once per
+    /// `interval` so the response grows live in the chat
+    async fn stream_into_message<S>(
+        store: &JsonChatStore,
+        room_id: &str,
+        message_id: &str,
+        chunks: S,
+        interval: Duration,
+    ) -> Result<String>
+    where
+        S: Stream<Item = Result<String>>,
+    {
+        let mut chunks = std::pin::pin!(chunks);
+        let mut text = String::new();
+        let mut last_edit = Instant::now();
+
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            if chunk.is_empty() {
+                continue;
+            }
+            text.push_str(&chunk);
 
-        let response_text = response
-            .first_text()
-            .unwrap_or("*No response generated*")
-            .to_string();
+            if last_edit.elapsed() >= interval {
+                if let Err(e) = store.edit_message(room_id, message_id, &text).await {
+                    warn!("[@BraidBot] Failed to update streaming message: {}", e);
+                }
+                last_edit = Instant::now();
+            }
+        }
 
-        Ok(response_text)
+        Ok(text)
     }
 
     /// Static helper for markdown append in spawned task
@@ -442,7 +767,9 @@ impl AiChatManager {
     }
 
     /// Start watching AI chat files for external changes
-    pub async fn start_watching(&self) -> Result<()> {
+    ///
+    /// The watcher stops when `shutdown` fires.
+    pub async fn start_watching(&self, shutdown: Shutdown) -> Result<()> {
         let (tx, mut rx) = mpsc::channel(100);
 
         let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
@@ -458,7 +785,17 @@ impl AiChatManager {
         let _ai_chats_dir = self.ai_chats_dir.clone();
 
         tokio::spawn(async move {
-            while let Some(event) = rx.recv().await {
+            loop {
+                let event = tokio::select! {
+                    event = rx.recv() => match event {
+                        Some(event) => event,
+                        None => break,
+                    },
+                    _ = shutdown.wait() => {
+                        info!("[@BraidBot] Stopped watching AI chat files");
+                        break;
+                    }
+                };
                 for path in event.paths {
                     if let Some(ext) = path.extension() {
                         if ext == "md" {
@@ -493,6 +830,53 @@ impl AiChatManager {
         }
     }
 
+    /// List the files available to `ai read context`
+    pub async fn list_context_files(&self) -> Result<Vec<ContextFileInfo>> {
+        let mut files = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.context_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if metadata.is_file() {
+                files.push(ContextFileInfo {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    size: metadata.len(),
+                });
+            }
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    /// Write (or replace) a context file
+    pub async fn add_context_file(&self, name: &str, bytes: &[u8]) -> Result<()> {
+        let path = context_file_path(&self.context_dir, name)?;
+        tokio::fs::create_dir_all(&self.context_dir).await?;
+        tokio::fs::write(&path, bytes).await?;
+        info!(
+            "[@BraidBot] Added context file {} ({} bytes)",
+            name,
+            bytes.len()
+        );
+        Ok(())
+    }
+
+    /// Delete a context file
+    pub async fn remove_context_file(&self, name: &str) -> Result<()> {
+        let path = context_file_path(&self.context_dir, name)?;
+        tokio::fs::remove_file(&path).await?;
+        info!("[@BraidBot] Removed context file {}", name);
+        Ok(())
+    }
+
+    /// Token and latency totals, including registered rooms with no usage yet
+    pub async fn metrics(&self) -> AiMetrics {
+        let mut metrics = self.metrics.read().await.clone();
+        for room_id in self.ai_rooms.read().await.keys() {
+            metrics.rooms.entry(room_id.clone()).or_default();
+        }
+        metrics
+    }
+
     /// List all AI chat rooms
     pub async fn list_ai_rooms(&self) -> Vec<String> {
         self.ai_rooms.read().await.keys().cloned().collect()
@@ -511,3 +895,420 @@ pub async fn on_new_message(
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::ChatServerConfig;
+    use crate::core::store::json_store::UpdateType;
+    use tempfile::TempDir;
+
+    /// Build a manager rooted in `dir` without touching the global BRAID_ROOT
+    async fn test_manager(dir: &Path) -> AiChatManager {
+        let config = ChatServerConfig::with_base_dir(dir);
+        let store = Arc::new(JsonChatStore::new(config).await.unwrap());
+        let ai_chats_dir = dir.join("ai");
+        tokio::fs::create_dir_all(&ai_chats_dir).await.unwrap();
+        AiChatManager {
+            config: AiConfig::default(),
+            _store: store,
+            ai_chats_dir,
+            ai_rooms: Arc::new(RwLock::new(HashMap::new())),
+            genai_client: GenAIClient::default(),
+            pending_responses: Arc::new(RwLock::new(HashMap::new())),
+            context_dir: {
+                let context_dir = dir.join("context");
+                std::fs::create_dir_all(&context_dir).unwrap();
+                context_dir
+            },
+            metrics: Arc::new(RwLock::new(AiMetrics::default())),
+        }
+    }
+
+    /// Reply streamed by the mock backend
+    const MOCK_STREAM: &str = concat!(
+        r#"data: {"choices":[{"index":0,"delta":{"content":"Hello"}}]}"#,
+        "\n\n",
+        r#"data: {"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":3}}"#,
+        "\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    /// A genai client whose requests all go to a local OpenAI-style server
+    ///
+    /// Also returns the model named in each request the server receives.
+    async fn mock_backend() -> (GenAIClient, Arc<std::sync::Mutex<Vec<String>>>) {
+        use axum::{http::header, routing::post, Json, Router};
+        use genai::adapter::AdapterKind;
+        use genai::resolver::{AuthData, Endpoint, ServiceTargetResolver};
+        use genai::{ModelIden, ServiceTarget};
+
+        let models: Arc<std::sync::Mutex<Vec<String>>> = Arc::default();
+        let seen = models.clone();
+        let app = Router::new().route(
+            "/v1/chat/completions",
+            post(move |Json(body): Json<serde_json::Value>| async move {
+                let model = body["model"].as_str().unwrap_or_default().to_string();
+                seen.lock().unwrap().push(model);
+                ([(header::CONTENT_TYPE, "text/event-stream")], MOCK_STREAM)
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/v1/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let resolver = ServiceTargetResolver::from_resolver_fn(
+            move |target: ServiceTarget| -> genai::resolver::Result<ServiceTarget> {
+                Ok(ServiceTarget {
+                    endpoint: Endpoint::from_owned(endpoint.clone()),
+                    auth: AuthData::from_single("test-key"),
+                    model: ModelIden::new(AdapterKind::OpenAI, target.model.model_name),
+                })
+            },
+        );
+        let client = GenAIClient::builder()
+            .with_service_target_resolver(resolver)
+            .build();
+        (client, models)
+    }
+
+    /// Mention the bot in `room_id` and wait for the response to finish
+    async fn mention_and_wait(manager: &AiChatManager, room_id: &str) {
+        let mention = manager
+            ._store
+            .add_message(
+                room_id,
+                "alice",
+                "@BraidBot hello",
+                MessageType::Text,
+                None,
+                vec![],
+            )
+            .await
+            .unwrap();
+        manager.process_message(room_id, &mention).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while manager.pending_responses.read().await.contains_key(room_id) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_saved_ai_rooms_are_registered() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = test_manager(temp_dir.path()).await;
+        let mut ai_room = crate::core::models::ChatRoom::new("bot", "Bot", "alice");
+        ai_room.is_ai_chat = true;
+        manager._store.create_room(ai_room).await.unwrap();
+        let plain = crate::core::models::ChatRoom::new("team", "Team", "alice");
+        manager._store.create_room(plain).await.unwrap();
+
+        assert_eq!(manager.register_saved_rooms().await.unwrap(), 1);
+        assert_eq!(manager.list_ai_rooms().await, vec!["bot"]);
+        assert!(temp_dir.path().join("ai").join("bot.md").exists());
+        assert_eq!(manager.register_saved_rooms().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_room_override_model() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = test_manager(temp_dir.path()).await;
+        let (client, models) = mock_backend().await;
+        manager.genai_client = client;
+        manager
+            .register_ai_room(
+                "custom",
+                Some(AiRoomOverride {
+                    model: Some("openai::gpt-4o-mini".to_string()),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+        manager.register_ai_room("plain", None).await.unwrap();
+
+        let custom = manager.room_config("custom").await;
+        assert_eq!(custom.model, "openai::gpt-4o-mini");
+        assert_eq!(custom.system_prompt, AiConfig::default().system_prompt);
+        assert_eq!(
+            manager.room_config("plain").await.model,
+            AiConfig::default().model
+        );
+
+        // Each room's model is the one the backend is asked for
+        mention_and_wait(&manager, "custom").await;
+        mention_and_wait(&manager, "plain").await;
+        assert_eq!(*models.lock().unwrap(), ["gpt-4o-mini", "qwen3:4b"]);
+    }
+
+    #[tokio::test]
+    async fn test_model_command_sets_room_override() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = test_manager(temp_dir.path()).await;
+        manager.register_ai_room("room", None).await.unwrap();
+
+        let command = manager
+            ._store
+            .add_message(
+                "room",
+                "alice",
+                "/model ollama::llama3",
+                MessageType::Text,
+                None,
+                vec![],
+            )
+            .await
+            .unwrap();
+        let reply = manager
+            .process_message("room", &command)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(reply.content.contains("ollama::llama3"));
+        assert_eq!(manager.room_config("room").await.model, "ollama::llama3");
+
+        let reset = manager
+            ._store
+            .add_message(
+                "room",
+                "alice",
+                "/model default",
+                MessageType::Text,
+                None,
+                vec![],
+            )
+            .await
+            .unwrap();
+        manager.process_message("room", &reset).await.unwrap();
+        assert_eq!(
+            manager.room_config("room").await.model,
+            AiConfig::default().model
+        );
+    }
+
+    #[tokio::test]
+    async fn test_context_file_reaches_chat_request() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = test_manager(temp_dir.path()).await;
+
+        manager
+            .add_context_file("notes.txt", b"Braid syncs state over HTTP.")
+            .await
+            .unwrap();
+        let files = manager.list_context_files().await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "notes.txt");
+        assert_eq!(files[0].size, 28);
+
+        let trigger = manager
+            ._store
+            .add_message(
+                "room",
+                "alice",
+                "@BraidBot ai read context notes.txt",
+                MessageType::Text,
+                None,
+                vec![],
+            )
+            .await
+            .unwrap();
+        let messages = AiChatManager::build_chat_messages(
+            &AiConfig::default(),
+            &[],
+            &trigger,
+            &manager.context_dir,
+        )
+        .await;
+        assert!(messages.iter().any(|m| {
+            matches!(m.role, genai::chat::ChatRole::System)
+                && m.content
+                    .first_text()
+                    .is_some_and(|t| t.contains("Braid syncs state over HTTP."))
+        }));
+
+        manager.remove_context_file("notes.txt").await.unwrap();
+        assert!(manager.list_context_files().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_context_file_names_cannot_escape() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = test_manager(temp_dir.path()).await;
+
+        for name in ["../escape.txt", "sub/file.txt", "..", "", "/etc/passwd"] {
+            assert!(
+                manager.add_context_file(name, b"x").await.is_err(),
+                "{:?} accepted",
+                name
+            );
+        }
+        assert!(!temp_dir.path().join("escape.txt").exists());
+    }
+
+    #[test]
+    fn test_context_command() {
+        let mut overrides = AiRoomOverride::default();
+        overrides.apply_command("/context off").unwrap();
+        assert_eq!(overrides.enable_context, Some(false));
+        assert!(!AiConfig::default().merged(&overrides).enable_context);
+        assert!(overrides.apply_command("hello /model x").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_mentions_start_one_generation() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = test_manager(temp_dir.path()).await;
+        manager.register_ai_room("room", None).await.unwrap();
+
+        let mut mentions = Vec::new();
+        for sender in ["alice", "bob"] {
+            let msg = manager
+                ._store
+                .add_message(
+                    "room",
+                    sender,
+                    "@BraidBot what's new?",
+                    MessageType::Text,
+                    None,
+                    vec![],
+                )
+                .await
+                .unwrap();
+            mentions.push(msg);
+        }
+
+        let (first, second) = tokio::join!(
+            manager.process_message("room", &mentions[0]),
+            manager.process_message("room", &mentions[1]),
+        );
+        let replies = [first.unwrap().unwrap(), second.unwrap().unwrap()];
+        let thinking = replies
+            .iter()
+            .filter(|m| m.content == THINKING_MARKER)
+            .count();
+        let busy = replies.iter().filter(|m| m.content == BUSY_MARKER).count();
+        assert_eq!((thinking, busy), (1, 1));
+
+        // Our own status messages never trigger a response
+        let echo = manager
+            ._store
+            .add_message(
+                "room",
+                "alice",
+                &format!("{} @BraidBot", THINKING_MARKER),
+                MessageType::Text,
+                None,
+                vec![],
+            )
+            .await
+            .unwrap();
+        assert!(manager
+            .process_message("room", &echo)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_metrics_include_idle_rooms() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = test_manager(temp_dir.path()).await;
+        let (client, _models) = mock_backend().await;
+        manager.genai_client = client;
+        manager.register_ai_room("busy", None).await.unwrap();
+        manager.register_ai_room("idle", None).await.unwrap();
+
+        for _ in 0..2 {
+            mention_and_wait(&manager, "busy").await;
+        }
+        let messages = manager._store.get_messages("busy", None).await.unwrap();
+        assert_eq!(messages.last().unwrap().content, "Hello");
+
+        // Token counts come from the usage the backend streamed
+        let metrics = manager.metrics().await;
+        assert_eq!(metrics.rooms["busy"].responses, 2);
+        assert_eq!(metrics.rooms["busy"].prompt_tokens, 24);
+        assert_eq!(metrics.rooms["busy"].completion_tokens, 6);
+        assert_eq!(metrics.rooms["idle"], UsageTotals::default());
+        assert_eq!(metrics.total.responses, 2);
+        assert_eq!(metrics.total.prompt_tokens, 24);
+    }
+
+    #[tokio::test]
+    async fn test_stream_edits_grow_message() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+        let placeholder = store
+            .add_message(
+                "room",
+                "@BraidBot",
+                THINKING_MARKER,
+                MessageType::Text,
+                None,
+                vec![],
+            )
+            .await
+            .unwrap();
+        let mut updates = store.get_channel("room").await.tx.subscribe();
+
+        let chunks = futures::stream::iter(["Hello", ", ", "world"].map(|c| Ok(c.to_string())));
+        let text = AiChatManager::stream_into_message(
+            &store,
+            "room",
+            &placeholder.id,
+            chunks,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
+        assert_eq!(text, "Hello, world");
+
+        let mut edits = Vec::new();
+        while let Ok(update) = updates.try_recv() {
+            if matches!(update.update_type, UpdateType::Message) {
+                let msg: Message = serde_json::from_value(update.data).unwrap();
+                edits.push(msg.content);
+            }
+        }
+        assert_eq!(edits, vec!["Hello", "Hello, ", "Hello, world"]);
+        assert!(edits
+            .windows(2)
+            .all(|w| w[1].starts_with(&w[0]) && w[1].len() > w[0].len()));
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_propagates() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+        let placeholder = store
+            .add_message(
+                "room",
+                "@BraidBot",
+                THINKING_MARKER,
+                MessageType::Text,
+                None,
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let chunks = futures::stream::iter(vec![
+            Ok("partial".to_string()),
+            Err(anyhow::anyhow!("connection reset")),
+        ]);
+        let result = AiChatManager::stream_into_message(
+            &store,
+            "room",
+            &placeholder.id,
+            chunks,
+            Duration::ZERO,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
@@ -3,21 +3,41 @@
 //! Provides a simplified API for chat rooms to use Diamond-types
 //! conflict resolution with full edit history support.
 
-use crate::core::models::{BlobRef, ChatUpdate, EditRecord, Message, MessageType};
+use crate::core::models::{
+    BlobRef, ChatPatch, ChatUpdate, EditRecord, Message, MessageType, Reaction, ReactionEntry,
+};
 use braid_core::core::merge::diamond::DiamondCRDT;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use uuid::Uuid;
 
+/// Early edits held across all messages; the oldest go once this is reached
+const MAX_PENDING_EDITS: usize = 1000;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ChatCrdtState {
     pub room_id: String,
     pub node_id: String,
     pub next_seq: u64,
     pub current_version: Vec<braid_http::types::Version>,
-    pub version_graph: HashMap<String, Vec<braid_http::types::Version>>,
-    pub messages: HashMap<String, Message>,
+    pub version_graph: BTreeMap<String, Vec<braid_http::types::Version>>,
+    /// Messages by id
+    pub messages: BTreeMap<String, Message>,
+    /// Sorted by (message id, user, emoji)
+    #[serde(default)]
+    pub reactions: Vec<ReactionEntry>,
+}
+
+/// (message id, user, emoji)
+type ReactionKey = (String, String, String);
+
+/// A peer's edit, held until the message it edits arrives
+struct PendingEdit {
+    version: String,
+    parents: Vec<braid_http::types::Version>,
+    edited_at: DateTime<Utc>,
+    content: String,
 }
 
 pub struct ChatCrdt {
@@ -28,6 +48,10 @@ pub struct ChatCrdt {
     messages: HashMap<String, Message>,
     /// Track message IDs by their version (for edit lookups)
     version_to_msg: HashMap<String, String>,
+    /// Winning op for each reaction, including removed ones
+    reactions: HashMap<ReactionKey, ReactionEntry>,
+    /// Peer edits that arrived before their message, with its id, oldest first
+    pending_edits: VecDeque<(String, PendingEdit)>,
 }
 
 impl ChatCrdt {
@@ -39,6 +63,8 @@ impl ChatCrdt {
             next_seq: 1,
             messages: HashMap::new(),
             version_to_msg: HashMap::new(),
+            reactions: HashMap::new(),
+            pending_edits: VecDeque::new(),
         }
     }
 
@@ -47,22 +73,36 @@ impl ChatCrdt {
         crdt.next_seq = state.next_seq;
         
         // Rebuild message index
-        for (version, msg) in &state.messages {
-            crdt.messages.insert(msg.id.clone(), msg.clone());
-            crdt.version_to_msg.insert(version.clone(), msg.id.clone());
+        // Messages are keyed by id, so index them by their own versions
+        for msg in state.messages.into_values() {
+            let versions = std::iter::once(&msg.version)
+                .chain(msg.edit_history.iter().map(|r| &r.version));
+            for version in versions {
+                crdt.version_to_msg.insert(version.clone(), msg.id.clone());
+            }
+            crdt.messages.insert(msg.id.clone(), msg);
+        }
+        for entry in state.reactions {
+            let key = (entry.msg_id.clone(), entry.user.clone(), entry.emoji.clone());
+            crdt.reactions.insert(key, entry);
         }
         
         crdt
     }
 
+    /// Snapshot for persistence, in a canonical order so the same history
+    /// always serializes to the same bytes
     pub fn export_state(&self) -> ChatCrdtState {
+        let mut reactions: Vec<_> = self.reactions.iter().collect();
+        reactions.sort_by(|a, b| a.0.cmp(b.0));
         ChatCrdtState {
             room_id: self.room_id.clone(),
             node_id: self.node_id.clone(),
             next_seq: self.next_seq,
             current_version: self.get_frontier(),
-            version_graph: HashMap::new(), // Simplified for now
-            messages: self.messages.clone(),
+            version_graph: BTreeMap::new(), // Simplified for now
+            messages: self.messages.iter().map(|(id, m)| (id.clone(), m.clone())).collect(),
+            reactions: reactions.into_iter().map(|(_, e)| e.clone()).collect(),
         }
     }
 
@@ -81,6 +121,14 @@ impl ChatCrdt {
         version
     }
 
+    /// Lamport step: make sure our next version sorts after `version`
+    fn observe_version(&mut self, version: &str) {
+        let (seq, _) = version_order(version);
+        if seq >= self.next_seq {
+            self.next_seq = seq + 1;
+        }
+    }
+
     /// Add a new message to the chat
     pub fn add_message(
         &mut self,
@@ -163,29 +211,27 @@ impl ChatCrdt {
         Ok((new_version, msg_clone))
     }
 
-    /// Soft-delete a message (sets deleted flag)
-    pub fn delete_message(
-        &mut self,
-        msg_id: &str,
-        deleter: &str,
-    ) -> anyhow::Result<(String, Message)> {
-        // Check permissions first
+    /// Soft-delete a message
+    ///
+    /// Creates a new version whose parent is the message's current one and
+    /// leaves a tombstone behind, so the delete syncs like any other edit.
+    /// Permission checks are up to the caller.
+    pub fn delete_message(&mut self, msg_id: &str) -> anyhow::Result<(String, Message)> {
         let msg = self.messages.get(msg_id)
             .ok_or_else(|| anyhow::anyhow!("Message not found: {}", msg_id))?;
-        
-        // Only sender can delete
-        if msg.sender != deleter {
-            anyhow::bail!("Only the original sender can delete this message");
+        if msg.deleted {
+            anyhow::bail!("Message already deleted");
         }
-        
+        let parents = vec![braid_http::types::Version::String(msg.version.clone())];
+
         let version = self.generate_version();
-        
-        // Now modify
         let msg = self.messages.get_mut(msg_id).unwrap();
-        msg.deleted = true;
-        msg.edited_at = Some(Utc::now());
-        
-        Ok((version, msg.clone()))
+        msg.mark_deleted(version.clone(), parents);
+
+        let msg_clone = msg.clone();
+        self.version_to_msg.insert(version.clone(), msg_id.to_string());
+
+        Ok((version, msg_clone))
     }
 
     /// Get a message by ID
@@ -224,49 +270,171 @@ impl ChatCrdt {
             .collect()
     }
 
-    /// Add a reaction to a message
+    /// Case-insensitive search over live message content, newest first
+    ///
+    /// Every whitespace-separated token in `query` must appear in the
+    /// message. An empty query matches nothing.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&Message> {
+        let tokens: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits: Vec<&Message> = self.messages.values()
+            .filter(|m| !m.deleted)
+            .filter(|m| {
+                let content = m.content.to_lowercase();
+                tokens.iter().all(|t| content.contains(t.as_str()))
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.created_at.cmp(&a.created_at)
+                .then_with(|| version_order(&b.version).cmp(&version_order(&a.version)))
+        });
+        hits.truncate(limit);
+        hits
+    }
+
+    /// React to a message with `emoji`
+    ///
+    /// Idempotent: reacting twice with the same emoji leaves one reaction.
+    /// Returns the version of the op and the updated message.
     pub fn add_reaction(
         &mut self,
         msg_id: &str,
-        emoji: &str,
         user: &str,
-    ) -> anyhow::Result<()> {
-        let msg = self.messages.get_mut(msg_id)
-            .ok_or_else(|| anyhow::anyhow!("Message not found"))?;
-        
-        // Check if user already reacted with this emoji
-        if msg.reactions.iter().any(|r| r.emoji == emoji && r.user == user) {
-            anyhow::bail!("Already reacted");
-        }
-        
-        msg.reactions.push(crate::core::models::Reaction {
-            emoji: emoji.to_string(),
-            user: user.to_string(),
-            timestamp: Utc::now(),
-        });
-        
-        Ok(())
+        emoji: &str,
+    ) -> anyhow::Result<(String, Message)> {
+        self.set_reaction(msg_id, user, emoji, true)
     }
 
-    /// Remove a reaction
+    /// Take back a reaction; a no-op if `user` hadn't reacted with `emoji`
     pub fn remove_reaction(
         &mut self,
         msg_id: &str,
+        user: &str,
         emoji: &str,
+    ) -> anyhow::Result<(String, Message)> {
+        self.set_reaction(msg_id, user, emoji, false)
+    }
+
+    fn set_reaction(
+        &mut self,
+        msg_id: &str,
         user: &str,
-    ) -> anyhow::Result<()> {
-        let msg = self.messages.get_mut(msg_id)
-            .ok_or_else(|| anyhow::anyhow!("Message not found"))?;
-        
-        msg.reactions.retain(|r| !(r.emoji == emoji && r.user == user));
-        Ok(())
+        emoji: &str,
+        present: bool,
+    ) -> anyhow::Result<(String, Message)> {
+        if !self.messages.contains_key(msg_id) {
+            anyhow::bail!("Message not found: {}", msg_id);
+        }
+
+        let version = self.generate_version();
+        self.apply_reaction(ReactionEntry {
+            msg_id: msg_id.to_string(),
+            user: user.to_string(),
+            emoji: emoji.to_string(),
+            version: version.clone(),
+            present,
+        });
+        self.version_to_msg.insert(version.clone(), msg_id.to_string());
+
+        Ok((version, self.messages[msg_id].clone()))
+    }
+
+    /// Record a reaction op if it beats the one we have, then refresh the
+    /// message's reaction list
+    ///
+    /// Ops for messages we haven't seen yet are kept and applied once the
+    /// message arrives.
+    fn apply_reaction(&mut self, entry: ReactionEntry) {
+        let key = (entry.msg_id.clone(), entry.user.clone(), entry.emoji.clone());
+        if let Some(current) = self.reactions.get(&key) {
+            if version_order(&current.version) >= version_order(&entry.version) {
+                return;
+            }
+        }
+        self.reactions.insert(key, entry.clone());
+        self.sync_reaction(&entry);
+    }
+
+    /// Bring `msg.reactions` in line with the winning op for one reaction
+    fn sync_reaction(&mut self, entry: &ReactionEntry) {
+        let Some(msg) = self.messages.get_mut(&entry.msg_id) else {
+            return;
+        };
+        let existing = msg
+            .reactions
+            .iter()
+            .position(|r| r.user == entry.user && r.emoji == entry.emoji);
+        match (entry.present, existing) {
+            (true, None) => {
+                msg.reactions.push(Reaction {
+                    emoji: entry.emoji.clone(),
+                    user: entry.user.clone(),
+                    timestamp: Utc::now(),
+                });
+                // Same order on every peer regardless of arrival order
+                msg.reactions
+                    .sort_by(|a, b| (&a.emoji, &a.user).cmp(&(&b.emoji, &b.user)));
+            }
+            (false, Some(idx)) => {
+                msg.reactions.remove(idx);
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply any reaction ops that arrived before their message
+    fn sync_reactions_for(&mut self, msg_id: &str) {
+        let entries: Vec<_> = self
+            .reactions
+            .values()
+            .filter(|e| e.msg_id == msg_id)
+            .cloned()
+            .collect();
+        for entry in &entries {
+            self.sync_reaction(entry);
+        }
+    }
+
+    /// Apply a peer's edit, or hold it until its message arrives
+    ///
+    /// The highest version wins and deletes win over edits, so peers agree
+    /// whatever order edits arrive in. Returns whether the content changed.
+    fn merge_edit(&mut self, msg_id: &str, edit: PendingEdit) -> bool {
+        let Some(msg) = self.messages.get_mut(msg_id) else {
+            if self.pending_edits.len() >= MAX_PENDING_EDITS {
+                self.pending_edits.pop_front();
+            }
+            self.pending_edits.push_back((msg_id.to_string(), edit));
+            return false;
+        };
+        if msg.deleted {
+            return false;
+        }
+        self.version_to_msg.insert(edit.version.clone(), msg_id.to_string());
+        if version_order(&edit.version) <= version_order(&msg.version) {
+            return false;
+        }
+        msg.add_edit(edit.version, edit.content, edit.parents);
+        msg.edited_at = Some(edit.edited_at);
+        true
     }
 
     /// Merge updates from remote
+    ///
+    /// Returns the messages the updates added, edited or deleted.
     pub fn merge_updates(&mut self, updates: Vec<ChatUpdate>) -> Vec<Message> {
-        let mut new_msgs = Vec::new();
-        
+        let mut changed: Vec<String> = Vec::new();
+        let mut mark_changed = |id: &str| {
+            if !changed.iter().any(|c| c == id) {
+                changed.push(id.to_string());
+            }
+        };
+
         for update in updates {
+            self.observe_version(&update.version);
             for patch in update.patches {
                 match patch {
                     crate::core::models::ChatPatch::AddMessage {
@@ -274,57 +442,265 @@ impl ChatCrdt {
                         content,
                         sender,
                         message_type,
+                        reply_to,
+                        blob_refs,
                     } => {
                         if !self.messages.contains_key(&id) {
-                            let m = Message::new(
+                            let mut m = Message::new(
                                 id.clone(),
                                 sender,
                                 content,
                                 update.version.clone(),
                                 update.parents.clone(),
                             ).with_message_type(message_type);
-                            
-                            self.messages.insert(id.clone(), m.clone());
-                            self.version_to_msg.insert(update.version.clone(), id);
-                            new_msgs.push(m);
+                            m.created_at = update.timestamp;
+                            m.reply_to = reply_to;
+                            m.blob_refs = blob_refs;
+
+                            self.messages.insert(id.clone(), m);
+                            self.version_to_msg.insert(update.version.clone(), id.clone());
+                            self.sync_reactions_for(&id);
+                            let (early, rest) = std::mem::take(&mut self.pending_edits)
+                                .into_iter()
+                                .partition(|(msg_id, _)| *msg_id == id);
+                            self.pending_edits = rest;
+                            for (_, edit) in early {
+                                self.merge_edit(&id, edit);
+                            }
+                            mark_changed(&id);
+                        } else if let Some(tombstone) = self
+                            .messages
+                            .get_mut(&id)
+                            .filter(|m| m.deleted && m.sender.is_empty())
+                        {
+                            // Its delete came first; fill in what the add knows
+                            tombstone.sender = sender;
+                            tombstone.message_type = message_type;
+                            tombstone.created_at = update.timestamp;
+                            tombstone.reply_to = reply_to;
+                            tombstone.blob_refs = blob_refs;
+                            self.version_to_msg.insert(update.version.clone(), id.clone());
+                            mark_changed(&id);
                         }
                     }
                     crate::core::models::ChatPatch::EditMessage { id, new_content } => {
-                        if let Some(msg) = self.messages.get_mut(&id) {
-                            msg.add_edit(
-                                update.version.clone(),
-                                new_content,
-                                update.parents.clone(),
-                            );
+                        let edit = PendingEdit {
+                            version: update.version.clone(),
+                            parents: update.parents.clone(),
+                            edited_at: update.timestamp,
+                            content: new_content,
+                        };
+                        if self.merge_edit(&id, edit) {
+                            mark_changed(&id);
                         }
                     }
                     crate::core::models::ChatPatch::DeleteMessage { id } => {
-                        if let Some(msg) = self.messages.get_mut(&id) {
-                            msg.deleted = true;
-                            msg.edited_at = Some(Utc::now());
+                        // A delete can arrive before the message; keep the
+                        // tombstone so the add that follows doesn't revive it
+                        let msg = self.messages.entry(id.clone()).or_insert_with(|| {
+                            let mut placeholder = Message::new(id.clone(), "", "", "", Vec::new());
+                            placeholder.created_at = update.timestamp;
+                            placeholder
+                        });
+                        if !msg.deleted {
+                            msg.mark_deleted(update.version.clone(), update.parents.clone());
+                            msg.edited_at = Some(update.timestamp);
+                            self.version_to_msg.insert(update.version.clone(), id.clone());
+                            self.pending_edits.retain(|(msg_id, _)| *msg_id != id);
+                            mark_changed(&id);
                         }
                     }
                     crate::core::models::ChatPatch::AddReaction { msg_id, emoji, user } => {
-                        let _ = self.add_reaction(&msg_id, &emoji, &user);
+                        self.apply_reaction(ReactionEntry {
+                            msg_id,
+                            user,
+                            emoji,
+                            version: update.version.clone(),
+                            present: true,
+                        });
                     }
                     crate::core::models::ChatPatch::RemoveReaction { msg_id, emoji, user } => {
-                        let _ = self.remove_reaction(&msg_id, &emoji, &user);
+                        self.apply_reaction(ReactionEntry {
+                            msg_id,
+                            user,
+                            emoji,
+                            version: update.version.clone(),
+                            present: false,
+                        });
                     }
                 }
             }
         }
-        
-        new_msgs
+
+        changed
+            .iter()
+            .filter_map(|id| self.messages.get(id).cloned())
+            .collect()
     }
 
     /// Generate sync updates for Braid protocol
+    ///
+    /// Returns the ops a peer at `known_versions` is missing, oldest first.
+    /// A peer that knows `n@node` is assumed to know every earlier op from
+    /// `node`. Deleted messages are sent as tombstones.
     pub fn generate_sync_braid(
         &self,
-        _known_versions: &[braid_http::types::Version],
+        known_versions: &[braid_http::types::Version],
     ) -> Vec<ChatUpdate> {
-        // Return all messages as updates
-        // Real implementation would filter by known_versions
-        vec![]
+        let known: Vec<String> = known_versions.iter().map(|v| v.to_string()).collect();
+        let is_known = |version: &str| {
+            let (seq, node) = version_order(version);
+            known.iter().any(|k| {
+                let (known_seq, known_node) = version_order(k);
+                known_node == node && known_seq >= seq
+            })
+        };
+        let add_patch = |msg: &Message, content: &str| ChatPatch::AddMessage {
+            id: msg.id.clone(),
+            content: content.to_string(),
+            sender: msg.sender.clone(),
+            message_type: msg.message_type.clone(),
+            reply_to: msg.reply_to.clone(),
+            blob_refs: msg.blob_refs.clone(),
+        };
+
+        let mut updates = Vec::new();
+        for msg in self.messages.values() {
+            let changed_at = msg.edited_at.unwrap_or(msg.created_at);
+            if msg.deleted {
+                if !is_known(&msg.version) {
+                    updates.push(sync_update(
+                        &msg.version,
+                        &msg.parents,
+                        &msg.sender,
+                        changed_at,
+                        vec![add_patch(msg, ""), ChatPatch::DeleteMessage { id: msg.id.clone() }],
+                    ));
+                }
+                continue;
+            }
+
+            // Edited messages keep their original as the first history entry
+            let original = msg.edit_history.first();
+            let (created, parents, content) = match original {
+                Some(o) => (&o.version, &o.parents, &o.content),
+                None => (&msg.version, &msg.parents, &msg.content),
+            };
+            if !is_known(created) {
+                updates.push(sync_update(
+                    created,
+                    parents,
+                    &msg.sender,
+                    msg.created_at,
+                    vec![add_patch(msg, content)],
+                ));
+            }
+            if original.is_some() && !is_known(&msg.version) {
+                updates.push(sync_update(
+                    &msg.version,
+                    &msg.parents,
+                    &msg.sender,
+                    changed_at,
+                    vec![ChatPatch::EditMessage {
+                        id: msg.id.clone(),
+                        new_content: msg.content.clone(),
+                    }],
+                ));
+            }
+        }
+
+        for entry in self.reactions.values() {
+            if is_known(&entry.version) {
+                continue;
+            }
+            let (msg_id, user, emoji) =
+                (entry.msg_id.clone(), entry.user.clone(), entry.emoji.clone());
+            let patch = if entry.present {
+                ChatPatch::AddReaction { msg_id, emoji, user }
+            } else {
+                ChatPatch::RemoveReaction { msg_id, emoji, user }
+            };
+            updates.push(sync_update(&entry.version, &[], &entry.user, Utc::now(), vec![patch]));
+        }
+
+        updates.sort_by(|a, b| version_order(&a.version).cmp(&version_order(&b.version)));
+        updates
+    }
+
+    /// Highest sequence number `s` such that every message op up to `s`
+    /// happened before `cutoff`
+    ///
+    /// Reaction ops carry no timestamp and don't hold the cutoff back.
+    pub fn seq_before(&self, cutoff: DateTime<Utc>) -> u64 {
+        let mut ops: Vec<(&str, DateTime<Utc>)> = Vec::new();
+        for msg in self.messages.values() {
+            ops.push((&msg.version, msg.edited_at.unwrap_or(msg.created_at)));
+            ops.extend(msg.edit_history.iter().map(|r| (r.version.as_str(), r.timestamp)));
+        }
+        let recent = ops.iter().filter(|(_, at)| *at >= cutoff);
+        match recent.map(|(v, _)| version_order(v).0).min() {
+            Some(first_recent) => first_recent.saturating_sub(1),
+            None => self.next_seq.saturating_sub(1),
+        }
+    }
+
+    /// Drop version metadata for ops everyone has acknowledged
+    ///
+    /// Versions with a sequence number up to `acked_through` lose their
+    /// version index entry and parent links, and removed reactions lose
+    /// their tombstones. Message content, each message's current version
+    /// and the frontier are kept, as is any version an unacknowledged op
+    /// still lists as a parent. Versions in `pinned` (say, ones read
+    /// markers point at) keep their index entry so they still resolve.
+    ///
+    /// Returns how many index entries, tombstones and parent lists were
+    /// dropped.
+    pub fn prune_acknowledged<'a>(
+        &mut self,
+        acked_through: u64,
+        pinned: impl IntoIterator<Item = &'a str>,
+    ) -> usize {
+        let acked = |version: &str| version_order(version).0 <= acked_through;
+
+        let mut parents_of: HashMap<String, Vec<String>> = HashMap::new();
+        for msg in self.messages.values() {
+            let records = std::iter::once((&msg.version, &msg.parents))
+                .chain(msg.edit_history.iter().map(|r| (&r.version, &r.parents)));
+            for (version, parents) in records {
+                parents_of.insert(version.clone(), parents.iter().map(|p| p.to_string()).collect());
+            }
+        }
+
+        // Everything reachable from an unacknowledged op stays
+        let mut kept: HashSet<String> = HashSet::new();
+        let mut pending: Vec<String> = parents_of.keys().filter(|v| !acked(v)).cloned().collect();
+        while let Some(version) = pending.pop() {
+            for parent in parents_of.get(&version).into_iter().flatten() {
+                if kept.insert(parent.clone()) {
+                    pending.push(parent.clone());
+                }
+            }
+        }
+        let prunable = |version: &str| acked(version) && !kept.contains(version);
+
+        let mut resolvable: HashSet<String> = pinned.into_iter().map(str::to_string).collect();
+        resolvable.extend(self.messages.values().map(|m| m.version.clone()));
+        let before = self.version_to_msg.len() + self.reactions.len();
+        self.version_to_msg.retain(|v, _| resolvable.contains(v) || !prunable(v));
+        self.reactions.retain(|_, e| e.present || !prunable(&e.version));
+        let mut pruned = before - self.version_to_msg.len() - self.reactions.len();
+
+        for msg in self.messages.values_mut() {
+            let records = std::iter::once((&msg.version, &mut msg.parents))
+                .chain(msg.edit_history.iter_mut().map(|r| (&r.version, &mut r.parents)));
+            for (version, parents) in records {
+                if prunable(version) && !parents.is_empty() {
+                    parents.clear();
+                    pruned += 1;
+                }
+            }
+        }
+        pruned
     }
 
     /// Get the underlying Diamond CRDT content (for serialization)
@@ -357,6 +733,30 @@ impl ChatCrdt {
     }
 }
 
+fn sync_update(
+    version: &str,
+    parents: &[braid_http::types::Version],
+    author: &str,
+    timestamp: DateTime<Utc>,
+    patches: Vec<ChatPatch>,
+) -> ChatUpdate {
+    ChatUpdate {
+        version: version.to_string(),
+        parents: parents.to_vec(),
+        patches,
+        timestamp,
+        author: author.to_string(),
+    }
+}
+
+/// Total order on `seq@node` versions: sequence first, node id breaks ties
+pub(crate) fn version_order(version: &str) -> (u64, &str) {
+    match version.split_once('@') {
+        Some((seq, node)) => (seq.parse().unwrap_or(0), node),
+        None => (0, version),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -403,7 +803,528 @@ mod tests {
         let mut crdt = ChatCrdt::new("room1", "alice");
         let (_, msg) = crdt.add_message("alice", "Hello", MessageType::Text, None, vec![]);
         
-        let (_, deleted) = crdt.delete_message(&msg.id, "alice").unwrap();
+        let (version, deleted) = crdt.delete_message(&msg.id).unwrap();
         assert!(deleted.deleted);
+        assert!(deleted.content.is_empty());
+        assert_eq!(deleted.version, version);
+        assert_eq!(deleted.parents, vec![braid_http::types::Version::String(msg.version)]);
+        assert!(crdt.delete_message(&msg.id).is_err());
+    }
+
+    #[test]
+    fn test_delete_syncs_as_tombstone() {
+        let mut alice = ChatCrdt::new("room1", "alice");
+        let mut bob = ChatCrdt::new("room1", "bob");
+
+        let (_, msg) = alice.add_message("alice", "Oops", MessageType::Text, None, vec![]);
+        let (_, kept) = alice.add_message("alice", "Hi", MessageType::Text, None, vec![]);
+        bob.merge_updates(alice.generate_sync_braid(&[]));
+        assert_eq!(bob.get_message(&msg.id).unwrap().content, "Oops");
+
+        let bob_knows = bob.get_message(&kept.id).unwrap().version.clone();
+        let (delete_version, _) = alice.delete_message(&msg.id).unwrap();
+
+        // Bob only needs the delete
+        let braid = alice.generate_sync_braid(&[braid_http::types::Version::String(bob_knows)]);
+        assert_eq!(braid.len(), 1);
+        assert_eq!(braid[0].version, delete_version);
+        bob.merge_updates(braid);
+
+        let tombstone = bob.get_message(&msg.id).unwrap();
+        assert!(tombstone.deleted);
+        assert!(tombstone.content.is_empty());
+        assert_eq!(tombstone.version, delete_version);
+        assert!(!bob.get_message(&kept.id).unwrap().deleted);
+
+        // A peer that never saw the message still ends up with the tombstone
+        let mut carol = ChatCrdt::new("room1", "carol");
+        carol.merge_updates(alice.generate_sync_braid(&[]));
+        assert!(carol.get_message(&msg.id).unwrap().deleted);
+        assert_eq!(carol.get_message(&kept.id).unwrap().content, "Hi");
+    }
+
+    fn update(version: &str, author: &str, patch: ChatPatch) -> ChatUpdate {
+        ChatUpdate {
+            version: version.to_string(),
+            parents: vec![],
+            patches: vec![patch],
+            timestamp: Utc::now(),
+            author: author.to_string(),
+        }
+    }
+
+    fn add_message_update(version: &str, msg: &Message) -> ChatUpdate {
+        update(
+            version,
+            &msg.sender,
+            ChatPatch::AddMessage {
+                id: msg.id.clone(),
+                content: msg.content.clone(),
+                sender: msg.sender.clone(),
+                message_type: MessageType::Text,
+                reply_to: msg.reply_to.clone(),
+                blob_refs: msg.blob_refs.clone(),
+            },
+        )
+    }
+
+    fn reaction_update(
+        version: &str,
+        msg_id: &str,
+        user: &str,
+        emoji: &str,
+        add: bool,
+    ) -> ChatUpdate {
+        let (msg_id, user, emoji) = (msg_id.to_string(), user.to_string(), emoji.to_string());
+        let author = user.clone();
+        let patch = if add {
+            ChatPatch::AddReaction { msg_id, emoji, user }
+        } else {
+            ChatPatch::RemoveReaction { msg_id, emoji, user }
+        };
+        update(version, &author, patch)
+    }
+
+    fn reactions(crdt: &ChatCrdt, msg_id: &str) -> Vec<(String, String)> {
+        crdt.get_message(msg_id)
+            .unwrap()
+            .reactions
+            .iter()
+            .map(|r| (r.emoji.clone(), r.user.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_add_reaction_is_idempotent() {
+        let mut crdt = ChatCrdt::new("room1", "alice");
+        let (_, msg) = crdt.add_message("alice", "Hello", MessageType::Text, None, vec![]);
+
+        let (v1, _) = crdt.add_reaction(&msg.id, "bob", "👍").unwrap();
+        let (v2, reacted) = crdt.add_reaction(&msg.id, "bob", "👍").unwrap();
+
+        assert_ne!(v1, v2);
+        assert_eq!(reacted.reactions.len(), 1);
+        assert!(crdt.add_reaction("missing", "bob", "👍").is_err());
+    }
+
+    #[test]
+    fn test_remove_reaction_toggles_off() {
+        let mut crdt = ChatCrdt::new("room1", "alice");
+        let (_, msg) = crdt.add_message("alice", "Hello", MessageType::Text, None, vec![]);
+
+        crdt.add_reaction(&msg.id, "bob", "👍").unwrap();
+        crdt.add_reaction(&msg.id, "bob", "🎉").unwrap();
+        crdt.remove_reaction(&msg.id, "bob", "👍").unwrap();
+        assert_eq!(reactions(&crdt, &msg.id), vec![("🎉".to_string(), "bob".to_string())]);
+
+        // Toggling back on works after a removal
+        crdt.add_reaction(&msg.id, "bob", "👍").unwrap();
+        assert_eq!(reactions(&crdt, &msg.id).len(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_reactions_from_two_nodes_both_survive() {
+        let mut alice = ChatCrdt::new("room1", "alice");
+        let mut bob = ChatCrdt::new("room1", "bob");
+
+        let (version, msg) = alice.add_message("alice", "Hello", MessageType::Text, None, vec![]);
+        bob.merge_updates(vec![add_message_update(&version, &msg)]);
+
+        // Both react before hearing from each other
+        let (alice_v, _) = alice.add_reaction(&msg.id, "alice", "❤️").unwrap();
+        let (bob_v, _) = bob.add_reaction(&msg.id, "bob", "👍").unwrap();
+        alice.merge_updates(vec![reaction_update(&bob_v, &msg.id, "bob", "👍", true)]);
+        bob.merge_updates(vec![reaction_update(&alice_v, &msg.id, "alice", "❤️", true)]);
+
+        let expected = vec![
+            ("❤️".to_string(), "alice".to_string()),
+            ("👍".to_string(), "bob".to_string()),
+        ];
+        assert_eq!(reactions(&alice, &msg.id), expected);
+        assert_eq!(reactions(&bob, &msg.id), expected);
+    }
+
+    #[test]
+    fn test_reaction_ops_commute() {
+        let (version, msg) = ChatCrdt::new("room1", "alice")
+            .add_message("alice", "Hello", MessageType::Text, None, vec![]);
+        // Bob reacts from a phone, then takes it back from a laptop
+        let add = reaction_update("2@phone", &msg.id, "bob", "👍", true);
+        let remove = reaction_update("3@laptop", &msg.id, "bob", "👍", false);
+
+        let mut in_order = ChatCrdt::new("room1", "carol");
+        in_order.merge_updates(vec![
+            add_message_update(&version, &msg),
+            add.clone(),
+            remove.clone(),
+        ]);
+        // Reaction ops can even arrive before the message itself
+        let mut reversed = ChatCrdt::new("room1", "dave");
+        reversed.merge_updates(vec![remove, add, add_message_update(&version, &msg)]);
+
+        assert!(reactions(&in_order, &msg.id).is_empty());
+        assert!(reactions(&reversed, &msg.id).is_empty());
+
+        // A later local op wins over everything merged so far
+        let (_, msg) = reversed.add_reaction(&msg.id, "bob", "👍").unwrap();
+        assert_eq!(msg.reactions.len(), 1);
+    }
+
+    fn edit_update(version: &str, msg: &Message, new_content: &str) -> ChatUpdate {
+        let patch = ChatPatch::EditMessage {
+            id: msg.id.clone(),
+            new_content: new_content.to_string(),
+        };
+        update(version, &msg.sender, patch)
+    }
+
+    #[test]
+    fn test_merge_applies_edit_after_add() {
+        let mut alice = ChatCrdt::new("room1", "alice");
+        let mut bob = ChatCrdt::new("room1", "bob");
+        let (version, msg) = alice.add_message("alice", "Helo", MessageType::Text, None, vec![]);
+        bob.merge_updates(vec![add_message_update(&version, &msg)]);
+
+        let (edit_version, _) = alice.edit_message(&msg.id, "Hello", "alice").unwrap();
+        let changed = bob.merge_updates(alice.generate_sync_braid(&[]));
+
+        assert_eq!(changed.len(), 1);
+        let merged = bob.get_message(&msg.id).unwrap();
+        assert_eq!(merged.content, "Hello");
+        assert_eq!(merged.version, edit_version);
+        assert!(merged.edited_at.is_some());
+        assert_eq!(merged.edit_history[0].content, "Helo");
+        assert_eq!(bob.get_message_by_version(&edit_version).unwrap().id, msg.id);
+
+        // An older edit arriving late doesn't win, and replays are no-ops
+        assert!(bob.merge_updates(vec![edit_update("1@carol", &msg, "Hi")]).is_empty());
+        assert!(bob.merge_updates(vec![edit_update(&edit_version, &msg, "Hello")]).is_empty());
+        assert_eq!(bob.get_message(&msg.id).unwrap().content, "Hello");
+    }
+
+    #[test]
+    fn test_merge_buffers_edit_that_arrives_before_add() {
+        let (version, msg) = ChatCrdt::new("room1", "alice")
+            .add_message("alice", "Helo", MessageType::Text, None, vec![]);
+        let edit = edit_update("5@alice", &msg, "Hello");
+
+        let mut bob = ChatCrdt::new("room1", "bob");
+        assert!(bob.merge_updates(vec![edit]).is_empty());
+        assert!(bob.get_message(&msg.id).is_none());
+
+        let changed = bob.merge_updates(vec![add_message_update(&version, &msg)]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].content, "Hello");
+        assert_eq!(bob.get_message(&msg.id).unwrap().version, "5@alice");
+        assert!(bob.pending_edits.is_empty());
+    }
+
+    #[test]
+    fn test_pending_edits_drop_oldest_when_full() {
+        let (first_version, first) = ChatCrdt::new("room1", "alice")
+            .add_message("alice", "Helo", MessageType::Text, None, vec![]);
+        let mut bob = ChatCrdt::new("room1", "bob");
+
+        bob.merge_updates(vec![edit_update("5@alice", &first, "Hello")]);
+        let missing = Message::new("never-sent", "alice", "", "", Vec::new());
+        let flood: Vec<_> = (0..MAX_PENDING_EDITS)
+            .map(|i| edit_update(&format!("{}@alice", i + 10), &missing, "spam"))
+            .collect();
+        bob.merge_updates(flood);
+        assert_eq!(bob.pending_edits.len(), MAX_PENDING_EDITS);
+
+        // The first message's early edit was the oldest, so it was dropped
+        bob.merge_updates(vec![add_message_update(&first_version, &first)]);
+        assert_eq!(bob.get_message(&first.id).unwrap().content, "Helo");
+        assert_eq!(bob.pending_edits.len(), MAX_PENDING_EDITS);
+    }
+
+    #[test]
+    fn test_merge_delete_before_add_stays_deleted() {
+        let (version, msg) = ChatCrdt::new("room1", "alice")
+            .add_message("alice", "Oops", MessageType::Text, None, vec![]);
+        let edit = edit_update("2@alice", &msg, "Oops!");
+        let delete = update("3@alice", "alice", ChatPatch::DeleteMessage { id: msg.id.clone() });
+
+        let mut bob = ChatCrdt::new("room1", "bob");
+        let changed = bob.merge_updates(vec![delete, edit, add_message_update(&version, &msg)]);
+
+        assert_eq!(changed.len(), 1);
+        let tombstone = bob.get_message(&msg.id).unwrap();
+        assert!(tombstone.deleted);
+        assert!(tombstone.content.is_empty());
+        assert_eq!(tombstone.version, "3@alice");
+        assert_eq!(tombstone.sender, "alice");
+        assert!(bob.pending_edits.is_empty());
+    }
+
+    #[test]
+    fn test_late_add_fills_in_tombstone() {
+        let mut alice = ChatCrdt::new("room1", "alice");
+        let (_, question) = alice.add_message("alice", "Logs?", MessageType::Text, None, vec![]);
+        let attachment = BlobRef {
+            hash: "abc123".to_string(),
+            content_type: "text/plain".to_string(),
+            filename: "server.log".to_string(),
+            size: 42,
+            inline_data: None,
+        };
+        let (version, reply) = alice.add_message(
+            "bob",
+            "Attached",
+            MessageType::Text,
+            Some(&question.id),
+            vec![attachment],
+        );
+        let delete = update("3@bob", "bob", ChatPatch::DeleteMessage { id: reply.id.clone() });
+
+        let mut carol = ChatCrdt::new("room1", "carol");
+        carol.merge_updates(vec![delete]);
+        let changed = carol.merge_updates(vec![add_message_update(&version, &reply)]);
+
+        assert_eq!(changed.len(), 1);
+        let tombstone = carol.get_message(&reply.id).unwrap();
+        assert!(tombstone.deleted && tombstone.content.is_empty());
+        assert_eq!(tombstone.sender, "bob");
+        assert_eq!(tombstone.reply_to.as_deref(), Some(question.id.as_str()));
+        assert_eq!(tombstone.blob_refs.len(), 1);
+        assert_eq!(tombstone.blob_refs[0].hash, "abc123");
+        assert_eq!(carol.get_message_by_version(&version).unwrap().id, reply.id);
+    }
+
+    #[test]
+    fn test_reply_and_attachments_sync_to_fresh_peer() {
+        let mut alice = ChatCrdt::new("room1", "alice");
+        let (_, question) = alice.add_message("alice", "Logs?", MessageType::Text, None, vec![]);
+        let attachment = BlobRef {
+            hash: "abc123".to_string(),
+            content_type: "text/plain".to_string(),
+            filename: "server.log".to_string(),
+            size: 42,
+            inline_data: None,
+        };
+        let (_, reply) = alice.add_message(
+            "bob",
+            "Attached",
+            MessageType::Text,
+            Some(&question.id),
+            vec![attachment],
+        );
+
+        // Over the wire and into a peer that never saw either message
+        let wire = serde_json::to_string(&alice.generate_sync_braid(&[])).unwrap();
+        let mut carol = ChatCrdt::new("room1", "carol");
+        carol.merge_updates(serde_json::from_str(&wire).unwrap());
+
+        let synced = carol.get_message(&reply.id).unwrap();
+        assert_eq!(synced.reply_to.as_deref(), Some(question.id.as_str()));
+        assert_eq!(synced.blob_refs.len(), 1);
+        let blob = &synced.blob_refs[0];
+        assert_eq!(
+            (blob.hash.as_str(), blob.filename.as_str(), blob.content_type.as_str(), blob.size),
+            ("abc123", "server.log", "text/plain", 42)
+        );
+        let plain = carol.get_message(&question.id).unwrap();
+        assert!(plain.reply_to.is_none() && plain.blob_refs.is_empty());
+    }
+
+    #[test]
+    fn test_same_history_serializes_identically() {
+        let add = |version: &str, id: &str, sender: &str| {
+            update(
+                version,
+                sender,
+                ChatPatch::AddMessage {
+                    id: id.to_string(),
+                    content: format!("{} from {}", id, sender),
+                    sender: sender.to_string(),
+                    message_type: MessageType::Text,
+                    reply_to: None,
+                    blob_refs: vec![],
+                },
+            )
+        };
+        let history = vec![
+            add("1@alice", "m1", "alice"),
+            add("2@bob", "m2", "bob"),
+            add("3@alice", "m3", "alice"),
+            update(
+                "4@alice",
+                "alice",
+                ChatPatch::EditMessage { id: "m1".into(), new_content: "edited".into() },
+            ),
+            update("5@bob", "bob", ChatPatch::DeleteMessage { id: "m2".into() }),
+            reaction_update("6@bob", "m1", "bob", "👍", true),
+            reaction_update("7@alice", "m3", "alice", "🎉", true),
+            reaction_update("8@bob", "m1", "bob", "👍", false),
+            reaction_update("9@alice", "m3", "alice", "🎉", false),
+        ];
+
+        let serialize = |crdt: &ChatCrdt| serde_json::to_string(&crdt.export_state()).unwrap();
+        let mut crdt = ChatCrdt::new("room1", "carol");
+        crdt.merge_updates(history);
+        let bytes = serialize(&crdt);
+
+        // Each reload rebuilds the in-memory maps, whose order varies
+        for _ in 0..8 {
+            let reloaded = ChatCrdt::import_state(crdt.export_state());
+            assert_eq!(serialize(&reloaded), bytes);
+        }
+    }
+
+    #[test]
+    fn test_import_state_indexes_message_versions() {
+        let mut alice = ChatCrdt::new("room1", "alice");
+        let (first_version, msg) = alice.add_message("alice", "Hi", MessageType::Text, None, vec![]);
+        let (edit_version, _) = alice.edit_message(&msg.id, "Hi!", "alice").unwrap();
+
+        let reloaded = ChatCrdt::import_state(alice.export_state());
+        assert_eq!(reloaded.get_message_by_version(&first_version).unwrap().id, msg.id);
+        assert_eq!(reloaded.get_message_by_version(&edit_version).unwrap().id, msg.id);
+        assert!(reloaded.get_message_by_version(&msg.id).is_none());
+    }
+
+    #[test]
+    fn test_merged_times_come_from_the_update() {
+        let sent = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let deleted = DateTime::from_timestamp(1_700_000_060, 0).unwrap();
+        let add = ChatUpdate {
+            timestamp: sent,
+            ..update(
+                "1@alice",
+                "alice",
+                ChatPatch::AddMessage {
+                    id: "m1".into(),
+                    content: "hi".into(),
+                    sender: "alice".into(),
+                    message_type: MessageType::Text,
+                    reply_to: None,
+                    blob_refs: vec![],
+                },
+            )
+        };
+        let delete = ChatUpdate {
+            timestamp: deleted,
+            ..update("2@alice", "alice", ChatPatch::DeleteMessage { id: "m1".into() })
+        };
+
+        let mut bob = ChatCrdt::new("room1", "bob");
+        bob.merge_updates(vec![add.clone()]);
+        assert_eq!(bob.get_message("m1").unwrap().created_at, sent);
+        bob.merge_updates(vec![delete.clone()]);
+        assert_eq!(bob.get_message("m1").unwrap().edited_at, Some(deleted));
+
+        // A peer merging the same history later ends up with the same state
+        let mut carol = ChatCrdt::new("room1", "bob");
+        carol.merge_updates(vec![add, delete]);
+        assert_eq!(
+            serde_json::to_string(&carol.export_state()).unwrap(),
+            serde_json::to_string(&bob.export_state()).unwrap()
+        );
+    }
+
+    fn say(crdt: &mut ChatCrdt, sender: &str, content: &str) -> Message {
+        crdt.add_message(sender, content, MessageType::Text, None, vec![]).1
+    }
+
+    #[test]
+    fn test_search_matches_all_tokens_newest_first() {
+        let mut crdt = ChatCrdt::new("room1", "alice");
+        let older = say(&mut crdt, "alice", "Deploy the Braid server");
+        say(&mut crdt, "bob", "braid is neat");
+        let newer = say(&mut crdt, "bob", "SERVER deploy done, braid up");
+
+        let hits = crdt.search("braid  deploy", 10);
+        let ids: Vec<_> = hits.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec![newer.id.as_str(), older.id.as_str()]);
+
+        assert_eq!(crdt.search("braid", 1).len(), 1);
+        assert!(crdt.search("   ", 10).is_empty());
+        assert!(crdt.search("braid missing", 10).is_empty());
+    }
+
+    #[test]
+    fn test_search_skips_deleted_messages() {
+        let mut crdt = ChatCrdt::new("room1", "alice");
+        let gone = say(&mut crdt, "alice", "secret plan");
+        let kept = say(&mut crdt, "alice", "public plan");
+        crdt.delete_message(&gone.id).unwrap();
+
+        let hits = crdt.search("plan", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, kept.id);
+        assert!(crdt.search("secret", 10).is_empty());
+    }
+
+    fn contents(crdt: &ChatCrdt) -> Vec<(String, String, String, bool)> {
+        let mut all: Vec<_> = crdt
+            .messages()
+            .values()
+            .map(|m| (m.id.clone(), m.content.clone(), m.version.clone(), m.deleted))
+            .collect();
+        all.sort();
+        all
+    }
+
+    #[test]
+    fn test_prune_acknowledged_keeps_frontier_and_messages() {
+        let mut crdt = ChatCrdt::new("room1", "alice");
+        let first = say(&mut crdt, "alice", "one"); // 1@alice
+        crdt.edit_message(&first.id, "one!", "alice").unwrap(); // 2@alice
+        crdt.edit_message(&first.id, "one!!", "alice").unwrap(); // 3@alice
+        crdt.add_reaction(&first.id, "bob", "👍").unwrap(); // 4@alice
+        crdt.remove_reaction(&first.id, "bob", "👍").unwrap(); // 5@alice
+        let second = say(&mut crdt, "bob", "two"); // 6@alice
+        crdt.edit_message(&second.id, "two!", "bob").unwrap(); // 7@alice
+
+        let frontier = crdt.get_frontier();
+        let before = contents(&crdt);
+
+        // 6@alice is acknowledged and superseded, but the unacknowledged
+        // edit at 7@alice still names it as a parent
+        assert_eq!(crdt.prune_acknowledged(6, []), 7);
+        assert_eq!(crdt.get_frontier(), frontier);
+        assert_eq!(contents(&crdt), before);
+        for gone in ["1@alice", "2@alice", "4@alice", "5@alice"] {
+            assert!(crdt.get_message_by_version(gone).is_none(), "{}", gone);
+        }
+        assert!(crdt.reactions.is_empty());
+        assert_eq!(crdt.get_message_by_version("3@alice").unwrap().id, first.id);
+        assert_eq!(crdt.get_message_by_version("6@alice").unwrap().id, second.id);
+        assert!(crdt.get_message(&first.id).unwrap().parents.is_empty());
+        assert_eq!(
+            crdt.get_message(&second.id).unwrap().parents,
+            vec![braid_http::types::Version::String("6@alice".to_string())]
+        );
+
+        // Once everything is acknowledged the rest goes too
+        assert_eq!(crdt.prune_acknowledged(7, []), 3);
+        assert_eq!(crdt.get_frontier(), frontier);
+        assert_eq!(contents(&crdt), before);
+        assert!(crdt.get_message_by_version("6@alice").is_none());
+        assert_eq!(crdt.get_message_by_version("7@alice").unwrap().id, second.id);
+        assert_eq!(crdt.prune_acknowledged(7, []), 0);
+
+        // A fresh peer still gets every message
+        assert!(crdt.generate_sync_braid(&frontier).is_empty());
+        let mut peer = ChatCrdt::new("room1", "carol");
+        peer.merge_updates(crdt.generate_sync_braid(&[]));
+        let without_versions = |all: Vec<(String, String, String, bool)>| -> Vec<_> {
+            all.into_iter().map(|(id, content, _, _)| (id, content)).collect()
+        };
+        assert_eq!(without_versions(contents(&peer)), without_versions(before));
+    }
+
+    #[test]
+    fn test_seq_before_stops_at_first_recent_op() {
+        let mut crdt = ChatCrdt::new("room1", "alice");
+        let first = say(&mut crdt, "alice", "old");
+        say(&mut crdt, "alice", "new");
+        let two_days_ago = Utc::now() - chrono::Duration::days(2);
+        crdt.messages.get_mut(&first.id).unwrap().created_at = two_days_ago;
+
+        assert_eq!(crdt.seq_before(Utc::now() - chrono::Duration::days(1)), 1);
+        assert_eq!(crdt.seq_before(Utc::now() - chrono::Duration::days(3)), 0);
+        assert_eq!(crdt.seq_before(Utc::now() + chrono::Duration::days(1)), 2);
     }
 }
@@ -3,14 +3,20 @@
 //! Handles friend requests, contacts, and user relationships.
 //! Stored in the same SQLite database as auth (users.sqlite).
 
+use crate::core::presence::PresenceTracker;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
 use std::path::Path;
+use std::sync::Arc;
 use tracing::info;
 use uuid::Uuid;
 
+/// A pending sent request: id, sender username and email, recipient id and
+/// email, message, created_at
+type SentRequestRow = (String, String, String, String, String, Option<String>, String);
+
 /// Friend request status
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(rename_all = "snake_case")]
@@ -51,37 +57,45 @@ pub struct Contact {
 
 /// Friend manager handles all friend-related operations
 pub struct FriendManager {
-    db_path: std::path::PathBuf,
+    /// Pool on `users.sqlite`, usually shared with `AuthManager`
+    pool: SqlitePool,
+    /// Source of contacts' online status
+    presence: Arc<PresenceTracker>,
 }
 
 impl FriendManager {
     /// Create new friend manager
     pub async fn new(base_dir: &Path) -> Result<Self> {
-        let db_path = base_dir.join("users.sqlite");
+        let pool = crate::core::db::open_pool(&crate::core::db::users_db_path(base_dir)).await?;
+        Self::from_pool(pool).await
+    }
 
-        let manager = Self { db_path };
+    /// Create a friend manager on an already-open pool
+    pub async fn from_pool(pool: SqlitePool) -> Result<Self> {
+        let manager = Self {
+            pool,
+            presence: Arc::new(PresenceTracker::new()),
+        };
         manager.init_db().await?;
 
         info!("[Friends] Initialized");
         Ok(manager)
     }
 
-    /// Get database connection
-    async fn get_pool(&self) -> Result<sqlx::SqlitePool> {
-        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-        use std::str::FromStr;
-
-        let options = SqliteConnectOptions::from_str(&format!(
-            "sqlite://{}",
-            self.db_path.to_string_lossy().replace('\\', "/")
-        ))?
-        .create_if_missing(true);
-        Ok(SqlitePoolOptions::new().connect_with(options).await?)
+    /// The database pool
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Share a presence tracker so contacts report who is online
+    pub fn with_presence(mut self, presence: Arc<PresenceTracker>) -> Self {
+        self.presence = presence;
+        self
     }
 
     /// Initialize database tables
     async fn init_db(&self) -> Result<()> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
 
         // Create friend_requests table
         sqlx::query(
@@ -100,7 +114,7 @@ impl FriendManager {
             )
             "#,
         )
-        .execute(&pool)
+        .execute(pool)
         .await?;
 
         // Create contacts table (established friendships)
@@ -117,27 +131,45 @@ impl FriendManager {
             )
             "#,
         )
-        .execute(&pool)
+        .execute(pool)
+        .await?;
+
+        // Create blocked_users table (one row per blocker -> blocked pair)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS blocked_users (
+                user_id TEXT NOT NULL,
+                blocked_user_id TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (user_id, blocked_user_id),
+                FOREIGN KEY (user_id) REFERENCES users(id),
+                FOREIGN KEY (blocked_user_id) REFERENCES users(id)
+            )
+            "#,
+        )
+        .execute(pool)
         .await?;
 
-        pool.close().await;
         Ok(())
     }
 
     /// Send a friend request
+    ///
+    /// If the recipient has blocked the sender the request is dropped, but the
+    /// sender still gets a normal-looking pending request back.
     pub async fn send_request(
         &self,
         from_user_id: String,
         to_email: String,
         message: Option<String>,
     ) -> Result<FriendRequest> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
 
         // Get recipient user info
         let recipient: Option<(String, String)> =
             sqlx::query_as("SELECT id, username FROM users WHERE email = ?")
                 .bind(&to_email)
-                .fetch_optional(&pool)
+                .fetch_optional(pool)
                 .await?;
 
         let (to_user_id, _to_username) =
@@ -153,7 +185,7 @@ impl FriendManager {
         .bind(&to_user_id)
         .bind(&to_user_id)
         .bind(&from_user_id)
-        .fetch_optional(&pool)
+        .fetch_optional(pool)
         .await?;
 
         if existing.is_some() {
@@ -167,7 +199,7 @@ impl FriendManager {
         )
         .bind(&from_user_id)
         .bind(&to_user_id)
-        .fetch_optional(&pool)
+        .fetch_optional(pool)
         .await?;
 
         if existing_req.is_some() {
@@ -178,9 +210,17 @@ impl FriendManager {
         let sender: (String, String) =
             sqlx::query_as("SELECT username, email FROM users WHERE id = ?")
                 .bind(&from_user_id)
-                .fetch_one(&pool)
+                .fetch_one(pool)
                 .await?;
 
+        let blocked: Option<(String,)> = sqlx::query_as(
+            "SELECT user_id FROM blocked_users WHERE user_id = ? AND blocked_user_id = ?",
+        )
+        .bind(&to_user_id)
+        .bind(&from_user_id)
+        .fetch_optional(pool)
+        .await?;
+
         let request = FriendRequest {
             id: Uuid::new_v4().to_string(),
             from_user_id: from_user_id.clone(),
@@ -194,6 +234,14 @@ impl FriendManager {
             responded_at: None,
         };
 
+        if blocked.is_some() {
+            info!(
+                "[Friends] Dropped request from blocked user: {} -> {}",
+                request.from_username, to_email
+            );
+            return Ok(request);
+        }
+
         // Insert request
         sqlx::query(
             "INSERT INTO friend_requests (id, from_user_id, to_user_id, message, status, created_at) 
@@ -205,11 +253,9 @@ impl FriendManager {
         .bind(&request.message)
         .bind(&request.status)
         .bind(request.created_at.to_rfc3339())
-        .execute(&pool)
+        .execute(pool)
         .await?;
 
-        pool.close().await;
-
         info!(
             "[Friends] Request sent: {} -> {}",
             request.from_username, to_email
@@ -220,7 +266,7 @@ impl FriendManager {
 
     /// Get pending friend requests for a user
     pub async fn get_pending_requests(&self, user_id: &str) -> Result<Vec<FriendRequest>> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
 
         let rows: Vec<(
             String,
@@ -243,11 +289,9 @@ impl FriendManager {
             "#,
         )
         .bind(user_id)
-        .fetch_all(&pool)
+        .fetch_all(pool)
         .await?;
 
-        pool.close().await;
-
         Ok(rows
             .into_iter()
             .map(
@@ -273,16 +317,82 @@ impl FriendManager {
             .collect())
     }
 
+    /// Get pending friend requests a user has sent
+    pub async fn list_sent_requests(&self, user_id: &str) -> Result<Vec<FriendRequest>> {
+        let pool = &self.pool;
+
+        let rows: Vec<SentRequestRow> = sqlx::query_as(
+            r#"
+            SELECT
+                fr.id, s.username, s.email, fr.to_user_id, r.email,
+                fr.message, fr.created_at
+            FROM friend_requests fr
+            JOIN users s ON fr.from_user_id = s.id
+            JOIN users r ON fr.to_user_id = r.id
+            WHERE fr.from_user_id = ? AND fr.status = 'pending'
+            ORDER BY fr.created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, from_username, from_email, to_id, to_email, message, created_at)| {
+                    FriendRequest {
+                        id,
+                        from_user_id: user_id.to_string(),
+                        from_username,
+                        from_email,
+                        to_user_id: to_id,
+                        to_email,
+                        message,
+                        status: RequestStatus::Pending,
+                        created_at: created_at.parse().unwrap_or_else(|_| Utc::now()),
+                        responded_at: None,
+                    }
+                },
+            )
+            .collect())
+    }
+
+    /// Withdraw a pending request the caller sent
+    pub async fn cancel_request(&self, from_user_id: &str, request_id: &str) -> Result<()> {
+        let pool = &self.pool;
+
+        let result = sqlx::query(
+            "DELETE FROM friend_requests
+             WHERE id = ? AND from_user_id = ? AND status = 'pending'",
+        )
+        .bind(request_id)
+        .bind(from_user_id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!(
+                "No pending request {} to cancel",
+                request_id
+            ));
+        }
+
+        info!("[Friends] Request {} cancelled", request_id);
+
+        Ok(())
+    }
+
     /// Respond to a friend request (accept/reject)
     pub async fn respond_to_request(&self, request_id: &str, accept: bool) -> Result<()> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
 
         // Get request details
         let req: (String, String, String) = sqlx::query_as(
             "SELECT from_user_id, to_user_id, status FROM friend_requests WHERE id = ?",
         )
         .bind(request_id)
-        .fetch_one(&pool)
+        .fetch_one(pool)
         .await?;
 
         let (from_id, to_id, status) = req;
@@ -299,7 +409,7 @@ impl FriendManager {
             .bind(new_status)
             .bind(responded_at.to_rfc3339())
             .bind(request_id)
-            .execute(&pool)
+            .execute(pool)
             .await?;
 
         // If accepted, create contact entries (bidirectional)
@@ -315,7 +425,7 @@ impl FriendManager {
             .bind(&from_id)
             .bind(&to_id)
             .bind(now.to_rfc3339())
-            .execute(&pool)
+            .execute(pool)
             .await?;
 
             sqlx::query(
@@ -325,7 +435,7 @@ impl FriendManager {
             .bind(&to_id)
             .bind(&from_id)
             .bind(now.to_rfc3339())
-            .execute(&pool)
+            .execute(pool)
             .await?;
 
             info!(
@@ -336,41 +446,38 @@ impl FriendManager {
             info!("[Friends] Request {} rejected", request_id);
         }
 
-        pool.close().await;
         Ok(())
     }
 
     /// Get user's contacts (friends)
     pub async fn get_contacts(&self, user_id: &str) -> Result<Vec<Contact>> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
 
         let rows: Vec<(String, String, String, String, Option<String>)> = sqlx::query_as(
             r#"
             SELECT 
-                c.id, c.contact_user_id, u.username, u.email, u.avatar_url
+                c.id, c.contact_user_id, u.username, u.email, u.avatar_blob_hash
             FROM contacts c
             JOIN users u ON c.contact_user_id = u.id
-            WHERE c.user_id = ?
+            WHERE c.user_id = ? AND u.is_active = 1
             ORDER BY u.username
             "#,
         )
         .bind(user_id)
-        .fetch_all(&pool)
+        .fetch_all(pool)
         .await?;
 
-        pool.close().await;
-
         Ok(rows
             .into_iter()
             .map(|(id, contact_id, username, email, avatar)| Contact {
                 id,
                 user_id: user_id.to_string(),
+                is_online: self.presence.is_online(&contact_id),
+                last_seen: self.presence.last_seen(&contact_id),
                 contact_user_id: contact_id,
                 username,
                 email,
-                avatar_url: avatar,
-                is_online: false,
-                last_seen: None,
+                avatar_url: avatar.map(|hash| format!("/blobs/{}", hash)),
                 created_at: Utc::now(),
             })
             .collect())
@@ -378,7 +485,7 @@ impl FriendManager {
 
     /// Remove a contact (unfriend)
     pub async fn remove_contact(&self, user_id: &str, contact_user_id: &str) -> Result<()> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
 
         // Remove both directions
         sqlx::query(
@@ -390,11 +497,9 @@ impl FriendManager {
         .bind(contact_user_id)
         .bind(contact_user_id)
         .bind(user_id)
-        .execute(&pool)
+        .execute(pool)
         .await?;
 
-        pool.close().await;
-
         info!(
             "[Friends] Contact removed: {} <-> {}",
             user_id, contact_user_id
@@ -402,4 +507,257 @@ impl FriendManager {
 
         Ok(())
     }
+
+    /// Drop every contact and pending request involving a user, e.g. when
+    /// their account is deleted
+    pub async fn remove_user(&self, user_id: &str) -> Result<()> {
+        let pool = &self.pool;
+
+        sqlx::query("DELETE FROM contacts WHERE user_id = ? OR contact_user_id = ?")
+            .bind(user_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        sqlx::query(
+            "DELETE FROM friend_requests
+             WHERE (from_user_id = ? OR to_user_id = ?) AND status = 'pending'",
+        )
+        .bind(user_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        info!("[Friends] Removed all relationships for {}", user_id);
+
+        Ok(())
+    }
+
+    /// Block a user, removing any contact between them and pending requests
+    /// from the blocked user
+    pub async fn block_user(&self, user_id: &str, blocked_user_id: &str) -> Result<()> {
+        if user_id == blocked_user_id {
+            return Err(anyhow::anyhow!("Cannot block yourself"));
+        }
+
+        let pool = &self.pool;
+
+        let exists: Option<(String,)> = sqlx::query_as("SELECT id FROM users WHERE id = ?")
+            .bind(blocked_user_id)
+            .fetch_optional(pool)
+            .await?;
+        if exists.is_none() {
+            return Err(anyhow::anyhow!("User not found: {}", blocked_user_id));
+        }
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO blocked_users (user_id, blocked_user_id, created_at) VALUES (?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(blocked_user_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "DELETE FROM contacts WHERE
+             (user_id = ? AND contact_user_id = ?) OR
+             (user_id = ? AND contact_user_id = ?)",
+        )
+        .bind(user_id)
+        .bind(blocked_user_id)
+        .bind(blocked_user_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "DELETE FROM friend_requests
+             WHERE from_user_id = ? AND to_user_id = ? AND status = 'pending'",
+        )
+        .bind(blocked_user_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        info!("[Friends] {} blocked {}", user_id, blocked_user_id);
+
+        Ok(())
+    }
+
+    /// Unblock a previously blocked user
+    pub async fn unblock_user(&self, user_id: &str, blocked_user_id: &str) -> Result<()> {
+        let pool = &self.pool;
+
+        sqlx::query("DELETE FROM blocked_users WHERE user_id = ? AND blocked_user_id = ?")
+            .bind(user_id)
+            .bind(blocked_user_id)
+            .execute(pool)
+            .await?;
+
+        info!("[Friends] {} unblocked {}", user_id, blocked_user_id);
+
+        Ok(())
+    }
+
+    /// Whether `user_id` has blocked `other_user_id`
+    pub async fn is_blocked(&self, user_id: &str, other_user_id: &str) -> Result<bool> {
+        let pool = &self.pool;
+
+        let row: Option<(String,)> = sqlx::query_as(
+            "SELECT user_id FROM blocked_users WHERE user_id = ? AND blocked_user_id = ?",
+        )
+        .bind(user_id)
+        .bind(other_user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::auth::AuthManager;
+    use tempfile::TempDir;
+
+    /// Set up both managers on one database with users `a`, `b` and `c`
+    async fn setup(dir: &Path) -> FriendManager {
+        let auth = AuthManager::new(dir).await.unwrap();
+        let friends = FriendManager::from_pool(auth.pool().clone()).await.unwrap();
+
+        let pool = &friends.pool;
+        for id in ["a", "b", "c"] {
+            sqlx::query(
+                "INSERT INTO users (id, email, username, password_hash, created_at) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(format!("{}@example.com", id))
+            .bind(id)
+            .bind("x")
+            .bind(Utc::now().to_rfc3339())
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+
+        friends
+    }
+
+    #[tokio::test]
+    async fn test_blocked_request_not_delivered() {
+        let temp_dir = TempDir::new().unwrap();
+        let friends = setup(temp_dir.path()).await;
+
+        friends.block_user("b", "a").await.unwrap();
+        assert!(friends.is_blocked("b", "a").await.unwrap());
+        assert!(!friends.is_blocked("a", "b").await.unwrap());
+
+        // The sender sees an ordinary pending request
+        let request = friends
+            .send_request("a".into(), "b@example.com".into(), None)
+            .await
+            .unwrap();
+        assert!(matches!(request.status, RequestStatus::Pending));
+        assert!(friends.get_pending_requests("b").await.unwrap().is_empty());
+
+        // Other senders are unaffected
+        friends
+            .send_request("c".into(), "b@example.com".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(friends.get_pending_requests("b").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_block_removes_contact_and_pending_request() {
+        let temp_dir = TempDir::new().unwrap();
+        let friends = setup(temp_dir.path()).await;
+
+        let request = friends
+            .send_request("a".into(), "b@example.com".into(), None)
+            .await
+            .unwrap();
+        friends.respond_to_request(&request.id, true).await.unwrap();
+        friends
+            .send_request("c".into(), "b@example.com".into(), None)
+            .await
+            .unwrap();
+
+        friends.block_user("b", "a").await.unwrap();
+        friends.block_user("b", "c").await.unwrap();
+        assert!(friends.get_contacts("a").await.unwrap().is_empty());
+        assert!(friends.get_contacts("b").await.unwrap().is_empty());
+        assert!(friends.get_pending_requests("b").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unblock_allows_requests_again() {
+        let temp_dir = TempDir::new().unwrap();
+        let friends = setup(temp_dir.path()).await;
+
+        friends.block_user("b", "a").await.unwrap();
+        friends.unblock_user("b", "a").await.unwrap();
+        assert!(!friends.is_blocked("b", "a").await.unwrap());
+
+        friends
+            .send_request("a".into(), "b@example.com".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(friends.get_pending_requests("b").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_request() {
+        let temp_dir = TempDir::new().unwrap();
+        let friends = setup(temp_dir.path()).await;
+
+        let request = friends
+            .send_request("a".into(), "b@example.com".into(), None)
+            .await
+            .unwrap();
+        let sent = friends.list_sent_requests("a").await.unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to_email, "b@example.com");
+
+        // Only the sender can cancel
+        assert!(friends.cancel_request("b", &request.id).await.is_err());
+
+        friends.cancel_request("a", &request.id).await.unwrap();
+        assert!(friends.list_sent_requests("a").await.unwrap().is_empty());
+        assert!(friends.get_pending_requests("b").await.unwrap().is_empty());
+
+        // Already gone
+        assert!(friends.cancel_request("a", &request.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_contacts_report_presence() {
+        let temp_dir = TempDir::new().unwrap();
+        let now = Arc::new(parking_lot::Mutex::new(Utc::now()));
+        let clock = now.clone();
+        let presence = Arc::new(PresenceTracker::with_clock(Arc::new(move || *clock.lock())));
+        let friends = setup(temp_dir.path()).await.with_presence(presence.clone());
+
+        let request = friends
+            .send_request("a".into(), "b@example.com".into(), None)
+            .await
+            .unwrap();
+        friends.respond_to_request(&request.id, true).await.unwrap();
+
+        let contacts = friends.get_contacts("a").await.unwrap();
+        assert!(!contacts[0].is_online);
+        assert!(contacts[0].last_seen.is_none());
+
+        presence.heartbeat("b");
+        let contacts = friends.get_contacts("a").await.unwrap();
+        assert!(contacts[0].is_online);
+        assert_eq!(contacts[0].last_seen, Some(*now.lock()));
+
+        *now.lock() += crate::core::presence::ONLINE_THRESHOLD + chrono::Duration::seconds(1);
+        let contacts = friends.get_contacts("a").await.unwrap();
+        assert!(!contacts[0].is_online);
+        assert!(contacts[0].last_seen.is_some());
+    }
 }
@@ -0,0 +1,102 @@
+//! AI Context File Handlers
+//!
+//! Manage the files BraidBot can pull in with `ai read context <file>`, and
+//! report BraidBot's usage metrics.
+
+use crate::chat::ai::{is_valid_context_file_name, AiChatManager, AiMetrics, ContextFileInfo};
+use crate::core::config::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+/// Request to upload a context file
+#[derive(Debug, Deserialize)]
+pub struct AddContextFileRequest {
+    pub name: String,
+    pub content: String,
+}
+
+fn ai_manager(state: &AppState) -> std::result::Result<&Arc<AiChatManager>, StatusCode> {
+    state
+        .ai_manager
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)
+}
+
+/// GET /ai/metrics
+///
+/// Token usage and generation latency, per room and in total.
+pub async fn get_metrics(
+    State(state): State<AppState>,
+) -> std::result::Result<Json<AiMetrics>, StatusCode> {
+    Ok(Json(ai_manager(&state)?.metrics().await))
+}
+
+/// GET /ai/context
+///
+/// List the available context files.
+pub async fn list_context_files(
+    State(state): State<AppState>,
+) -> std::result::Result<Json<Vec<ContextFileInfo>>, StatusCode> {
+    let files = ai_manager(&state)?
+        .list_context_files()
+        .await
+        .map_err(|e| {
+            error!("Failed to list context files: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(files))
+}
+
+/// POST /ai/context
+///
+/// Upload (or replace) a context file.
+pub async fn add_context_file(
+    State(state): State<AppState>,
+    Json(input): Json<AddContextFileRequest>,
+) -> std::result::Result<StatusCode, StatusCode> {
+    let manager = ai_manager(&state)?;
+    if !is_valid_context_file_name(&input.name) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    manager
+        .add_context_file(&input.name, input.content.as_bytes())
+        .await
+        .map_err(|e| {
+            error!("Failed to add context file {}: {}", input.name, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(StatusCode::CREATED)
+}
+
+/// DELETE /ai/context/:name
+///
+/// Remove a context file.
+pub async fn remove_context_file(
+    Path(name): Path<String>,
+    State(state): State<AppState>,
+) -> std::result::Result<StatusCode, StatusCode> {
+    let manager = ai_manager(&state)?;
+    if !is_valid_context_file_name(&name) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    manager.remove_context_file(&name).await.map_err(|e| {
+        match e.downcast_ref::<std::io::Error>() {
+            Some(io) if io.kind() == std::io::ErrorKind::NotFound => StatusCode::NOT_FOUND,
+            _ => {
+                error!("Failed to remove context file {}: {}", name, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
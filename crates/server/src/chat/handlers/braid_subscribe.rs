@@ -29,10 +29,18 @@
 //! Content-Length: 45
 //!
 //! {"id": "...", "content": "Hello World"}
+//!
+//! Typing-Event: true
+//! Content-Length: 30
+//!
+//! {"user":"alice","typing":true}
 //! ```
+//!
+//! Typing events are transient: they have no version and are never stored.
 
 use crate::core::config::AppState;
-use crate::core::models::Message;
+use crate::core::ctx::Ctx;
+use crate::core::models::{Message, TypingEvent};
 use crate::core::store::json_store::UpdateType;
 use axum::{
     body::Body,
@@ -43,7 +51,9 @@ use axum::{
 use braid_http::protocol::{
     constants::headers,
     headers::{format_version_header, parse_heartbeat, parse_version_header},
+    write_update_frame,
 };
+use braid_http::types::Version;
 use bytes::Bytes;
 use std::convert::Infallible;
 use std::time::Duration;
@@ -53,25 +63,8 @@ use tracing::{debug, error, info};
 /// Based on xfmail's implementation for spec compliance.
 fn format_braid_update(message: &Message, crdt_version: Option<&str>) -> Bytes {
     let body = serde_json::to_string(message).unwrap_or_default();
-    let mut update = String::new();
-
-    // Version header (required)
     let version = crdt_version.unwrap_or(&message.version);
-    update.push_str(&format!("Version: \"{}\"\r\n", version));
-
-    // Parents header (if any)
-    if !message.parents.is_empty() {
-        update.push_str(&format!(
-            "Parents: {}\r\n",
-            format_version_header(&message.parents)
-        ));
-    }
-
-    // Content-Length header (required for multipart)
-    update.push_str(&format!("Content-Length: {}\r\n", body.len()));
-    update.push_str("\r\n");
-    update.push_str(&body);
-    update.push_str("\r\n\r\n");
+    let update = write_update_frame(&[Version::new(version)], &message.parents, body.as_bytes());
 
     // Log for Inspector in formal Braid-HTTP format
     let patch = format!(
@@ -103,7 +96,22 @@ fn format_braid_update(message: &Message, crdt_version: Option<&str>) -> Bytes {
         patch
     );
 
-    Bytes::from(update)
+    update
+}
+
+/// Header marking an out-of-band typing event in the update stream
+pub const TYPING_EVENT_HEADER: &str = "Typing-Event";
+
+/// Format a typing event for the wire, framed like an update but without
+/// a version so clients can't mistake it for a message
+fn format_typing_event(event: &TypingEvent) -> Bytes {
+    let body = serde_json::to_string(event).unwrap_or_default();
+    Bytes::from(format!(
+        "{}: true\r\nContent-Length: {}\r\n\r\n{}\r\n\r\n",
+        TYPING_EVENT_HEADER,
+        body.len(),
+        body
+    ))
 }
 
 /// Handle pure Braid subscription for conversation messages.
@@ -125,8 +133,10 @@ pub async fn braid_subscribe(
     Path(room_id): Path<String>,
     State(state): State<AppState>,
     headers: HeaderMap,
+    ctx: Ctx,
 ) -> std::result::Result<Response<Body>, StatusCode> {
     info!("[BraidSubscribe] /chat/{}", room_id);
+    super::chat::require_participant(&state, &room_id, &ctx).await?;
 
     // Check for Subscribe header (Braid protocol requirement)
     if headers.get(&headers::SUBSCRIBE).is_none() {
@@ -206,6 +216,7 @@ pub async fn braid_subscribe(
     // Get the broadcast channel for this room
     let channel = state.store.get_channel(&room_id).await;
     let mut rx = channel.tx.subscribe();
+    let mut typing_rx = state.store.typing_channel(&room_id).await.subscribe();
 
     // Create the Braid subscription stream
     let stream = async_stream::stream! {
@@ -235,6 +246,7 @@ pub async fn braid_subscribe(
                             let event_type = match update.update_type {
                                 UpdateType::Presence => "presence",
                                 UpdateType::Typing => "typing",
+                                UpdateType::ReadReceipt => "read",
                                 UpdateType::RoomUpdate => "room",
                                 UpdateType::Sync => "sync",
                                 _ => "unknown",
@@ -254,6 +266,11 @@ pub async fn braid_subscribe(
                     }
                 }
 
+                // Typing signals ride the same stream, out of band
+                Ok(event) = typing_rx.recv() => {
+                    yield Ok::<_, Infallible>(format_typing_event(&event));
+                }
+
                 // Send heartbeat (blank line = Braid keepalive)
                 _ = heartbeat_interval.tick() => {
                     yield Ok::<_, Infallible>(Bytes::from("\r\n".to_string()));
@@ -296,4 +313,19 @@ mod tests {
         assert!(formatted.contains("version:"));
         assert!(formatted.contains("42@server"));
     }
+
+    #[test]
+    fn test_typing_event_format() {
+        let event = TypingEvent {
+            user: "alice".to_string(),
+            typing: true,
+        };
+        let wire = String::from_utf8(format_typing_event(&event).to_vec()).unwrap();
+        assert!(wire.starts_with("Typing-Event: true\r\n"));
+        assert!(!wire.contains("Version:"));
+
+        let (_, body) = wire.split_once("\r\n\r\n").unwrap();
+        let parsed: TypingEvent = serde_json::from_str(body.trim_end()).unwrap();
+        assert_eq!(parsed, event);
+    }
 }
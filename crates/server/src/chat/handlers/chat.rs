@@ -5,13 +5,15 @@
 
 use crate::core::{
     config::AppState,
+    ctx::Ctx,
     models::{
-        BlobRef, ChatRoom, ChatSnapshot, CreateMessageInput, MessageType, MessageTypeInput,
-        RoomSyncStatus, SyncStatus,
+        BlobRef, ChatRoom, ChatSnapshot, CreateMessageInput, CreateRoomInput, Message,
+        MessageType, MessageTypeInput, ReactionInput, ReadInput, ReadReceipt, RoomSyncStatus,
+        SyncStatus,
     },
 };
 use axum::{
-    extract::{Multipart, Path, State},
+    extract::{Multipart, Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Json, Response},
 };
@@ -19,16 +21,56 @@ use braid_http::protocol::{
     constants::headers,
     headers::{format_version_header, parse_version_header},
 };
+use serde::Deserialize;
 use tracing::{error, info, warn};
 
+/// Page size when `before` is given without `limit`
+const DEFAULT_PAGE_LIMIT: usize = 50;
+
+/// Largest page a client can ask for
+const MAX_PAGE_LIMIT: usize = 200;
+
+/// Paging options for `GET /chat/:room_id`
+#[derive(Debug, Default, Deserialize)]
+pub struct MessagesQuery {
+    /// Message id or version to page back from (exclusive)
+    pub before: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Default number of search results
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Query for `GET /chat/:room_id/search`
+#[derive(Debug, Default, Deserialize)]
+pub struct MessageSearchQuery {
+    #[serde(default)]
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+impl MessagesQuery {
+    fn is_paged(&self) -> bool {
+        self.before.is_some() || self.limit.is_some()
+    }
+}
+
 /// GET /chat/:room_id
 ///
 /// Braid protocol endpoint for fetching chat room state.
 /// Returns current messages as JSON with Braid version headers.
+///
+/// With `?limit=` and/or `?before=` the response is one page of history,
+/// newest first, with a `next_cursor` to pass as `before` for the next page.
+///
+/// Only participants may read a room. A room that doesn't exist yet is
+/// created with the caller as its only participant.
 pub async fn get_chat_room(
     Path(room_id): Path<String>,
+    Query(query): Query<MessagesQuery>,
     headers: HeaderMap,
     State(state): State<AppState>,
+    ctx: Ctx,
 ) -> Result<Response, Response> {
     info!("GET /chat/{}", room_id);
 
@@ -39,15 +81,24 @@ pub async fn get_chat_room(
         .and_then(|v| parse_version_header(v).ok())
         .and_then(|versions| versions.first().map(|v| v.to_string()));
 
-    // Get or create room
+    // Get or create room; a room nobody has opened yet becomes the caller's
+    let load_error = |e: anyhow::Error| {
+        error!("Failed to get/create room {}: {}", room_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    };
+    if state.store.get_room(&room_id).await.map_err(load_error)?.is_none() {
+        let room = ChatRoom::new(&room_id, format!("Room {}", room_id), ctx.user_id());
+        state.store.create_room(room).await.map_err(load_error)?;
+    }
+    require_participant(&state, &room_id, &ctx)
+        .await
+        .map_err(IntoResponse::into_response)?;
     let room_lock = state
         .store
-        .get_or_create_room(&room_id, Some("anonymous"))
+        .get_room(&room_id)
         .await
-        .map_err(|e| {
-            error!("Failed to get/create room: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-        })?;
+        .map_err(load_error)?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
 
     // Get room info and version while holding lock
     let (room_info, frontier) = {
@@ -60,15 +111,32 @@ pub async fn get_chat_room(
         .cloned()
         .unwrap_or_else(|| braid_http::types::Version::String("0@server".to_string()));
 
-    // Get messages (optionally since a specific version) - NO LOCK HELD
-    let messages = state
-        .store
-        .get_messages(&room_id, since_version.as_deref())
-        .await
-        .map_err(|e| {
-            error!("Failed to get/messages: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-        })?;
+    // Get messages (a page, or optionally since a specific version) - NO LOCK HELD
+    let (messages, next_cursor) = if query.is_paged() {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_LIMIT)
+            .clamp(1, MAX_PAGE_LIMIT);
+        let page = state
+            .store
+            .get_messages_page(&room_id, query.before.as_deref(), limit)
+            .await
+            .map_err(|e| {
+                warn!("Bad page request for room {}: {}", room_id, e);
+                StatusCode::BAD_REQUEST.into_response()
+            })?;
+        (page.messages, page.next_cursor)
+    } else {
+        let messages = state
+            .store
+            .get_messages(&room_id, since_version.as_deref())
+            .await
+            .map_err(|e| {
+                error!("Failed to get/messages: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            })?;
+        (messages, None)
+    };
 
     // Build response headers using braid-http
     let mut response_headers = HeaderMap::new();
@@ -101,6 +169,7 @@ pub async fn get_chat_room(
     let snapshot = ChatSnapshot {
         room: room_info,
         messages,
+        next_cursor,
     };
 
     Ok((response_headers, Json(snapshot)).into_response())
@@ -110,20 +179,20 @@ pub async fn get_chat_room(
 ///
 /// Braid protocol endpoint for adding a message.
 /// Uses antimatter merge type for CRDT consistency.
+///
+/// Responds with the stored `Message` (`id`, `version`, `parents`,
+/// `created_at`, ...) and its version in the `Version` header, so clients
+/// can swap an optimistic message for the server's record. A repeated
+/// `client_msg_id` returns the message the first send created.
+/// Only participants may post, and the message is sent as the caller.
 pub async fn put_message(
     Path(room_id): Path<String>,
-    headers: HeaderMap,
     State(state): State<AppState>,
+    ctx: Ctx,
     Json(input): Json<CreateMessageInput>,
-) -> std::result::Result<(HeaderMap, StatusCode), StatusCode> {
+) -> std::result::Result<(HeaderMap, Json<Message>), StatusCode> {
     info!("PUT /chat/{}", room_id);
-
-    // Extract sender from header (in real app, use auth)
-    let sender = headers
-        .get("x-user")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("anonymous")
-        .to_string();
+    require_participant(&state, &room_id, &ctx).await?;
 
     // Convert message type
     let msg_type = match input.message_type {
@@ -149,21 +218,36 @@ pub async fn put_message(
         .unwrap_or_default();
 
     // Create message using CRDT
-    let message = state
-        .store
-        .add_message(
-            &room_id,
-            &sender,
-            &input.content,
-            msg_type,
-            input.reply_to,
-            blob_refs,
-        )
-        .await
-        .map_err(|e| {
-            error!("Failed to create message: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    let add = state.store.add_message(
+        &room_id,
+        ctx.user_id(),
+        &input.content,
+        msg_type,
+        input.reply_to,
+        blob_refs,
+    );
+    let added = match input.client_msg_id.as_deref() {
+        Some(key) => state.store.add_message_once(&room_id, key, add).await,
+        None => add.await.map(|message| (message, true)),
+    };
+    let (message, created) = added.map_err(|e| {
+        error!("Failed to create message: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Return updated version headers using braid-http
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        headers::VERSION.clone(),
+        format_version_header(&[braid_http::types::Version::String(message.version.clone())])
+            .parse()
+            .unwrap(),
+    );
+
+    if !created {
+        info!("Duplicate send of message {} in room {}", message.id, room_id);
+        return Ok((response_headers, Json(message)));
+    }
 
     // Check for AI trigger
     if let Some(ref ai_manager) = state.ai_manager {
@@ -191,26 +275,157 @@ pub async fn put_message(
         message.id, room_id, message.version
     );
 
-    // Return updated version headers using braid-http
-    let mut response_headers = HeaderMap::new();
-    response_headers.insert(
-        headers::VERSION.clone(),
-        format_version_header(&[braid_http::types::Version::String(message.version.clone())])
-            .parse()
-            .unwrap(),
-    );
+    Ok((response_headers, Json(message)))
+}
+
+/// DELETE /chat/:room_id/messages/:message_id
+///
+/// Soft-delete one of the caller's own messages. The message stays as a
+/// tombstone so the delete reaches peers.
+pub async fn delete_message(
+    Path((room_id, message_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+    ctx: Ctx,
+) -> std::result::Result<StatusCode, StatusCode> {
+    let message = state
+        .store
+        .get_message(&room_id, &message_id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    if message.sender != ctx.user_id() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    state
+        .store
+        .delete_message(&room_id, &message_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete message: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Sync to daemon if available (braidfs-daemon)
+    if let Some(ref daemon) = state.daemon {
+        if let Err(e) = daemon.sync_room_to_daemon(&room_id).await {
+            warn!("Failed to sync room {} to daemon: {}", room_id, e);
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Longest emoji accepted as a reaction, in bytes (covers ZWJ sequences)
+const MAX_REACTION_LEN: usize = 64;
+
+/// PUT /chat/:room_id/messages/:message_id/reactions
+///
+/// Add or remove the caller's reaction on a message. Adding twice is a
+/// no-op. Returns the message with its updated reactions. Only
+/// participants may react.
+pub async fn put_reaction(
+    Path((room_id, message_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+    ctx: Ctx,
+    Json(input): Json<ReactionInput>,
+) -> std::result::Result<Json<Message>, StatusCode> {
+    require_participant(&state, &room_id, &ctx).await?;
 
-    Ok((response_headers, StatusCode::OK))
+    let emoji = input.emoji.trim();
+    if emoji.is_empty() || emoji.len() > MAX_REACTION_LEN {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    state
+        .store
+        .get_message(&room_id, &message_id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let message = state
+        .store
+        .set_reaction(&room_id, &message_id, ctx.user_id(), emoji, input.action)
+        .await
+        .map_err(|e| {
+            error!("Failed to update reaction: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Sync to daemon if available (braidfs-daemon)
+    if let Some(ref daemon) = state.daemon {
+        if let Err(e) = daemon.sync_room_to_daemon(&room_id).await {
+            warn!("Failed to sync room {} to daemon: {}", room_id, e);
+        }
+    }
+
+    Ok(Json(message))
+}
+
+/// GET /chat/:room_id/search?q=
+///
+/// Messages containing every word of `q` (case-insensitive), newest first.
+/// Only participants of the room may search it.
+pub async fn search_messages(
+    Path(room_id): Path<String>,
+    Query(query): Query<MessageSearchQuery>,
+    State(state): State<AppState>,
+    ctx: Ctx,
+) -> std::result::Result<Json<Vec<Message>>, StatusCode> {
+    require_participant(&state, &room_id, &ctx).await?;
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_PAGE_LIMIT);
+    let results = state
+        .store
+        .search_messages(&room_id, &query.q, limit)
+        .await
+        .map_err(|e| {
+            error!("Failed to search room {}: {}", room_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(results))
+}
+
+/// PUT /chat/:room_id/read
+///
+/// Record that the caller has read up to `version`. The marker never moves
+/// backward; the response is the caller's marker after the update.
+/// Only participants have read markers.
+pub async fn mark_read(
+    Path(room_id): Path<String>,
+    State(state): State<AppState>,
+    ctx: Ctx,
+    Json(input): Json<ReadInput>,
+) -> std::result::Result<Json<ReadReceipt>, StatusCode> {
+    require_participant(&state, &room_id, &ctx).await?;
+
+    let receipt = state
+        .store
+        .mark_read(&room_id, ctx.user_id(), &input.version)
+        .await
+        .map_err(|e| {
+            warn!("Failed to mark {} read: {}", room_id, e);
+            StatusCode::BAD_REQUEST
+        })?;
+
+    Ok(Json(receipt))
 }
 
 /// GET /chat/:room_id/status
 ///
-/// Get sync status for a room from daemon.
+/// Get sync status for a room from daemon, with read receipts.
+/// Only participants may see it.
 pub async fn get_room_status(
     Path(room_id): Path<String>,
     State(state): State<AppState>,
+    ctx: Ctx,
 ) -> std::result::Result<Json<RoomSyncStatus>, StatusCode> {
-    let status = if let Some(ref daemon) = state.daemon {
+    require_participant(&state, &room_id, &ctx).await?;
+
+    let mut status = if let Some(ref daemon) = state.daemon {
         daemon.get_sync_status(&room_id).await
     } else {
         RoomSyncStatus {
@@ -218,12 +433,86 @@ pub async fn get_room_status(
             status: SyncStatus::Offline,
             last_sync: None,
             pending_changes: 0,
+            read_receipts: Vec::new(),
         }
     };
+    status.read_receipts = state
+        .store
+        .read_receipts(&room_id)
+        .await
+        .unwrap_or_default();
 
     Ok(Json(status))
 }
 
+/// 404 if the room doesn't exist, 403 if the caller isn't a participant
+pub(crate) async fn require_participant(
+    state: &AppState,
+    room_id: &str,
+    ctx: &Ctx,
+) -> std::result::Result<(), StatusCode> {
+    let is_participant = state
+        .store
+        .is_participant(room_id, ctx.user_id())
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    if !is_participant {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(())
+}
+
+/// POST /chat/rooms
+///
+/// Create a room with the caller and `participants` (user ids or emails) as
+/// its members. 409 if the id is taken, 400 for an unknown participant.
+/// AI chats are registered with @BraidBot when AI is enabled.
+pub async fn create_room(
+    State(state): State<AppState>,
+    ctx: Ctx,
+    Json(input): Json<CreateRoomInput>,
+) -> std::result::Result<(StatusCode, Json<ChatRoom>), (StatusCode, String)> {
+    let mut members = Vec::with_capacity(input.participants.len());
+    for participant in &input.participants {
+        let user = if participant.contains('@') {
+            state.auth.get_user_by_email(participant).await
+        } else {
+            state.auth.get_user(participant).await
+        };
+        let user = user.map_err(|_| {
+            let msg = format!("Unknown participant: {}", participant);
+            (StatusCode::BAD_REQUEST, msg)
+        })?;
+        members.push(user.id);
+    }
+
+    let room_id = input
+        .id
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let mut room = ChatRoom::new(&room_id, &input.name, ctx.user_id());
+    room.participants = members;
+    room.is_direct_message = input.is_direct_message;
+    room.is_ai_chat = input.is_ai_chat;
+    match state.store.create_room(room).await {
+        Ok(Some(room)) => {
+            if let (true, Some(ai)) = (room.is_ai_chat, &state.ai_manager) {
+                if let Err(e) = ai.register_ai_room(&room.id, None).await {
+                    error!("Failed to register AI room {}: {}", room.id, e);
+                }
+            }
+            Ok((StatusCode::CREATED, Json(room)))
+        }
+        Ok(None) => Err((
+            StatusCode::CONFLICT,
+            format!("Room {} already exists", room_id),
+        )),
+        Err(e) => {
+            error!("Failed to create room {}: {}", room_id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        }
+    }
+}
+
 /// GET /chat/rooms
 ///
 /// List all chat rooms.
@@ -285,3 +574,165 @@ pub async fn clear_drafts(
 
     Ok(StatusCode::OK)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::test_support::test_state;
+    use tempfile::TempDir;
+
+    async fn send(state: &AppState, content: &str) -> (HeaderMap, Message) {
+        send_as(state, "alice", content).await.unwrap()
+    }
+
+    async fn send_as(
+        state: &AppState,
+        user: &str,
+        content: &str,
+    ) -> std::result::Result<(HeaderMap, Message), StatusCode> {
+        let input: CreateMessageInput =
+            serde_json::from_value(serde_json::json!({ "content": content })).unwrap();
+        let (headers, Json(message)) = put_message(
+            Path("room".to_string()),
+            State(state.clone()),
+            Ctx::new(user.to_string()),
+            Json(input),
+        )
+        .await?;
+        Ok((headers, message))
+    }
+
+    #[tokio::test]
+    async fn test_put_message_returns_stored_message() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = test_state(temp_dir.path()).await;
+        let room = ChatRoom::new("room", "Room", "alice");
+        state.store.create_room(room).await.unwrap();
+
+        let (headers, first) = send(&state, "hello").await;
+        assert_eq!(first.content, "hello");
+        assert!(!first.version.is_empty());
+        assert_eq!(
+            headers[&headers::VERSION],
+            format_version_header(&[braid_http::types::Version::String(first.version.clone())])
+        );
+
+        let (_, second) = send(&state, "again").await;
+        assert_eq!(second.content, "again");
+        assert_ne!(second.version, first.version);
+
+        let stored = state.store.get_messages("room", None).await.unwrap();
+        let mut stored: Vec<_> = stored
+            .iter()
+            .map(|m| (&m.id, &m.version, &m.parents, m.created_at))
+            .collect();
+        stored.sort_by_key(|m| m.1);
+        assert_eq!(
+            stored,
+            vec![
+                (&first.id, &first.version, &first.parents, first.created_at),
+                (&second.id, &second.version, &second.parents, second.created_at),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_the_sender_can_delete_a_message() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = test_state(temp_dir.path()).await;
+        let mut room = ChatRoom::new("room", "Room", "alice");
+        room.participants = vec!["bob".to_string()];
+        state.store.create_room(room).await.unwrap();
+
+        // The sender is the authenticated caller
+        let (_, message) = send(&state, "hello").await;
+        assert_eq!(message.sender, "alice");
+
+        let delete = |user: &str| {
+            delete_message(
+                Path(("room".to_string(), message.id.clone())),
+                State(state.clone()),
+                Ctx::new(user.to_string()),
+            )
+        };
+        assert_eq!(delete("bob").await, Err(StatusCode::FORBIDDEN));
+        assert_eq!(delete("alice").await, Ok(StatusCode::NO_CONTENT));
+        assert!(state
+            .store
+            .get_message("room", &message.id)
+            .await
+            .unwrap()
+            .deleted);
+    }
+
+    #[tokio::test]
+    async fn test_only_participants_mark_read() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = test_state(temp_dir.path()).await;
+        state
+            .store
+            .create_room(ChatRoom::new("room", "Room", "alice"))
+            .await
+            .unwrap();
+        let (_, message) = send(&state, "hello").await;
+
+        let read = |user: &str, room: &str| {
+            mark_read(
+                Path(room.to_string()),
+                State(state.clone()),
+                Ctx::new(user.to_string()),
+                Json(ReadInput {
+                    version: message.version.clone(),
+                }),
+            )
+        };
+        assert_eq!(
+            read("mallory", "room").await.unwrap_err(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            read("alice", "missing").await.unwrap_err(),
+            StatusCode::NOT_FOUND
+        );
+        let Json(receipt) = read("alice", "room").await.unwrap();
+        assert_eq!(receipt.version, message.version);
+        let receipts = state.store.read_receipts("room").await.unwrap();
+        let readers: Vec<_> = receipts.iter().map(|r| r.user.as_str()).collect();
+        assert_eq!(readers, vec!["alice"]);
+    }
+
+    #[tokio::test]
+    async fn test_outsiders_cannot_react_or_read_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = test_state(temp_dir.path()).await;
+        state
+            .store
+            .create_room(ChatRoom::new("room", "Room", "alice"))
+            .await
+            .unwrap();
+        let (_, message) = send(&state, "hello").await;
+
+        let react = |user: &str| {
+            let input: ReactionInput =
+                serde_json::from_value(serde_json::json!({ "emoji": "👍" })).unwrap();
+            put_reaction(
+                Path(("room".to_string(), message.id.clone())),
+                State(state.clone()),
+                Ctx::new(user.to_string()),
+                Json(input),
+            )
+        };
+        assert_eq!(react("mallory").await.unwrap_err(), StatusCode::FORBIDDEN);
+        assert!(react("alice").await.is_ok());
+
+        let status = |user: &str| {
+            get_room_status(
+                Path("room".to_string()),
+                State(state.clone()),
+                Ctx::new(user.to_string()),
+            )
+        };
+        assert_eq!(status("mallory").await.unwrap_err(), StatusCode::FORBIDDEN);
+        assert!(status("alice").await.is_ok());
+    }
+}
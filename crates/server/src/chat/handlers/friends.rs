@@ -23,6 +23,12 @@ pub struct RespondRequest {
     pub action: String, // "accept" or "reject"
 }
 
+/// Request to block a user
+#[derive(Debug, Deserialize)]
+pub struct BlockRequest {
+    pub user_id: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
     pub error: String,
@@ -129,6 +135,88 @@ pub async fn list_pending_requests(
     }
 }
 
+/// GET /friends/requests/sent - Get outgoing friend requests
+pub async fn list_sent_requests(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<FriendRequest>>, (StatusCode, Json<ErrorResponse>)> {
+    let token = get_token_from_headers(&headers).unwrap_or_default();
+
+    if token.is_empty() {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse {
+                error: "Missing authorization".to_string(),
+            }),
+        ));
+    }
+
+    let user = match state.auth.validate_session(&token).await {
+        Ok(u) => u,
+        Err(_) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse {
+                    error: "Invalid session".to_string(),
+                }),
+            ))
+        }
+    };
+
+    match state.friends.list_sent_requests(&user.id).await {
+        Ok(requests) => Ok(Json(requests)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )),
+    }
+}
+
+/// DELETE /friends/requests/{request_id}/cancel - Withdraw an outgoing request
+pub async fn cancel_friend_request(
+    Path(request_id): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let token = get_token_from_headers(&headers).unwrap_or_default();
+
+    if token.is_empty() {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse {
+                error: "Missing authorization".to_string(),
+            }),
+        ));
+    }
+
+    let user = match state.auth.validate_session(&token).await {
+        Ok(u) => u,
+        Err(_) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse {
+                    error: "Invalid session".to_string(),
+                }),
+            ))
+        }
+    };
+
+    match state.friends.cancel_request(&user.id, &request_id).await {
+        Ok(_) => {
+            info!("Friend request {} cancelled", request_id);
+            Ok(StatusCode::OK)
+        }
+        Err(e) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )),
+    }
+}
+
 /// PUT /friends/requests/{request_id} - Accept or reject friend request
 pub async fn respond_friend_request(
     Path(request_id): Path<String>,
@@ -218,3 +306,86 @@ pub async fn list_friends(
         )),
     }
 }
+
+/// POST /friends/block - Block a user
+pub async fn block_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<BlockRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let token = get_token_from_headers(&headers).unwrap_or_default();
+
+    if token.is_empty() {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse {
+                error: "Missing authorization".to_string(),
+            }),
+        ));
+    }
+
+    let user = match state.auth.validate_session(&token).await {
+        Ok(u) => u,
+        Err(_) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse {
+                    error: "Invalid session".to_string(),
+                }),
+            ))
+        }
+    };
+
+    match state.friends.block_user(&user.id, &req.user_id).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => {
+            warn!("Block failed: {}", e);
+            Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ))
+        }
+    }
+}
+
+/// DELETE /friends/block/{user_id} - Unblock a user
+pub async fn unblock_user(
+    Path(blocked_user_id): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let token = get_token_from_headers(&headers).unwrap_or_default();
+
+    if token.is_empty() {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse {
+                error: "Missing authorization".to_string(),
+            }),
+        ));
+    }
+
+    let user = match state.auth.validate_session(&token).await {
+        Ok(u) => u,
+        Err(_) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse {
+                    error: "Invalid session".to_string(),
+                }),
+            ))
+        }
+    };
+
+    match state.friends.unblock_user(&user.id, &blocked_user_id).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )),
+    }
+}
@@ -8,6 +8,7 @@ use axum::{
     Router,
 };
 
+pub mod ai_context;
 pub mod braid_subscribe;
 pub mod chat;
 pub mod friends;
@@ -18,7 +19,7 @@ pub mod typing;
 pub fn router() -> Router<AppState> {
     Router::new()
         // Core Braid protocol endpoints (NO SSE)
-        .route("/chat/rooms", get(chat::list_rooms))
+        .route("/chat/rooms", get(chat::list_rooms).post(chat::create_room))
         .route(
             "/chat/{room_id}",
             get(chat::get_chat_room).put(chat::put_message),
@@ -28,8 +29,18 @@ pub fn router() -> Router<AppState> {
             "/chat/{room_id}/subscribe",
             get(braid_subscribe::braid_subscribe),
         )
+        .route(
+            "/chat/{room_id}/messages/{message_id}",
+            delete(chat::delete_message),
+        )
+        .route(
+            "/chat/{room_id}/messages/{message_id}/reactions",
+            axum::routing::put(chat::put_reaction),
+        )
+        .route("/chat/{room_id}/search", get(chat::search_messages))
         // Room status and offline support
         .route("/chat/{room_id}/status", get(chat::get_room_status))
+        .route("/chat/{room_id}/read", axum::routing::put(chat::mark_read))
         .route(
             "/chat/{room_id}/drafts",
             get(chat::get_drafts)
@@ -46,6 +57,17 @@ pub fn router() -> Router<AppState> {
             "/friends/requests/{request_id}",
             axum::routing::put(friends::respond_friend_request),
         )
+        .route("/friends/requests/sent", get(friends::list_sent_requests))
+        .route(
+            "/friends/requests/{request_id}/cancel",
+            axum::routing::delete(friends::cancel_friend_request),
+        )
+        .route("/friends/block", post(friends::block_user))
+        .route(
+            "/friends/block/{user_id}",
+            axum::routing::delete(friends::unblock_user),
+        )
+        .route("/presence/heartbeat", post(presence::heartbeat))
         // Chat-specific extensions
         .route(
             "/chat/{room_id}/presence",
@@ -53,7 +75,19 @@ pub fn router() -> Router<AppState> {
         )
         .route(
             "/chat/{room_id}/typing",
-            get(typing::get_typing).put(typing::update_typing),
+            get(typing::get_typing)
+                .put(typing::update_typing)
+                .post(typing::post_typing),
+        )
+        // AI metrics and context files
+        .route("/ai/metrics", get(ai_context::get_metrics))
+        .route(
+            "/ai/context",
+            get(ai_context::list_context_files).post(ai_context::add_context_file),
+        )
+        .route(
+            "/ai/context/{name}",
+            delete(ai_context::remove_context_file),
         )
         // Config (Daemon cookie)
         .route("/config/cookie", post(handler_config::set_daemon_cookie))
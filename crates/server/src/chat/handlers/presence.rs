@@ -1,4 +1,5 @@
 use crate::core::config::AppState;
+use crate::core::ctx::Ctx;
 use crate::core::models::{Presence, PresenceStatus};
 use axum::{
     extract::{Path, State},
@@ -27,6 +28,15 @@ async fn get_presence_store() -> Arc<RwLock<HashMap<String, Presence>>> {
     store
 }
 
+/// POST /presence/heartbeat
+///
+/// Lets idle clients stay online; the auth middleware records the heartbeat,
+/// this just makes it explicit.
+pub async fn heartbeat(State(state): State<AppState>, ctx: Ctx) -> StatusCode {
+    state.presence.heartbeat(ctx.user_id());
+    StatusCode::NO_CONTENT
+}
+
 /// GET /chat/:room_id/presence
 pub async fn get_presence(
     Path(room_id): Path<String>,
@@ -1,11 +1,15 @@
+use super::chat::require_participant;
 use crate::core::config::AppState;
+use crate::core::ctx::Ctx;
 use crate::core::models::TypingIndicator;
+use crate::core::store::json_store::{RoomUpdate, UpdateType};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     Json,
 };
 use chrono::Utc;
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -28,12 +32,49 @@ async fn get_typing_store() -> Arc<RwLock<HashMap<String, TypingIndicator>>> {
     store
 }
 
+/// Body for `POST /chat/:room_id/typing`
+#[derive(Debug, Deserialize)]
+pub struct TypingInput {
+    pub typing: bool,
+}
+
+/// Remember the indicator for polling clients and push it to subscribers
+async fn record_typing(state: &AppState, indicator: TypingIndicator) {
+    let store = get_typing_store().await;
+    store.write().await.insert(
+        format!("{}:{}", indicator.room_id, indicator.user),
+        indicator.clone(),
+    );
+
+    let sent = state
+        .store
+        .send_typing(&indicator.room_id, &indicator.user, indicator.is_typing)
+        .await;
+
+    // Clients still listening for `type: typing` room updates get it there too
+    if sent {
+        let update = RoomUpdate {
+            room_id: indicator.room_id.clone(),
+            update_type: UpdateType::Typing,
+            data: serde_json::to_value(&indicator).unwrap_or_default(),
+            crdt_version: None,
+        };
+        if let Err(e) = state.store.broadcast(&indicator.room_id, update).await {
+            warn!("Failed to broadcast typing: {}", e);
+        }
+    }
+}
+
 /// GET /chat/:room_id/typing
+///
+/// Only participants may see who is typing.
 pub async fn get_typing(
     Path(room_id): Path<String>,
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
+    ctx: Ctx,
 ) -> std::result::Result<Json<Vec<TypingIndicator>>, StatusCode> {
     info!("GET /chat/{}/typing", room_id);
+    require_participant(&state, &room_id, &ctx).await?;
 
     let store = get_typing_store().await;
     let typing_map = store.read().await;
@@ -50,38 +91,138 @@ pub async fn get_typing(
 }
 
 /// PUT /chat/:room_id/typing
+///
+/// The indicator is recorded for the caller, whatever `user` it names.
+/// Only participants may send one.
 pub async fn update_typing(
     Path(room_id): Path<String>,
     State(state): State<AppState>,
+    ctx: Ctx,
     Json(typing): Json<TypingIndicator>,
 ) -> std::result::Result<StatusCode, StatusCode> {
     info!(
         "PUT /chat/{}/typing - {} is_typing={}",
-        room_id, typing.user, typing.is_typing
+        room_id,
+        ctx.user_id(),
+        typing.is_typing
     );
-
-    let store = get_typing_store().await;
-    let mut typing_map = store.write().await;
+    require_participant(&state, &room_id, &ctx).await?;
 
     let indicator = TypingIndicator {
-        room_id: room_id.clone(),
+        user: ctx.user_id().to_string(),
+        room_id,
         timestamp: Utc::now(),
         ..typing
     };
+    record_typing(&state, indicator).await;
+
+    Ok(StatusCode::OK)
+}
 
-    typing_map.insert(format!("{}:{}", room_id, indicator.user), indicator.clone());
+/// POST /chat/:room_id/typing
+///
+/// Signal that the caller started or stopped typing. Delivered to
+/// subscribers as a `Typing-Event` and never stored as a message.
+/// Only participants may send one.
+pub async fn post_typing(
+    Path(room_id): Path<String>,
+    State(state): State<AppState>,
+    ctx: Ctx,
+    Json(input): Json<TypingInput>,
+) -> std::result::Result<StatusCode, StatusCode> {
+    require_participant(&state, &room_id, &ctx).await?;
 
-    // Broadcast typing update
-    let update = crate::core::store::json_store::RoomUpdate {
-        room_id: room_id.clone(),
-        update_type: crate::core::store::json_store::UpdateType::Typing,
-        data: serde_json::to_value(&indicator).unwrap_or_default(),
-        crdt_version: None,
+    let indicator = TypingIndicator {
+        user: ctx.user_id().to_string(),
+        room_id,
+        is_typing: input.typing,
+        timestamp: Utc::now(),
     };
+    record_typing(&state, indicator).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::test_support::test_state;
+    use crate::core::models::ChatRoom;
+    use tempfile::TempDir;
 
-    if let Err(e) = state.store.broadcast(&room_id, update).await {
-        warn!("Failed to broadcast typing: {}", e);
+    #[tokio::test]
+    async fn test_outsiders_cannot_use_typing_indicators() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = test_state(temp_dir.path()).await;
+        state
+            .store
+            .create_room(ChatRoom::new("typing-room", "Room", "alice"))
+            .await
+            .unwrap();
+        let room = || Path("typing-room".to_string());
+        let ctx = |user: &str| Ctx::new(user.to_string());
+        let indicator = |user: &str| {
+            Json(TypingIndicator {
+                room_id: "typing-room".to_string(),
+                user: user.to_string(),
+                is_typing: true,
+                timestamp: Utc::now(),
+            })
+        };
+
+        let post = |user: &str| {
+            post_typing(
+                room(),
+                State(state.clone()),
+                ctx(user),
+                Json(TypingInput { typing: true }),
+            )
+        };
+        assert_eq!(post("mallory").await, Err(StatusCode::FORBIDDEN));
+        assert_eq!(post("alice").await, Ok(StatusCode::NO_CONTENT));
+
+        // Naming a member in the body doesn't help an outsider
+        let put = update_typing(
+            room(),
+            State(state.clone()),
+            ctx("mallory"),
+            indicator("alice"),
+        );
+        assert_eq!(put.await, Err(StatusCode::FORBIDDEN));
+        let put = update_typing(room(), State(state.clone()), ctx("alice"), indicator("bob"));
+        assert_eq!(put.await, Ok(StatusCode::OK));
+
+        let get = get_typing(room(), State(state.clone()), ctx("mallory")).await;
+        assert_eq!(get.unwrap_err(), StatusCode::FORBIDDEN);
+        let Json(typing) = get_typing(room(), State(state.clone()), ctx("alice"))
+            .await
+            .unwrap();
+        assert!(typing.iter().all(|t| t.user == "alice"));
     }
 
-    Ok(StatusCode::OK)
+    #[tokio::test]
+    async fn test_typing_is_broadcast_as_room_update() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = test_state(temp_dir.path()).await;
+        state
+            .store
+            .create_room(ChatRoom::new("typing-room", "Room", "alice"))
+            .await
+            .unwrap();
+        let mut rx = state.store.get_channel("typing-room").await.tx.subscribe();
+
+        let status = post_typing(
+            Path("typing-room".to_string()),
+            State(state.clone()),
+            Ctx::new("alice".to_string()),
+            Json(TypingInput { typing: true }),
+        )
+        .await;
+        assert_eq!(status, Ok(StatusCode::NO_CONTENT));
+
+        let update = rx.try_recv().unwrap();
+        assert!(matches!(update.update_type, UpdateType::Typing));
+        assert_eq!(update.data["user"], "alice");
+        assert_eq!(update.data["is_typing"], true);
+    }
 }
@@ -5,22 +5,30 @@
 //! When user clicks subscribe, messages appear in the UI via Braid protocol.
 
 use crate::core::config::AppState;
+use crate::core::shutdown::Shutdown;
 use crate::core::store::json_store::JsonChatStore;
 use anyhow::Result;
 use axum::{
     body::Body,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{header, HeaderMap, StatusCode},
     response::{Json, Response},
+    routing::{get, post},
+    Router,
 };
-use braid_http::protocol::constants::headers;
+use braid_http::protocol::{constants::headers, parse_heartbeat, write_update_frame};
+use braid_http::types::Version;
 use braid_http::{BraidClient, BraidRequest};
 use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
+use std::ops::RangeBounds;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, Notify, RwLock};
 use tracing::{debug, error, info, warn};
 
 /// Mail feed item with Braid protocol metadata
@@ -41,6 +49,12 @@ pub struct MailFeedItem {
     pub parents: Option<String>,
     /// Braid merge-type header
     pub merge_type: Option<String>,
+    /// Whether the user has read this item
+    #[serde(default)]
+    pub read: bool,
+    /// Why the post couldn't be loaded, if hydration gave up on it
+    #[serde(default)]
+    pub error: Option<String>,
 }
 
 /// Mail post content with Braid protocol metadata
@@ -63,11 +77,108 @@ pub struct MailPost {
     pub link: Option<String>,
 }
 
+/// Result of [`MailManager::delete_post`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeletePostOutcome {
+    Deleted,
+    /// The post couldn't be found locally or at its origin
+    NotFound,
+    /// The post wasn't sent from the configured user email
+    NotOwner,
+}
+
+/// Resolve a post link against its feed so relative and absolute forms compare equal
+fn normalize_post_url(url: &str, feed_url: &str) -> String {
+    reqwest::Url::parse(feed_url)
+        .and_then(|base| base.join(url))
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+/// Parents as stored on posts: comma-separated, `None` when there are none
+fn join_versions(versions: &[Version]) -> Option<String> {
+    if versions.is_empty() {
+        return None;
+    }
+    Some(
+        versions
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// Number of recent items returned for an empty search
+const RECENT_SEARCH_LIMIT: usize = 50;
+
+/// How many posts are fetched in parallel while hydrating a feed
+const HYDRATE_CONCURRENCY: usize = 20;
+
+/// Fetch attempts per post before it's marked as failed
+const HYDRATE_ATTEMPTS: u32 = 3;
+
+/// Wait before the second attempt; doubled for each one after
+const HYDRATE_RETRY_BACKOFF: Duration = Duration::from_millis(200);
+
+impl From<MailPost> for MailFeedItem {
+    fn from(post: MailPost) -> Self {
+        Self {
+            id: post.url.clone(),
+            url: post.url,
+            subject: post.subject,
+            from: post.from,
+            to: post.to,
+            cc: post.cc,
+            date: post.date,
+            body: post.body,
+            is_network: true,
+            version: post.version,
+            parents: post.parents,
+            merge_type: post.merge_type,
+            read: false,
+            error: None,
+        }
+    }
+}
+
 /// Mail subscription state
 #[derive(Debug, Clone)]
 struct FeedSubscription {
-    feed_url: String,
     last_version: Option<String>,
+    /// Wakes the sync task before its interval is up
+    refresh: Arc<Notify>,
+}
+
+/// Per-manager settings handed to each feed sync task
+#[derive(Debug, Clone, Copy)]
+struct FeedSyncSettings {
+    max_feed_items: usize,
+    interval: Duration,
+}
+
+/// Broadcasts feed changes, numbering each one so subscribers can be
+/// sent a monotonic `Version`
+#[derive(Debug, Clone)]
+struct FeedUpdates {
+    tx: broadcast::Sender<u64>,
+    version: Arc<AtomicU64>,
+}
+
+impl FeedUpdates {
+    fn new() -> Self {
+        let (tx, _) = broadcast::channel(16);
+        Self {
+            tx,
+            version: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Bump the feed version and notify subscribers of it
+    fn publish(&self) {
+        let version = self.version.fetch_add(1, Ordering::SeqCst) + 1;
+        let _ = self.tx.send(version);
+    }
 }
 
 /// Mail manager - handles external feed subscriptions
@@ -75,32 +186,61 @@ pub struct MailManager {
     _store: Arc<JsonChatStore>,
     /// Feed URL -> Subscription state
     subscriptions: Arc<RwLock<HashMap<String, FeedSubscription>>>,
+    /// Feed URL -> Background sync task
+    sync_tasks: Arc<RwLock<HashMap<String, tokio::task::AbortHandle>>>,
     /// Cached feed items
     feed_items: Arc<RwLock<Vec<MailFeedItem>>>,
     /// Cached posts
     posts: Arc<RwLock<HashMap<String, MailPost>>>,
     /// Notification channel for updates
-    update_tx: broadcast::Sender<()>,
+    updates: FeedUpdates,
     /// User authentication cookie for posting
     user_cookie: Arc<RwLock<Option<String>>>,
     /// User email identity
     user_email: Arc<RwLock<Option<String>>>,
+    /// IDs of items the user has read
+    read_ids: Arc<RwLock<HashSet<String>>>,
+    /// Sidecar file persisting `read_ids`
+    read_state_path: std::path::PathBuf,
+    /// Cap on `feed_items` and the pause between syncs
+    sync_settings: FeedSyncSettings,
+    /// Stops feed sync tasks on server shutdown
+    shutdown: Shutdown,
 }
 
 impl MailManager {
     pub fn new(store: Arc<JsonChatStore>) -> Self {
-        let (update_tx, _) = broadcast::channel(16);
+        let read_state_path = store.config().storage_dir.join("mail").join("read.json");
+        let read_ids = std::fs::read_to_string(&read_state_path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<HashSet<String>>(&s).ok())
+            .unwrap_or_default();
+        let sync_settings = FeedSyncSettings {
+            max_feed_items: store.config().max_feed_items,
+            interval: store.config().mail_sync_interval,
+        };
         Self {
             _store: store,
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            sync_tasks: Arc::new(RwLock::new(HashMap::new())),
             feed_items: Arc::new(RwLock::new(Vec::new())),
             posts: Arc::new(RwLock::new(HashMap::new())),
-            update_tx,
+            updates: FeedUpdates::new(),
             user_cookie: Arc::new(RwLock::new(None)),
             user_email: Arc::new(RwLock::new(None)),
+            read_ids: Arc::new(RwLock::new(read_ids)),
+            read_state_path,
+            sync_settings,
+            shutdown: Shutdown::new(),
         }
     }
 
+    /// Stop feed sync tasks when `shutdown` fires
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
     /// Set authentication cookie for posting
     pub async fn set_cookie(&self, cookie: String) {
         info!("[MailManager] Setting authentication cookie");
@@ -131,8 +271,8 @@ impl MailManager {
         subs.insert(
             feed_url.to_string(),
             FeedSubscription {
-                feed_url: feed_url.to_string(),
                 last_version: None,
+                refresh: Arc::new(Notify::new()),
             },
         );
         drop(subs);
@@ -142,27 +282,210 @@ impl MailManager {
         let subscriptions = self.subscriptions.clone();
         let feed_items = self.feed_items.clone();
         let posts = self.posts.clone();
-        let update_tx = self.update_tx.clone();
-
-        tokio::spawn(async move {
-            if let Err(e) =
-                Self::feed_sync_task(feed_url, subscriptions, feed_items, posts, update_tx).await
+        let updates = self.updates.clone();
+        let sync_settings = self.sync_settings;
+        let shutdown = self.shutdown.clone();
+
+        let task_url = feed_url.clone();
+        let handle = tokio::spawn(async move {
+            if let Err(e) = Self::feed_sync_task(
+                task_url,
+                subscriptions,
+                feed_items,
+                posts,
+                updates,
+                sync_settings,
+                shutdown,
+            )
+            .await
             {
                 error!("[MailManager] Feed sync task failed: {}", e);
             }
         });
+        self.sync_tasks
+            .write()
+            .await
+            .insert(feed_url, handle.abort_handle());
 
         info!("[MailManager] Subscription started");
         Ok(())
     }
 
+    /// Stop syncing an external mail feed
+    ///
+    /// Returns false if the feed wasn't subscribed.
+    pub async fn unsubscribe_feed(&self, feed_url: &str) -> bool {
+        let removed = self.subscriptions.write().await.remove(feed_url).is_some();
+        // The task would exit on its next loop anyway; abort so a quick
+        // resubscribe doesn't leave two tasks polling the same feed.
+        if let Some(task) = self.sync_tasks.write().await.remove(feed_url) {
+            task.abort();
+        }
+        if removed {
+            info!("[MailManager] Unsubscribed from {}", feed_url);
+        }
+        removed
+    }
+
+    /// Drop cached feed items, e.g. after unsubscribing from the last feed
+    pub async fn clear_feed_items(&self) {
+        self.feed_items.write().await.clear();
+        self.updates.publish();
+    }
+
+    /// Fill in subject, sender and body for each feed item, from the post
+    /// cache or by fetching the post
+    ///
+    /// Every fetch goes through a clone of `client`, so they share its
+    /// connection pool.
+    async fn hydrate_items(
+        client: &BraidClient,
+        posts: &Arc<RwLock<HashMap<String, MailPost>>>,
+        items: Vec<MailFeedItem>,
+    ) -> Vec<MailFeedItem> {
+        info!("[MailManager] Starting hydration for {} items", items.len());
+
+        let futures = items
+            .into_iter()
+            .map(|item| Self::hydrate_item(client.clone(), posts.clone(), item));
+        let results: Vec<(MailFeedItem, bool)> = stream::iter(futures)
+            .buffer_unordered(HYDRATE_CONCURRENCY)
+            .collect()
+            .await;
+
+        let cached_count = results.iter().filter(|(_, c)| *c).count();
+        let fetched_count = results.len() - cached_count;
+
+        info!(
+            "[MailManager] Hydration complete: {} cached, {} fetched",
+            cached_count, fetched_count
+        );
+
+        results.into_iter().map(|(i, _)| i).collect()
+    }
+
+    /// Hydrate one item; the flag is true when it came from the cache
+    ///
+    /// A fetch is tried up to `HYDRATE_ATTEMPTS` times. If every attempt
+    /// fails, the item comes back unhydrated with `error` set.
+    async fn hydrate_item(
+        client: BraidClient,
+        posts: Arc<RwLock<HashMap<String, MailPost>>>,
+        mut item: MailFeedItem,
+    ) -> (MailFeedItem, bool) {
+        let full_url = if item.url.starts_with('/') {
+            format!("https://mail.braid.org{}", item.url)
+        } else {
+            item.url.clone()
+        };
+
+        // 1. Check Cache First
+        let cached_post = {
+            let posts_guard = posts.read().await;
+            posts_guard.get(&full_url).cloned()
+        };
+
+        if let Some(post) = cached_post {
+            item.subject = post.subject;
+            item.from = post.from;
+            item.to = post.to;
+            item.date = post.date;
+            item.body = post.body;
+            item.error = None;
+            return (item, true); // true = cached
+        }
+
+        // 2. Fetch if missing, retrying transient failures
+        let mut backoff = HYDRATE_RETRY_BACKOFF;
+        let mut attempt = 1;
+        let (resp, json) = loop {
+            let error = match client.fetch(&full_url, BraidRequest::new()).await {
+                Ok(resp) => {
+                    let body = String::from_utf8_lossy(&resp.body);
+                    match serde_json::from_str::<serde_json::Value>(&body) {
+                        Ok(json) => break (resp, json),
+                        Err(e) => format!("Invalid post body: {}", e),
+                    }
+                }
+                Err(e) => e.to_string(),
+            };
+            if attempt >= HYDRATE_ATTEMPTS {
+                warn!(
+                    "[MailManager] Failed to hydrate item {} after {} attempts: {}",
+                    full_url, attempt, error
+                );
+                item.error = Some(error);
+                return (item, false);
+            }
+            debug!(
+                "[MailManager] Hydrating {} failed (attempt {}): {}",
+                full_url, attempt, error
+            );
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+            attempt += 1;
+        };
+
+        item.error = None;
+        item.subject = json
+            .get("subject")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        item.from = json
+            .get("from")
+            .and_then(|v| serde_json::from_value(v.clone()).ok());
+        item.date = json.get("date").and_then(|v| v.as_u64()).map(|v| {
+            if v < 10000000000 {
+                v * 1000
+            } else {
+                v
+            }
+        });
+        item.body = json
+            .get("body")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        // Extract Braid protocol headers
+        let version = resp.version().map(|v| v.to_string());
+        let parents = join_versions(&resp.parents());
+        let merge_type = resp.merge_type();
+
+        // Update item with Braid metadata
+        item.version = version.clone();
+        item.parents = parents.clone();
+        item.merge_type = merge_type.clone();
+        item.cc = json
+            .get("cc")
+            .and_then(|v| serde_json::from_value(v.clone()).ok());
+
+        // 3. Update Cache
+        let new_post = MailPost {
+            url: full_url.clone(),
+            subject: item.subject.clone(),
+            from: item.from.clone(),
+            to: item.to.clone(),
+            cc: item.cc.clone(),
+            date: item.date,
+            body: item.body.clone(),
+            version,
+            parents,
+            merge_type,
+            link: None,
+        };
+        posts.write().await.insert(full_url, new_post);
+        (item, false) // false = fetched
+    }
+
     /// Background task to sync feed
     async fn feed_sync_task(
         feed_url: String,
         subscriptions: Arc<RwLock<HashMap<String, FeedSubscription>>>,
         feed_items: Arc<RwLock<Vec<MailFeedItem>>>,
         posts: Arc<RwLock<HashMap<String, MailPost>>>,
-        update_tx: broadcast::Sender<()>,
+        updates: FeedUpdates,
+        settings: FeedSyncSettings,
+        shutdown: Shutdown,
     ) -> Result<()> {
         let client = BraidClient::new()?;
 
@@ -171,10 +494,7 @@ impl MailManager {
             let sub = {
                 let subs = subscriptions.read().await;
                 match subs.get(&feed_url) {
-                    Some(s) => FeedSubscription {
-                        feed_url: s.feed_url.clone(),
-                        last_version: s.last_version.clone(),
-                    },
+                    Some(s) => s.clone(),
                     None => {
                         info!("[MailManager] Subscription ended for {}", feed_url);
                         break;
@@ -184,153 +504,30 @@ impl MailManager {
 
             // Fetch feed
             match Self::fetch_feed(&client, &feed_url, sub.last_version.clone()).await {
-                Ok((items, new_version)) => {
-                    // 2. Hydrate items (fetch details from mail.braid.org)
-                    // 2. Hydrate items (fetch details from mail.braid.org)
-                    let hydrated_items = {
-                        info!("[MailManager] Starting hydration for {} items", items.len());
-
-                        let futures = items.into_iter().map(|mut item| {
-                            // client is created fresh inside
-                            let posts = posts.clone();
-                            // We need to move `item` into the future.
-
-                            async move {
-                                let full_url = if item.url.starts_with("http") {
-                                    item.url.clone()
-                                } else {
-                                    if item.url.starts_with("/") {
-                                        format!("https://mail.braid.org{}", item.url)
-                                    } else {
-                                        item.url.clone()
-                                    }
-                                };
-
-                                // 1. Check Cache First
-                                let cached_post = {
-                                    let posts_guard = posts.read().await;
-                                    posts_guard.get(&full_url).cloned()
-                                };
-
-                                if let Some(post) = cached_post {
-                                    item.subject = post.subject;
-                                    item.from = post.from;
-                                    item.to = post.to;
-                                    item.date = post.date;
-                                    item.body = post.body;
-                                    return (item, true); // true = cached
-                                }
-
-                                // 2. Fetch if missing
-                                // We create a new client inside if needed, or better, we need `client` to be Clonable.
-                                // Let's check `braid_http` earlier view. It was just a mod file.
-                                // Assuming we can't easily clone client, let's create new one or use `reqwest` directly?
-                                // No, use `BraidClient::new()`. It returns Result.
-                                let client = match BraidClient::new() {
-                                    Ok(c) => c,
-                                    Err(_) => return (item, false),
-                                };
-
-                                match client.fetch(&full_url, BraidRequest::new()).await {
-                                    Ok(resp) => {
-                                        let body = String::from_utf8_lossy(&resp.body);
-                                        if let Ok(json) =
-                                            serde_json::from_str::<serde_json::Value>(&body)
-                                        {
-                                            item.subject = json
-                                                .get("subject")
-                                                .and_then(|v| v.as_str())
-                                                .map(|s| s.to_string());
-                                            item.from = json.get("from").and_then(|v| {
-                                                serde_json::from_value(v.clone()).ok()
-                                            });
-                                            item.date =
-                                                json.get("date").and_then(|v| v.as_u64()).map(
-                                                    |v| if v < 10000000000 { v * 1000 } else { v },
-                                                );
-                                            item.body = json
-                                                .get("body")
-                                                .and_then(|v| v.as_str())
-                                                .map(|s| s.to_string());
-
-                                            // Extract Braid protocol headers
-                                            let version = resp
-                                                .headers
-                                                .get("version")
-                                                .or(resp.headers.get("Version"))
-                                                .or(resp.headers.get("Current-Version"))
-                                                .map(|s| s.to_string());
-                                            let parents = resp
-                                                .headers
-                                                .get("parents")
-                                                .or(resp.headers.get("Parents"))
-                                                .map(|s| s.to_string());
-                                            let merge_type = resp
-                                                .headers
-                                                .get("merge-type")
-                                                .or(resp.headers.get("Merge-Type"))
-                                                .map(|s| s.to_string());
-
-                                            // Update item with Braid metadata
-                                            item.version = version.clone();
-                                            item.parents = parents.clone();
-                                            item.merge_type = merge_type.clone();
-                                            item.cc = json.get("cc").and_then(|v| {
-                                                serde_json::from_value(v.clone()).ok()
-                                            });
-
-                                            // 3. Update Cache
-                                            let new_post = MailPost {
-                                                url: full_url.clone(),
-                                                subject: item.subject.clone(),
-                                                from: item.from.clone(),
-                                                to: item.to.clone(),
-                                                cc: item.cc.clone(),
-                                                date: item.date,
-                                                body: item.body.clone(),
-                                                version,
-                                                parents,
-                                                merge_type,
-                                                link: None,
-                                            };
-                                            posts.write().await.insert(full_url.clone(), new_post);
-                                            return (item, false); // false = fetched
-                                        }
-                                    }
-                                    Err(e) => {
-                                        warn!(
-                                            "[MailManager] Failed to hydrate item {}: {}",
-                                            full_url, e
-                                        );
-                                    }
-                                }
-                                (item, false)
-                            }
-                        });
-
-                        let results: Vec<(MailFeedItem, bool)> = stream::iter(futures)
-                            .buffer_unordered(20) // Parallel fetch 20 items
-                            .collect()
-                            .await;
-
-                        let cached_count = results.iter().filter(|(_, c)| *c).count();
-                        let fetched_count = results.len() - cached_count;
-
-                        info!(
-                            "[MailManager] Hydration complete: {} cached, {} fetched",
-                            cached_count, fetched_count
-                        );
+                Ok((mut items, new_version)) => {
+                    // Items that failed last time get another go on every sync
+                    items.extend(
+                        feed_items
+                            .read()
+                            .await
+                            .iter()
+                            .filter(|item| item.error.is_some())
+                            .cloned(),
+                    );
 
-                        results.into_iter().map(|(i, _)| i).collect::<Vec<_>>()
-                    };
+                    // 2. Hydrate items (fetch details from mail.braid.org)
+                    let hydrated_items = Self::hydrate_items(&client, &posts, items).await;
 
-                    // 3. Only add items that have SOME data (subject OR body)
+                    // 3. Only add items that have SOME data (subject OR body), or
+                    // that failed to load, so the UI can offer a retry
+                    let fetched_count = hydrated_items.len();
                     let hydrated_items: Vec<_> = hydrated_items
                         .into_iter()
                         .filter(|item| {
                             let has_data = item.subject.is_some()
                                 || item.body.is_some()
-                                || item.from.is_some();
+                                || item.from.is_some()
+                                || item.error.is_some();
                             if !has_data {
                                 warn!("[MailManager] Skipping unhydrated post: {}", item.url);
                             }
@@ -341,25 +538,16 @@ impl MailManager {
                     info!(
                         "[MailManager] Adding {} hydrated items to feed (filtered from {})",
                         hydrated_items.len(),
-                        hydrated_items.len()
+                        fetched_count
                     );
 
-                    let has_hydrated_items = !hydrated_items.is_empty();
                     let mut feed_guard = feed_items.write().await;
-                    for item in hydrated_items {
-                        // Check if already exists
-                        if let Some(pos) = feed_guard.iter().position(|i| i.id == item.id) {
-                            // If the new item is hydrated (has subject OR body), update the cache
-                            // Even if subject is missing, if we fetched a body, we know more than before.
-                            if item.subject.is_some() || item.body.is_some() {
-                                feed_guard[pos] = item;
-                            }
-                        } else {
-                            feed_guard.push(item);
-                        }
-                    }
-                    // Sort by date descending
-                    feed_guard.sort_by(|a, b| b.date.unwrap_or(0).cmp(&a.date.unwrap_or(0)));
+                    let changed = Self::merge_feed_items(
+                        &mut feed_guard,
+                        hydrated_items,
+                        &feed_url,
+                        settings.max_feed_items,
+                    );
                     drop(feed_guard);
 
                     // Update last version
@@ -370,9 +558,10 @@ impl MailManager {
                         }
                     }
 
-                    // 4. Notify subscribers of new data
-                    if has_hydrated_items {
-                        let _ = update_tx.send(());
+                    // 4. Notify subscribers of new data, not of a failed
+                    // post failing the same way again
+                    if changed {
+                        updates.publish();
                         info!("[MailManager] Broadcast update for {}", feed_url);
                     }
                 }
@@ -381,13 +570,70 @@ impl MailManager {
                 }
             }
 
-            // Sleep before next sync
-            tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
+            // Sleep before next sync; a sync already in progress finishes first.
+            // A refresh requested mid-sync leaves a permit, so it isn't lost.
+            tokio::select! {
+                _ = tokio::time::sleep(settings.interval) => {}
+                _ = sub.refresh.notified() => {
+                    debug!("[MailManager] Refresh requested for {}", feed_url);
+                }
+                _ = shutdown.wait() => {
+                    info!("[MailManager] Stopping sync for {} on shutdown", feed_url);
+                    break;
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// Merge freshly hydrated items into the cached feed
+    ///
+    /// Items are de-duplicated on their absolute URL (relative links resolve
+    /// against `feed_url`), sorted newest first, and trimmed to `max_items`.
+    ///
+    /// Returns whether the feed changed: an item was added, a post that had
+    /// failed to load was hydrated, or a failed post's error changed.
+    fn merge_feed_items(
+        feed: &mut Vec<MailFeedItem>,
+        new_items: Vec<MailFeedItem>,
+        feed_url: &str,
+        max_items: usize,
+    ) -> bool {
+        let mut changed = HashSet::new();
+        for item in new_items {
+            let key = normalize_post_url(&item.url, feed_url);
+            if let Some(pos) = feed
+                .iter()
+                .position(|i| normalize_post_url(&i.url, feed_url) == key)
+            {
+                let existing = &feed[pos];
+                let was_hydrated = existing.subject.is_some() || existing.body.is_some();
+                // If the new item is hydrated (has subject OR body), update the cache
+                // Even if subject is missing, if we fetched a body, we know more than before.
+                if item.subject.is_some() || item.body.is_some() {
+                    if !was_hydrated {
+                        changed.insert(key);
+                    }
+                    feed[pos] = item;
+                } else if !was_hydrated && item.error.is_some() && item.error != existing.error {
+                    feed[pos] = item;
+                    changed.insert(key);
+                }
+            } else {
+                feed.push(item);
+                changed.insert(key);
+            }
+        }
+        // Sort by date descending
+        feed.sort_by_key(|i| Reverse(i.date.unwrap_or(0)));
+        feed.truncate(max_items);
+
+        // Items added only to be trimmed off again don't count
+        feed.iter()
+            .any(|i| changed.contains(&normalize_post_url(&i.url, feed_url)))
+    }
+
     /// Fetch feed from external source
     async fn fetch_feed(
         client: &BraidClient,
@@ -464,6 +710,8 @@ impl MailManager {
                         version: None,
                         parents: None,
                         merge_type: None,
+                        read: false,
+                        error: None,
                     })
                 })
                 .collect();
@@ -492,22 +740,9 @@ impl MailManager {
         let json: serde_json::Value = serde_json::from_str(&body)?;
 
         // Extract Braid protocol headers
-        let version = resp
-            .headers
-            .get("version")
-            .or(resp.headers.get("Version"))
-            .or(resp.headers.get("Current-Version"))
-            .map(|s| s.to_string());
-        let parents = resp
-            .headers
-            .get("parents")
-            .or(resp.headers.get("Parents"))
-            .map(|s| s.to_string());
-        let merge_type = resp
-            .headers
-            .get("merge-type")
-            .or(resp.headers.get("Merge-Type"))
-            .map(|s| s.to_string());
+        let version = resp.version().map(|v| v.to_string());
+        let parents = join_versions(&resp.parents());
+        let merge_type = resp.merge_type();
 
         let post = MailPost {
             url: url.to_string(),
@@ -547,14 +782,139 @@ impl MailManager {
         Ok(post)
     }
 
-    /// Get update receiver
-    pub fn subscribe_updates(&self) -> broadcast::Receiver<()> {
-        self.update_tx.subscribe()
+    /// Get update receiver; each update carries the new feed version
+    pub fn subscribe_updates(&self) -> broadcast::Receiver<u64> {
+        self.updates.tx.subscribe()
+    }
+
+    /// Version of the feed as of the last update, 0 before any
+    pub fn feed_version(&self) -> u64 {
+        self.updates.version.load(Ordering::SeqCst)
     }
 
-    /// Get all feed items
-    pub async fn get_feed_items(&self) -> Vec<MailFeedItem> {
-        self.feed_items.read().await.clone()
+    /// Mark a feed item as read
+    pub async fn mark_read(&self, id: &str) -> Result<()> {
+        let inserted = self.read_ids.write().await.insert(id.to_string());
+        if inserted {
+            self.save_read_state().await?;
+        }
+        Ok(())
+    }
+
+    /// Mark every item currently in the feed as read
+    pub async fn mark_all_read(&self) -> Result<()> {
+        let ids: Vec<String> = self
+            .feed_items
+            .read()
+            .await
+            .iter()
+            .map(|i| i.id.clone())
+            .collect();
+        self.read_ids.write().await.extend(ids);
+        self.save_read_state().await
+    }
+
+    /// Number of feed items not yet read
+    pub async fn unread_count(&self) -> usize {
+        let read_ids = self.read_ids.read().await;
+        self.feed_items
+            .read()
+            .await
+            .iter()
+            .filter(|i| !read_ids.contains(&i.id))
+            .count()
+    }
+
+    async fn save_read_state(&self) -> Result<()> {
+        let json = serde_json::to_string(&*self.read_ids.read().await)?;
+        if let Some(parent) = self.read_state_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&self.read_state_path, json).await?;
+        Ok(())
+    }
+
+    /// Set each item's `read` flag from the persisted read state
+    async fn apply_read_state(&self, items: &mut [MailFeedItem]) {
+        let read_ids = self.read_ids.read().await;
+        for item in items {
+            item.read = read_ids.contains(&item.id);
+        }
+    }
+
+    /// Get feed items in `range`, newest first
+    pub async fn get_feed_items(&self, range: impl RangeBounds<usize>) -> Vec<MailFeedItem> {
+        let items = self.feed_items.read().await;
+        let start = match range.start_bound() {
+            std::ops::Bound::Included(&n) => n,
+            std::ops::Bound::Excluded(&n) => n + 1,
+            std::ops::Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            std::ops::Bound::Included(&n) => n + 1,
+            std::ops::Bound::Excluded(&n) => n,
+            std::ops::Bound::Unbounded => items.len(),
+        };
+        let end = end.min(items.len());
+        let mut page = items.get(start.min(end)..end).unwrap_or_default().to_vec();
+        drop(items);
+        self.apply_read_state(&mut page).await;
+        page
+    }
+
+    /// Get one page of the feed along with the total item count
+    pub async fn get_feed_page(&self, offset: usize, limit: Option<usize>) -> MailFeedPage {
+        let total = self.feed_items.read().await.len();
+        let end = limit.map_or(total, |limit| offset.saturating_add(limit));
+        let items = self.get_feed_items(offset..end).await;
+        MailFeedPage {
+            has_more: end < total,
+            items,
+            total,
+        }
+    }
+
+    /// Search cached feed items and posts by subject, body, and sender
+    ///
+    /// The query is split on whitespace and every token must appear
+    /// (case-insensitively) somewhere in the post. Results are newest first.
+    /// An empty query returns the most recent items.
+    pub async fn search(&self, query: &str) -> Vec<MailFeedItem> {
+        let tokens: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if tokens.is_empty() {
+            return self.get_feed_items(..RECENT_SEARCH_LIMIT).await;
+        }
+
+        let mut candidates = self.get_feed_items(..).await;
+        {
+            // Posts fetched directly may not be in the feed
+            let posts = self.posts.read().await;
+            for post in posts.values() {
+                if !candidates
+                    .iter()
+                    .any(|i| i.url == post.url || i.id == post.url)
+                {
+                    candidates.push(MailFeedItem::from(post.clone()));
+                }
+            }
+        }
+        self.apply_read_state(&mut candidates).await;
+
+        let mut results: Vec<MailFeedItem> = candidates
+            .into_iter()
+            .filter(|item| {
+                let haystack = [
+                    item.subject.clone().unwrap_or_default(),
+                    item.body.clone().unwrap_or_default(),
+                    item.from.clone().unwrap_or_default().join(" "),
+                ]
+                .join("\n")
+                .to_lowercase();
+                tokens.iter().all(|t| haystack.contains(t.as_str()))
+            })
+            .collect();
+        results.sort_by_key(|i| Reverse(i.date.unwrap_or(0)));
+        results
     }
 
     /// Get cached posts
@@ -567,6 +927,26 @@ impl MailManager {
         !self.subscriptions.read().await.is_empty()
     }
 
+    /// Sync `feed_url` now instead of waiting out the interval
+    ///
+    /// Returns false if the feed isn't subscribed.
+    pub async fn refresh_now(&self, feed_url: &str) -> bool {
+        match self.subscriptions.read().await.get(feed_url) {
+            Some(sub) => {
+                sub.refresh.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sync every subscribed feed now
+    async fn refresh_all(&self) {
+        for sub in self.subscriptions.read().await.values() {
+            sub.refresh.notify_one();
+        }
+    }
+
     /// Send a mail post to mail.braid.org
     pub async fn send_mail(&self, mut post: MailPost) -> Result<String> {
         let client = BraidClient::new()?;
@@ -612,13 +992,8 @@ impl MailManager {
             .with_body(body_json.to_string());
 
         // Add authentication cookie if available
-        if let Some(cookie) = self.user_cookie.read().await.clone() {
-            let cookie_header = if cookie.contains('=') {
-                cookie
-            } else {
-                format!("client={}", cookie)
-            };
-            req = req.with_header("Cookie", cookie_header);
+        if let Some(cookie) = self.cookie_header().await {
+            req = req.with_header("Cookie", cookie);
         }
 
         match client.fetch(&url, req).await {
@@ -643,6 +1018,8 @@ impl MailManager {
                         version: None,
                         parents: None,
                         merge_type: Some("sync9".to_string()),
+                        read: false,
+                        error: None,
                     };
 
                     // Insert at the beginning (newest first)
@@ -651,7 +1028,10 @@ impl MailManager {
                     drop(feed);
 
                     // Notify subscribers of update
-                    let _ = self.update_tx.send(());
+                    self.updates.publish();
+
+                    // Fetch the authoritative version rather than wait for the next sync
+                    self.refresh_all().await;
 
                     Ok(url)
                 } else {
@@ -666,6 +1046,82 @@ impl MailManager {
             }
         }
     }
+
+    /// Retract a post this user sent
+    ///
+    /// Sends a DELETE to the post's origin, then drops it from the post
+    /// cache and the feed.
+    pub async fn delete_post(&self, url: &str) -> Result<DeletePostOutcome> {
+        let Ok(post) = self.fetch_post(url).await else {
+            return Ok(DeletePostOutcome::NotFound);
+        };
+        let Some(email) = self.user_email.read().await.clone() else {
+            return Ok(DeletePostOutcome::NotOwner);
+        };
+        let is_owner = post
+            .from
+            .iter()
+            .flatten()
+            .any(|from| from.eq_ignore_ascii_case(&email));
+        if !is_owner {
+            return Ok(DeletePostOutcome::NotOwner);
+        }
+
+        let mut req = BraidRequest::new().with_method("DELETE");
+        if let Some(cookie) = self.cookie_header().await {
+            req = req.with_header("Cookie", cookie);
+        }
+        let resp = BraidClient::new()?.fetch(url, req).await?;
+        // Already gone at the origin is as good as deleted
+        if !(200..300).contains(&resp.status) && resp.status != 404 {
+            let body = String::from_utf8_lossy(&resp.body);
+            return Err(anyhow::anyhow!(
+                "Server returned status {}: {}",
+                resp.status,
+                body
+            ));
+        }
+        info!("[Mail] Deleted post {}", url);
+
+        self.posts.write().await.remove(url);
+        self.feed_items
+            .write()
+            .await
+            .retain(|item| item.id != url && normalize_post_url(&item.url, url) != url);
+        self.updates.publish();
+
+        Ok(DeletePostOutcome::Deleted)
+    }
+
+    /// The stored auth cookie as a `Cookie` header value
+    async fn cookie_header(&self) -> Option<String> {
+        let cookie = self.user_cookie.read().await.clone()?;
+        Some(if cookie.contains('=') {
+            cookie
+        } else {
+            format!("client={}", cookie)
+        })
+    }
+}
+
+/// Mail routes. The feed is shared rather than per-user, but the routes act
+/// on the user's mail account, so `app` puts them behind the auth layer.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/mail/subscribe",
+            post(subscribe_mail).delete(unsubscribe_mail),
+        )
+        .route("/mail/subscription", get(is_subscribed))
+        .route("/mail/refresh", post(refresh_mail))
+        .route("/mail/feed", get(get_mail_feed))
+        .route("/mail/search", get(search_mail))
+        .route("/mail/read", axum::routing::put(mark_all_mail_read))
+        .route("/mail/read/{*id}", axum::routing::put(mark_mail_read))
+        .route("/mail/post", axum::routing::delete(delete_mail_post))
+        .route("/mail/post/{*url}", get(get_mail_post))
+        .route("/mail/send", post(send_mail))
+        .route("/mail/auth", post(set_mail_auth))
 }
 
 /// API: Subscribe to mail feed
@@ -689,9 +1145,137 @@ pub async fn subscribe_mail(
     }
 }
 
+/// API: Sync a subscribed feed immediately
+pub async fn refresh_mail(
+    State(state): State<AppState>,
+    Json(req): Json<SubscribeMailRequest>,
+) -> StatusCode {
+    let feed_url = req
+        .feed_url
+        .unwrap_or_else(|| "https://mail.braid.org/feed".to_string());
+
+    if state.mail_manager.refresh_now(&feed_url).await {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// API: Unsubscribe from mail feed
+///
+/// Cached items are only cleared when `clear_cache` is set and no other feed
+/// remains subscribed, since items aren't tracked per feed.
+pub async fn unsubscribe_mail(
+    State(state): State<AppState>,
+    Json(req): Json<UnsubscribeMailRequest>,
+) -> std::result::Result<Json<SubscribeMailResponse>, axum::http::StatusCode> {
+    let feed_url = req
+        .feed_url
+        .unwrap_or_else(|| "https://mail.braid.org/feed".to_string());
+
+    let removed = state.mail_manager.unsubscribe_feed(&feed_url).await;
+    if req.clear_cache && !state.mail_manager.is_subscribed().await {
+        state.mail_manager.clear_feed_items().await;
+    }
+
+    Ok(Json(SubscribeMailResponse {
+        success: removed,
+        message: if removed {
+            "Unsubscribed from mail feed".to_string()
+        } else {
+            "Not subscribed to mail feed".to_string()
+        },
+    }))
+}
+
+/// Frame the whole feed as one update at `version`
+async fn feed_update_frame(
+    mail_manager: &MailManager,
+    version: u64,
+    parent: Option<u64>,
+) -> bytes::Bytes {
+    let items = mail_manager.get_feed_items(..).await;
+    let body = serde_json::to_string(&items).unwrap_or_default();
+    let parents: Vec<Version> = parent
+        .map(|p| Version::new(p.to_string()))
+        .into_iter()
+        .collect();
+    write_update_frame(
+        &[Version::new(version.to_string())],
+        &parents,
+        body.as_bytes(),
+    )
+}
+
+/// Heartbeat interval when the subscriber doesn't send `Heartbeats`
+const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(30);
+
+/// Blank line sent while idle so proxies don't drop the connection;
+/// Braid parsers skip it between updates
+const KEEPALIVE: &[u8] = b"\r\n";
+
+/// The subscriber's requested `Heartbeats` interval, or the default
+fn heartbeat_interval(headers: &HeaderMap) -> Duration {
+    headers
+        .get(&headers::HEARTBEATS)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_heartbeat(v).ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_HEARTBEAT)
+}
+
+/// The feed's subscription body: the current feed, then the whole feed
+/// again after each update, versioned by [`MailManager::feed_version`]
+///
+/// A keepalive goes out every `heartbeat` the stream is otherwise idle.
+fn feed_update_stream(
+    mail_manager: Arc<MailManager>,
+    heartbeat: Duration,
+) -> impl futures::Stream<Item = std::result::Result<bytes::Bytes, Infallible>> {
+    let mut rx = mail_manager.subscribe_updates();
+    async_stream::stream! {
+        // 1. Send initial feed state
+        let mut current = mail_manager.feed_version();
+        yield Ok(feed_update_frame(&mail_manager, current, None).await);
+
+        // 2. Stream updates, with heartbeats in between
+        let first_tick = tokio::time::Instant::now() + heartbeat;
+        let mut ticks = tokio::time::interval_at(first_tick, heartbeat);
+        loop {
+            let update = tokio::select! {
+                update = rx.recv() => update,
+                _ = ticks.tick() => {
+                    yield Ok(bytes::Bytes::from_static(KEEPALIVE));
+                    continue;
+                }
+            };
+            ticks.reset();
+            match update {
+                // Each frame carries the whole feed, so an update that
+                // raced past a newer one has nothing left to send
+                Ok(version) if version <= current => continue,
+                Ok(version) => {
+                    yield Ok(feed_update_frame(&mail_manager, version, Some(current)).await);
+                    current = version;
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    warn!("[MailAPI] Subscription lagged, continuing...");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
 /// API: Get mail feed
+///
+/// Plain GETs return a [`MailFeedPage`] selected by `offset` and `limit`;
+/// subscriptions always stream the whole feed.
 pub async fn get_mail_feed(
     State(state): State<AppState>,
+    Query(query): Query<MailFeedQuery>,
     headers: HeaderMap,
 ) -> std::result::Result<Response<Body>, StatusCode> {
     info!("[MailAPI] GET /mail/feed");
@@ -700,43 +1284,7 @@ pub async fn get_mail_feed(
     if headers.get(&headers::SUBSCRIBE).is_some() {
         info!("[MailAPI] Establishing Braid subscription for mail feed");
 
-        let mut rx = state.mail_manager.subscribe_updates();
-        let mail_manager = state.mail_manager.clone();
-
-        let stream = async_stream::stream! {
-            // 1. Send initial feed state
-            let initial_items = mail_manager.get_feed_items().await;
-            let body = serde_json::to_string(&initial_items).unwrap_or_default();
-
-            let mut update = String::new();
-            update.push_str(&format!("Content-Length: {}\r\n", body.len()));
-            update.push_str("\r\n");
-            update.push_str(&body);
-            update.push_str("\r\n\r\n");
-            yield Ok::<_, Infallible>(bytes::Bytes::from(update));
-
-            // 2. Stream updates
-            loop {
-                match rx.recv().await {
-                    Ok(_) => {
-                        let items = mail_manager.get_feed_items().await;
-                        let body = serde_json::to_string(&items).unwrap_or_default();
-
-                        let mut update = String::new();
-                        update.push_str(&format!("Content-Length: {}\r\n", body.len()));
-                        update.push_str("\r\n");
-                        update.push_str(&body);
-                        update.push_str("\r\n\r\n");
-                        yield Ok::<_, Infallible>(bytes::Bytes::from(update));
-                    }
-                    Err(broadcast::error::RecvError::Lagged(_)) => {
-                        warn!("[MailAPI] Subscription lagged, continuing...");
-                        continue;
-                    }
-                    Err(broadcast::error::RecvError::Closed) => break,
-                }
-            }
-        };
+        let stream = feed_update_stream(state.mail_manager.clone(), heartbeat_interval(&headers));
 
         return Response::builder()
             .status(StatusCode::from_u16(209).unwrap())
@@ -751,16 +1299,57 @@ pub async fn get_mail_feed(
     }
 
     // Standard JSON response
-    let items = state.mail_manager.get_feed_items().await;
+    let page = state
+        .mail_manager
+        .get_feed_page(query.offset.unwrap_or(0), query.limit)
+        .await;
     Ok(Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "application/json")
-        .body(Body::from(
-            serde_json::to_string(&items).unwrap_or_default(),
-        ))
+        .body(Body::from(serde_json::to_string(&page).unwrap_or_default()))
         .unwrap())
 }
 
+/// API: Mark one item read
+pub async fn mark_mail_read(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> std::result::Result<Json<serde_json::Value>, StatusCode> {
+    // Unencoded ids like "/post/abc" lose their leading slash to the wildcard
+    let id = if id.starts_with('/') || id.starts_with("http") {
+        id
+    } else {
+        format!("/{}", id)
+    };
+    state.mail_manager.mark_read(&id).await.map_err(|e| {
+        error!("[MailAPI] Mark read failed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "unread": state.mail_manager.unread_count().await
+    })))
+}
+
+/// API: Mark every item read
+pub async fn mark_all_mail_read(
+    State(state): State<AppState>,
+) -> std::result::Result<Json<serde_json::Value>, StatusCode> {
+    state.mail_manager.mark_all_read().await.map_err(|e| {
+        error!("[MailAPI] Mark all read failed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(serde_json::json!({ "success": true, "unread": 0 })))
+}
+
+/// API: Search cached mail
+pub async fn search_mail(
+    State(state): State<AppState>,
+    Query(query): Query<MailSearchQuery>,
+) -> Json<Vec<MailFeedItem>> {
+    Json(state.mail_manager.search(&query.q).await)
+}
+
 /// API: Get a specific mail post
 pub async fn get_mail_post(
     Path(url): Path<String>,
@@ -776,6 +1365,22 @@ pub async fn get_mail_post(
     }
 }
 
+/// API: Delete a post the user sent
+pub async fn delete_mail_post(
+    State(state): State<AppState>,
+    Query(query): Query<MailPostQuery>,
+) -> StatusCode {
+    match state.mail_manager.delete_post(&query.url).await {
+        Ok(DeletePostOutcome::Deleted) => StatusCode::NO_CONTENT,
+        Ok(DeletePostOutcome::NotFound) => StatusCode::NOT_FOUND,
+        Ok(DeletePostOutcome::NotOwner) => StatusCode::FORBIDDEN,
+        Err(e) => {
+            error!("[MailAPI] Delete post failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
 /// API: Check subscription status
 pub async fn is_subscribed(
     State(state): State<AppState>,
@@ -813,11 +1418,44 @@ pub async fn send_mail(
 
 // Request/Response types
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MailFeedQuery {
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MailSearchQuery {
+    #[serde(default)]
+    pub q: String,
+}
+
+/// One page of the mail feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailFeedPage {
+    pub items: Vec<MailFeedItem>,
+    /// Number of items in the whole feed
+    pub total: usize,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MailPostQuery {
+    pub url: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SubscribeMailRequest {
     pub feed_url: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnsubscribeMailRequest {
+    pub feed_url: Option<String>,
+    #[serde(default)]
+    pub clear_cache: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SubscribeMailResponse {
     pub success: bool,
@@ -857,3 +1495,600 @@ pub struct SetAuthRequest {
     pub cookie: Option<String>,
     pub email: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::ChatServerConfig;
+    use tempfile::TempDir;
+
+    fn item(n: u64) -> MailFeedItem {
+        MailFeedItem {
+            id: format!("/post/{}", n),
+            url: format!("/post/{}", n),
+            subject: Some(format!("Post {}", n)),
+            from: None,
+            to: None,
+            cc: None,
+            date: Some(n),
+            body: None,
+            is_network: true,
+            version: None,
+            parents: None,
+            merge_type: None,
+            read: false,
+            error: None,
+        }
+    }
+
+    async fn manager(temp_dir: &TempDir) -> MailManager {
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        MailManager::new(Arc::new(JsonChatStore::new(config).await.unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_feed_page() {
+        let temp_dir = TempDir::new().unwrap();
+        let mail = manager(&temp_dir).await;
+        // Newest first, as feed_sync_task keeps them
+        *mail.feed_items.write().await = (0..50).rev().map(item).collect();
+
+        let page = mail.get_feed_page(20, Some(10)).await;
+        assert_eq!(page.total, 50);
+        assert!(page.has_more);
+        let dates: Vec<u64> = page.items.iter().map(|i| i.date.unwrap()).collect();
+        assert_eq!(dates, (20..30).map(|n| 49 - n).collect::<Vec<_>>());
+
+        let last = mail.get_feed_page(45, Some(10)).await;
+        assert_eq!(last.items.len(), 5);
+        assert!(!last.has_more);
+
+        assert!(mail.get_feed_page(60, Some(10)).await.items.is_empty());
+        assert_eq!(mail.get_feed_page(0, None).await.items.len(), 50);
+    }
+
+    #[test]
+    fn test_parse_feed_link_strings() {
+        let items = MailManager::parse_feed_items(r#"["/post/1", null, "/post/2"]"#);
+        let urls: Vec<&str> = items.iter().map(|i| i.url.as_str()).collect();
+        assert_eq!(urls, vec!["/post/1", "/post/2"]);
+        assert!(items.iter().all(|i| i.id == i.url && i.is_network));
+    }
+
+    #[test]
+    fn test_parse_feed_link_objects() {
+        let body = r#"[{"link": "/post/1"}, null, {"nolink": true}, 42, {"link": "/post/2"}]"#;
+        let urls: Vec<String> = MailManager::parse_feed_items(body)
+            .into_iter()
+            .map(|i| i.url)
+            .collect();
+        assert_eq!(urls, vec!["/post/1", "/post/2"]);
+    }
+
+    #[test]
+    fn test_parse_feed_unparseable() {
+        assert!(MailManager::parse_feed_items("not json").is_empty());
+        assert!(MailManager::parse_feed_items(r#"{"link": "/post/1"}"#).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hydration_reuses_client_connections() {
+        use std::net::SocketAddr;
+        use std::sync::Mutex;
+
+        // Record the client end of every connection that fetches a post
+        let peers = Arc::new(Mutex::new(HashSet::new()));
+        let app = axum::Router::new().route(
+            "/post/{n}",
+            axum::routing::get({
+                let peers = peers.clone();
+                move |Path(n): Path<u64>,
+                      axum::extract::ConnectInfo(peer): axum::extract::ConnectInfo<SocketAddr>| {
+                    peers.lock().unwrap().insert(peer);
+                    async move { Json(serde_json::json!({ "subject": format!("Post {}", n) })) }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+        });
+
+        let client = BraidClient::new().unwrap();
+        let posts = Arc::new(RwLock::new(HashMap::new()));
+        let batch = |range: std::ops::Range<u64>| {
+            range
+                .map(|n| MailFeedItem {
+                    url: format!("http://{}/post/{}", addr, n),
+                    subject: None,
+                    ..item(n)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let first = MailManager::hydrate_items(&client, &posts, batch(0..5)).await;
+        assert!(first.iter().all(|i| i.subject.is_some()));
+        let opened = peers.lock().unwrap().len();
+
+        // A second feed refresh goes over the connections the first one opened
+        let second = MailManager::hydrate_items(&client, &posts, batch(5..10)).await;
+        assert_eq!(second.len(), 5);
+        assert!(second.iter().all(|i| i.subject.is_some()));
+        assert_eq!(peers.lock().unwrap().len(), opened);
+        assert_eq!(posts.read().await.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_hydration_retries_and_keeps_failed_items() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // /post/flaky fails twice then succeeds; /post/broken always fails
+        let flaky_hits = Arc::new(AtomicUsize::new(0));
+        let app = axum::Router::new()
+            .route(
+                "/post/flaky",
+                axum::routing::get({
+                    let flaky_hits = flaky_hits.clone();
+                    move || {
+                        let hit = flaky_hits.fetch_add(1, Ordering::SeqCst);
+                        async move {
+                            if hit < 2 {
+                                Err(StatusCode::SERVICE_UNAVAILABLE)
+                            } else {
+                                Ok(Json(serde_json::json!({ "subject": "Made it" })))
+                            }
+                        }
+                    }
+                }),
+            )
+            .route(
+                "/post/broken",
+                axum::routing::get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = BraidClient::new().unwrap();
+        let posts = Arc::new(RwLock::new(HashMap::new()));
+        let items = ["flaky", "broken"]
+            .iter()
+            .enumerate()
+            .map(|(n, name)| MailFeedItem {
+                url: format!("http://{}/post/{}", addr, name),
+                subject: None,
+                ..item(n as u64)
+            })
+            .collect();
+
+        let hydrated = MailManager::hydrate_items(&client, &posts, items).await;
+        let flaky = hydrated.iter().find(|i| i.url.ends_with("flaky")).unwrap();
+        assert_eq!(flaky.subject.as_deref(), Some("Made it"));
+        assert!(flaky.error.is_none());
+        assert_eq!(flaky_hits.load(Ordering::SeqCst), 3);
+
+        // Still in the batch, marked as failed rather than dropped
+        let broken = hydrated.iter().find(|i| i.url.ends_with("broken")).unwrap();
+        assert!(broken.subject.is_none());
+        assert!(broken.error.is_some());
+        assert_eq!(posts.read().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mail_routes_require_auth() {
+        use crate::core::config::test_support::test_state;
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let app = crate::app(test_state(temp_dir.path()).await);
+
+        for (method, uri) in [
+            ("POST", "/mail/subscribe"),
+            ("POST", "/mail/refresh"),
+            ("GET", "/mail/feed"),
+            ("PUT", "/mail/read"),
+            ("POST", "/mail/send"),
+            ("POST", "/mail/auth"),
+        ] {
+            let req = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from("{}"))
+                .unwrap();
+            let status = app.clone().oneshot(req).await.unwrap().status();
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{} {}", method, uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_post_requires_auth() {
+        use crate::core::config::test_support::{test_state, test_user};
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let state = test_state(temp_dir.path()).await;
+        let (_, token) = test_user(&state, "alice").await;
+        let url = "https://mail.braid.org/post/1";
+        state.mail_manager.posts.write().await.insert(
+            url.to_string(),
+            MailPost {
+                url: url.to_string(),
+                subject: None,
+                from: Some(vec!["bob@example.org".to_string()]),
+                to: None,
+                cc: None,
+                date: None,
+                body: None,
+                version: None,
+                parents: None,
+                merge_type: None,
+                link: None,
+            },
+        );
+        let app = crate::app(state);
+
+        let delete = |token: Option<&str>| {
+            let mut req = Request::builder()
+                .method("DELETE")
+                .uri(format!("/mail/post?url={}", url));
+            if let Some(token) = token {
+                req = req.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            req.body(Body::empty()).unwrap()
+        };
+
+        let resp = app.clone().oneshot(delete(None)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // Signed in, the request reaches the handler, which still refuses
+        // someone else's post
+        let resp = app.oneshot(delete(Some(&token))).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_syncs_before_interval() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let syncs = Arc::new(AtomicUsize::new(0));
+        let app = axum::Router::new().route(
+            "/feed",
+            axum::routing::get({
+                let syncs = syncs.clone();
+                move || {
+                    syncs.fetch_add(1, Ordering::SeqCst);
+                    async { Json(serde_json::json!([])) }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let feed_url = format!("http://{}/feed", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = ChatServerConfig::with_base_dir(temp_dir.path());
+        config.mail_sync_interval = Duration::from_secs(3600);
+        let mail = MailManager::new(Arc::new(JsonChatStore::new(config).await.unwrap()));
+        assert!(!mail.refresh_now(&feed_url).await);
+
+        let wait_for = |n: usize| {
+            let syncs = syncs.clone();
+            tokio::time::timeout(Duration::from_secs(5), async move {
+                while syncs.load(Ordering::SeqCst) < n {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+        };
+
+        mail.subscribe_feed(&feed_url).await.unwrap();
+        wait_for(1).await.expect("initial sync");
+
+        assert!(mail.refresh_now(&feed_url).await);
+        wait_for(2)
+            .await
+            .expect("refresh should sync without waiting an hour");
+        mail.unsubscribe_feed(&feed_url).await;
+    }
+
+    #[tokio::test]
+    async fn test_send_then_delete_post() {
+        use std::sync::Mutex;
+
+        let deleted = Arc::new(Mutex::new(Vec::new()));
+        let app = axum::Router::new().route(
+            "/post/{id}",
+            axum::routing::put(|| async { StatusCode::OK }).delete({
+                let deleted = deleted.clone();
+                move |Path(id): Path<String>| {
+                    deleted.lock().unwrap().push(id);
+                    async { StatusCode::OK }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let temp_dir = TempDir::new().unwrap();
+        let mail = manager(&temp_dir).await;
+        mail.set_email("me@example.org".to_string()).await;
+
+        let mine = MailPost {
+            url: format!("{}/post/mine", origin),
+            subject: Some("Hello".to_string()),
+            from: Some(vec!["me@example.org".to_string()]),
+            to: None,
+            cc: None,
+            date: Some(1),
+            body: Some("hi".to_string()),
+            version: None,
+            parents: None,
+            merge_type: None,
+            link: None,
+        };
+        let url = mail.send_mail(mine.clone()).await.unwrap();
+        assert!(mail.get_feed_items(..).await.iter().any(|i| i.url == url));
+
+        // Someone else's post can't be retracted
+        let theirs = MailPost {
+            url: format!("{}/post/theirs", origin),
+            from: Some(vec!["other@example.org".to_string()]),
+            ..mine
+        };
+        mail.send_mail(theirs.clone()).await.unwrap();
+        assert_eq!(
+            mail.delete_post(&theirs.url).await.unwrap(),
+            DeletePostOutcome::NotOwner
+        );
+
+        assert_eq!(
+            mail.delete_post(&url).await.unwrap(),
+            DeletePostOutcome::Deleted
+        );
+        assert_eq!(*deleted.lock().unwrap(), vec!["mine".to_string()]);
+        let feed = mail.get_feed_items(..).await;
+        assert!(!feed.iter().any(|i| i.url == url));
+        assert!(feed.iter().any(|i| i.url == theirs.url));
+        assert!(!mail.get_posts().await.iter().any(|p| p.url == url));
+    }
+
+    fn post(n: u64, subject: &str, body: &str) -> MailFeedItem {
+        MailFeedItem {
+            subject: Some(subject.to_string()),
+            body: Some(body.to_string()),
+            from: Some(vec![format!("user{}@example.org", n)]),
+            ..item(n)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_subject() {
+        let temp_dir = TempDir::new().unwrap();
+        let mail = manager(&temp_dir).await;
+        *mail.feed_items.write().await = vec![
+            post(3, "Weekly sync", "nothing here"),
+            post(2, "Braid meeting notes", "agenda"),
+            post(1, "lunch?", "braid is great"),
+        ];
+
+        let ids: Vec<String> = mail
+            .search("MEETING")
+            .await
+            .into_iter()
+            .map(|i| i.id)
+            .collect();
+        assert_eq!(ids, vec!["/post/2"]);
+
+        // Subject and body both count, newest first
+        let ids: Vec<String> = mail
+            .search("braid")
+            .await
+            .into_iter()
+            .map(|i| i.id)
+            .collect();
+        assert_eq!(ids, vec!["/post/2", "/post/1"]);
+
+        assert_eq!(mail.search("user3@").await.len(), 1);
+        assert_eq!(mail.search("   ").await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_search_multiple_tokens() {
+        let temp_dir = TempDir::new().unwrap();
+        let mail = manager(&temp_dir).await;
+        *mail.feed_items.write().await = vec![
+            post(3, "protocol draft", "for braid"),
+            post(2, "braid", "no match here"),
+            post(1, "Protocol and Braid", ""),
+        ];
+        mail.posts.write().await.insert(
+            "/post/9".to_string(),
+            MailPost {
+                url: "/post/9".to_string(),
+                subject: Some("cached only".to_string()),
+                from: None,
+                to: None,
+                cc: None,
+                date: Some(9),
+                body: Some("the braid protocol".to_string()),
+                version: None,
+                parents: None,
+                merge_type: None,
+                link: None,
+            },
+        );
+
+        let ids: Vec<String> = mail
+            .search("braid protocol")
+            .await
+            .into_iter()
+            .map(|i| i.id)
+            .collect();
+        assert_eq!(ids, vec!["/post/9", "/post/3", "/post/1"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_state_survives_refresh() {
+        let temp_dir = TempDir::new().unwrap();
+        let mail = manager(&temp_dir).await;
+        *mail.feed_items.write().await = (1..=3).map(item).collect();
+
+        mail.mark_read("/post/2").await.unwrap();
+        assert_eq!(mail.unread_count().await, 2);
+
+        // A refresh replaces the items with freshly parsed, unread copies
+        *mail.feed_items.write().await = (1..=3).map(item).collect();
+        assert_eq!(mail.unread_count().await, 2);
+        let page = mail.get_feed_page(0, None).await;
+        let read: Vec<bool> = page.items.iter().map(|i| i.read).collect();
+        assert_eq!(read, vec![false, true, false]);
+
+        // And it's persisted across restarts
+        let reloaded = manager(&temp_dir).await;
+        *reloaded.feed_items.write().await = (1..=3).map(item).collect();
+        assert_eq!(reloaded.unread_count().await, 2);
+
+        reloaded.mark_all_read().await.unwrap();
+        assert_eq!(reloaded.unread_count().await, 0);
+    }
+
+    #[test]
+    fn test_merge_dedups_relative_and_absolute_urls() {
+        let feed_url = "https://mail.braid.org/feed";
+        let mut feed = vec![MailFeedItem {
+            id: "https://mail.braid.org/post/1".to_string(),
+            url: "https://mail.braid.org/post/1".to_string(),
+            ..item(1)
+        }];
+
+        MailManager::merge_feed_items(&mut feed, vec![item(1), item(1)], feed_url, 500);
+        assert_eq!(feed.len(), 1);
+        assert_eq!(
+            normalize_post_url(&feed[0].url, feed_url),
+            "https://mail.braid.org/post/1"
+        );
+    }
+
+    #[test]
+    fn test_merge_caps_feed_size() {
+        let mut feed = Vec::new();
+        MailManager::merge_feed_items(
+            &mut feed,
+            (0..10).map(item).collect(),
+            "https://mail.braid.org/feed",
+            4,
+        );
+        let dates: Vec<u64> = feed.iter().map(|i| i.date.unwrap()).collect();
+        assert_eq!(dates, vec![9, 8, 7, 6]);
+
+        // Posts too old to make the cut don't change anything
+        let old = (0..4).map(item).collect();
+        let changed =
+            MailManager::merge_feed_items(&mut feed, old, "https://mail.braid.org/feed", 4);
+        assert!(!changed);
+    }
+
+    #[test]
+    fn test_merge_reports_only_real_changes() {
+        let merge = |feed: &mut Vec<MailFeedItem>, item: MailFeedItem| {
+            MailManager::merge_feed_items(feed, vec![item], "https://mail.braid.org/feed", 500)
+        };
+        let failed = |error: &str| MailFeedItem {
+            subject: None,
+            error: Some(error.to_string()),
+            ..item(1)
+        };
+        let mut feed = Vec::new();
+
+        assert!(merge(&mut feed, failed("timeout")));
+        // Failing the same way again isn't news
+        assert!(!merge(&mut feed, failed("timeout")));
+        assert!(merge(&mut feed, failed("HTTP 500")));
+        assert_eq!(feed[0].error.as_deref(), Some("HTTP 500"));
+
+        // Finally loading is, but loading from the cache again isn't
+        assert!(merge(&mut feed, item(1)));
+        assert!(!merge(&mut feed, item(1)));
+        assert_eq!(feed.len(), 1);
+        assert!(feed[0].error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_feed() {
+        let temp_dir = TempDir::new().unwrap();
+        let mail = manager(&temp_dir).await;
+        // Unroutable, so the sync task never gets far
+        let feed = "http://127.0.0.1:9/feed";
+
+        mail.subscribe_feed(feed).await.unwrap();
+        assert!(mail.is_subscribed().await);
+
+        assert!(mail.unsubscribe_feed(feed).await);
+        assert!(!mail.is_subscribed().await);
+        assert!(mail.sync_tasks.read().await.is_empty());
+        assert!(!mail.unsubscribe_feed(feed).await);
+    }
+
+    #[tokio::test]
+    async fn test_subscription_frames_carry_feed_versions() {
+        let temp_dir = TempDir::new().unwrap();
+        let mail = Arc::new(manager(&temp_dir).await);
+        mail.feed_items.write().await.push(item(1));
+
+        let mut stream = Box::pin(feed_update_stream(mail.clone(), DEFAULT_HEARTBEAT));
+        let mut parser = braid_http::client::MessageParser::new();
+
+        let frame = stream.next().await.unwrap().unwrap();
+        let initial = parser.feed(&frame).unwrap();
+        assert_eq!(initial.len(), 1);
+        assert_eq!(initial[0].version(), Some("\"0\""));
+        assert_eq!(initial[0].parents(), None);
+        let items: Vec<MailFeedItem> = serde_json::from_slice(&initial[0].body).unwrap();
+        assert_eq!(items[0].id, "/post/1");
+
+        mail.clear_feed_items().await;
+        let frame = stream.next().await.unwrap().unwrap();
+        let update = parser.feed(&frame).unwrap();
+        assert_eq!(update.len(), 1);
+        assert_eq!(update[0].version(), Some("\"1\""));
+        assert_eq!(update[0].parents(), Some("\"0\""));
+        assert_eq!(update[0].body_text().as_deref(), Some("[]"));
+    }
+
+    #[tokio::test]
+    async fn test_idle_subscription_sends_keepalives() {
+        let temp_dir = TempDir::new().unwrap();
+        let mail = Arc::new(manager(&temp_dir).await);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(headers::HEARTBEATS, "1s".parse().unwrap());
+        assert_eq!(heartbeat_interval(&headers), Duration::from_secs(1));
+        assert_eq!(heartbeat_interval(&HeaderMap::new()), DEFAULT_HEARTBEAT);
+
+        let heartbeat = Duration::from_millis(100);
+        let mut stream = Box::pin(feed_update_stream(mail, heartbeat));
+        stream.next().await.unwrap().unwrap();
+
+        let started = tokio::time::Instant::now();
+        for n in 1..=3 {
+            let frame = tokio::time::timeout(Duration::from_secs(1), stream.next())
+                .await
+                .expect("keepalive should arrive while idle")
+                .unwrap()
+                .unwrap();
+            assert_eq!(&frame[..], KEEPALIVE);
+            assert!(started.elapsed() >= heartbeat * n);
+        }
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -1,13 +1,15 @@
 //! Auth handlers
 
+use crate::core::auth::middleware;
 use crate::core::config::AppState;
+use crate::core::error::Error;
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     Json,
 };
 use serde::{Deserialize, Serialize};
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 #[derive(Debug, Deserialize)]
 pub struct SignupRequest {
@@ -23,7 +25,34 @@ pub struct LoginRequest {
     pub password: String,
 }
 
-use super::super::UserInfo;
+#[derive(Debug, Deserialize)]
+pub struct UserSearchQuery {
+    pub q: String,
+    #[serde(default = "default_search_limit")]
+    pub limit: usize,
+}
+
+fn default_search_limit() -> usize {
+    20
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetConfirmRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+use super::super::{Session, SessionInfo, UserInfo};
 
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
@@ -37,8 +66,28 @@ pub struct ErrorResponse {
     pub error: String,
 }
 
+/// Map a bearer-auth failure to this module's error body
+fn unauthorized(e: Error) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ErrorResponse {
+            error: e.to_string(),
+        }),
+    )
+}
+
+/// Device label for a new session: `X-Device-Label`, falling back to `User-Agent`
+fn device_label(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("x-device-label")
+        .or_else(|| headers.get(header::USER_AGENT))
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.chars().take(200).collect())
+}
+
 pub async fn signup(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<SignupRequest>,
 ) -> Result<Json<AuthResponse>, (StatusCode, Json<ErrorResponse>)> {
     info!("POST /auth/signup - {}", req.email);
@@ -57,7 +106,11 @@ pub async fn signup(
             // Create session
             match state
                 .auth
-                .login(req.email.clone(), req.password.clone())
+                .login(
+                    req.email.clone(),
+                    req.password.clone(),
+                    device_label(&headers),
+                )
                 .await
             {
                 Ok((_, session)) => {
@@ -93,13 +146,18 @@ pub async fn signup(
 
 pub async fn login(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<LoginRequest>,
 ) -> Result<Json<AuthResponse>, (StatusCode, Json<ErrorResponse>)> {
     info!("POST /auth/login - {}", req.email);
 
     match state
         .auth
-        .login(req.email.clone(), req.password.clone())
+        .login(
+            req.email.clone(),
+            req.password.clone(),
+            device_label(&headers),
+        )
         .await
     {
         Ok((user, session)) => {
@@ -110,6 +168,15 @@ pub async fn login(
                 username: user.username,
             }))
         }
+        Err(e) if matches!(e.downcast_ref(), Some(Error::TooManyAttempts)) => {
+            warn!("Login locked out for {}", req.email);
+            Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(ErrorResponse {
+                    error: "Too many failed login attempts, try again later".to_string(),
+                }),
+            ))
+        }
         Err(e) => {
             warn!("Login failed for {}: {}", req.email, e);
             Err((
@@ -122,12 +189,183 @@ pub async fn login(
     }
 }
 
+/// POST /auth/refresh
+pub async fn refresh(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Session>, (StatusCode, Json<ErrorResponse>)> {
+    info!("POST /auth/refresh");
+
+    let token = middleware::bearer_token(&headers).map_err(unauthorized)?;
+
+    match state.auth.refresh_session(token).await {
+        Ok(session) => Ok(Json(session)),
+        Err(e) => {
+            warn!("Session refresh failed: {}", e);
+            Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ))
+        }
+    }
+}
+
+/// GET /auth/sessions
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SessionInfo>>, (StatusCode, Json<ErrorResponse>)> {
+    info!("GET /auth/sessions");
+    let ctx = middleware::authenticate(&state, &headers)
+        .await
+        .map_err(unauthorized)?;
+
+    state
+        .auth
+        .list_sessions(ctx.user_id())
+        .await
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })
+}
+
+/// DELETE /auth/sessions/{token_prefix}
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(token_prefix): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    info!("DELETE /auth/sessions/{}", token_prefix);
+    let ctx = middleware::authenticate(&state, &headers)
+        .await
+        .map_err(unauthorized)?;
+
+    match state
+        .auth
+        .revoke_session(ctx.user_id(), &token_prefix)
+        .await
+    {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            warn!("Session revoke failed for {}: {}", ctx.user_id(), e);
+            Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ))
+        }
+    }
+}
+
+/// POST /auth/reset/request
+///
+/// Always accepts so callers can't probe which emails are registered. There is
+/// no mail delivery yet; set `BRAID_DEV_LOG_RESET_TOKENS` to debug-log the token
+/// during development.
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    Json(req): Json<ResetRequest>,
+) -> StatusCode {
+    info!("POST /auth/reset/request - {}", req.email);
+
+    match state.auth.create_reset_token(&req.email).await {
+        Ok(token) => {
+            info!("Password reset token issued for {}", req.email);
+            if state.config.log_reset_tokens {
+                debug!("Password reset token for {}: {}", req.email, token);
+            }
+        }
+        Err(e) => warn!("Password reset not issued for {}: {}", req.email, e),
+    }
+    StatusCode::ACCEPTED
+}
+
+/// POST /auth/reset/confirm
+pub async fn confirm_password_reset(
+    State(state): State<AppState>,
+    Json(req): Json<ResetConfirmRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    info!("POST /auth/reset/confirm");
+
+    match state
+        .auth
+        .consume_reset_token(&req.token, &req.new_password)
+        .await
+    {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            warn!("Password reset failed: {}", e);
+            Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ))
+        }
+    }
+}
+
+/// DELETE /auth/account
+pub async fn delete_account(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<DeleteAccountRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    info!("DELETE /auth/account");
+    let ctx = middleware::authenticate(&state, &headers)
+        .await
+        .map_err(unauthorized)?;
+
+    match state
+        .auth
+        .delete_account(ctx.user_id(), &req.password, &state.friends)
+        .await
+    {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            warn!("Account deletion failed for {}: {}", ctx.user_id(), e);
+            Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ))
+        }
+    }
+}
+
 /// POST /auth/logout
-pub async fn logout(State(_state): State<AppState>) -> StatusCode {
+///
+/// Invalidates the bearer token's session. A token that is already invalid
+/// or expired is accepted, so logging out twice succeeds.
+pub async fn logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
     info!("POST /auth/logout");
-    // In a real implementation, we would invalidate the session
-    // For now, just return OK
-    StatusCode::OK
+    let token = middleware::bearer_token(&headers).map_err(unauthorized)?;
+
+    match state.auth.logout(token).await {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(e) => {
+            warn!("Logout failed: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ))
+        }
+    }
 }
 
 /// GET /auth/me
@@ -140,6 +378,32 @@ pub async fn me(
     Ok(Json(user))
 }
 
+/// GET /auth/users/search?q=
+pub async fn search_users(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<UserSearchQuery>,
+) -> Result<Json<Vec<UserInfo>>, (StatusCode, Json<ErrorResponse>)> {
+    info!("GET /auth/users/search - {}", query.q);
+    let ctx = middleware::authenticate(&state, &headers)
+        .await
+        .map_err(unauthorized)?;
+
+    state
+        .auth
+        .search_users(&query.q, ctx.user_id(), query.limit)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })
+}
+
 /// GET /users
 pub async fn list_users(State(state): State<AppState>) -> Result<Json<Vec<UserInfo>>, StatusCode> {
     info!("GET /users");
@@ -186,3 +450,63 @@ pub async fn update_profile(
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::test_support::test_state;
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_logout_invalidates_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        signup(
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(SignupRequest {
+                email: "alice@example.com".to_string(),
+                username: "alice".to_string(),
+                password: "hunter22".to_string(),
+                avatar_blob_hash: None,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let Json(auth) = login(
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(LoginRequest {
+                email: "alice@example.com".to_string(),
+                password: "hunter22".to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(state.auth.validate_session(&auth.token).await.is_ok());
+
+        let status = logout(State(state.clone()), bearer(&auth.token))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert!(state.auth.validate_session(&auth.token).await.is_err());
+
+        // Already invalid, still fine
+        let status = logout(State(state.clone()), bearer(&auth.token))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+
+        let err = logout(State(state), HeaderMap::new()).await.unwrap_err();
+        assert_eq!(err.0, StatusCode::UNAUTHORIZED);
+    }
+}
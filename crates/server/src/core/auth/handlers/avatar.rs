@@ -0,0 +1,161 @@
+//! Avatar Handler
+//!
+//! Serves a user's avatar out of the blob store so clients can use
+//! `/auth/users/{id}/avatar` directly as an image URL.
+
+use crate::core::auth::AuthManager;
+use crate::core::config::AppState;
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use braid_blob::BlobStore;
+use tracing::error;
+
+/// The URL is per user, not per image, so the avatar can change under it;
+/// clients revalidate with the ETag once this expires.
+const AVATAR_CACHE_CONTROL: &str = "public, max-age=86400";
+
+/// Whether `If-None-Match` already names `etag`
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Look up `user_id`'s avatar and build the response
+///
+/// 404 when the user doesn't exist, has no avatar, or the blob is gone;
+/// 304 when the client's `If-None-Match` matches the blob hash.
+pub async fn serve_avatar(
+    auth: &AuthManager,
+    blobs: &BlobStore,
+    user_id: &str,
+    headers: &HeaderMap,
+) -> Response {
+    let Some(hash) = auth
+        .get_user(user_id)
+        .await
+        .ok()
+        .and_then(|user| user.avatar_blob_hash)
+        .filter(|hash| !hash.is_empty())
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let etag = format!("\"{}\"", hash);
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, AVATAR_CACHE_CONTROL.to_string()),
+    ];
+    if etag_matches(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+
+    match blobs.get(&hash).await {
+        Ok(Some((data, meta))) => {
+            let content_type = meta
+                .content_type
+                .and_then(|ct| ct.parse().ok())
+                .unwrap_or(HeaderValue::from_static("application/octet-stream"));
+            (cache_headers, [(header::CONTENT_TYPE, content_type)], data).into_response()
+        }
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("Failed to read avatar {} for {}: {}", hash, user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// GET /auth/users/{id}/avatar
+pub async fn get_avatar(
+    Path(user_id): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    serve_avatar(&state.auth, state.store.blob_store(), &user_id, &headers).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    async fn setup(dir: &TempDir, with_avatar: bool) -> (AuthManager, BlobStore, String, String) {
+        let auth = AuthManager::new(dir.path())
+            .await
+            .unwrap()
+            .with_bcrypt_cost(4);
+        let blobs = BlobStore::new(dir.path().join("blobs"), dir.path().join("meta.sqlite"))
+            .await
+            .unwrap();
+        let hash = blobs
+            .put_with_type(
+                bytes::Bytes::from_static(PNG),
+                Some("image/png".to_string()),
+            )
+            .await
+            .unwrap();
+        let user = auth
+            .signup(
+                "a@example.com".to_string(),
+                "alice".to_string(),
+                "password123".to_string(),
+                with_avatar.then(|| hash.clone()),
+            )
+            .await
+            .unwrap();
+        (auth, blobs, user.id, hash)
+    }
+
+    #[tokio::test]
+    async fn test_avatar_served_with_content_type() {
+        let dir = TempDir::new().unwrap();
+        let (auth, blobs, user_id, hash) = setup(&dir, true).await;
+
+        let response = serve_avatar(&auth, &blobs, &user_id, &HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        assert_eq!(response.headers()[header::ETAG], format!("\"{}\"", hash));
+        assert!(response.headers().contains_key(header::CACHE_CONTROL));
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], PNG);
+    }
+
+    #[tokio::test]
+    async fn test_unset_avatar_is_not_found() {
+        let dir = TempDir::new().unwrap();
+        let (auth, blobs, user_id, _) = setup(&dir, false).await;
+
+        let response = serve_avatar(&auth, &blobs, &user_id, &HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = serve_avatar(&auth, &blobs, "no-such-user", &HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_if_none_match_is_not_modified() {
+        let dir = TempDir::new().unwrap();
+        let (auth, blobs, user_id, hash) = setup(&dir, true).await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            format!("\"other\", W/\"{}\"", hash).parse().unwrap(),
+        );
+        let response = serve_avatar(&auth, &blobs, &user_id, &headers).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], format!("\"{}\"", hash));
+    }
+}
@@ -2,6 +2,11 @@
 
 pub mod auth;
 pub mod auth_me;
+pub mod avatar;
 
-pub use auth::{signup, login, logout, list_users, update_profile};
+pub use auth::{
+    signup, login, logout, refresh, list_sessions, revoke_session, request_password_reset,
+    confirm_password_reset, delete_account, search_users, list_users, update_profile,
+};
 pub use auth_me::me;
+pub use avatar::get_avatar;
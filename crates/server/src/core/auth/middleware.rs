@@ -3,7 +3,7 @@ use crate::core::ctx::Ctx;
 use crate::core::error::{Error, Result};
 use axum::{
     extract::{Request, State},
-    http::header,
+    http::{header, HeaderMap},
     middleware::Next,
     response::Response,
 };
@@ -16,18 +16,31 @@ pub async fn mw_require_auth(
 ) -> Result<Response> {
     debug!("MIDDLEWARE: require_auth");
 
-    let auth_header = req.headers().get(header::AUTHORIZATION);
+    let ctx = authenticate(&state, req.headers()).await?;
+
+    // Store Ctx in request extensions
+    req.extensions_mut().insert(ctx);
+
+    Ok(next.run(req).await)
+}
+
+/// Bearer token from the Authorization header
+pub fn bearer_token(headers: &HeaderMap) -> Result<&str> {
+    let auth_header = headers.get(header::AUTHORIZATION);
     let auth_header = match auth_header {
         Some(h) => h.to_str().map_err(|_| Error::AuthFailTokenWrongFormat)?,
         None => return Err(Error::AuthFailNoToken),
     };
 
     // Format: "Bearer <token>"
-    if !auth_header.starts_with("Bearer ") {
-        return Err(Error::AuthFailTokenWrongFormat);
-    }
+    auth_header
+        .strip_prefix("Bearer ")
+        .ok_or(Error::AuthFailTokenWrongFormat)
+}
 
-    let token = &auth_header[7..];
+/// Validate the request's bearer token, for routes outside the auth layer
+pub async fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<Ctx> {
+    let token = bearer_token(headers)?;
 
     // Validate token
     let user_info = state
@@ -36,11 +49,7 @@ pub async fn mw_require_auth(
         .await
         .map_err(|_| Error::LoginFail)?;
 
-    // Create Ctx
-    let ctx = Ctx::new(user_info.id);
-
-    // Store Ctx in request extensions
-    req.extensions_mut().insert(ctx);
+    state.presence.heartbeat(&user_info.id);
 
-    Ok(next.run(req).await)
+    Ok(Ctx::new(user_info.id))
 }
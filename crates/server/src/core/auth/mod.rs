@@ -6,11 +6,15 @@
 pub mod handlers;
 pub mod middleware;
 
+use crate::chat::friends::FriendManager;
 use anyhow::{Context, Result};
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sqlx::SqlitePool;
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{info, warn};
 use uuid::Uuid;
@@ -58,44 +62,127 @@ pub struct Session {
     pub user_id: String,
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
+    /// Client-supplied device or user-agent label captured at login
+    #[serde(default)]
+    pub device_label: Option<String>,
+}
+
+/// Session summary shown to its owner (never exposes the full token)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub token_prefix: String,
+    pub device_label: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Shortest query accepted by `search_users`
+pub const MIN_USER_SEARCH_LEN: usize = 2;
+/// Upper bound on `search_users` results
+const MAX_USER_SEARCH_RESULTS: usize = 50;
+
+/// Number of leading token characters used to identify a session
+pub const TOKEN_PREFIX_LEN: usize = 8;
+
+/// How long a session stays valid after it is created or refreshed
+const SESSION_LIFETIME_DAYS: i64 = 30;
+
+fn session_lifetime() -> chrono::Duration {
+    chrono::Duration::days(SESSION_LIFETIME_DAYS)
+}
+
+/// Failed logins allowed within `LOGIN_FAILURE_WINDOW` before locking out
+const MAX_LOGIN_FAILURES: usize = 5;
+const LOGIN_FAILURE_WINDOW: Duration = Duration::from_secs(5 * 60);
+const LOGIN_LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+/// How long a password reset token stays usable
+const RESET_TOKEN_LIFETIME_MINUTES: i64 = 60;
+
+/// Default bcrypt cost for new password hashes (kept low in tests so they run quickly)
+const PASSWORD_HASH_COST: u32 = if cfg!(test) { 4 } else { DEFAULT_COST };
+
+/// Recent failed logins for one email
+#[derive(Debug, Default)]
+struct AttemptState {
+    failures: VecDeque<Instant>,
+    locked_until: Option<Instant>,
+}
+
+impl AttemptState {
+    fn is_locked(&self, now: Instant) -> bool {
+        self.locked_until.is_some_and(|until| until > now)
+    }
+
+    /// Whether this entry carries no information and can be dropped
+    fn is_stale(&self, now: Instant) -> bool {
+        !self.is_locked(now)
+            && self
+                .failures
+                .back()
+                .is_none_or(|last| now.duration_since(*last) > LOGIN_FAILURE_WINDOW)
+    }
 }
 
 /// Auth manager handles all authentication
 pub struct AuthManager {
-    db_path: std::path::PathBuf,
+    /// Pool on `users.sqlite`, shared with `FriendManager`
+    pool: SqlitePool,
     /// In-memory session cache
     sessions: RwLock<HashMap<String, Session>>,
+    /// Extend sessions on validation once half their lifetime has elapsed
+    sliding_expiry: bool,
+    /// Failed login tracking, keyed on lowercased email
+    login_attempts: RwLock<HashMap<String, AttemptState>>,
+    /// bcrypt work factor for new hashes; weaker stored hashes are upgraded on login
+    bcrypt_cost: u32,
 }
 
 impl AuthManager {
     /// Create new auth manager
     pub async fn new(base_dir: &std::path::Path) -> Result<Self> {
-        let db_path = base_dir.join("users.sqlite");
+        let db_path = crate::core::db::users_db_path(base_dir);
+        let manager = Self::from_pool(crate::core::db::open_pool(&db_path).await?).await?;
+        info!("[Auth] Initialized at {:?}", db_path);
+        Ok(manager)
+    }
 
+    /// Create an auth manager on an already-open pool
+    pub async fn from_pool(pool: SqlitePool) -> Result<Self> {
         let manager = Self {
-            db_path,
+            pool,
             sessions: RwLock::new(HashMap::new()),
+            sliding_expiry: true,
+            login_attempts: RwLock::new(HashMap::new()),
+            bcrypt_cost: PASSWORD_HASH_COST,
         };
 
         // Initialize database
         manager.init_db().await?;
 
-        info!("[Auth] Initialized at {:?}", manager.db_path);
-
         Ok(manager)
     }
 
+    /// The database pool, for sharing with other managers
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Enable or disable sliding session expiry (enabled by default)
+    pub fn with_sliding_expiry(mut self, enabled: bool) -> Self {
+        self.sliding_expiry = enabled;
+        self
+    }
+
+    /// Set the bcrypt cost used for new password hashes
+    pub fn with_bcrypt_cost(mut self, cost: u32) -> Self {
+        self.bcrypt_cost = cost.clamp(4, 31);
+        self
+    }
+
     /// Initialize SQLite database
     async fn init_db(&self) -> Result<()> {
-        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-        use std::str::FromStr;
-
-        let options = SqliteConnectOptions::from_str(&format!(
-            "sqlite://{}",
-            self.db_path.to_string_lossy().replace('\\', "/")
-        ))?
-        .create_if_missing(true);
-        let pool = SqlitePoolOptions::new().connect_with(options).await?;
+        let pool = &self.pool;
 
         // Create users table
         sqlx::query(
@@ -112,12 +199,12 @@ impl AuthManager {
             )
             "#,
         )
-        .execute(&pool)
+        .execute(pool)
         .await?;
 
         // Migration: Add avatar_blob_hash if it doesn't exist
         let _ = sqlx::query("ALTER TABLE users ADD COLUMN avatar_blob_hash TEXT")
-            .execute(&pool)
+            .execute(pool)
             .await;
 
         // Create sessions table
@@ -132,24 +219,37 @@ impl AuthManager {
             )
             "#,
         )
-        .execute(&pool)
+        .execute(pool)
+        .await?;
+
+        // Migration: Add device_label if it doesn't exist
+        let _ = sqlx::query("ALTER TABLE sessions ADD COLUMN device_label TEXT")
+            .execute(pool)
+            .await;
+
+        // Create password reset tokens table
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS reset_tokens (
+                token TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                FOREIGN KEY (user_id) REFERENCES users(id)
+            )
+            "#,
+        )
+        .execute(pool)
         .await?;
 
-        pool.close().await;
         Ok(())
     }
 
-    /// Get database connection
-    async fn get_pool(&self) -> Result<sqlx::SqlitePool> {
-        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-        use std::str::FromStr;
-
-        let options = SqliteConnectOptions::from_str(&format!(
-            "sqlite://{}",
-            self.db_path.to_string_lossy().replace('\\', "/")
-        ))?
-        .create_if_missing(true);
-        Ok(SqlitePoolOptions::new().connect_with(options).await?)
+    /// Check that the user database can be opened and queried
+    pub async fn ping(&self) -> Result<()> {
+        let pool = &self.pool;
+        let result = sqlx::query("SELECT 1").execute(pool).await;
+        result?;
+        Ok(())
     }
 
     /// Register a new user
@@ -160,12 +260,12 @@ impl AuthManager {
         password: String,
         avatar_blob_hash: Option<String>,
     ) -> Result<User> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
 
         // Check if email already exists
         let existing: Option<(String,)> = sqlx::query_as("SELECT id FROM users WHERE email = ?")
             .bind(&email)
-            .fetch_optional(&pool)
+            .fetch_optional(pool)
             .await?;
 
         if existing.is_some() {
@@ -173,7 +273,7 @@ impl AuthManager {
         }
 
         // Hash password
-        let password_hash = hash(&password, DEFAULT_COST).context("Failed to hash password")?;
+        let password_hash = hash(&password, self.bcrypt_cost).context("Failed to hash password")?;
 
         // Create user
         let user = User {
@@ -198,48 +298,124 @@ impl AuthManager {
         .bind(&user.avatar_blob_hash)
         .bind(user.created_at.to_rfc3339())
         .bind(user.is_active)
-        .execute(&pool)
+        .execute(pool)
         .await?;
 
-        pool.close().await;
-
         info!("[Auth] User registered: {} ({})", username, email);
 
         Ok(user)
     }
 
+    /// Record a failed login, locking the email out once the limit is hit
+    async fn record_login_failure(&self, email: &str) {
+        let now = Instant::now();
+        let mut attempts = self.login_attempts.write().await;
+        attempts.retain(|_, state| !state.is_stale(now));
+
+        let state = attempts.entry(email.to_lowercase()).or_default();
+        while state
+            .failures
+            .front()
+            .is_some_and(|first| now.duration_since(*first) > LOGIN_FAILURE_WINDOW)
+        {
+            state.failures.pop_front();
+        }
+        state.failures.push_back(now);
+
+        if state.failures.len() >= MAX_LOGIN_FAILURES {
+            warn!("[Auth] Locking out {} after repeated failed logins", email);
+            state.failures.clear();
+            state.locked_until = Some(now + LOGIN_LOCKOUT);
+        }
+    }
+
     /// Login user and create session
-    pub async fn login(&self, email: String, password: String) -> Result<(User, Session)> {
-        let pool = self.get_pool().await?;
+    ///
+    /// Repeated failures for an email lock it out with `Error::TooManyAttempts`,
+    /// whether or not an account exists for it.
+    pub async fn login(
+        &self,
+        email: String,
+        password: String,
+        device_label: Option<String>,
+    ) -> Result<(User, Session)> {
+        if self
+            .login_attempts
+            .read()
+            .await
+            .get(&email.to_lowercase())
+            .is_some_and(|state| state.is_locked(Instant::now()))
+        {
+            return Err(crate::core::error::Error::TooManyAttempts.into());
+        }
+
+        let pool = &self.pool;
 
         // Find user by email
         let row: Option<(String, String, String, String, Option<String>, String)> = sqlx::query_as(
             "SELECT id, email, username, password_hash, avatar_blob_hash, created_at FROM users WHERE email = ? AND is_active = 1"
         )
         .bind(&email)
-        .fetch_optional(&pool)
+        .fetch_optional(pool)
         .await?;
 
-        let (user_id, email, username, password_hash, avatar_blob_hash, created_at) =
-            row.ok_or_else(|| anyhow::anyhow!("Invalid email or password"))?;
+        let Some((user_id, email, username, password_hash, avatar_blob_hash, created_at)) = row
+        else {
+            self.record_login_failure(&email).await;
+            return Err(anyhow::anyhow!("Invalid email or password"));
+        };
 
         // Verify password
         let valid = verify(&password, &password_hash).context("Failed to verify password")?;
 
         if !valid {
             warn!("[Auth] Failed login attempt for {}", email);
+            self.record_login_failure(&email).await;
             return Err(anyhow::anyhow!("Invalid email or password"));
         }
 
+        self.login_attempts
+            .write()
+            .await
+            .remove(&email.to_lowercase());
+
+        // Upgrade hashes made with a lower work factor while we have the password
+        let stored_cost = password_hash
+            .parse::<bcrypt::HashParts>()
+            .map(|parts| parts.get_cost())
+            .unwrap_or(self.bcrypt_cost);
+        if stored_cost < self.bcrypt_cost {
+            match hash(&password, self.bcrypt_cost) {
+                Ok(new_hash) => {
+                    // The password was right, so a failed upgrade doesn't fail the login
+                    let saved = sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+                        .bind(new_hash)
+                        .bind(&user_id)
+                        .execute(pool)
+                        .await;
+                    match saved {
+                        Ok(_) => info!(
+                            "[Auth] Rehashed password for {} (cost {} -> {})",
+                            email, stored_cost, self.bcrypt_cost
+                        ),
+                        Err(e) => {
+                            warn!("[Auth] Failed to save rehashed password for {}: {}", email, e)
+                        }
+                    }
+                }
+                Err(e) => warn!("[Auth] Failed to rehash password for {}: {}", email, e),
+            }
+        }
+
         // Update last login
         sqlx::query("UPDATE users SET last_login = ? WHERE id = ?")
             .bind(Utc::now().to_rfc3339())
             .bind(&user_id)
-            .execute(&pool)
+            .execute(pool)
             .await?;
 
         // Create session
-        let session = self.create_session(&pool, &user_id).await?;
+        let session = self.create_session(pool, &user_id, device_label).await?;
 
         let user = User {
             id: user_id,
@@ -252,29 +428,34 @@ impl AuthManager {
             is_active: true,
         };
 
-        pool.close().await;
-
         info!("[Auth] User logged in: {}", user.username);
 
         Ok((user, session))
     }
 
     /// Create new session
-    async fn create_session(&self, pool: &sqlx::SqlitePool, user_id: &str) -> Result<Session> {
+    async fn create_session(
+        &self,
+        pool: &sqlx::SqlitePool,
+        user_id: &str,
+        device_label: Option<String>,
+    ) -> Result<Session> {
         let session = Session {
             token: Uuid::new_v4().to_string(),
             user_id: user_id.to_string(),
             created_at: Utc::now(),
-            expires_at: Utc::now() + chrono::Duration::days(30),
+            expires_at: Utc::now() + session_lifetime(),
+            device_label,
         };
 
         sqlx::query(
-            "INSERT INTO sessions (token, user_id, created_at, expires_at) VALUES (?, ?, ?, ?)",
+            "INSERT INTO sessions (token, user_id, created_at, expires_at, device_label) VALUES (?, ?, ?, ?, ?)",
         )
         .bind(&session.token)
         .bind(&session.user_id)
         .bind(session.created_at.to_rfc3339())
         .bind(session.expires_at.to_rfc3339())
+        .bind(&session.device_label)
         .execute(pool)
         .await?;
 
@@ -287,69 +468,88 @@ impl AuthManager {
         Ok(session)
     }
 
-    /// Validate session token
-    pub async fn validate_session(&self, token: &str) -> Result<UserInfo> {
-        // Check cache first
-        {
-            let sessions = self.sessions.read().await;
-            if let Some(session) = sessions.get(token) {
-                if session.expires_at > Utc::now() {
-                    // Get user info
-                    let pool = self.get_pool().await?;
-                    let row: Option<(String, String, String, Option<String>, String)> = sqlx::query_as(
-                        "SELECT id, email, username, avatar_blob_hash, created_at FROM users WHERE id = ?"
-                    )
-                    .bind(&session.user_id)
-                    .fetch_optional(&pool)
-                    .await?;
-                    pool.close().await;
-
-                    if let Some((id, email, username, avatar_blob_hash, created_at)) = row {
-                        return Ok(UserInfo {
-                            id,
-                            email,
-                            username,
-                            avatar_blob_hash,
-                            created_at: created_at.parse().unwrap_or_else(|_| Utc::now()),
-                        });
-                    }
-                }
-            }
+    /// Look up a session by token, checking the cache before the database
+    async fn find_session(&self, token: &str) -> Result<Option<Session>> {
+        if let Some(session) = self.sessions.read().await.get(token) {
+            return Ok(Some(session.clone()));
         }
 
-        // Check database
-        let pool = self.get_pool().await?;
-
-        let row: Option<(String, String, String, Option<String>, String, String)> = sqlx::query_as(
-            r#"
-            SELECT u.id, u.email, u.username, u.avatar_blob_hash, u.created_at, s.expires_at 
-            FROM users u 
-            JOIN sessions s ON u.id = s.user_id 
-            WHERE s.token = ?
-            "#,
+        let pool = &self.pool;
+        let row: Option<(String, String, String, String, Option<String>)> = sqlx::query_as(
+            "SELECT token, user_id, created_at, expires_at, device_label FROM sessions WHERE token = ?",
         )
         .bind(token)
-        .fetch_optional(&pool)
+        .fetch_optional(pool)
         .await?;
 
-        pool.close().await;
-
-        if let Some((id, email, username, avatar_blob_hash, created_at, expires_at)) = row {
-            let expires: DateTime<Utc> = expires_at
+        let Some((token, user_id, created_at, expires_at, device_label)) = row else {
+            return Ok(None);
+        };
+        let session = Session {
+            token,
+            user_id,
+            created_at: created_at.parse().unwrap_or_else(|_| Utc::now()),
+            expires_at: expires_at
                 .parse()
-                .map_err(|_| anyhow::anyhow!("Invalid date"))?;
-            if expires > Utc::now() {
-                return Ok(UserInfo {
-                    id,
-                    email,
-                    username,
-                    avatar_blob_hash,
-                    created_at: created_at.parse().unwrap_or_else(|_| Utc::now()),
-                });
-            }
+                .map_err(|_| anyhow::anyhow!("Invalid date"))?,
+            device_label,
+        };
+
+        self.sessions
+            .write()
+            .await
+            .insert(session.token.clone(), session.clone());
+
+        Ok(Some(session))
+    }
+
+    /// Push a session's expiry a full lifetime from now
+    async fn extend_session(&self, mut session: Session) -> Result<Session> {
+        session.expires_at = Utc::now() + session_lifetime();
+
+        let pool = &self.pool;
+        sqlx::query("UPDATE sessions SET expires_at = ? WHERE token = ?")
+            .bind(session.expires_at.to_rfc3339())
+            .bind(&session.token)
+            .execute(pool)
+            .await?;
+
+        self.sessions
+            .write()
+            .await
+            .insert(session.token.clone(), session.clone());
+
+        Ok(session)
+    }
+
+    /// Refresh a valid session, extending its expiry
+    pub async fn refresh_session(&self, token: &str) -> Result<Session> {
+        match self.find_session(token).await? {
+            Some(session) if session.expires_at > Utc::now() => self.extend_session(session).await,
+            _ => Err(anyhow::anyhow!("Invalid or expired session")),
         }
+    }
 
-        Err(anyhow::anyhow!("Invalid or expired session"))
+    /// Validate session token
+    ///
+    /// With sliding expiry enabled, a session past half its lifetime is
+    /// extended so active users stay logged in.
+    pub async fn validate_session(&self, token: &str) -> Result<UserInfo> {
+        let session = match self.find_session(token).await? {
+            Some(session) if session.expires_at > Utc::now() => session,
+            _ => return Err(anyhow::anyhow!("Invalid or expired session")),
+        };
+
+        let user = self
+            .get_user(&session.user_id)
+            .await
+            .map_err(|_| anyhow::anyhow!("Invalid or expired session"))?;
+
+        if self.sliding_expiry && session.expires_at - Utc::now() < session_lifetime() / 2 {
+            self.extend_session(session).await?;
+        }
+
+        Ok(user)
     }
 
     /// Logout user (invalidate session)
@@ -358,30 +558,249 @@ impl AuthManager {
         self.sessions.write().await.remove(token);
 
         // Remove from database
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
         sqlx::query("DELETE FROM sessions WHERE token = ?")
             .bind(token)
-            .execute(&pool)
+            .execute(pool)
             .await?;
-        pool.close().await;
 
         info!("[Auth] Session invalidated");
 
         Ok(())
     }
 
+    /// Delete an account after re-checking its password
+    ///
+    /// The user row is kept but deactivated; sessions are cleared and the
+    /// user's contacts and pending friend requests are removed.
+    pub async fn delete_account(
+        &self,
+        user_id: &str,
+        password: &str,
+        friends: &FriendManager,
+    ) -> Result<()> {
+        let pool = &self.pool;
+
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT password_hash FROM users WHERE id = ? AND is_active = 1")
+                .bind(user_id)
+                .fetch_optional(pool)
+                .await?;
+        let Some((password_hash,)) = row else {
+            return Err(anyhow::anyhow!("User not found"));
+        };
+
+        if !verify(password, &password_hash).context("Failed to verify password")? {
+            return Err(anyhow::anyhow!("Invalid password"));
+        }
+
+        sqlx::query("UPDATE users SET is_active = 0 WHERE id = ?")
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        sqlx::query("DELETE FROM sessions WHERE user_id = ?")
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        sqlx::query("DELETE FROM reset_tokens WHERE user_id = ?")
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        self.sessions
+            .write()
+            .await
+            .retain(|_, session| session.user_id != user_id);
+
+        friends.remove_user(user_id).await?;
+
+        info!("[Auth] Account deleted: {}", user_id);
+
+        Ok(())
+    }
+
+    /// Create a single-use password reset token for an active user
+    pub async fn create_reset_token(&self, email: &str) -> Result<String> {
+        let pool = &self.pool;
+
+        let user: Option<(String,)> =
+            sqlx::query_as("SELECT id FROM users WHERE email = ? AND is_active = 1")
+                .bind(email)
+                .fetch_optional(pool)
+                .await?;
+        let Some((user_id,)) = user else {
+            return Err(anyhow::anyhow!("User not found"));
+        };
+
+        // Drop stale tokens while we're here
+        sqlx::query("DELETE FROM reset_tokens WHERE expires_at < ?")
+            .bind(Utc::now().to_rfc3339())
+            .execute(pool)
+            .await?;
+
+        let token = Uuid::new_v4().to_string();
+        let expires_at = Utc::now() + chrono::Duration::minutes(RESET_TOKEN_LIFETIME_MINUTES);
+        sqlx::query("INSERT INTO reset_tokens (token, user_id, expires_at) VALUES (?, ?, ?)")
+            .bind(&token)
+            .bind(&user_id)
+            .bind(expires_at.to_rfc3339())
+            .execute(pool)
+            .await?;
+
+        info!("[Auth] Password reset requested for {}", email);
+
+        Ok(token)
+    }
+
+    /// Reset a password with a token from `create_reset_token`
+    ///
+    /// The token is consumed even if it turns out to be expired, and every
+    /// existing session for the user is invalidated.
+    pub async fn consume_reset_token(&self, token: &str, new_password: &str) -> Result<()> {
+        let pool = &self.pool;
+
+        // Delete and read in one statement so a token can't be used twice
+        let row: Option<(String, String)> = sqlx::query_as(
+            "DELETE FROM reset_tokens WHERE token = ? RETURNING user_id, expires_at",
+        )
+        .bind(token)
+        .fetch_optional(pool)
+        .await?;
+
+        let valid_user = row.and_then(|(user_id, expires_at)| {
+            let expires_at: DateTime<Utc> = expires_at.parse().ok()?;
+            (expires_at > Utc::now()).then_some(user_id)
+        });
+        let Some(user_id) = valid_user else {
+            return Err(anyhow::anyhow!("Invalid or expired reset token"));
+        };
+
+        let password_hash =
+            hash(new_password, self.bcrypt_cost).context("Failed to hash password")?;
+        sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+            .bind(password_hash)
+            .bind(&user_id)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("DELETE FROM sessions WHERE user_id = ?")
+            .bind(&user_id)
+            .execute(pool)
+            .await?;
+
+        self.sessions
+            .write()
+            .await
+            .retain(|_, session| session.user_id != user_id);
+
+        info!("[Auth] Password reset for user {}", user_id);
+
+        Ok(())
+    }
+
+    /// List a user's unexpired sessions, newest first
+    pub async fn list_sessions(&self, user_id: &str) -> Result<Vec<SessionInfo>> {
+        let pool = &self.pool;
+        let rows: Vec<(String, String, String, Option<String>)> = sqlx::query_as(
+            "SELECT token, created_at, expires_at, device_label FROM sessions WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        let now = Utc::now();
+        let mut sessions: Vec<SessionInfo> = rows
+            .into_iter()
+            .filter_map(|(token, created_at, expires_at, device_label)| {
+                let expires_at: DateTime<Utc> = expires_at.parse().ok()?;
+                (expires_at > now).then(|| SessionInfo {
+                    token_prefix: token.chars().take(TOKEN_PREFIX_LEN).collect(),
+                    device_label,
+                    created_at: created_at.parse().unwrap_or_else(|_| Utc::now()),
+                    expires_at,
+                })
+            })
+            .collect();
+        sessions.sort_by_key(|s| Reverse(s.created_at));
+
+        Ok(sessions)
+    }
+
+    /// Revoke one of a user's sessions, identified by its token prefix
+    ///
+    /// Fails if the prefix matches no session owned by `user_id`, or more than one.
+    pub async fn revoke_session(&self, user_id: &str, token_prefix: &str) -> Result<()> {
+        if token_prefix.len() < TOKEN_PREFIX_LEN {
+            return Err(anyhow::anyhow!("Session prefix too short"));
+        }
+
+        let pool = &self.pool;
+        let tokens: Vec<(String,)> = sqlx::query_as(
+            "SELECT token FROM sessions WHERE user_id = ? AND substr(token, 1, ?) = ?",
+        )
+        .bind(user_id)
+        .bind(token_prefix.len() as i64)
+        .bind(token_prefix)
+        .fetch_all(pool)
+        .await?;
+
+        let token = match tokens.as_slice() {
+            [(token,)] => token.clone(),
+            [] => {
+                return Err(anyhow::anyhow!("Session not found"));
+            }
+            _ => {
+                return Err(anyhow::anyhow!("Session prefix is ambiguous"));
+            }
+        };
+
+        sqlx::query("DELETE FROM sessions WHERE token = ?")
+            .bind(&token)
+            .execute(pool)
+            .await?;
+
+        self.sessions.write().await.remove(&token);
+
+        info!("[Auth] Session revoked for user {}", user_id);
+
+        Ok(())
+    }
+
     /// Get user by ID
     pub async fn get_user(&self, user_id: &str) -> Result<UserInfo> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
 
         let row: Option<(String, String, String, Option<String>, String)> = sqlx::query_as(
             "SELECT id, email, username, avatar_blob_hash, created_at FROM users WHERE id = ?",
         )
         .bind(user_id)
-        .fetch_optional(&pool)
+        .fetch_optional(pool)
         .await?;
 
-        pool.close().await;
+        if let Some((id, email, username, avatar_blob_hash, created_at)) = row {
+            Ok(UserInfo {
+                id,
+                email,
+                username,
+                avatar_blob_hash,
+                created_at: created_at.parse().unwrap_or_else(|_| Utc::now()),
+            })
+        } else {
+            Err(anyhow::anyhow!("User not found"))
+        }
+    }
+
+    /// Get an active user by email
+    pub async fn get_user_by_email(&self, email: &str) -> Result<UserInfo> {
+        let pool = &self.pool;
+
+        let row: Option<(String, String, String, Option<String>, String)> = sqlx::query_as(
+            "SELECT id, email, username, avatar_blob_hash, created_at FROM users \
+             WHERE email = ? AND is_active = 1",
+        )
+        .bind(email)
+        .fetch_optional(pool)
+        .await?;
 
         if let Some((id, email, username, avatar_blob_hash, created_at)) = row {
             Ok(UserInfo {
@@ -396,18 +815,77 @@ impl AuthManager {
         }
     }
 
+    /// Search active users by username or email prefix (case-insensitive)
+    ///
+    /// The caller and their existing contacts are left out of the results.
+    pub async fn search_users(
+        &self,
+        query: &str,
+        exclude_user_id: &str,
+        limit: usize,
+    ) -> Result<Vec<UserInfo>> {
+        let query = query.trim().to_lowercase();
+        if query.chars().count() < MIN_USER_SEARCH_LEN {
+            return Err(anyhow::anyhow!(
+                "Search query must be at least {} characters",
+                MIN_USER_SEARCH_LEN
+            ));
+        }
+
+        // Escape LIKE wildcards so they match literally
+        let pattern = format!(
+            "{}%",
+            query
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let limit = limit.min(MAX_USER_SEARCH_RESULTS) as i64;
+
+        let pool = &self.pool;
+        let rows: Vec<(String, String, String, Option<String>, String)> = sqlx::query_as(
+            r#"
+            SELECT id, email, username, avatar_blob_hash, created_at FROM users
+            WHERE is_active = 1
+              AND id != ?
+              AND (LOWER(username) LIKE ? ESCAPE '\' OR LOWER(email) LIKE ? ESCAPE '\')
+              AND id NOT IN (SELECT contact_user_id FROM contacts WHERE user_id = ?)
+            ORDER BY username
+            LIMIT ?
+            "#,
+        )
+        .bind(exclude_user_id)
+        .bind(&pattern)
+        .bind(&pattern)
+        .bind(exclude_user_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, email, username, avatar_blob_hash, created_at)| UserInfo {
+                    id,
+                    email,
+                    username,
+                    avatar_blob_hash,
+                    created_at: created_at.parse().unwrap_or_else(|_| Utc::now()),
+                },
+            )
+            .collect())
+    }
+
     /// List all users (for contact discovery)
     pub async fn list_users(&self) -> Result<Vec<UserInfo>> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
 
         let rows: Vec<(String, String, String, Option<String>, String)> = sqlx::query_as(
             "SELECT id, email, username, avatar_blob_hash, created_at FROM users WHERE is_active = 1"
         )
-        .fetch_all(&pool)
+        .fetch_all(pool)
         .await?;
 
-        pool.close().await;
-
         Ok(rows
             .into_iter()
             .map(
@@ -431,13 +909,13 @@ impl AuthManager {
         password: Option<String>,
         avatar_blob_hash: Option<String>,
     ) -> Result<UserInfo> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
 
         if let Some(username) = username {
             sqlx::query("UPDATE users SET username = ? WHERE id = ?")
                 .bind(username)
                 .bind(user_id)
-                .execute(&pool)
+                .execute(pool)
                 .await?;
         }
 
@@ -447,7 +925,7 @@ impl AuthManager {
                 sqlx::query_as("SELECT id FROM users WHERE email = ? AND id != ?")
                     .bind(&email)
                     .bind(user_id)
-                    .fetch_optional(&pool)
+                    .fetch_optional(pool)
                     .await?;
 
             if existing.is_some() {
@@ -457,17 +935,18 @@ impl AuthManager {
             sqlx::query("UPDATE users SET email = ? WHERE id = ?")
                 .bind(email)
                 .bind(user_id)
-                .execute(&pool)
+                .execute(pool)
                 .await?;
         }
 
         if let Some(password) = password {
-            let password_hash = hash(&password, DEFAULT_COST).context("Failed to hash password")?;
+            let password_hash =
+                hash(&password, self.bcrypt_cost).context("Failed to hash password")?;
 
             sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
                 .bind(password_hash)
                 .bind(user_id)
-                .execute(&pool)
+                .execute(pool)
                 .await?;
         }
 
@@ -475,24 +954,588 @@ impl AuthManager {
             sqlx::query("UPDATE users SET avatar_blob_hash = ? WHERE id = ?")
                 .bind(avatar)
                 .bind(user_id)
-                .execute(&pool)
+                .execute(pool)
                 .await?;
         }
 
         let user = self.get_user(user_id).await?;
-        pool.close().await;
         Ok(user)
     }
 
     /// Set user avatar
     pub async fn set_avatar(&self, user_id: &str, avatar_hash: String) -> Result<()> {
-        let pool = self.get_pool().await?;
+        let pool = &self.pool;
         sqlx::query("UPDATE users SET avatar_blob_hash = ? WHERE id = ?")
             .bind(avatar_hash)
             .bind(user_id)
-            .execute(&pool)
+            .execute(pool)
             .await?;
-        pool.close().await;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Insert a user and a session expiring `expires_in` from now, bypassing bcrypt
+    async fn seed_session(auth: &AuthManager, expires_in: chrono::Duration) -> String {
+        let pool = auth.pool();
+        sqlx::query(
+            "INSERT INTO users (id, email, username, password_hash, created_at) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind("user-1")
+        .bind("alice@example.com")
+        .bind("alice")
+        .bind("x")
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO sessions (token, user_id, created_at, expires_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind("token-1")
+        .bind("user-1")
+        .bind(Utc::now().to_rfc3339())
+        .bind((Utc::now() + expires_in).to_rfc3339())
+        .execute(pool)
+        .await
+        .unwrap();
+        "token-1".to_string()
+    }
+
+    /// Insert a user with a cheaply hashed password
+    async fn seed_user(auth: &AuthManager, email: &str, password: &str) {
+        let pool = auth.pool();
+        sqlx::query(
+            "INSERT INTO users (id, email, username, password_hash, created_at) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(email)
+        .bind(email.split('@').next().unwrap())
+        .bind(hash(password, 4).unwrap())
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn stored_expiry(auth: &AuthManager, token: &str) -> DateTime<Utc> {
+        let pool = auth.pool();
+        let (expires_at,): (String,) =
+            sqlx::query_as("SELECT expires_at FROM sessions WHERE token = ?")
+                .bind(token)
+                .fetch_one(pool)
+                .await
+                .unwrap();
+        expires_at.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_validate_session_reuses_pool() {
+        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+        use std::str::FromStr;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let dir = TempDir::new().unwrap();
+        let db_path = crate::core::db::users_db_path(dir.path());
+        let options = SqliteConnectOptions::from_str(&braid_common::sqlite_url(&db_path))
+            .unwrap()
+            .create_if_missing(true);
+        let connects = Arc::new(AtomicUsize::new(0));
+        let counter = connects.clone();
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .after_connect(move |_, _| {
+                let counter = counter.clone();
+                Box::pin(async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                })
+            })
+            .connect_with(options)
+            .await
+            .unwrap();
+        let auth = AuthManager::from_pool(pool).await.unwrap();
+        let token = seed_session(&auth, chrono::Duration::days(30)).await;
+
+        for _ in 0..100 {
+            auth.validate_session(&token).await.unwrap();
+            // A second between requests. The clock only stays paused for the
+            // advance: paused time would skip ahead to the pool's acquire
+            // timeout while a query waits on SQLite's worker thread.
+            tokio::time::pause();
+            tokio::time::advance(Duration::from_secs(1)).await;
+            tokio::time::resume();
+        }
+
+        // Every lookup went over the connection opened at startup
+        assert_eq!(connects.load(Ordering::SeqCst), 1);
+        assert_eq!(auth.pool().size(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_validate_slides_near_expiry_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        let token = seed_session(&auth, chrono::Duration::days(1)).await;
+        let before = stored_expiry(&auth, &token).await;
+
+        let user = auth.validate_session(&token).await.unwrap();
+        assert_eq!(user.id, "user-1");
+
+        let after = stored_expiry(&auth, &token).await;
+        assert!(after > before + chrono::Duration::days(28));
+        assert_eq!(auth.sessions.read().await[&token].expires_at, after);
+    }
+
+    #[tokio::test]
+    async fn test_validate_without_sliding_keeps_expiry() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path())
+            .await
+            .unwrap()
+            .with_sliding_expiry(false);
+        let token = seed_session(&auth, chrono::Duration::days(1)).await;
+        let before = stored_expiry(&auth, &token).await;
+
+        auth.validate_session(&token).await.unwrap();
+        assert_eq!(stored_expiry(&auth, &token).await, before);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rejects_expired_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        let token = seed_session(&auth, chrono::Duration::days(-1)).await;
+
+        assert!(auth.refresh_session(&token).await.is_err());
+        assert!(auth.validate_session(&token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_extends_valid_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        let token = seed_session(&auth, chrono::Duration::days(20)).await;
+
+        let session = auth.refresh_session(&token).await.unwrap();
+        assert!(session.expires_at > Utc::now() + chrono::Duration::days(29));
+        assert_eq!(stored_expiry(&auth, &token).await, session.expires_at);
+    }
+
+    fn is_too_many_attempts(err: &anyhow::Error) -> bool {
+        matches!(
+            err.downcast_ref(),
+            Some(crate::core::error::Error::TooManyAttempts)
+        )
+    }
+
+    #[tokio::test]
+    async fn test_login_lockout_rejects_correct_password() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        seed_user(&auth, "bob@example.com", "hunter2").await;
+
+        for _ in 0..MAX_LOGIN_FAILURES {
+            let err = auth
+                .login("bob@example.com".into(), "wrong".into(), None)
+                .await
+                .unwrap_err();
+            assert!(!is_too_many_attempts(&err));
+        }
+
+        let err = auth
+            .login("bob@example.com".into(), "hunter2".into(), None)
+            .await
+            .unwrap_err();
+        assert!(is_too_many_attempts(&err));
+    }
+
+    #[tokio::test]
+    async fn test_login_lockout_for_unknown_email() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+
+        for _ in 0..MAX_LOGIN_FAILURES {
+            auth.login("nobody@example.com".into(), "guess".into(), None)
+                .await
+                .unwrap_err();
+        }
+
+        let err = auth
+            .login("nobody@example.com".into(), "guess".into(), None)
+            .await
+            .unwrap_err();
+        assert!(is_too_many_attempts(&err));
+    }
+
+    #[tokio::test]
+    async fn test_successful_login_resets_failures() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        seed_user(&auth, "bob@example.com", "hunter2").await;
+
+        for _ in 0..MAX_LOGIN_FAILURES - 1 {
+            auth.login("bob@example.com".into(), "wrong".into(), None)
+                .await
+                .unwrap_err();
+        }
+        auth.login("bob@example.com".into(), "hunter2".into(), None)
+            .await
+            .unwrap();
+
+        let err = auth
+            .login("bob@example.com".into(), "wrong".into(), None)
+            .await
+            .unwrap_err();
+        assert!(!is_too_many_attempts(&err));
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_across_devices() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        seed_user(&auth, "bob@example.com", "hunter2").await;
+
+        let (user, laptop) = auth
+            .login(
+                "bob@example.com".into(),
+                "hunter2".into(),
+                Some("laptop".into()),
+            )
+            .await
+            .unwrap();
+        auth.login(
+            "bob@example.com".into(),
+            "hunter2".into(),
+            Some("phone".into()),
+        )
+        .await
+        .unwrap();
+
+        let sessions = auth.list_sessions(&user.id).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        let mut labels: Vec<_> = sessions
+            .iter()
+            .map(|s| s.device_label.clone().unwrap())
+            .collect();
+        labels.sort();
+        assert_eq!(labels, vec!["laptop", "phone"]);
+        assert!(sessions
+            .iter()
+            .any(|s| laptop.token.starts_with(&s.token_prefix)));
+        assert!(sessions
+            .iter()
+            .all(|s| s.token_prefix.len() == TOKEN_PREFIX_LEN));
+    }
+
+    #[tokio::test]
+    async fn test_revoke_other_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        seed_user(&auth, "bob@example.com", "hunter2").await;
+        seed_user(&auth, "eve@example.com", "hunter3").await;
+
+        let (user, laptop) = auth
+            .login(
+                "bob@example.com".into(),
+                "hunter2".into(),
+                Some("laptop".into()),
+            )
+            .await
+            .unwrap();
+        let (_, phone) = auth
+            .login(
+                "bob@example.com".into(),
+                "hunter2".into(),
+                Some("phone".into()),
+            )
+            .await
+            .unwrap();
+        let (eve, _) = auth
+            .login("eve@example.com".into(), "hunter3".into(), None)
+            .await
+            .unwrap();
+        let phone_prefix = &phone.token[..TOKEN_PREFIX_LEN];
+
+        // Another user can't revoke it
+        assert!(auth.revoke_session(&eve.id, phone_prefix).await.is_err());
+
+        auth.revoke_session(&user.id, phone_prefix).await.unwrap();
+        assert!(auth.validate_session(&phone.token).await.is_err());
+        assert!(auth.validate_session(&laptop.token).await.is_ok());
+
+        let sessions = auth.list_sessions(&user.id).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].device_label.as_deref(), Some("laptop"));
+    }
+
+    #[tokio::test]
+    async fn test_reset_invalidates_old_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        seed_user(&auth, "bob@example.com", "hunter2").await;
+        let (_, old) = auth
+            .login("bob@example.com".into(), "hunter2".into(), None)
+            .await
+            .unwrap();
+
+        let token = auth.create_reset_token("bob@example.com").await.unwrap();
+        auth.consume_reset_token(&token, "correct horse")
+            .await
+            .unwrap();
+
+        assert!(auth.validate_session(&old.token).await.is_err());
+        assert!(auth
+            .login("bob@example.com".into(), "hunter2".into(), None)
+            .await
+            .is_err());
+        auth.login("bob@example.com".into(), "correct horse".into(), None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reset_token_is_single_use() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        seed_user(&auth, "bob@example.com", "hunter2").await;
+
+        let token = auth.create_reset_token("bob@example.com").await.unwrap();
+        auth.consume_reset_token(&token, "first").await.unwrap();
+        assert!(auth.consume_reset_token(&token, "second").await.is_err());
+
+        auth.login("bob@example.com".into(), "first".into(), None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_expired_reset_token_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        seed_user(&auth, "bob@example.com", "hunter2").await;
+
+        let token = auth.create_reset_token("bob@example.com").await.unwrap();
+        let pool = auth.pool();
+        sqlx::query("UPDATE reset_tokens SET expires_at = ? WHERE token = ?")
+            .bind((Utc::now() - chrono::Duration::minutes(1)).to_rfc3339())
+            .bind(&token)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        assert!(auth.consume_reset_token(&token, "new").await.is_err());
+        auth.login("bob@example.com".into(), "hunter2".into(), None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reset_token_unknown_email() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        assert!(auth.create_reset_token("nobody@example.com").await.is_err());
+    }
+
+    async fn stored_hash_cost(auth: &AuthManager, email: &str) -> u32 {
+        let pool = auth.pool();
+        let (password_hash,): (String,) =
+            sqlx::query_as("SELECT password_hash FROM users WHERE email = ?")
+                .bind(email)
+                .fetch_one(pool)
+                .await
+                .unwrap();
+        password_hash
+            .parse::<bcrypt::HashParts>()
+            .unwrap()
+            .get_cost()
+    }
+
+    #[tokio::test]
+    async fn test_login_rehashes_weaker_password_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path())
+            .await
+            .unwrap()
+            .with_bcrypt_cost(5);
+        seed_user(&auth, "bob@example.com", "hunter2").await;
+        assert_eq!(stored_hash_cost(&auth, "bob@example.com").await, 4);
+
+        auth.login("bob@example.com".into(), "hunter2".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(stored_hash_cost(&auth, "bob@example.com").await, 5);
+
+        // The upgraded hash still accepts the same password
+        auth.login("bob@example.com".into(), "hunter2".into(), None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_login_survives_failed_rehash_save() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path())
+            .await
+            .unwrap()
+            .with_bcrypt_cost(5);
+        seed_user(&auth, "bob@example.com", "hunter2").await;
+        sqlx::query(
+            "CREATE TRIGGER keep_hash BEFORE UPDATE OF password_hash ON users
+             BEGIN SELECT RAISE(ABORT, 'password_hash is read-only'); END",
+        )
+        .execute(auth.pool())
+        .await
+        .unwrap();
+
+        auth.login("bob@example.com".into(), "hunter2".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(stored_hash_cost(&auth, "bob@example.com").await, 4);
+    }
+
+    #[tokio::test]
+    async fn test_delete_account_cascades() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        let friends = FriendManager::new(temp_dir.path()).await.unwrap();
+        seed_user(&auth, "bob@example.com", "hunter2").await;
+        seed_user(&auth, "carol@example.com", "hunter3").await;
+        seed_user(&auth, "dave@example.com", "hunter4").await;
+
+        let (bob, bob_session) = auth
+            .login("bob@example.com".into(), "hunter2".into(), None)
+            .await
+            .unwrap();
+        let (carol, _) = auth
+            .login("carol@example.com".into(), "hunter3".into(), None)
+            .await
+            .unwrap();
+        let (dave, _) = auth
+            .login("dave@example.com".into(), "hunter4".into(), None)
+            .await
+            .unwrap();
+
+        let request = friends
+            .send_request(bob.id.clone(), "carol@example.com".into(), None)
+            .await
+            .unwrap();
+        friends.respond_to_request(&request.id, true).await.unwrap();
+        friends
+            .send_request(bob.id.clone(), "dave@example.com".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(friends.get_contacts(&carol.id).await.unwrap().len(), 1);
+
+        // Wrong password leaves the account alone
+        assert!(auth
+            .delete_account(&bob.id, "wrong", &friends)
+            .await
+            .is_err());
+        assert!(auth.validate_session(&bob_session.token).await.is_ok());
+
+        auth.delete_account(&bob.id, "hunter2", &friends)
+            .await
+            .unwrap();
+
+        assert!(auth
+            .login("bob@example.com".into(), "hunter2".into(), None)
+            .await
+            .is_err());
+        assert!(auth.validate_session(&bob_session.token).await.is_err());
+        assert!(friends.get_contacts(&carol.id).await.unwrap().is_empty());
+        assert!(friends
+            .get_pending_requests(&dave.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_users_prefix_match() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        FriendManager::new(temp_dir.path()).await.unwrap();
+        seed_user(&auth, "bob@example.com", "pw").await;
+        seed_user(&auth, "robert@example.com", "pw").await;
+        seed_user(&auth, "alice@example.com", "pw").await;
+        seed_user(&auth, "b_underscore@example.com", "pw").await;
+
+        let emails = |users: Vec<UserInfo>| {
+            let mut emails: Vec<_> = users.into_iter().map(|u| u.email).collect();
+            emails.sort();
+            emails
+        };
+
+        let found = auth.search_users("BO", "nobody", 10).await.unwrap();
+        assert_eq!(emails(found), vec!["bob@example.com"]);
+
+        // Prefix only, not substring
+        assert!(auth
+            .search_users("ob", "nobody", 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Wildcards are matched literally
+        let found = auth.search_users("b_", "nobody", 10).await.unwrap();
+        assert_eq!(emails(found), vec!["b_underscore@example.com"]);
+
+        assert!(auth.search_users("b", "nobody", 10).await.is_err());
+        assert_eq!(auth.search_users("al", "nobody", 0).await.unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_users_excludes_self_contacts_and_inactive() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(temp_dir.path()).await.unwrap();
+        let friends = FriendManager::new(temp_dir.path()).await.unwrap();
+        seed_user(&auth, "sam@example.com", "pw").await;
+        seed_user(&auth, "sally@example.com", "pw").await;
+        seed_user(&auth, "sue@example.com", "pw").await;
+        seed_user(&auth, "sid@example.com", "pw").await;
+
+        let (sam, _) = auth
+            .login("sam@example.com".into(), "pw".into(), None)
+            .await
+            .unwrap();
+        let (sid, _) = auth
+            .login("sid@example.com".into(), "pw".into(), None)
+            .await
+            .unwrap();
+        let request = friends
+            .send_request(sam.id.clone(), "sally@example.com".into(), None)
+            .await
+            .unwrap();
+        friends.respond_to_request(&request.id, true).await.unwrap();
+        auth.delete_account(&sid.id, "pw", &friends).await.unwrap();
+
+        let found: Vec<_> = auth
+            .search_users("su", &sam.id, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|u| u.email)
+            .collect();
+        assert_eq!(found, vec!["sue@example.com"]);
+
+        let found: Vec<_> = auth
+            .search_users("sa", &sam.id, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|u| u.email)
+            .collect();
+        assert!(found.is_empty(), "self and contacts excluded: {:?}", found);
+        assert!(auth
+            .search_users("si", &sam.id, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -10,69 +10,104 @@ use axum::{
 };
 use crate::core::AppState;
 use crate::core::models::BlobRef;
-use tracing::{info, error};
+use braid_common::BLOB_MERGE_TYPE;
+use tracing::{info, error, warn};
 
 /// POST /blobs
+///
+/// The file field is streamed into the blob store chunk by chunk, so uploads
+/// aren't limited by available memory. The route's body limit is raised to
+/// `max_blob_request_body`, which is also checked against the file's size here.
 pub async fn upload_blob(
     State(state): State<AppState>,
+    headers: HeaderMap,
     mut multipart: Multipart,
 ) -> std::result::Result<Json<BlobRef>, StatusCode> {
-    use bytes::Bytes;
-    use sha2::{Digest, Sha256};
-
     info!("POST /blobs - uploading blob");
+    check_blob_merge_type(&headers)?;
 
-    let mut filename = None;
-    let mut content_type = None;
-    let mut data = None;
+    let mut stored = None;
 
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
+    while let Some(mut field) = multipart.next_field().await.map_err(|e| {
         error!("Failed to read multipart field: {}", e);
-        StatusCode::BAD_REQUEST
+        e.status()
     })? {
         let name = field.name().unwrap_or("").to_string();
-        
+
         if name == "file" {
-            filename = field.file_name().map(|s| s.to_string());
-            content_type = field.content_type().map(|s| s.to_string());
-            data = Some(field.bytes().await.map_err(|e| {
+            let filename = field.file_name().map(|s| s.to_string());
+            let declared_type = field.content_type().map(|s| s.to_string());
+
+            let mut writer = state.store.blob_store().writer().await.map_err(|e| {
+                error!("Failed to start blob write: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            let mut content_type = None;
+            while let Some(chunk) = field.chunk().await.map_err(|e| {
+                // 413 once the body limit is hit
                 error!("Failed to read file data: {}", e);
-                StatusCode::BAD_REQUEST
-            })?);
+                e.status()
+            })? {
+                if writer.size() + chunk.len() as u64 > state.config.max_blob_request_body as u64 {
+                    // Dropping the writer removes the partial file
+                    error!("Blob upload exceeds {} bytes", state.config.max_blob_request_body);
+                    return Err(StatusCode::PAYLOAD_TOO_LARGE);
+                }
+                // Sniff from the first chunk, which holds any magic bytes
+                if content_type.is_none() {
+                    content_type = Some(detect_content_type(declared_type.clone(), &chunk));
+                }
+                writer.write_chunk(&chunk).await.map_err(|e| {
+                    error!("Failed to write blob chunk: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            }
+
+            let content_type =
+                content_type.unwrap_or_else(|| detect_content_type(declared_type, &[]));
+            let size = writer.size();
+            let hash = writer.finish(Some(content_type.clone())).await.map_err(|e| {
+                error!("Failed to store blob: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            stored = Some((hash, content_type, filename, size));
         }
     }
 
-    let data = data.ok_or(StatusCode::BAD_REQUEST)?;
+    let (hash, content_type, filename, size) = stored.ok_or(StatusCode::BAD_REQUEST)?;
     let filename = filename.unwrap_or_else(|| "unnamed".to_string());
-    let content_type = content_type.unwrap_or_else(|| "application/octet-stream".to_string());
-
-    let mut hasher = Sha256::new();
-    hasher.update(&data);
-    let hash = format!("{:x}", hasher.finalize());
-
-    let version = vec![braid_http::types::Version::from(hash.clone())];
-    let parents = vec![];
 
-    let data_len = data.len();
-    state.store.blob_store()
-        .put(&hash, Bytes::from(data), version, parents, Some(content_type.clone()))
-        .await
-        .map_err(|e| {
-            error!("Failed to store blob: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-    info!("Stored blob {} ({} bytes)", hash, data_len);
+    info!("Stored blob {} ({} bytes)", hash, size);
 
     Ok(Json(BlobRef {
         hash,
         content_type,
         filename,
-        size: data_len as u64,
+        size,
         inline_data: None,
     }))
 }
 
+/// Blobs only support [`BLOB_MERGE_TYPE`]; a missing `Merge-Type` means the same.
+fn check_blob_merge_type(headers: &HeaderMap) -> std::result::Result<(), StatusCode> {
+    match headers.get("Merge-Type").map(|v| v.to_str().map(str::trim)) {
+        None => Ok(()),
+        Some(Ok(merge_type)) if merge_type.eq_ignore_ascii_case(BLOB_MERGE_TYPE) => Ok(()),
+        Some(merge_type) => {
+            warn!("Rejecting blob upload with Merge-Type {:?}", merge_type);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// Use the client-declared MIME type, or sniff one from the data.
+fn detect_content_type(declared: Option<String>, data: &[u8]) -> String {
+    declared
+        .filter(|ct| ct != "application/octet-stream")
+        .or_else(|| infer::get(data).map(|kind| kind.mime_type().to_string()))
+        .unwrap_or_else(|| "application/octet-stream".to_string())
+}
+
 /// GET /blobs/:hash
 pub async fn get_blob(
     Path(hash): Path<String>,
@@ -89,13 +124,120 @@ pub async fn get_blob(
         })?
         .ok_or(StatusCode::NOT_FOUND)?;
 
+    Ok((blob_headers(meta.content_type, Some(data.len() as u64)), data))
+}
+
+/// HEAD /blobs/:hash
+///
+/// Lets clients skip uploading content the store already has. Only the
+/// metadata is read, never the blob itself.
+pub async fn head_blob(
+    Path(hash): Path<String>,
+    State(state): State<AppState>,
+) -> std::result::Result<HeaderMap, StatusCode> {
+    let meta = state.store.blob_store()
+        .get_meta(&hash)
+        .await
+        .map_err(|e| {
+            error!("Failed to read blob metadata: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(blob_headers(meta.content_type, meta.size))
+}
+
+fn blob_headers(content_type: Option<String>, size: Option<u64>) -> HeaderMap {
     let mut headers = HeaderMap::new();
     headers.insert(
         axum::http::header::CONTENT_TYPE,
-        meta.content_type.unwrap_or_else(|| "application/octet-stream".to_string())
-            .parse()
-            .unwrap(),
+        content_type
+            .and_then(|ct| ct.parse().ok())
+            .unwrap_or(axum::http::HeaderValue::from_static("application/octet-stream")),
     );
+    if let Some(size) = size {
+        headers.insert(axum::http::header::CONTENT_LENGTH, size.into());
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[tokio::test]
+    async fn test_sniffed_png_roundtrips() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = braid_blob::BlobStore::new(dir.path().join("blobs"), dir.path().join("meta.sqlite"))
+            .await
+            .unwrap();
+
+        let content_type = detect_content_type(None, PNG);
+        assert_eq!(content_type, "image/png");
 
-    Ok((headers, data))
+        let hash = store
+            .put_with_type(bytes::Bytes::from_static(PNG), Some(content_type))
+            .await
+            .unwrap();
+        let (_, meta) = store.get(&hash).await.unwrap().unwrap();
+        assert_eq!(meta.content_type.as_deref(), Some("image/png"));
+    }
+
+    #[test]
+    fn test_blob_merge_type_is_checked() {
+        let with = |merge_type: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("Merge-Type", merge_type.parse().unwrap());
+            check_blob_merge_type(&headers)
+        };
+        assert_eq!(check_blob_merge_type(&HeaderMap::new()), Ok(()));
+        assert_eq!(with(BLOB_MERGE_TYPE), Ok(()));
+        for other in ["diamond", "simpleton", "antimatter"] {
+            assert_eq!(with(other), Err(StatusCode::BAD_REQUEST), "{}", other);
+        }
+    }
+
+    #[test]
+    fn test_declared_content_type_wins() {
+        assert_eq!(detect_content_type(Some("text/plain".into()), PNG), "text/plain");
+        assert_eq!(
+            detect_content_type(Some("application/octet-stream".into()), PNG),
+            "image/png"
+        );
+        assert_eq!(detect_content_type(None, b"plain"), "application/octet-stream");
+    }
+
+    #[tokio::test]
+    async fn test_upload_body_limit_follows_config() {
+        use axum::body::Body;
+        use axum::http::{header, Request};
+        use tower::ServiceExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = crate::core::config::test_support::test_state(dir.path()).await;
+        state.config.max_blob_request_body = 4 * 1024 * 1024;
+        let app = crate::app(state);
+
+        let upload = |size: usize| {
+            let mut body = b"--XX\r\nContent-Disposition: form-data; name=\"file\"; \
+                             filename=\"big.bin\"\r\n\r\n"
+                .to_vec();
+            body.resize(body.len() + size, b'x');
+            body.extend_from_slice(b"\r\n--XX--\r\n");
+            Request::builder()
+                .method("POST")
+                .uri("/blobs")
+                .header(header::CONTENT_TYPE, "multipart/form-data; boundary=XX")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        // Over axum's 2 MiB default but within the configured limit
+        let resp = app.clone().oneshot(upload(3 * 1024 * 1024)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = app.oneshot(upload(5 * 1024 * 1024)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
 }
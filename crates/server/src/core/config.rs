@@ -2,14 +2,17 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::chat::ai::AiChatManager;
 use crate::chat::friends::FriendManager;
 use crate::chat::mail::MailManager;
 use crate::core::auth::AuthManager;
 use crate::core::daemon::DaemonIntegration;
+use crate::core::dispatch::RouteResolver;
 use crate::core::store::JsonChatStore;
 use crate::core::pages::{LocalOrgManager, PagesManager};
+use crate::core::presence::PresenceTracker;
 
 /// Configuration for the Braid Chat Server
 #[derive(Clone, Debug)]
@@ -32,6 +35,24 @@ pub struct ChatServerConfig {
     pub inline_threshold: usize,
     /// Node ID for CRDT
     pub node_id: String,
+    /// Max mail feed items kept in memory (oldest are dropped)
+    pub max_feed_items: usize,
+    /// Pause between mail feed syncs; `POST /mail/refresh` skips the wait
+    pub mail_sync_interval: Duration,
+    /// bcrypt work factor for password hashes
+    pub bcrypt_cost: u32,
+    /// Chat/wiki routing for the catch-all GET/PUT dispatcher
+    pub routes: RouteResolver,
+    /// Largest request body accepted by the catch-all dispatcher, in bytes
+    pub max_request_body: usize,
+    /// Body limit for `POST /blobs`, which streams to disk, in bytes
+    pub max_blob_request_body: usize,
+    /// Chat history older than this is pruned even if not everyone has read it
+    pub history_retention: Duration,
+    /// Pause between chat history prunes
+    pub history_prune_interval: Duration,
+    /// Dev only: debug-log password reset tokens, since there is no mail delivery
+    pub log_reset_tokens: bool,
 }
 
 impl Default for ChatServerConfig {
@@ -52,6 +73,21 @@ impl Default for ChatServerConfig {
                 "server-{}",
                 uuid::Uuid::new_v4().to_string()[..8].to_string()
             ),
+            max_feed_items: 500,
+            mail_sync_interval: Duration::from_secs(30),
+            bcrypt_cost: std::env::var("BCRYPT_COST")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(bcrypt::DEFAULT_COST),
+            routes: match std::env::var("BRAID_ROUTE_PREFIXES") {
+                Ok(spec) => RouteResolver::default().with_prefix_spec(&spec),
+                Err(_) => RouteResolver::default(),
+            },
+            max_request_body: 50 * 1024 * 1024,
+            max_blob_request_body: 1024 * 1024 * 1024,
+            history_retention: Duration::from_secs(30 * 24 * 60 * 60),
+            history_prune_interval: Duration::from_secs(60 * 60),
+            log_reset_tokens: std::env::var("BRAID_DEV_LOG_RESET_TOKENS").is_ok(),
         }
     }
 }
@@ -83,9 +119,54 @@ pub struct AppState {
     pub store: Arc<JsonChatStore>,
     pub auth: Arc<AuthManager>,
     pub friends: Arc<FriendManager>,
+    /// Last-seen times for authenticated users
+    pub presence: Arc<PresenceTracker>,
     pub ai_manager: Option<Arc<AiChatManager>>,
     pub daemon: Option<Arc<DaemonIntegration>>,
     pub mail_manager: Arc<MailManager>,
     pub pages_manager: Arc<PagesManager>,
     pub local_org_manager: Arc<LocalOrgManager>,
 }
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+    use std::path::Path;
+
+    /// App state rooted in `dir`, without AI or the daemon
+    pub(crate) async fn test_state(dir: &Path) -> AppState {
+        let config = ChatServerConfig::with_base_dir(dir);
+        let store = Arc::new(JsonChatStore::new(config.clone()).await.unwrap());
+        let auth = Arc::new(AuthManager::new(dir).await.unwrap().with_bcrypt_cost(4));
+        let friends = Arc::new(FriendManager::from_pool(auth.pool().clone()).await.unwrap());
+        AppState {
+            config,
+            auth,
+            friends,
+            presence: Arc::new(PresenceTracker::new()),
+            ai_manager: None,
+            daemon: None,
+            mail_manager: Arc::new(MailManager::new(store.clone())),
+            pages_manager: Arc::new(PagesManager::new(0, dir.join("pages"))),
+            local_org_manager: Arc::new(LocalOrgManager::new(&dir.to_string_lossy())),
+            store,
+        }
+    }
+
+    /// Sign up a user named `username` and log them in, returning the
+    /// user id and session token
+    pub(crate) async fn test_user(state: &AppState, username: &str) -> (String, String) {
+        let email = format!("{}@example.com", username);
+        let user = state
+            .auth
+            .signup(email.clone(), username.to_string(), "hunter22".to_string(), None)
+            .await
+            .unwrap();
+        let (_, session) = state
+            .auth
+            .login(email, "hunter22".to_string(), None)
+            .await
+            .unwrap();
+        (user.id, session.token)
+    }
+}
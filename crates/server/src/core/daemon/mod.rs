@@ -7,6 +7,7 @@
 //! - Bidirectional sync protocol
 
 use crate::core::models::{ChatRoom, RoomSyncStatus, SyncStatus};
+use crate::core::shutdown::Shutdown;
 use crate::core::store::json_store::JsonChatStore;
 use anyhow::{Context, Result};
 use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
@@ -91,7 +92,7 @@ impl DaemonIntegration {
     }
 
     /// Check if daemon is healthy
-    async fn check_daemon_health(&self) -> Result<()> {
+    pub async fn check_daemon_health(&self) -> Result<()> {
         let resp = self
             .daemon_client
             .get(format!("{}/health", self.daemon_url))
@@ -202,6 +203,7 @@ impl DaemonIntegration {
                     status: SyncStatus::Syncing,
                     last_sync: None,
                     pending_changes: 0,
+                    read_receipts: Vec::new(),
                 },
             );
         }
@@ -353,6 +355,7 @@ impl DaemonIntegration {
                 status: SyncStatus::Offline,
                 last_sync: None,
                 pending_changes: 0,
+                read_receipts: Vec::new(),
             })
     }
 
@@ -390,14 +393,35 @@ impl DaemonIntegration {
 }
 
 /// Background task for handling file change events
+///
+/// On shutdown, stops accepting new events and handles the ones already
+/// queued before returning.
 pub async fn file_watcher_task(
     mut rx: mpsc::Receiver<FileChangeEvent>,
     _integration: Arc<DaemonIntegration>,
+    shutdown: Shutdown,
 ) {
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                // Handle file change
+                Some(event) => debug!("File change event: {:?}", event),
+                None => return,
+            },
+            _ = shutdown.wait() => break,
+        }
+    }
+
+    rx.close();
+    let mut flushed = 0;
     while let Some(event) = rx.recv().await {
-        // Handle file change
         debug!("File change event: {:?}", event);
+        flushed += 1;
     }
+    info!(
+        "Daemon file watcher stopped ({} queued events flushed)",
+        flushed
+    );
 }
 
 /// Background task for periodic sync
@@ -0,0 +1,71 @@
+//! SQLite Connection Pool
+//!
+//! Auth and friends both live in `users.sqlite`; the server opens one pool
+//! here and hands clones of it to each manager.
+
+use anyhow::Result;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Upper bound on open connections per pool
+pub const MAX_CONNECTIONS: u32 = 8;
+
+/// How long a connection waits on a locked database before erroring
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The auth/friends database under `base_dir`
+pub fn users_db_path(base_dir: &Path) -> PathBuf {
+    base_dir.join("users.sqlite")
+}
+
+/// Open a pool on `db_path`, creating the file if needed
+///
+/// WAL mode lets readers proceed while a write is in progress.
+pub async fn open_pool(db_path: &Path) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::from_str(&braid_common::sqlite_url(db_path))?
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .busy_timeout(BUSY_TIMEOUT);
+    Ok(SqlitePoolOptions::new()
+        .max_connections(MAX_CONNECTIONS)
+        .connect_with(options)
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::friends::FriendManager;
+    use crate::core::auth::AuthManager;
+    use tempfile::TempDir;
+
+    async fn main_db_file(pool: &SqlitePool) -> String {
+        let (file,): (String,) =
+            sqlx::query_as("SELECT file FROM pragma_database_list WHERE name = 'main'")
+                .fetch_one(pool)
+                .await
+                .unwrap();
+        file
+    }
+
+    #[tokio::test]
+    async fn test_managers_open_same_file_with_awkward_path() {
+        let temp_dir = TempDir::new().unwrap();
+        // On Windows the backslash is a separator, elsewhere part of the name
+        let base = temp_dir.path().join("Jane Doe\\braid data");
+        std::fs::create_dir_all(&base).unwrap();
+
+        let auth = AuthManager::new(&base).await.unwrap();
+        let friends = FriendManager::new(&base).await.unwrap();
+
+        let auth_file = main_db_file(auth.pool()).await;
+        assert_eq!(auth_file, main_db_file(friends.pool()).await);
+        assert_eq!(
+            std::fs::canonicalize(&auth_file).unwrap(),
+            std::fs::canonicalize(users_db_path(&base)).unwrap()
+        );
+    }
+}
@@ -0,0 +1,355 @@
+//! Top-level Request Routing
+//!
+//! Decides whether a request on the catch-all `/{*path}` route is meant for
+//! a chat room, a wiki page or a local.org page. Signals are checked from
+//! most to least explicit:
+//!
+//! 1. `Braid-Resource: chat | wiki | local`
+//! 2. Configured path prefixes (longest match wins)
+//! 3. `Merge-Type: simpleton`
+//! 4. Media type: `Content-Type` for writes, `Accept` for reads
+//! 5. A file extension on the last path segment, or a trailing `/` asking
+//!    for a directory index
+//!
+//! Anything left over (no extension, no hints) goes to chat, matching how
+//! clients address rooms.
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use bytes::{Bytes, BytesMut};
+use futures::StreamExt;
+use std::cmp::Reverse;
+use std::str::FromStr;
+
+/// Header a client can send to pick the service explicitly
+pub const BRAID_RESOURCE: &str = "Braid-Resource";
+
+/// Prefix that local.org pages are served under
+pub const LOCAL_ORG_PREFIX: &str = "local.org/";
+
+/// Which service handles a request
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouteKind {
+    Chat,
+    Wiki,
+    LocalPage,
+}
+
+impl FromStr for RouteKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "chat" => Ok(Self::Chat),
+            "wiki" | "page" => Ok(Self::Wiki),
+            "local" | "local.org" => Ok(Self::LocalPage),
+            other => Err(format!("Unknown route kind: {}", other)),
+        }
+    }
+}
+
+/// Where a request should be dispatched, with the path that service expects
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RouteTarget {
+    /// Chat room, by room id
+    Chat(String),
+    /// Wiki page, by path under the pages directory
+    Wiki(String),
+    /// Local.org page, with the `local.org/` prefix removed
+    LocalPage(String),
+}
+
+impl RouteTarget {
+    fn new(kind: RouteKind, path: &str) -> Self {
+        match kind {
+            RouteKind::Chat => Self::Chat(path.to_string()),
+            RouteKind::Wiki => Self::Wiki(path.to_string()),
+            RouteKind::LocalPage => Self::LocalPage(
+                path.strip_prefix(LOCAL_ORG_PREFIX)
+                    .unwrap_or(path)
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+/// Resolves catch-all requests to a `RouteTarget`
+#[derive(Clone, Debug)]
+pub struct RouteResolver {
+    prefixes: Vec<(String, RouteKind)>,
+}
+
+impl Default for RouteResolver {
+    fn default() -> Self {
+        Self::new().with_prefix(LOCAL_ORG_PREFIX, RouteKind::LocalPage)
+    }
+}
+
+impl RouteResolver {
+    /// A resolver with no prefix rules
+    pub fn new() -> Self {
+        Self {
+            prefixes: Vec::new(),
+        }
+    }
+
+    /// Route every path starting with `prefix` to `kind`
+    pub fn with_prefix(mut self, prefix: impl Into<String>, kind: RouteKind) -> Self {
+        let prefix = prefix.into();
+        self.prefixes.retain(|(p, _)| *p != prefix);
+        self.prefixes.push((prefix, kind));
+        // Longest first, so more specific prefixes win
+        self.prefixes.sort_by_key(|(p, _)| Reverse(p.len()));
+        self
+    }
+
+    /// Add prefix rules from a spec like `"rooms/=chat,docs/=wiki"`
+    ///
+    /// Malformed entries are skipped.
+    pub fn with_prefix_spec(mut self, spec: &str) -> Self {
+        for entry in spec.split(',') {
+            let Some((prefix, kind)) = entry.split_once('=') else {
+                continue;
+            };
+            match kind.parse() {
+                Ok(kind) if !prefix.trim().is_empty() => {
+                    self = self.with_prefix(prefix.trim(), kind);
+                }
+                _ => continue,
+            }
+        }
+        self
+    }
+
+    /// Decide which service handles `method` on `path`
+    pub fn resolve(&self, method: &Method, path: &str, headers: &HeaderMap) -> RouteTarget {
+        RouteTarget::new(self.resolve_kind(method, path, headers), path)
+    }
+
+    fn resolve_kind(&self, method: &Method, path: &str, headers: &HeaderMap) -> RouteKind {
+        if let Some(kind) = header_str(headers, BRAID_RESOURCE).and_then(|v| v.parse().ok()) {
+            return kind;
+        }
+
+        if let Some((_, kind)) = self
+            .prefixes
+            .iter()
+            .find(|(p, _)| path.starts_with(p.as_str()))
+        {
+            return *kind;
+        }
+
+        if header_str(headers, "Merge-Type") == Some("simpleton") {
+            return RouteKind::Wiki;
+        }
+
+        let media_header = if method == Method::PUT || method == Method::POST {
+            header::CONTENT_TYPE
+        } else {
+            header::ACCEPT
+        };
+        if let Some(kind) = header_str(headers, media_header.as_str()).and_then(media_kind) {
+            return kind;
+        }
+
+        if path.ends_with('/') || std::path::Path::new(path).extension().is_some() {
+            return RouteKind::Wiki;
+        }
+
+        RouteKind::Chat
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|h| h.to_str().ok())
+}
+
+/// First media type in a (possibly comma-separated) header that picks a
+/// service; wildcards and unknown types are skipped
+fn media_kind(value: &str) -> Option<RouteKind> {
+    value.split(',').find_map(|item| {
+        let mime = item.split(';').next()?.trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "application/json" => Some(RouteKind::Chat),
+            "text/plain" | "text/markdown" | "text/html" => Some(RouteKind::Wiki),
+            _ => None,
+        }
+    })
+}
+
+/// Buffer a request body, giving up once it grows past `limit` bytes
+///
+/// Returns `413 Payload Too Large` over the limit and `400 Bad Request` if
+/// the body can't be read.
+pub async fn read_body(body: Body, limit: usize) -> Result<Bytes, StatusCode> {
+    let mut stream = body.into_data_stream();
+    let mut buf = BytesMut::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if buf.len() + chunk.len() > limit {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(buf.freeze())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    fn get(path: &str, pairs: &[(&'static str, &'static str)]) -> RouteTarget {
+        RouteResolver::default().resolve(&Method::GET, path, &headers(pairs))
+    }
+
+    fn put(path: &str, pairs: &[(&'static str, &'static str)]) -> RouteTarget {
+        RouteResolver::default().resolve(&Method::PUT, path, &headers(pairs))
+    }
+
+    #[test]
+    fn test_braid_resource_header_wins() {
+        assert_eq!(
+            get("notes/today.md", &[("braid-resource", "chat")]),
+            RouteTarget::Chat("notes/today.md".into())
+        );
+        assert_eq!(
+            put(
+                "about",
+                &[
+                    ("braid-resource", "wiki"),
+                    ("content-type", "application/json")
+                ]
+            ),
+            RouteTarget::Wiki("about".into())
+        );
+        assert_eq!(
+            get("local.org/home", &[("braid-resource", "local")]),
+            RouteTarget::LocalPage("home".into())
+        );
+    }
+
+    #[test]
+    fn test_local_org_prefix() {
+        assert_eq!(
+            get("local.org/home", &[]),
+            RouteTarget::LocalPage("home".into())
+        );
+        assert_eq!(
+            put("local.org/a/b.md", &[("content-type", "application/json")]),
+            RouteTarget::LocalPage("a/b.md".into())
+        );
+    }
+
+    #[test]
+    fn test_configured_prefixes_longest_match() {
+        let resolver = RouteResolver::default().with_prefix_spec("docs/=wiki, docs/chat/=chat,bad");
+        let none = HeaderMap::new();
+        assert_eq!(
+            resolver.resolve(&Method::GET, "docs/intro", &none),
+            RouteTarget::Wiki("docs/intro".into())
+        );
+        assert_eq!(
+            resolver.resolve(&Method::GET, "docs/chat/room1", &none),
+            RouteTarget::Chat("docs/chat/room1".into())
+        );
+    }
+
+    #[test]
+    fn test_simpleton_merge_type_is_wiki() {
+        assert_eq!(
+            get("about", &[("merge-type", "simpleton")]),
+            RouteTarget::Wiki("about".into())
+        );
+    }
+
+    #[test]
+    fn test_media_types() {
+        // Extensionless wiki page identified by its content type
+        assert_eq!(
+            put("about", &[("content-type", "text/plain; charset=utf-8")]),
+            RouteTarget::Wiki("about".into())
+        );
+        assert_eq!(
+            get("about", &[("accept", "text/html, */*")]),
+            RouteTarget::Wiki("about".into())
+        );
+        // JSON chat payload without a merge header
+        assert_eq!(
+            put("room.v2", &[("content-type", "application/json")]),
+            RouteTarget::Chat("room.v2".into())
+        );
+        assert_eq!(
+            get("room1", &[("accept", "application/json")]),
+            RouteTarget::Chat("room1".into())
+        );
+        // Reads ignore Content-Type, writes ignore Accept
+        assert_eq!(
+            get("room1", &[("content-type", "text/plain")]),
+            RouteTarget::Chat("room1".into())
+        );
+        assert_eq!(
+            put("room1", &[("accept", "text/plain")]),
+            RouteTarget::Chat("room1".into())
+        );
+    }
+
+    #[test]
+    fn test_extension_fallback() {
+        assert_eq!(
+            get("notes/today.md", &[]),
+            RouteTarget::Wiki("notes/today.md".into())
+        );
+        assert_eq!(
+            get("pages.d/about", &[]),
+            RouteTarget::Chat("pages.d/about".into())
+        );
+        assert_eq!(
+            get("notes/today.md", &[("accept", "*/*")]),
+            RouteTarget::Wiki("notes/today.md".into())
+        );
+        assert_eq!(get("notes/", &[]), RouteTarget::Wiki("notes/".into()));
+    }
+
+    #[test]
+    fn test_ambiguous_extensionless_defaults_to_chat() {
+        assert_eq!(get("general", &[]), RouteTarget::Chat("general".into()));
+        assert_eq!(put("general", &[]), RouteTarget::Chat("general".into()));
+    }
+
+    #[tokio::test]
+    async fn test_read_body_limit() {
+        let body = read_body(Body::from(vec![7u8; 10]), 10).await.unwrap();
+        assert_eq!(body.len(), 10);
+
+        let chunks: Vec<Result<Vec<u8>, std::io::Error>> = vec![Ok(vec![0; 6]), Ok(vec![0; 6])];
+        let streamed = Body::from_stream(futures::stream::iter(chunks));
+        assert_eq!(
+            read_body(streamed, 10).await,
+            Err(StatusCode::PAYLOAD_TOO_LARGE)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_pages_parse_the_query() {
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let app = crate::app(crate::core::config::test_support::test_state(dir.path()).await);
+        let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+
+        // A chat room gets as far as authentication
+        let resp = app.clone().oneshot(get("/general?history=yes")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let resp = app.oneshot(get("/notes.md?history=yes")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -9,6 +9,7 @@ use serde_json::json;
 pub enum Error {
     // Auth Errors
     LoginFail,
+    TooManyAttempts,
     AuthFailNoToken,
     AuthFailTokenWrongFormat,
     AuthFailCtxNotInRequestExt,
@@ -28,6 +29,10 @@ impl IntoResponse for Error {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
             Error::LoginFail => (StatusCode::UNAUTHORIZED, "Login failed".to_string()),
+            Error::TooManyAttempts => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many failed login attempts, try again later".to_string(),
+            ),
             Error::AuthFailNoToken => (StatusCode::UNAUTHORIZED, "No auth token found".to_string()),
             Error::AuthFailTokenWrongFormat => (
                 StatusCode::UNAUTHORIZED,
@@ -55,6 +60,14 @@ impl IntoResponse for Error {
     }
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+
 // Allow conversion from other errors (e.g., anyhow, sqlx) easiest via string
 impl From<anyhow::Error> for Error {
     fn from(err: anyhow::Error) -> Self {
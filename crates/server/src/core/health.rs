@@ -0,0 +1,175 @@
+//! Health Check
+//!
+//! Probes the subsystems the server depends on so load balancers can tell a
+//! degraded instance from a healthy one. The auth database and blob
+//! directory are critical; the daemon is optional and only degrades status.
+
+use crate::core::auth::AuthManager;
+use crate::core::config::AppState;
+use crate::core::daemon::DaemonIntegration;
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long to wait for the daemon before calling it unreachable
+const DAEMON_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Overall instance status
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    /// A non-critical check failed; the instance can still serve
+    Degraded,
+    /// A critical check failed
+    Unhealthy,
+}
+
+/// Result of a single probe
+#[derive(Clone, Debug, Serialize)]
+pub struct CheckResult {
+    pub ok: bool,
+    pub critical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CheckResult {
+    fn new(critical: bool, result: anyhow::Result<()>) -> Self {
+        Self {
+            ok: result.is_ok(),
+            critical,
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// Response body for `GET /health`
+#[derive(Clone, Debug, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checks: BTreeMap<String, CheckResult>,
+}
+
+impl HealthReport {
+    fn from_checks(checks: BTreeMap<String, CheckResult>) -> Self {
+        let failed = checks.values().filter(|c| !c.ok);
+        let status = match failed.map(|c| c.critical).max() {
+            None => HealthStatus::Healthy,
+            Some(false) => HealthStatus::Degraded,
+            Some(true) => HealthStatus::Unhealthy,
+        };
+        Self { status, checks }
+    }
+
+    /// 503 only when a critical check failed
+    pub fn status_code(&self) -> StatusCode {
+        match self.status {
+            HealthStatus::Unhealthy => StatusCode::SERVICE_UNAVAILABLE,
+            HealthStatus::Healthy | HealthStatus::Degraded => StatusCode::OK,
+        }
+    }
+}
+
+/// Confirm files can be created in `dir`
+async fn check_writable(dir: &Path) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    let probe = dir.join(format!(".health-{}", uuid::Uuid::new_v4()));
+    tokio::fs::write(&probe, b"ok").await?;
+    tokio::fs::remove_file(&probe).await?;
+    Ok(())
+}
+
+/// Run every probe; the daemon is only checked when integration is enabled
+pub async fn check(
+    auth: &AuthManager,
+    blob_dir: &Path,
+    daemon: Option<&DaemonIntegration>,
+) -> HealthReport {
+    let mut checks = BTreeMap::new();
+    checks.insert(
+        "auth_db".to_string(),
+        CheckResult::new(true, auth.ping().await),
+    );
+    checks.insert(
+        "blob_store".to_string(),
+        CheckResult::new(true, check_writable(blob_dir).await),
+    );
+    if let Some(daemon) = daemon {
+        checks.insert(
+            "daemon".to_string(),
+            CheckResult::new(
+                false,
+                tokio::time::timeout(DAEMON_CHECK_TIMEOUT, daemon.check_daemon_health())
+                    .await
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("Daemon health check timed out"))),
+            ),
+        );
+    }
+    HealthReport::from_checks(checks)
+}
+
+/// GET /health
+pub async fn health_check(State(state): State<AppState>) -> (StatusCode, Json<HealthReport>) {
+    let report = check(&state.auth, &state.config.blob_dir, state.daemon.as_deref()).await;
+    (report.status_code(), Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::ChatServerConfig;
+    use crate::core::store::JsonChatStore;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_healthy() {
+        let dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(dir.path()).await.unwrap();
+
+        let report = check(&auth, &dir.path().join("blobs"), None).await;
+        assert_eq!(report.status, HealthStatus::Healthy);
+        assert_eq!(report.status_code(), StatusCode::OK);
+        assert!(report.checks["auth_db"].ok);
+        assert!(report.checks["blob_store"].ok);
+        assert!(!report.checks.contains_key("daemon"));
+    }
+
+    #[tokio::test]
+    async fn test_db_failure_is_unavailable() {
+        let dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(dir.path()).await.unwrap();
+        // Simulate losing the database
+        auth.pool().close().await;
+
+        let report = check(&auth, &dir.path().join("blobs"), None).await;
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+        assert_eq!(report.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!report.checks["auth_db"].ok);
+        assert!(report.checks["auth_db"].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_daemon_is_degraded() {
+        let dir = TempDir::new().unwrap();
+        let auth = AuthManager::new(dir.path()).await.unwrap();
+        // Grab a free port, then close it so nothing is listening
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut config = ChatServerConfig::with_base_dir(dir.path());
+        config.daemon_port = port;
+        let store = Arc::new(JsonChatStore::new(config.clone()).await.unwrap());
+        let (daemon, _rx) = DaemonIntegration::new(config, store).await.unwrap();
+
+        let report = check(&auth, &dir.path().join("blobs"), Some(&daemon)).await;
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert_eq!(report.status_code(), StatusCode::OK);
+        assert!(!report.checks["daemon"].ok);
+    }
+}
@@ -8,11 +8,16 @@ pub mod blobs;
 pub mod config;
 pub mod ctx;
 pub mod daemon;
+pub mod db;
+pub mod dispatch;
 pub mod error;
+pub mod health;
 pub mod models;
 pub mod pages;
+pub mod presence;
 pub mod protocol;
 pub mod router;
+pub mod shutdown;
 pub mod store;
 
 // Re-exports for convenience
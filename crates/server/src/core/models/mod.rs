@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
 /// A chat room with CRDT state
@@ -11,6 +11,14 @@ pub struct ChatRoom {
     pub created_at: DateTime<Utc>,
     pub created_by: String,
     pub participants: Vec<String>,
+    #[serde(default)]
+    pub is_direct_message: bool,
+    /// Answered by @BraidBot
+    #[serde(default)]
+    pub is_ai_chat: bool,
+    /// How far each participant has read, by user
+    #[serde(default)]
+    pub read_markers: HashMap<String, ReadReceipt>,
     #[serde(flatten)]
     pub crdt_state: CrdtState,
 }
@@ -29,6 +37,9 @@ impl ChatRoom {
             created_at: now,
             created_by: created_by.into(),
             participants: Vec::new(),
+            is_direct_message: false,
+            is_ai_chat: false,
+            read_markers: HashMap::new(),
             crdt_state: CrdtState::new(&id),
         }
     }
@@ -44,9 +55,12 @@ pub struct CrdtState {
     /// Current frontier versions (leaves of the DAG)
     pub current_version: Vec<braid_http::types::Version>,
     /// The version graph (DAG): version -> set of parent versions
-    pub version_graph: HashMap<String, Vec<braid_http::types::Version>>,
+    pub version_graph: BTreeMap<String, Vec<braid_http::types::Version>>,
     /// Messages in this room (version -> message)
-    pub messages: HashMap<String, Message>,
+    pub messages: BTreeMap<String, Message>,
+    /// Latest add/remove seen for each reaction, so merges stay commutative
+    #[serde(default)]
+    pub reactions: Vec<ReactionEntry>,
 }
 
 impl CrdtState {
@@ -55,8 +69,9 @@ impl CrdtState {
             node_id: node_id.into(),
             next_seq: 0,
             current_version: Vec::new(),
-            version_graph: HashMap::new(),
-            messages: HashMap::new(),
+            version_graph: BTreeMap::new(),
+            messages: BTreeMap::new(),
+            reactions: Vec::new(),
         }
     }
 
@@ -189,6 +204,21 @@ impl Message {
         self.edited_at = Some(Utc::now());
     }
 
+    /// Turn the message into a tombstone at `version`
+    ///
+    /// The id and version stay so peers can converge on the delete; the
+    /// content, history and attachments are dropped.
+    pub fn mark_deleted(&mut self, version: String, parents: Vec<braid_http::types::Version>) {
+        self.content.clear();
+        self.edit_history.clear();
+        self.blob_refs.clear();
+        self.reactions.clear();
+        self.version = version;
+        self.parents = parents;
+        self.edited_at = Some(Utc::now());
+        self.deleted = true;
+    }
+
     pub fn with_blob(mut self, blob_ref: BlobRef) -> Self {
         self.blob_refs.push(blob_ref);
         self
@@ -230,6 +260,20 @@ pub struct Reaction {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Last-writer-wins register for one user's emoji on one message
+///
+/// The op with the highest version decides whether the reaction is present,
+/// whatever order ops arrive in. Removed reactions keep their entry as a
+/// tombstone so a stale add can't bring them back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReactionEntry {
+    pub msg_id: String,
+    pub user: String,
+    pub emoji: String,
+    pub version: String,
+    pub present: bool,
+}
+
 /// Reference to a blob in storage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlobRef {
@@ -267,11 +311,29 @@ pub struct TypingIndicator {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Transient typing signal pushed to room subscribers; never stored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypingEvent {
+    pub user: String,
+    pub typing: bool,
+}
+
 /// Chat room snapshot (returned by Braid GET)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatSnapshot {
     pub room: ChatRoom,
     pub messages: Vec<Message>,
+    /// Pass as `before` to fetch the next (older) page; only set on paged
+    /// requests that have more to give
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// One page of a room's history, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagePage {
+    pub messages: Vec<Message>,
+    pub next_cursor: Option<String>,
 }
 
 /// Input for creating a message
@@ -282,6 +344,40 @@ pub struct CreateMessageInput {
     pub message_type: MessageTypeInput,
     pub reply_to: Option<String>,
     pub blob_refs: Option<Vec<BlobRefInput>>,
+    /// Idempotency key; a retried send reuses it so only one message is made
+    pub client_msg_id: Option<String>,
+}
+
+/// Input for creating a chat room
+#[derive(Debug, Deserialize)]
+pub struct CreateRoomInput {
+    /// Room id; a random one is picked if missing
+    pub id: Option<String>,
+    pub name: String,
+    /// Members besides the creator, by user id or email
+    #[serde(default)]
+    pub participants: Vec<String>,
+    #[serde(default)]
+    pub is_direct_message: bool,
+    /// Register the room with @BraidBot
+    #[serde(default)]
+    pub is_ai_chat: bool,
+}
+
+/// Input for adding or removing a reaction
+#[derive(Debug, Deserialize)]
+pub struct ReactionInput {
+    pub emoji: String,
+    #[serde(default)]
+    pub action: ReactionAction,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReactionAction {
+    #[default]
+    Add,
+    Remove,
 }
 
 #[derive(Debug, Deserialize)]
@@ -335,6 +431,10 @@ pub enum ChatPatch {
         sender: String,
         #[serde(rename = "type")]
         message_type: MessageType,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reply_to: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        blob_refs: Vec<BlobRef>,
     },
     EditMessage {
         id: String,
@@ -362,6 +462,23 @@ pub struct RoomSyncStatus {
     pub status: SyncStatus,
     pub last_sync: Option<DateTime<Utc>>,
     pub pending_changes: usize,
+    /// Each participant's read marker, for "seen by" ticks
+    #[serde(default)]
+    pub read_receipts: Vec<ReadReceipt>,
+}
+
+/// The last message version a participant has seen in a room
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadReceipt {
+    pub user: String,
+    pub version: String,
+    pub read_at: DateTime<Utc>,
+}
+
+/// Body for `PUT /chat/:room_id/read`
+#[derive(Debug, Deserialize)]
+pub struct ReadInput {
+    pub version: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
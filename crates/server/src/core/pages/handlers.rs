@@ -14,15 +14,16 @@ use axum::{
 use braid_core::core::merge::{
     merge_type::{MergePatch, MergeType},
     simpleton::SimpletonMergeType,
-    MergeResult,
+    History, MergeResult, Revision,
 };
-use braid_http::protocol::constants::headers::{PATCHES, VERSION};
+use braid_http::protocol::constants::headers::{PARENTS, PATCHES, VERSION};
 use braid_http::protocol::headers as header_utils;
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::path::{Component, PathBuf};
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 use tracing::{error, info, warn};
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -32,14 +33,29 @@ struct PageMeta {
     version: Vec<braid_http::types::Version>,
 }
 
+/// Query options for GET /{path}
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    /// List the page's versions instead of its content
+    #[serde(default)]
+    pub history: bool,
+    /// Serve the content as it was at this version
+    pub version: Option<String>,
+    /// Only list files with this extension in a directory index
+    pub ext: Option<String>,
+}
+
 /// GET /{path}
 /// Reads file content and returns with Version header.
 /// Also handles Braid subscriptions (Subscribe: true) with 209 Subscription.
-/// GET /{path}
-/// Reads file content and returns with Version header.
-/// Also handles Braid subscriptions (Subscribe: true) with 209 Subscription.
+///
+/// `?history=true` lists the page's versions with their parents and
+/// timestamps, oldest first; `?version=` serves the page at that version.
+/// A path ending in `/` returns a JSON index of that directory, filtered
+/// by `?ext=`.
 pub async fn get_wiki_page(
     Path(path_str): Path<String>,
+    Query(query): Query<PageQuery>,
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> Response {
@@ -51,9 +67,42 @@ pub async fn get_wiki_page(
         Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
     };
 
+    if path_str.ends_with('/') {
+        return get_wiki_index(&state, &path_str, query.ext.as_deref()).await;
+    }
+
+    // History views are served from the page's revision log
+    if query.history {
+        let history = load_history(&get_history_path(&file_path)).await;
+        return Json(history.list()).into_response();
+    }
+    if let Some(version) = query.version {
+        let history = load_history(&get_history_path(&file_path)).await;
+        let Some(content) = history.content_at(&version) else {
+            return (StatusCode::NOT_FOUND, format!("Unknown version: {}", version))
+                .into_response();
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            VERSION.clone(),
+            braid_http::protocol::headers::format_version_header(&[version.into()])
+                .parse()
+                .unwrap(),
+        );
+        headers.insert(
+            axum::http::header::CONTENT_TYPE,
+            content_type_for(&file_path).parse().unwrap(),
+        );
+        headers.insert(
+            axum::http::header::X_CONTENT_TYPE_OPTIONS,
+            "nosniff".parse().unwrap(),
+        );
+        return (headers, content).into_response();
+    }
+
     // 2. Read content & meta
-    let content = match fs::read_to_string(&file_path).await {
-        Ok(c) => c,
+    let data = match fs::read(&file_path).await {
+        Ok(d) => d,
         Err(_) => return StatusCode::NOT_FOUND.into_response(),
     };
     let meta_path = get_meta_path(&file_path);
@@ -69,6 +118,7 @@ pub async fn get_wiki_page(
         info!("Handling Wiki Subscription for {}", path_str);
 
         let _path_clone = path_str.clone();
+        let content = String::from_utf8_lossy(&data).into_owned();
         let (mut rx, last_update) = state.pages_manager.subscribe(&path_str).await;
 
         // If there's a newer update than what we read from disk, use that
@@ -150,14 +200,31 @@ pub async fn get_wiki_page(
     );
     headers.insert(
         axum::http::header::CONTENT_TYPE,
-        "text/plain".parse().unwrap(),
+        content_type_for(&file_path).parse().unwrap(),
+    );
+    // Uploaded assets mustn't be sniffed into something executable
+    headers.insert(
+        axum::http::header::X_CONTENT_TYPE_OPTIONS,
+        "nosniff".parse().unwrap(),
     );
     headers.insert(
         axum::http::header::CACHE_CONTROL,
         "no-cache".parse().unwrap(),
     );
 
-    (headers, content).into_response()
+    (headers, data).into_response()
+}
+
+/// JSON index of a directory under the pages root.
+async fn get_wiki_index(state: &AppState, dir: &str, ext: Option<&str>) -> Response {
+    match state.pages_manager.index(dir, ext).await {
+        Ok(index) => Json(index).into_response(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("Index of {} failed: {}", dir, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
 }
 
 // Braid Wire Protocol Formatter
@@ -212,6 +279,10 @@ fn format_wiki_update(
 
 /// PUT /{path}
 /// Applies Simpleton patch to file.
+///
+/// A full-body PUT whose `Parents` header names an older version is diffed
+/// against that version's content and rebased over the edits made since, so
+/// concurrent writers don't clobber each other. The merged page is returned.
 pub async fn put_wiki_page(
     Path(path_str): Path<String>,
     State(state): State<AppState>,
@@ -226,6 +297,11 @@ pub async fn put_wiki_page(
         Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
     };
 
+    // Concurrent PUTs to one page would each merge against the same state
+    // and the last write would win, so take them one at a time
+    let page_lock = state.pages_manager.page_lock(&file_path).await;
+    let _guard = page_lock.lock().await;
+
     // 2. Load current state (or init)
     let meta_path = get_meta_path(&file_path);
     let mut simpleton = match load_meta(&meta_path).await {
@@ -238,6 +314,16 @@ pub async fn put_wiki_page(
             s
         }
     };
+    let before = simpleton.content.clone();
+    let parents_before = simpleton.version.clone();
+    let history_path = get_history_path(&file_path);
+
+    // The version the client's edit is based on
+    let parents = headers
+        .get(&PARENTS)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| header_utils::parse_version_header(h).ok())
+        .unwrap_or_default();
 
     // 3. Parse Patch (if provided) or use body as Full Replacement
     // Check if Patches header exists
@@ -247,7 +333,10 @@ pub async fn put_wiki_page(
         match serde_json::from_str::<Vec<MergePatch>>(json_str) {
             Ok(patches) => {
                 let mut result = None;
-                for patch in patches {
+                for mut patch in patches {
+                    if patch.parents.is_empty() {
+                        patch.parents = parents.clone();
+                    }
                     // Apply each patch
                     // Note: Simpleton local_edit/apply_patch expect ONE patch at a time updating state
                     // logic here might need refinement for multiple patches transaction
@@ -261,6 +350,24 @@ pub async fn put_wiki_page(
             }
             Err(_) => braid_core::core::merge::MergeResult::failure("Invalid Patches JSON"),
         }
+    } else if !parents.is_empty() && parents != simpleton.version {
+        // Snapshot based on a stale version: diff it against what the client
+        // started from and let the merge type rebase the splice
+        let base = match parents.first() {
+            Some(p) => load_history(&history_path).await.content_at(&p.to_string()),
+            None => None,
+        };
+        match base {
+            Some(base) => {
+                let mut patch = simpleton.diff(&base, &body);
+                patch.parents = parents;
+                simpleton.apply_patch(patch)
+            }
+            None => {
+                warn!("Unknown parents {:?} for {}, replacing content", parents, path_str);
+                simpleton.local_edit(MergePatch::new("everything", Value::String(body)))
+            }
+        }
     } else {
         // No Patches header -> Treat body as "everything" replacement (Snapshot)
         let patch = MergePatch::new("everything", Value::String(body));
@@ -289,6 +396,18 @@ pub async fn put_wiki_page(
         error!("Meta write failed: {}", e);
     }
 
+    // 6b. Record the revision, unless the merge left the version as it was
+    let changed = simpleton.version != parents_before;
+    if let Some(version) = simpleton.version.first().filter(|_| changed) {
+        let parents = parents_before.iter().map(|p| p.to_string()).collect();
+        let first = !fs::metadata(&history_path).await.is_ok_and(|m| m.len() > 0);
+        let revision =
+            Revision::new(&version.to_string(), parents, &before, &simpleton.content, first);
+        if let Err(e) = append_history(&history_path, &revision).await {
+            error!("History write failed: {}", e);
+        }
+    }
+
     // 7. Notify Subscribers
     info!("[PUT Wiki] Notifying subscribers for path: {}", path_str);
     
@@ -302,7 +421,12 @@ pub async fn put_wiki_page(
     
     // For full replacement (no patches header), create a patch that represents the entire change
     let broadcast_patches = if let Some(json_str) = patch_json {
-        serde_json::from_str::<Vec<MergePatch>>(json_str).ok()
+        // Rebased ranges no longer match the client's, so send the content
+        if merge_result.rebased_patches.is_empty() {
+            serde_json::from_str::<Vec<MergePatch>>(json_str).ok()
+        } else {
+            None
+        }
     } else {
         // Full replacement - send as a patch that replaces everything
         // This ensures clients can apply the update incrementally
@@ -339,7 +463,7 @@ pub async fn put_wiki_page(
             .unwrap(),
     );
 
-    (resp_headers, StatusCode::OK).into_response()
+    (StatusCode::OK, resp_headers, simpleton.content).into_response()
 }
 
 /// GET /wiki/index
@@ -375,6 +499,18 @@ fn resolve_path(base: &std::path::Path, path_str: &str) -> Result<PathBuf, Strin
     Ok(base.join(path))
 }
 
+/// MIME type for a page or asset, from its extension. Pages without one
+/// are markdown.
+fn content_type_for(path: &std::path::Path) -> String {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("md") => "text/markdown".to_string(),
+        Some(_) => mime_guess::from_path(path)
+            .first_or_octet_stream()
+            .to_string(),
+        None => "text/markdown".to_string(),
+    }
+}
+
 fn get_meta_path(path: &std::path::Path) -> PathBuf {
     let mut name = path.file_name().unwrap_or_default().to_os_string();
     name.push(".braid-meta");
@@ -393,6 +529,38 @@ async fn save_meta(path: &std::path::Path, meta: &SimpletonMergeType) -> std::io
     fs::write(path, json).await
 }
 
+fn get_history_path(path: &std::path::Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".braid-history");
+    path.with_file_name(name)
+}
+
+/// Each line of a history file is one revision, so an edit only appends
+async fn load_history(path: &std::path::Path) -> History {
+    let mut history = History::default();
+    let Ok(lines) = fs::read_to_string(path).await else {
+        return history;
+    };
+    for line in lines.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str::<Revision>(line) {
+            Ok(revision) => history.revisions.push(revision),
+            Err(e) => warn!("Corrupt revision in {:?}: {}", path, e),
+        }
+    }
+    history
+}
+
+async fn append_history(path: &std::path::Path, revision: &Revision) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(revision)?;
+    line.push('\n');
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(line.as_bytes()).await
+}
+
 // ============== LOCAL.ORG HANDLERS ==============
 
 use super::local_org::TextPatch;
@@ -549,3 +717,238 @@ pub async fn put_local_page(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::test_support::test_state;
+    use crate::core::pages::manager::IndexEntry;
+    use braid_core::core::merge::RevisionInfo;
+
+    async fn body(resp: Response) -> Bytes {
+        axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap()
+    }
+
+    async fn get(state: &AppState, query: PageQuery) -> Response {
+        get_wiki_page(
+            Path("notes.md".to_string()),
+            Query(query),
+            State(state.clone()),
+            HeaderMap::new(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_history_lists_edits_and_rebuilds_past_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+
+        let edits = ["# Notes\n", "# Notes\nfirst\n", "# Notes\nsecond\n"];
+        let history_path = get_history_path(&state.pages_manager.storage_dir.join("notes.md"));
+        let mut versions = Vec::new();
+        let mut logged = String::new();
+        for content in edits {
+            let resp = put_wiki_page(
+                Path("notes.md".to_string()),
+                State(state.clone()),
+                HeaderMap::new(),
+                content.to_string(),
+            )
+            .await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            let version = resp.headers()[&VERSION].to_str().unwrap();
+            let version = header_utils::parse_version_header(version).unwrap();
+            versions.push(version[0].to_string());
+
+            // Each edit appends one line and leaves earlier ones alone
+            let log = fs::read_to_string(&history_path).await.unwrap();
+            assert!(log.starts_with(&logged));
+            assert_eq!(log.lines().count(), versions.len());
+            logged = log;
+        }
+
+        let history = PageQuery {
+            history: true,
+            ..Default::default()
+        };
+        let listed: Vec<RevisionInfo> =
+            serde_json::from_slice(&body(get(&state, history).await).await).unwrap();
+        let listed_versions: Vec<_> = listed.iter().map(|r| r.version.clone()).collect();
+        assert_eq!(listed_versions, versions);
+        assert_eq!(listed[2].parents, vec![versions[1].clone()]);
+
+        for (version, content) in versions.iter().zip(edits) {
+            let at = PageQuery {
+                version: Some(version.clone()),
+                ..Default::default()
+            };
+            assert_eq!(&body(get(&state, at).await).await[..], content.as_bytes());
+        }
+
+        let unknown = PageQuery {
+            version: Some("nope".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(get(&state, unknown).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_assets_are_served_with_their_content_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        let root = state.pages_manager.storage_dir.join("braid.org");
+        fs::create_dir_all(&root).await.unwrap();
+
+        let png: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0xFF];
+        let cases: [(&str, &[u8], &str); 8] = [
+            ("logo.png", png, "image/png"),
+            ("style.css", b"body {}", "text/css"),
+            ("app.js", b"let x = 1;", "text/javascript"),
+            ("data.json", b"{}", "application/json"),
+            ("index.html", b"<p></p>", "text/html"),
+            ("notes.md", b"# Notes", "text/markdown"),
+            ("archive.unknownext", b"??", "application/octet-stream"),
+            ("about", b"# About", "text/markdown"),
+        ];
+        for (name, data, content_type) in cases {
+            fs::write(root.join(name), data).await.unwrap();
+            let resp = get_wiki_page(
+                Path(format!("braid.org/{}", name)),
+                Query(PageQuery::default()),
+                State(state.clone()),
+                HeaderMap::new(),
+            )
+            .await;
+            assert_eq!(resp.status(), StatusCode::OK, "{}", name);
+            assert_eq!(resp.headers()[axum::http::header::CONTENT_TYPE], content_type);
+            assert_eq!(resp.headers()[axum::http::header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+            assert_eq!(&body(resp).await[..], data, "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_directory_index_lists_pages_sorted() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        let root = state.pages_manager.storage_dir.join("braid.org");
+        fs::create_dir_all(root.join("protocol")).await.unwrap();
+        for name in ["zebra.md", "alpha.md", "middle.md", "style.css"] {
+            fs::write(root.join(name), name).await.unwrap();
+        }
+        fs::write(root.join("alpha.md.braid-meta"), "{}").await.unwrap();
+
+        let index = |ext: Option<&str>| {
+            let state = state.clone();
+            let query = PageQuery {
+                ext: ext.map(str::to_string),
+                ..Default::default()
+            };
+            async move {
+                let resp = get_wiki_page(
+                    Path("braid.org/".to_string()),
+                    Query(query),
+                    State(state),
+                    HeaderMap::new(),
+                )
+                .await;
+                assert_eq!(resp.status(), StatusCode::OK);
+                serde_json::from_slice::<Vec<IndexEntry>>(&body(resp).await).unwrap()
+            }
+        };
+
+        let all = index(None).await;
+        let names: Vec<_> = all.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["alpha.md", "middle.md", "protocol", "style.css", "zebra.md"]);
+        assert_eq!(all[0].path, "braid.org/alpha.md");
+        assert_eq!(all[0].size, "alpha.md".len() as u64);
+        assert!(all[0].last_modified > 0);
+        assert!(all[2].is_dir);
+
+        let md = index(Some("md")).await;
+        let names: Vec<_> = md.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["alpha.md", "middle.md", "protocol", "zebra.md"]);
+
+        let resp = get_wiki_page(
+            Path("missing/".to_string()),
+            Query(PageQuery::default()),
+            State(state.clone()),
+            HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    async fn put(state: &AppState, content: &str, parents: Option<&str>) -> Response {
+        let mut headers = HeaderMap::new();
+        if let Some(parents) = parents {
+            let parents = braid_http::protocol::headers::format_version_header(&[parents.into()]);
+            headers.insert(PARENTS, parents.parse().unwrap());
+        }
+        put_wiki_page(
+            Path("notes.md".to_string()),
+            State(state.clone()),
+            headers,
+            content.to_string(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_edits_from_same_parent_both_survive() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+
+        let resp = put(&state, "# Notes\nalpha\nbeta\n", None).await;
+        let version = resp.headers()[&VERSION].to_str().unwrap();
+        let base = header_utils::parse_version_header(version).unwrap()[0].to_string();
+
+        // Both clients edit the version they fetched
+        let resp = put(&state, "# Notes\nalpha one\nbeta\n", Some(&base)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = put(&state, "# Notes\nalpha\nbeta two\n", Some(&base)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let merged = "# Notes\nalpha one\nbeta two\n";
+        assert_eq!(&body(resp).await[..], merged.as_bytes());
+        let saved = fs::read_to_string(state.pages_manager.storage_dir.join("notes.md")).await;
+        assert_eq!(saved.ok().as_deref(), Some(merged));
+        assert_eq!(
+            &body(get(&state, PageQuery::default()).await).await[..],
+            merged.as_bytes()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_simultaneous_puts_to_one_page_all_survive() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+
+        let resp = put(&state, "a\nb\nc\nd\n", None).await;
+        let version = resp.headers()[&VERSION].to_str().unwrap();
+        let base = header_utils::parse_version_header(version).unwrap()[0].to_string();
+
+        let edits: [&'static str; 4] = [
+            "A\nb\nc\nd\n",
+            "a\nB\nc\nd\n",
+            "a\nb\nC\nd\n",
+            "a\nb\nc\nD\n",
+        ];
+        let tasks: Vec<_> = edits
+            .into_iter()
+            .map(|edit| {
+                let state = state.clone();
+                let base = base.clone();
+                tokio::spawn(async move { put(&state, edit, Some(&base)).await.status() })
+            })
+            .collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), StatusCode::OK);
+        }
+
+        let saved = fs::read_to_string(state.pages_manager.storage_dir.join("notes.md")).await;
+        assert_eq!(saved.ok().as_deref(), Some("A\nB\nC\nD\n"));
+    }
+}
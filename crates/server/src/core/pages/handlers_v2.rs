@@ -14,7 +14,7 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
-use braid_core::core::merge::merge_type::{MergePatch, MergeType};
+use braid_core::core::merge::merge_type::{MergePatch, MergeType, MergeTypeRegistry};
 use braid_http::protocol::constants::headers::{PATCHES, VERSION, PARENTS};
 use braid_http::types::Version;
 use bytes::Bytes;
@@ -36,12 +36,18 @@ pub struct PageQuery {
 /// GET /v2/pages/{path}
 /// Reads page content with version graph support.
 /// Supports Braid subscriptions (Subscribe: true) with HTTP 209.
+/// A trailing `/versions` serves the version graph instead.
 pub async fn get_page_v2(
     Path(path_str): Path<String>,
     Query(query): Query<PageQuery>,
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> Response {
+    // The router can't match a segment after the `{*path}` wildcard
+    if let Some(page) = path_str.strip_suffix("/versions") {
+        return get_page_versions_v2(Path(page.to_string()), State(state)).await;
+    }
+
     info!("[GET v2] {} (merge-type: {:?})", path_str, query.merge_type);
 
     let storage = VersionedStorage::new(state.pages_manager.storage_dir.clone());
@@ -158,7 +164,13 @@ pub async fn put_page_v2(
     info!("[PUT v2] {} (merge-type: {:?})", path_str, query.merge_type);
 
     let storage = VersionedStorage::new(state.pages_manager.storage_dir.clone());
-    let merge_type = query.merge_type.as_deref().unwrap_or("diamond");
+    let merge_type = requested_merge_type(&query, &headers);
+
+    // Reject unknown merge types before anything is stored under them
+    let mut merge_instance = match create_merge_type(merge_type) {
+        Ok(m) => m,
+        Err(rejection) => return rejection.into_response(),
+    };
 
     // Load existing page or create new
     let mut page = storage.load_or_create(&path_str, merge_type).await;
@@ -208,28 +220,6 @@ pub async fn put_page_v2(
         vec![MergePatch::new("[0:]", Value::String(body))]
     };
 
-    // Apply patches using merge type
-    let mut merge_instance = match merge_type {
-        "diamond" => {
-            match braid_core::core::merge::MergeTypeRegistry::new().create("diamond", "server") {
-                Some(m) => m,
-                None => {
-                    error!("[PUT v2] Diamond merge type not available");
-                    return (StatusCode::INTERNAL_SERVER_ERROR, "Merge type unavailable").into_response();
-                }
-            }
-        }
-        _ => {
-            match braid_core::core::merge::MergeTypeRegistry::new().create("simpleton", "server") {
-                Some(m) => m,
-                None => {
-                    error!("[PUT v2] Simpleton merge type not available");
-                    return (StatusCode::INTERNAL_SERVER_ERROR, "Merge type unavailable").into_response();
-                }
-            }
-        }
-    };
-
     // Initialize with current content
     merge_instance.initialize(&page.content);
 
@@ -300,6 +290,26 @@ pub async fn put_page_v2(
     (resp_headers, StatusCode::OK).into_response()
 }
 
+/// Merge type from the `merge-type` query parameter, then the `Merge-Type`
+/// header, defaulting to diamond.
+fn requested_merge_type<'a>(query: &'a PageQuery, headers: &'a HeaderMap) -> &'a str {
+    query
+        .merge_type
+        .as_deref()
+        .or_else(|| headers.get("Merge-Type").and_then(|h| h.to_str().ok()))
+        .unwrap_or("diamond")
+}
+
+/// Instantiate a registered merge type, or a 400 listing the supported ones.
+fn create_merge_type(name: &str) -> Result<Box<dyn MergeType>, (StatusCode, String)> {
+    MergeTypeRegistry::new()
+        .try_create(name, "server")
+        .map_err(|e| {
+            warn!("[PUT v2] {}", e);
+            (StatusCode::BAD_REQUEST, e.to_string())
+        })
+}
+
 /// GET /v2/pages
 /// List all pages with metadata
 pub async fn list_pages_v2(State(state): State<AppState>) -> Response {
@@ -395,3 +405,46 @@ mod header_utils {
         Ok(versions)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_headers(merge_type: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("Merge-Type", merge_type.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_unknown_merge_type_is_bad_request() {
+        let query = PageQuery { merge_type: None };
+        let headers = put_headers("sync9");
+        let name = requested_merge_type(&query, &headers);
+        assert_eq!(name, "sync9");
+
+        let (status, body) = create_merge_type(name).err().unwrap();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let supported = MergeTypeRegistry::new().list().join(", ");
+        assert_eq!(
+            body,
+            format!("Unknown merge type \"sync9\" (supported: {})", supported)
+        );
+    }
+
+    #[test]
+    fn test_registered_merge_types_are_accepted() {
+        for name in ["diamond", "simpleton", "braid-text"] {
+            let merge = create_merge_type(name).ok().unwrap();
+            assert!(!merge.name().is_empty(), "{}", name);
+        }
+
+        // The query parameter wins over the header; diamond is the default
+        let query = PageQuery {
+            merge_type: Some("simpleton".to_string()),
+        };
+        assert_eq!(requested_merge_type(&query, &put_headers("diamond")), "simpleton");
+        let query = PageQuery { merge_type: None };
+        assert_eq!(requested_merge_type(&query, &HeaderMap::new()), "diamond");
+    }
+}
@@ -31,6 +31,18 @@ pub struct PageInfo {
     pub size: u64,
 }
 
+/// One entry of a directory index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    /// Path relative to the pages directory, using `/` separators
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Unix time in seconds
+    pub last_modified: u64,
+}
+
 /// Channel state that keeps the broadcast alive
 struct ChannelState {
     tx: broadcast::Sender<PagesUpdate>,
@@ -45,6 +57,8 @@ pub struct PagesManager {
     pub storage_dir: PathBuf,
     // Map of page_path -> channel state
     channels: RwLock<HashMap<String, ChannelState>>,
+    /// Per-file locks held for the whole of a page update
+    page_locks: RwLock<HashMap<PathBuf, Arc<Mutex<()>>>>,
 }
 
 impl PagesManager {
@@ -57,6 +71,7 @@ impl PagesManager {
             daemon_port,
             storage_dir,
             channels: RwLock::new(HashMap::new()),
+            page_locks: RwLock::new(HashMap::new()),
         }
     }
 
@@ -101,6 +116,55 @@ impl PagesManager {
         pages
     }
 
+    /// List the entries of `dir`, relative to the pages directory, sorted by name
+    ///
+    /// Merge-state sidecars are skipped. With `ext`, only files with that
+    /// extension are listed; subdirectories are always included.
+    pub async fn index(&self, dir: &str, ext: Option<&str>) -> std::io::Result<Vec<IndexEntry>> {
+        let ext = ext.map(|e| e.trim_start_matches('.'));
+        let dir = dir.trim_matches('/');
+        let mut entries = fs::read_dir(self.storage_dir.join(dir)).await?;
+        let mut index = Vec::new();
+
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with(".braid-meta") || name.ends_with(".braid-history") {
+                continue;
+            }
+            let Ok(meta) = entry.metadata().await else {
+                continue;
+            };
+            let path = entry.path();
+            if meta.is_file() {
+                let matches = match ext {
+                    Some(ext) => path
+                        .extension()
+                        .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(ext)),
+                    None => true,
+                };
+                if !matches {
+                    continue;
+                }
+            }
+
+            index.push(IndexEntry {
+                path: if dir.is_empty() { name.clone() } else { format!("{}/{}", dir, name) },
+                name,
+                is_dir: meta.is_dir(),
+                size: if meta.is_dir() { 0 } else { meta.len() },
+                last_modified: meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+            });
+        }
+
+        index.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(index)
+    }
+
     /// Extract a title from the first H1 or return the filename
     async fn extract_title(&self, path: &Path) -> String {
         if let Ok(content) = fs::read_to_string(path).await {
@@ -217,6 +281,16 @@ impl PagesManager {
         }
     }
     
+    /// Lock serializing updates to the page stored at `file_path`
+    pub async fn page_lock(&self, file_path: &Path) -> Arc<Mutex<()>> {
+        self.page_locks
+            .write()
+            .await
+            .entry(file_path.to_path_buf())
+            .or_default()
+            .clone()
+    }
+
     /// Get subscriber count for a path (for debugging)
     pub async fn subscriber_count(&self, path: &str) -> usize {
         let channels = self.channels.read().await;
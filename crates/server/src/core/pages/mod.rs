@@ -38,5 +38,4 @@ pub fn router() -> Router<AppState> {
             "/v2/pages/{*path}",
             get(handlers_v2::get_page_v2).put(handlers_v2::put_page_v2),
         )
-        .route("/v2/pages/{*path}/versions", get(handlers_v2::get_page_versions_v2))
 }
@@ -178,8 +178,8 @@ impl VersionedStorage {
         // Create merge type instance
         let mut merge_type = self
             .registry
-            .create(&page.merge_type, "server")
-            .ok_or_else(|| format!("Unknown merge type: {}", page.merge_type))?;
+            .try_create(&page.merge_type, "server")
+            .map_err(|e| e.to_string())?;
 
         // Restore merge state
         merge_type.initialize(&page.content);
@@ -0,0 +1,58 @@
+//! Online Presence Tracking
+//!
+//! Records when each user was last seen making an authenticated request.
+//! A user counts as online if seen within `ONLINE_THRESHOLD`.
+
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// How recently a user must have been seen to count as online
+pub const ONLINE_THRESHOLD: Duration = Duration::minutes(2);
+
+type Clock = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+/// Last-seen timestamps per user, kept in memory
+pub struct PresenceTracker {
+    last_seen: RwLock<HashMap<String, DateTime<Utc>>>,
+    clock: Clock,
+}
+
+impl Default for PresenceTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PresenceTracker {
+    /// Create a tracker using the system clock
+    pub fn new() -> Self {
+        Self::with_clock(Arc::new(Utc::now))
+    }
+
+    /// Create a tracker with a custom time source
+    pub fn with_clock(clock: Clock) -> Self {
+        Self {
+            last_seen: RwLock::new(HashMap::new()),
+            clock,
+        }
+    }
+
+    /// Record that a user is active now
+    pub fn heartbeat(&self, user_id: &str) {
+        let now = (self.clock)();
+        self.last_seen.write().insert(user_id.to_string(), now);
+    }
+
+    /// When the user was last seen, if ever
+    pub fn last_seen(&self, user_id: &str) -> Option<DateTime<Utc>> {
+        self.last_seen.read().get(user_id).copied()
+    }
+
+    /// Whether the user was seen within `ONLINE_THRESHOLD`
+    pub fn is_online(&self, user_id: &str) -> bool {
+        self.last_seen(user_id)
+            .is_some_and(|seen| (self.clock)() - seen <= ONLINE_THRESHOLD)
+    }
+}
@@ -4,25 +4,50 @@
 
 use crate::core::auth::handlers as auth_handlers;
 use crate::core::blobs;
-use crate::core::AppState;
+use crate::core::{AppState, ChatServerConfig};
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{get, post},
     Router,
 };
 
-pub fn router() -> Router<AppState> {
+pub fn router(config: &ChatServerConfig) -> Router<AppState> {
     Router::new()
         // Auth routes
         .route("/auth/signup", post(auth_handlers::signup))
         .route("/auth/login", post(auth_handlers::login))
         .route("/auth/logout", post(auth_handlers::logout))
+        .route("/auth/refresh", post(auth_handlers::refresh))
+        .route(
+            "/auth/reset/request",
+            post(auth_handlers::request_password_reset),
+        )
+        .route(
+            "/auth/reset/confirm",
+            post(auth_handlers::confirm_password_reset),
+        )
+        .route(
+            "/auth/account",
+            axum::routing::delete(auth_handlers::delete_account),
+        )
+        .route("/auth/sessions", get(auth_handlers::list_sessions))
+        .route(
+            "/auth/sessions/{token_prefix}",
+            axum::routing::delete(auth_handlers::revoke_session),
+        )
         .route("/auth/me", get(auth_handlers::me))
         .route(
             "/auth/profile/{user_id}",
             axum::routing::put(auth_handlers::update_profile),
         )
+        .route("/auth/users/search", get(auth_handlers::search_users))
+        .route("/auth/users/{id}/avatar", get(auth_handlers::get_avatar))
         .route("/users", get(auth_handlers::list_users))
         // Blob routes
-        .route("/blobs", post(blobs::upload_blob))
-        .route("/blobs/{hash}", get(blobs::get_blob))
+        .route(
+            "/blobs",
+            post(blobs::upload_blob)
+                .layer(DefaultBodyLimit::max(config.max_blob_request_body)),
+        )
+        .route("/blobs/{hash}", get(blobs::get_blob).head(blobs::head_blob))
 }
@@ -0,0 +1,137 @@
+//! Graceful Shutdown
+//!
+//! A shutdown signal shared by the HTTP server and background tasks, plus
+//! in-flight request tracking so the server can stop accepting connections
+//! and let running requests finish first.
+
+use axum::{
+    extract::{Request, State},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Fires once when the process should stop
+///
+/// Cloning is cheap; every clone observes the same signal, including
+/// clones that start waiting after it fired.
+#[derive(Clone)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(false);
+        Self { tx: Arc::new(tx) }
+    }
+
+    /// Tell everything waiting on this signal to stop
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// Resolves once `trigger` has been called
+    pub async fn wait(&self) {
+        let mut rx = self.tx.subscribe();
+        // The sender lives as long as `self`, so this only returns on trigger
+        let _ = rx.wait_for(|triggered| *triggered).await;
+    }
+}
+
+/// Trigger `shutdown` on Ctrl-C or SIGTERM
+pub async fn listen_for_signals(shutdown: Shutdown) {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl-C"),
+        _ = terminate => info!("Received SIGTERM"),
+    }
+    shutdown.trigger();
+}
+
+/// Number of requests currently being handled
+#[derive(Clone, Default)]
+pub struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Decrements the counter even if the handler panics or is cancelled
+struct InFlightGuard(InFlight);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        (self.0).0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+async fn track_in_flight(State(in_flight): State<InFlight>, req: Request, next: Next) -> Response {
+    in_flight.0.fetch_add(1, Ordering::SeqCst);
+    let _guard = InFlightGuard(in_flight);
+    next.run(req).await
+}
+
+/// Serve `app` until `shutdown` fires, then wait for in-flight requests
+pub async fn serve(listener: TcpListener, app: Router, shutdown: Shutdown) -> std::io::Result<()> {
+    let in_flight = InFlight::default();
+    let app = app.layer(middleware::from_fn_with_state(
+        in_flight.clone(),
+        track_in_flight,
+    ));
+
+    let draining = in_flight.clone();
+    let signal = async move {
+        shutdown.wait().await;
+        info!(
+            "Shutting down: draining {} in-flight request(s)",
+            draining.count()
+        );
+    };
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(signal)
+        .await?;
+
+    info!("Server stopped, all in-flight requests drained");
+    Ok(())
+}
@@ -3,19 +3,23 @@
 //! This module replaces SQLite with JSON file storage,
 //! using atomic writes for durability and CRDT for conflict resolution.
 
-use crate::chat::crdt::{ChatCrdt, ChatCrdtState};
+use crate::chat::crdt::{version_order, ChatCrdt, ChatCrdtState};
 use crate::core::config::ChatServerConfig;
 use crate::core::models::{
-    BlobRef, ChatRoom, ChatUpdate, CrdtState, DraftMessage, Message, MessageType,
+    BlobRef, ChatRoom, ChatUpdate, CrdtState, DraftMessage, Message, MessagePage, MessageType,
+    ReactionAction, ReadReceipt, TypingEvent,
 };
+use crate::core::shutdown::Shutdown;
 use anyhow::{Context, Result};
 use braid_blob::BlobStore;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::fs;
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, OnceCell, RwLock};
 use tracing::{info, warn};
 use uuid::Uuid;
 
@@ -38,10 +42,21 @@ pub enum UpdateType {
     Message,
     Presence,
     Typing,
+    ReadReceipt,
     RoomUpdate,
     Sync,
 }
 
+/// Repeated "still typing" signals from one user within this window are dropped
+const TYPING_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// How long a send's `client_msg_id` is remembered for deduplicating retries
+const CLIENT_MSG_ID_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Id of the message a keyed send created (once it has), and when the key
+/// was first seen
+type KeyedSend = (Arc<OnceCell<String>>, Instant);
+
 /// JSON-based chat store with CRDT support
 pub struct JsonChatStore {
     config: ChatServerConfig,
@@ -53,6 +68,12 @@ pub struct JsonChatStore {
     channels: RwLock<HashMap<String, UpdateChannel>>,
     /// Draft messages for offline support
     drafts: RwLock<HashMap<String, Vec<DraftMessage>>>,
+    /// Typing channels for each room, separate from persisted updates
+    typing: RwLock<HashMap<String, broadcast::Sender<TypingEvent>>>,
+    /// Last typing signal sent per (room, user), for debouncing
+    typing_sent: RwLock<HashMap<(String, String), (bool, Instant)>>,
+    /// Message id created for each recent (room, client_msg_id)
+    client_msg_ids: RwLock<HashMap<(String, String), KeyedSend>>,
 }
 
 /// Room data including CRDT state
@@ -80,6 +101,9 @@ impl JsonChatStore {
             rooms: RwLock::new(HashMap::new()),
             channels: RwLock::new(HashMap::new()),
             drafts: RwLock::new(HashMap::new()),
+            typing: RwLock::new(HashMap::new()),
+            typing_sent: RwLock::new(HashMap::new()),
+            client_msg_ids: RwLock::new(HashMap::new()),
         };
 
         // Load existing rooms
@@ -146,11 +170,14 @@ impl JsonChatStore {
         path: &Path,
     ) -> Result<(ChatRoom, ChatCrdt)> {
         let content = fs::read_to_string(path).await?;
-        let room: ChatRoom = serde_json::from_str(&content)
+        let mut room: ChatRoom = serde_json::from_str(&content)
             .with_context(|| format!("Failed to parse room {} JSON", room_id))?;
 
         // Extract CRDT state from room or create new
-        let crdt = if !room.crdt_state.version_graph.is_empty() {
+        // export_state doesn't fill in the version graph, so go by messages too
+        let has_state =
+            !room.crdt_state.version_graph.is_empty() || !room.crdt_state.messages.is_empty();
+        let crdt = if has_state {
             // Convert old format to new ChatCrdt
             ChatCrdt::import_state(ChatCrdtState {
                 room_id: room_id.to_string(),
@@ -159,11 +186,40 @@ impl JsonChatStore {
                 current_version: room.crdt_state.current_version.clone(),
                 version_graph: room.crdt_state.version_graph.clone(),
                 messages: room.crdt_state.messages.clone(),
+                reactions: room.crdt_state.reactions.clone(),
             })
         } else {
             ChatCrdt::new(room_id, &self.config.node_id)
         };
 
+        // Rooms from before membership was tracked have no participant list.
+        // Seed it once from the creator and everyone who has posted, so
+        // membership never depends on message senders again.
+        if room.participants.is_empty() {
+            let mut members = vec![room.created_by.clone()];
+            let mut senders: Vec<&String> = crdt
+                .messages()
+                .values()
+                .map(|m| &m.sender)
+                .filter(|s| s.as_str() != "anonymous" && s.as_str() != "@BraidBot")
+                .collect();
+            senders.sort();
+            for sender in senders {
+                if !members.contains(sender) {
+                    members.push(sender.clone());
+                }
+            }
+            room.participants = members;
+            let room_data = RoomData { room, crdt };
+            self.save_room_to_disk(&room_data).await?;
+            info!(
+                "Seeded {} participants for room {}",
+                room_data.room.participants.len(),
+                room_id
+            );
+            return Ok((room_data.room, room_data.crdt));
+        }
+
         Ok((room, crdt))
     }
 
@@ -181,6 +237,7 @@ impl JsonChatStore {
             current_version: crdt_state.current_version,
             version_graph: crdt_state.version_graph,
             messages: crdt_state.messages,
+            reactions: crdt_state.reactions,
         };
 
         // Serialize room
@@ -245,6 +302,41 @@ impl JsonChatStore {
         Ok(room)
     }
 
+    /// Create `room`, adding its creator to the participants
+    ///
+    /// This is the room's authoritative membership. Returns `None` if the
+    /// room already exists.
+    pub async fn create_room(
+        &self,
+        mut room: ChatRoom,
+    ) -> Result<Option<ChatRoom>> {
+        let mut rooms = self.rooms.write().await;
+        if rooms.contains_key(&room.id) || self.room_path(&room.id).exists() {
+            return Ok(None);
+        }
+
+        let mut members = Vec::with_capacity(room.participants.len() + 1);
+        for member in std::iter::once(&room.created_by).chain(&room.participants) {
+            if !members.contains(member) {
+                members.push(member.clone());
+            }
+        }
+        room.participants = members;
+        let room_data = RoomData {
+            room: room.clone(),
+            crdt: ChatCrdt::new(&room.id, &self.config.node_id),
+        };
+        self.save_room_to_disk(&room_data).await?;
+        rooms.insert(room.id.clone(), Arc::new(RwLock::new(room_data)));
+
+        info!(
+            "Created room {} with {} participants",
+            room.id,
+            room.participants.len()
+        );
+        Ok(Some(room))
+    }
+
     /// Get a room if it exists
     pub async fn get_room(&self, room_id: &str) -> Result<Option<Arc<RwLock<RoomData>>>> {
         {
@@ -282,12 +374,20 @@ impl JsonChatStore {
         let room_lock = self.get_or_create_room(room_id, Some(sender)).await?;
         let mut room_data = room_lock.write().await;
 
+        let hashes: Vec<String> = blob_refs.iter().map(|b| b.hash.clone()).collect();
+
         // Use CRDT to add message
         let (version, message) =
             room_data
                 .crdt
                 .add_message(sender, content, msg_type, reply_to.as_deref(), blob_refs);
 
+        // Keep attachments alive for as long as the message references them
+        let referrer = format!("/chat/{}/{}", room_id, message.id);
+        for hash in &hashes {
+            self.blob_store.add_ref(hash, &referrer).await?;
+        }
+
         // Save to disk
         self.save_room_to_disk(&*room_data).await?;
 
@@ -308,6 +408,45 @@ impl JsonChatStore {
         Ok(message)
     }
 
+    /// Run `add` unless `client_msg_id` already created a message in the room
+    ///
+    /// Clients reuse the key when retrying a send, so a retry returns the
+    /// first attempt's message instead of posting it again, even if the
+    /// first attempt is still in flight. A failed attempt doesn't use up
+    /// the key. Keys are forgotten after `CLIENT_MSG_ID_WINDOW`.
+    ///
+    /// Returns the message and whether this call created it.
+    pub async fn add_message_once(
+        &self,
+        room_id: &str,
+        client_msg_id: &str,
+        add: impl Future<Output = Result<Message>>,
+    ) -> Result<(Message, bool)> {
+        let sent = {
+            let mut keys = self.client_msg_ids.write().await;
+            keys.retain(|_, (_, at)| at.elapsed() < CLIENT_MSG_ID_WINDOW);
+            let key = (room_id.to_string(), client_msg_id.to_string());
+            let (sent, _) = keys
+                .entry(key)
+                .or_insert_with(|| (Arc::default(), Instant::now()));
+            sent.clone()
+        };
+
+        let mut created = None;
+        let msg_id = sent
+            .get_or_try_init(|| async {
+                let message = add.await?;
+                let msg_id = message.id.clone();
+                created = Some(message);
+                Ok::<_, anyhow::Error>(msg_id)
+            })
+            .await?;
+        match created {
+            Some(message) => Ok((message, true)),
+            None => Ok((self.get_message(room_id, msg_id).await?, false)),
+        }
+    }
+
     /// Edit a message using CRDT
     pub async fn edit_message(
         &self,
@@ -342,6 +481,74 @@ impl JsonChatStore {
         Ok(message)
     }
 
+    /// Soft-delete a message using CRDT, releasing its attachments
+    pub async fn delete_message(&self, room_id: &str, msg_id: &str) -> Result<Message> {
+        let room_lock = self.get_room(room_id).await?.context("Room not found")?;
+        let mut room_data = room_lock.write().await;
+
+        let hashes: Vec<String> = room_data
+            .crdt
+            .get_message(msg_id)
+            .map(|m| m.blob_refs.iter().map(|b| b.hash.clone()).collect())
+            .unwrap_or_default();
+        let (version, message) = room_data.crdt.delete_message(msg_id)?;
+
+        let referrer = format!("/chat/{}/{}", room_id, msg_id);
+        for hash in &hashes {
+            if let Err(e) = self.blob_store.remove_ref(hash, &referrer).await {
+                warn!("Failed to release blob {} for {}: {}", hash, referrer, e);
+            }
+        }
+
+        // Save to disk
+        self.save_room_to_disk(&room_data).await?;
+
+        // Broadcast update
+        let update = RoomUpdate {
+            room_id: room_id.to_string(),
+            update_type: UpdateType::Message,
+            data: serde_json::to_value(&message)?,
+            crdt_version: Some(version),
+        };
+        self.broadcast(room_id, update).await?;
+
+        info!("Deleted message {} in room {}", msg_id, room_id);
+
+        Ok(message)
+    }
+
+    /// Add or remove `user`'s `emoji` reaction on a message using CRDT
+    pub async fn set_reaction(
+        &self,
+        room_id: &str,
+        msg_id: &str,
+        user: &str,
+        emoji: &str,
+        action: ReactionAction,
+    ) -> Result<Message> {
+        let room_lock = self.get_room(room_id).await?.context("Room not found")?;
+        let mut room_data = room_lock.write().await;
+
+        let (version, message) = match action {
+            ReactionAction::Add => room_data.crdt.add_reaction(msg_id, user, emoji)?,
+            ReactionAction::Remove => room_data.crdt.remove_reaction(msg_id, user, emoji)?,
+        };
+
+        // Save to disk
+        self.save_room_to_disk(&room_data).await?;
+
+        // Broadcast update
+        let update = RoomUpdate {
+            room_id: room_id.to_string(),
+            update_type: UpdateType::Message,
+            data: serde_json::to_value(&message)?,
+            crdt_version: Some(version),
+        };
+        self.broadcast(room_id, update).await?;
+
+        Ok(message)
+    }
+
     /// Get messages for a room (from CRDT state)
     pub async fn get_messages(
         &self,
@@ -373,6 +580,162 @@ impl JsonChatStore {
         Ok(messages)
     }
 
+    /// Get up to `limit` messages older than `before`, newest first
+    ///
+    /// `before` is a message id or version from a previous page; `None`
+    /// starts from the newest message. Deleted messages are skipped but can
+    /// still serve as cursors.
+    pub async fn get_messages_page(
+        &self,
+        room_id: &str,
+        before: Option<&str>,
+        limit: usize,
+    ) -> Result<MessagePage> {
+        let room_lock = self.get_room(room_id).await?.context("Room not found")?;
+        let room_data = room_lock.read().await;
+
+        // HashMap order is arbitrary; creation time then version is stable
+        let mut all: Vec<&Message> = room_data.crdt.messages().values().collect();
+        all.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| version_order(&b.version).cmp(&version_order(&a.version)))
+        });
+
+        let start = match before {
+            Some(cursor) => {
+                all.iter()
+                    .position(|m| m.id == cursor || m.version == cursor)
+                    .with_context(|| format!("Unknown cursor: {}", cursor))?
+                    + 1
+            }
+            None => 0,
+        };
+
+        let mut older = all[start..].iter().filter(|m| !m.deleted);
+        let messages: Vec<Message> = older.by_ref().take(limit).map(|m| (*m).clone()).collect();
+        let next_cursor = match (messages.last(), older.next()) {
+            (Some(last), Some(_)) => Some(last.id.clone()),
+            _ => None,
+        };
+
+        Ok(MessagePage {
+            messages,
+            next_cursor,
+        })
+    }
+
+    /// Search a room's messages, newest first; see [`ChatCrdt::search`]
+    pub async fn search_messages(
+        &self,
+        room_id: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<Message>> {
+        let room_lock = self.get_room(room_id).await?.context("Room not found")?;
+        let room_data = room_lock.read().await;
+        Ok(room_data
+            .crdt
+            .search(query, limit)
+            .into_iter()
+            .cloned()
+            .collect())
+    }
+
+    /// Whether `user` belongs to a room: its creator or a listed participant
+    pub async fn is_participant(&self, room_id: &str, user: &str) -> Result<bool> {
+        let room_lock = self.get_room(room_id).await?.context("Room not found")?;
+        let room_data = room_lock.read().await;
+        let room = &room_data.room;
+        Ok(room.created_by == user || room.participants.iter().any(|p| p == user))
+    }
+
+    /// Move `user`'s read marker up to the message at `version`
+    ///
+    /// Markers only move forward: reading an older message leaves the
+    /// marker where it is. Returns the marker after the call.
+    pub async fn mark_read(&self, room_id: &str, user: &str, version: &str) -> Result<ReadReceipt> {
+        let room_lock = self.get_room(room_id).await?.context("Room not found")?;
+        let mut room_data = room_lock.write().await;
+
+        // Compare by position in the room, not by version string
+        let position = |version: &str| {
+            room_data
+                .crdt
+                .get_message_by_version(version)
+                .map(|m| (m.created_at, m.id.clone()))
+        };
+        let new_position =
+            position(version).with_context(|| format!("Unknown version: {}", version))?;
+        if let Some(current) = room_data.room.read_markers.get(user) {
+            if position(&current.version).is_some_and(|p| p >= new_position) {
+                return Ok(current.clone());
+            }
+        }
+
+        let receipt = ReadReceipt {
+            user: user.to_string(),
+            version: version.to_string(),
+            read_at: Utc::now(),
+        };
+        room_data
+            .room
+            .read_markers
+            .insert(user.to_string(), receipt.clone());
+
+        // Save to disk
+        self.save_room_to_disk(&room_data).await?;
+        drop(room_data);
+
+        // Broadcast update
+        let update = RoomUpdate {
+            room_id: room_id.to_string(),
+            update_type: UpdateType::ReadReceipt,
+            data: serde_json::to_value(&receipt)?,
+            crdt_version: None,
+        };
+        self.broadcast(room_id, update).await?;
+
+        Ok(receipt)
+    }
+
+    /// Every participant's read marker in a room, by user
+    pub async fn read_receipts(&self, room_id: &str) -> Result<Vec<ReadReceipt>> {
+        let room_lock = self.get_room(room_id).await?.context("Room not found")?;
+        let room_data = room_lock.read().await;
+        let mut receipts: Vec<_> = room_data.room.read_markers.values().cloned().collect();
+        receipts.sort_by(|a, b| a.user.cmp(&b.user));
+        Ok(receipts)
+    }
+
+    /// Drop CRDT history that every participant has read, or that is older
+    /// than `retention`, from every loaded room
+    ///
+    /// Versions read markers point at are kept so `mark_read` can still
+    /// order them. Message content is never dropped. Returns how many
+    /// entries were pruned across all rooms.
+    pub async fn prune_history(&self, retention: Duration) -> Result<usize> {
+        let cutoff = chrono::Duration::from_std(retention)
+            .ok()
+            .and_then(|retention| Utc::now().checked_sub_signed(retention))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        let rooms: Vec<_> = self.rooms.read().await.values().cloned().collect();
+
+        let mut total = 0;
+        for room_lock in rooms {
+            let mut room_data = room_lock.write().await;
+            let RoomData { room, crdt } = &mut *room_data;
+            let acked = read_through(room, crdt).max(crdt.seq_before(cutoff));
+            let pinned = room.read_markers.values().map(|m| m.version.as_str());
+            let pruned = crdt.prune_acknowledged(acked, pinned);
+            if pruned > 0 {
+                self.save_room_to_disk(&room_data).await?;
+                total += pruned;
+            }
+        }
+        Ok(total)
+    }
+
     /// Get messages that are descendants of the given parents (for catch-up sync)
     /// Based on xfmail's get_messages_since_parents - returns messages newer than parents
     pub async fn get_messages_since_parents(
@@ -449,6 +812,11 @@ impl JsonChatStore {
             .context("Message not found")
     }
 
+    /// Get the server configuration
+    pub fn config(&self) -> &ChatServerConfig {
+        &self.config
+    }
+
     /// Get blob store reference
     pub fn blob_store(&self) -> &BlobStore {
         &self.blob_store
@@ -473,6 +841,40 @@ impl JsonChatStore {
         Ok(())
     }
 
+    /// Get the typing channel for a room
+    pub async fn typing_channel(&self, room_id: &str) -> broadcast::Sender<TypingEvent> {
+        let mut typing = self.typing.write().await;
+        typing
+            .entry(room_id.to_string())
+            .or_insert_with(|| broadcast::channel(32).0)
+            .clone()
+    }
+
+    /// Tell a room's subscribers whether `user` is typing
+    ///
+    /// Changes go out immediately; a repeat of the same state within
+    /// `TYPING_DEBOUNCE` is dropped. Returns whether the event was sent.
+    pub async fn send_typing(&self, room_id: &str, user: &str, typing: bool) -> bool {
+        {
+            let mut sent = self.typing_sent.write().await;
+            let key = (room_id.to_string(), user.to_string());
+            let now = Instant::now();
+            if let Some((last, at)) = sent.get(&key) {
+                if *last == typing && now.duration_since(*at) < TYPING_DEBOUNCE {
+                    return false;
+                }
+            }
+            sent.insert(key, (typing, now));
+        }
+
+        let event = TypingEvent {
+            user: user.to_string(),
+            typing,
+        };
+        let _ = self.typing_channel(room_id).await.send(event);
+        true
+    }
+
     /// Merge remote CRDT updates into a room
     pub async fn merge_updates(
         &self,
@@ -485,6 +887,20 @@ impl JsonChatStore {
         // Use CRDT to merge
         let new_messages = room_data.crdt.merge_updates(updates);
 
+        // Track attachments like local sends and deletes do
+        for message in &new_messages {
+            let referrer = format!("/chat/{}/{}", room_id, message.id);
+            if message.deleted {
+                if let Err(e) = self.blob_store.release(&referrer).await {
+                    warn!("Failed to release blobs for {}: {}", referrer, e);
+                }
+            } else {
+                for blob in &message.blob_refs {
+                    self.blob_store.add_ref(&blob.hash, &referrer).await?;
+                }
+            }
+        }
+
         // Save to disk
         if !new_messages.is_empty() {
             self.save_room_to_disk(&*room_data).await?;
@@ -556,6 +972,47 @@ impl JsonChatStore {
     }
 }
 
+/// Highest sequence number every participant has read through
+///
+/// Participants are the room's listed members plus anyone who has sent a
+/// message; if any of them has no read marker yet, nothing counts as read.
+fn read_through(room: &ChatRoom, crdt: &ChatCrdt) -> u64 {
+    let senders = crdt.messages().values().map(|m| m.sender.as_str());
+    let readers = room.participants.iter().map(String::as_str).chain(senders);
+    readers
+        .filter(|user| !user.is_empty())
+        .map(|user| {
+            room.read_markers
+                .get(user)
+                .map(|m| version_order(&m.version).0)
+        })
+        .min()
+        .flatten()
+        .unwrap_or(0)
+}
+
+/// Prune chat history every `interval` until `shutdown` fires
+pub async fn prune_history_task(
+    store: Arc<JsonChatStore>,
+    interval: Duration,
+    retention: Duration,
+    shutdown: Shutdown,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown.wait() => break,
+        }
+        match store.prune_history(retention).await {
+            Ok(0) => {}
+            Ok(pruned) => info!("Pruned {} chat history entries", pruned),
+            Err(e) => warn!("Failed to prune chat history: {}", e),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -576,6 +1033,75 @@ mod tests {
         assert_eq!(room.room.created_by, "user1");
     }
 
+    #[tokio::test]
+    async fn test_create_room_admits_only_its_members() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+
+        let mut room = ChatRoom::new("team", "Team", "alice");
+        room.participants = vec!["bob".to_string(), "alice".to_string()];
+        room.is_direct_message = true;
+        let room = store.create_room(room).await.unwrap().unwrap();
+        assert_eq!(room.participants, vec!["alice", "bob"]);
+        assert!(store.is_participant("team", "bob").await.unwrap());
+        assert!(!store.is_participant("team", "mallory").await.unwrap());
+
+        let listed = store.list_rooms().await;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "Team");
+        assert_eq!(listed[0].participants, vec!["alice", "bob"]);
+        assert!(listed[0].is_direct_message);
+
+        // The id is taken now, whoever asks
+        let again = store.create_room(ChatRoom::new("team", "Other", "mallory")).await;
+        assert!(again.unwrap().is_none());
+        assert!(!store.is_participant("team", "mallory").await.unwrap());
+        assert!(store.is_participant("missing", "alice").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_rooms_get_participants_seeded_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        {
+            // A room from before participant lists, with a couple of posts
+            let store = JsonChatStore::new(config.clone()).await.unwrap();
+            for sender in ["bob", "anonymous", "carol", "bob"] {
+                store
+                    .add_message("old", sender, "hi", MessageType::Text, None, vec![])
+                    .await
+                    .unwrap();
+            }
+            let room = store.get_room("old").await.unwrap().unwrap();
+            assert!(room.read().await.room.participants.is_empty());
+        }
+
+        let store = JsonChatStore::new(config.clone()).await.unwrap();
+        let room = store.get_room("old").await.unwrap().unwrap();
+        assert_eq!(room.read().await.room.participants, vec!["bob", "carol"]);
+        assert!(store.is_participant("old", "carol").await.unwrap());
+        assert!(!store.is_participant("old", "anonymous").await.unwrap());
+
+        // Posting no longer grants membership
+        store
+            .add_message(
+                "old",
+                "mallory",
+                "let me in",
+                MessageType::Text,
+                None,
+                vec![],
+            )
+            .await
+            .unwrap();
+        assert!(!store.is_participant("old", "mallory").await.unwrap());
+
+        // The seeded list was saved, so it isn't re-derived on the next load
+        let store = JsonChatStore::new(config).await.unwrap();
+        assert!(!store.is_participant("old", "mallory").await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_add_message_uses_crdt() {
         let temp_dir = TempDir::new().unwrap();
@@ -604,4 +1130,355 @@ mod tests {
         assert_eq!(messages.len(), 1);
         assert_eq!(messages[0].content, "Hello, world!");
     }
+
+    #[tokio::test]
+    async fn test_remote_messages_hold_and_release_blob_refs() {
+        use crate::core::models::{ChatPatch, ChatUpdate};
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+        let hash = store
+            .blob_store()
+            .put_with_type(bytes::Bytes::from_static(b"picture"), None)
+            .await
+            .unwrap();
+        let update = |version: &str, patch| ChatUpdate {
+            version: version.to_string(),
+            parents: vec![],
+            patches: vec![patch],
+            timestamp: Utc::now(),
+            author: "peer".to_string(),
+        };
+
+        let add = ChatPatch::AddMessage {
+            id: "remote-1".to_string(),
+            content: "look".to_string(),
+            sender: "bob".to_string(),
+            message_type: MessageType::Text,
+            reply_to: None,
+            blob_refs: vec![BlobRef {
+                hash: hash.clone(),
+                content_type: "image/png".to_string(),
+                filename: "picture.png".to_string(),
+                size: 7,
+                inline_data: None,
+            }],
+        };
+        store
+            .merge_updates("test-room", vec![update("1@peer", add)])
+            .await
+            .unwrap();
+        assert_eq!(store.blob_store().ref_count(&hash).await.unwrap(), 1);
+
+        let delete = ChatPatch::DeleteMessage {
+            id: "remote-1".to_string(),
+        };
+        store
+            .merge_updates("test-room", vec![update("2@peer", delete)])
+            .await
+            .unwrap();
+        assert_eq!(store.blob_store().ref_count(&hash).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_typing_is_broadcast_not_persisted() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+        store
+            .get_or_create_room("test-room", Some("user1"))
+            .await
+            .unwrap();
+
+        let mut rx = store.typing_channel("test-room").await.subscribe();
+        assert!(store.send_typing("test-room", "user1", true).await);
+        // Keystrokes right after are debounced; stopping is not
+        assert!(!store.send_typing("test-room", "user1", true).await);
+        assert!(store.send_typing("test-room", "user1", false).await);
+
+        let started = rx.recv().await.unwrap();
+        assert_eq!(started.user, "user1");
+        assert!(started.typing);
+        assert!(!rx.recv().await.unwrap().typing);
+        assert!(rx.try_recv().is_err());
+
+        assert!(store
+            .get_messages("test-room", None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_markers_per_participant() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+
+        let mut versions = Vec::new();
+        for i in 0..3 {
+            let msg = store
+                .add_message(
+                    "test-room",
+                    "alice",
+                    &format!("msg {}", i),
+                    MessageType::Text,
+                    None,
+                    vec![],
+                )
+                .await
+                .unwrap();
+            versions.push(msg.version);
+        }
+
+        let mut rx = store.get_channel("test-room").await.tx.subscribe();
+        store
+            .mark_read("test-room", "alice", &versions[2])
+            .await
+            .unwrap();
+        store
+            .mark_read("test-room", "bob", &versions[1])
+            .await
+            .unwrap();
+        assert!(matches!(
+            rx.recv().await.unwrap().update_type,
+            UpdateType::ReadReceipt
+        ));
+
+        // Going back is ignored
+        let kept = store
+            .mark_read("test-room", "alice", &versions[0])
+            .await
+            .unwrap();
+        assert_eq!(kept.version, versions[2]);
+        assert!(store
+            .mark_read("test-room", "bob", "99@nowhere")
+            .await
+            .is_err());
+
+        let receipts = store.read_receipts("test-room").await.unwrap();
+        let markers: Vec<_> = receipts
+            .iter()
+            .map(|r| (r.user.as_str(), r.version.as_str()))
+            .collect();
+        assert_eq!(
+            markers,
+            vec![
+                ("alice", versions[2].as_str()),
+                ("bob", versions[1].as_str())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_messages_page_walks_history_with_cursors() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+
+        for i in 0..30 {
+            store
+                .add_message(
+                    "test-room",
+                    "user1",
+                    &format!("msg {}", i),
+                    MessageType::Text,
+                    None,
+                    vec![],
+                )
+                .await
+                .unwrap();
+        }
+
+        let first = store
+            .get_messages_page("test-room", None, 10)
+            .await
+            .unwrap();
+        let contents: Vec<_> = first.messages.iter().map(|m| m.content.clone()).collect();
+        let expected: Vec<_> = (20..30).rev().map(|i| format!("msg {}", i)).collect();
+        assert_eq!(contents, expected);
+        let cursor = first.next_cursor.expect("more pages");
+        assert_eq!(cursor, first.messages[9].id);
+
+        let second = store
+            .get_messages_page("test-room", Some(&cursor), 10)
+            .await
+            .unwrap();
+        let contents: Vec<_> = second.messages.iter().map(|m| m.content.clone()).collect();
+        let expected: Vec<_> = (10..20).rev().map(|i| format!("msg {}", i)).collect();
+        assert_eq!(contents, expected);
+
+        // The last page has no cursor
+        let last = store
+            .get_messages_page("test-room", second.next_cursor.as_deref(), 10)
+            .await
+            .unwrap();
+        assert_eq!(last.messages.len(), 10);
+        assert!(last.next_cursor.is_none());
+
+        assert!(store
+            .get_messages_page("test-room", Some("nope"), 10)
+            .await
+            .is_err());
+    }
+
+    async fn message_contents(store: &JsonChatStore) -> Vec<(String, String, String)> {
+        let messages = store.get_messages("test-room", None).await.unwrap();
+        messages
+            .into_iter()
+            .map(|m| (m.id, m.content, m.version))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_prune_history_waits_for_every_reader() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+        let month = Duration::from_secs(30 * 24 * 60 * 60);
+
+        let first = store
+            .add_message("test-room", "alice", "hi", MessageType::Text, None, vec![])
+            .await
+            .unwrap();
+        store
+            .add_message("test-room", "bob", "hey", MessageType::Text, None, vec![])
+            .await
+            .unwrap();
+        let edited = store
+            .edit_message("test-room", &first.id, "hi!")
+            .await
+            .unwrap();
+        let before = message_contents(&store).await;
+
+        assert_eq!(store.prune_history(month).await.unwrap(), 0);
+        store
+            .mark_read("test-room", "bob", &edited.version)
+            .await
+            .unwrap();
+        // alice hasn't read anything yet
+        assert_eq!(store.prune_history(month).await.unwrap(), 0);
+
+        store
+            .mark_read("test-room", "alice", &edited.version)
+            .await
+            .unwrap();
+        assert_eq!(store.prune_history(month).await.unwrap(), 4);
+        assert_eq!(store.prune_history(month).await.unwrap(), 0);
+        assert_eq!(message_contents(&store).await, before);
+        assert_eq!(
+            store.get_conversation_tips("test-room").await.unwrap(),
+            vec![edited.version.clone()]
+        );
+
+        // Markers still resolve, so they still only move forward
+        let kept = store
+            .mark_read("test-room", "bob", &edited.version)
+            .await
+            .unwrap();
+        assert_eq!(kept.version, edited.version);
+    }
+
+    #[tokio::test]
+    async fn test_prune_history_drops_unread_history_past_retention() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+
+        let msg = store
+            .add_message("test-room", "alice", "hi", MessageType::Text, None, vec![])
+            .await
+            .unwrap();
+        store
+            .edit_message("test-room", &msg.id, "hi!")
+            .await
+            .unwrap();
+        let before = message_contents(&store).await;
+
+        assert_eq!(
+            store
+                .prune_history(Duration::from_secs(3600))
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(store.prune_history(Duration::ZERO).await.unwrap() > 0);
+        assert_eq!(message_contents(&store).await, before);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_client_msg_id_creates_one_message() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+        let send = |content: &'static str| {
+            store.add_message(
+                "test-room",
+                "alice",
+                content,
+                MessageType::Text,
+                None,
+                vec![],
+            )
+        };
+
+        let (first, created) = store
+            .add_message_once("test-room", "key-1", send("hello"))
+            .await
+            .unwrap();
+        assert!(created);
+        let (retry, created) = store
+            .add_message_once("test-room", "key-1", send("hello"))
+            .await
+            .unwrap();
+        assert!(!created);
+        assert_eq!(retry.id, first.id);
+        assert_eq!(
+            store.get_messages("test-room", None).await.unwrap().len(),
+            1
+        );
+
+        // Other keys, and the same key in another room, still send
+        let (other, created) = store
+            .add_message_once("test-room", "key-2", send("again"))
+            .await
+            .unwrap();
+        assert!(created);
+        assert_ne!(other.id, first.id);
+        let elsewhere =
+            store.add_message("other-room", "alice", "hi", MessageType::Text, None, vec![]);
+        assert!(
+            store
+                .add_message_once("other-room", "key-1", elsewhere)
+                .await
+                .unwrap()
+                .1
+        );
+        assert_eq!(
+            store.get_messages("test-room", None).await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_send_does_not_use_up_client_msg_id() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ChatServerConfig::with_base_dir(temp_dir.path());
+        let store = JsonChatStore::new(config).await.unwrap();
+
+        let failed = async { Err(anyhow::anyhow!("disk full")) };
+        assert!(store
+            .add_message_once("test-room", "key-1", failed)
+            .await
+            .is_err());
+
+        let retry = store.add_message("test-room", "alice", "hi", MessageType::Text, None, vec![]);
+        let (message, created) = store
+            .add_message_once("test-room", "key-1", retry)
+            .await
+            .unwrap();
+        assert!(created);
+        assert_eq!(message.content, "hi");
+    }
 }
@@ -11,8 +11,9 @@ pub use crate::core::ctx::Ctx;
 pub use crate::core::error::{Error, Result};
 pub use crate::core::config::{AppState, ChatServerConfig};
 
-use ax_auth::mw_require_auth;
-use axum::{routing::get, Router, middleware, response::IntoResponse, extract::{Path, State, Request}};
+use ax_auth::{authenticate, mw_require_auth};
+use axum::{routing::get, Router, middleware, response::IntoResponse, extract::{Path, Query, State, Request}};
+use axum::http::{Method, StatusCode};
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -24,8 +25,11 @@ use crate::chat::friends::FriendManager;
 use crate::core::store::json_store::JsonChatStore;
 use crate::chat::ai::{AiChatManager, AiConfig};
 use crate::core::daemon::DaemonIntegration;
+use crate::core::dispatch::RouteTarget;
 use crate::chat::mail::MailManager;
 use crate::core::pages::{LocalOrgManager, PagesManager};
+use crate::core::presence::PresenceTracker;
+use crate::core::shutdown::{self, Shutdown};
 
 // Alias authentication middleware for clarity
 use crate::core::auth::middleware as ax_auth;
@@ -53,18 +57,36 @@ pub async fn run() -> anyhow::Result<()> {
 
     info!("Storage directory: {:?}", config.storage_dir);
 
+    // Ctrl-C / SIGTERM stop the server and background tasks
+    let shutdown = Shutdown::new();
+    tokio::spawn(shutdown::listen_for_signals(shutdown.clone()));
+
     // 1. Initialize Core Infrastructure
-    let auth_manager = Arc::new(AuthManager::new(&braid_root).await?);
+    let auth_manager =
+        Arc::new(AuthManager::new(&braid_root).await?.with_bcrypt_cost(config.bcrypt_cost));
     let store = Arc::new(JsonChatStore::new(config.clone()).await?);
+    tokio::spawn(crate::core::store::json_store::prune_history_task(
+        store.clone(),
+        config.history_prune_interval,
+        config.history_retention,
+        shutdown.clone(),
+    ));
     
     // 2. Initialize Chat Services
-    let friend_manager = Arc::new(FriendManager::new(&braid_root).await?);
-    let mail_manager = Arc::new(MailManager::new(store.clone()));
+    let presence = Arc::new(PresenceTracker::new());
+    // Friends share the auth database pool (both live in users.sqlite)
+    let friend_manager = Arc::new(
+        FriendManager::from_pool(auth_manager.pool().clone())
+            .await?
+            .with_presence(presence.clone()),
+    );
+    let mail_manager = Arc::new(MailManager::new(store.clone()).with_shutdown(shutdown.clone()));
     
     let ai_manager = if std::env::var("DISABLE_AI").is_err() {
         let ai_config = AiConfig::default();
         let ai = Arc::new(AiChatManager::new(ai_config, store.clone(), &config.storage_dir).await?);
-        let _ = ai.start_watching().await;
+        ai.register_saved_rooms().await?;
+        let _ = ai.start_watching(shutdown.clone()).await;
         Some(ai)
     } else {
         None
@@ -84,14 +106,17 @@ pub async fn run() -> anyhow::Result<()> {
     local_org_manager.ensure_dir().await?;
 
     // 4. Initialize Shared Integrations
+    let mut daemon_watcher = None;
     let daemon = if config.enable_daemon {
         match DaemonIntegration::new(config.clone(), store.clone()).await {
             Ok((daemon_integration, file_events_rx)) => {
                 let daemon = Arc::new(daemon_integration);
                 let daemon_clone = daemon.clone();
-                tokio::spawn(async move {
-                    crate::core::daemon::file_watcher_task(file_events_rx, daemon_clone).await;
-                });
+                let shutdown = shutdown.clone();
+                daemon_watcher = Some(tokio::spawn(async move {
+                    crate::core::daemon::file_watcher_task(file_events_rx, daemon_clone, shutdown)
+                        .await;
+                }));
                 Some(daemon)
             }
             Err(_) => None,
@@ -106,6 +131,7 @@ pub async fn run() -> anyhow::Result<()> {
         store,
         auth: auth_manager,
         friends: friend_manager,
+        presence,
         ai_manager,
         daemon,
         mail_manager,
@@ -113,16 +139,42 @@ pub async fn run() -> anyhow::Result<()> {
         local_org_manager,
     };
 
-    // Build the Modular Router
+    let app = app(app_state);
+
+    // Start server
+    let port = std::env::var("PORT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(3001);
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
     
+    info!("Server starting at http://localhost:{}", port);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    shutdown::serve(listener, app, shutdown).await?;
+
+    // Let the daemon watcher flush queued events before exiting
+    if let Some(watcher) = daemon_watcher {
+        if tokio::time::timeout(std::time::Duration::from_secs(5), watcher).await.is_err() {
+            warn!("Daemon file watcher did not stop in time");
+        }
+    }
+
+    Ok(())
+}
+
+/// Build the Modular Router
+pub(crate) fn app(app_state: AppState) -> Router {
     // Services routers
-    let core_router = core::router();
+    let core_router = core::router(&app_state.config);
     let chat_router = chat::router()
         .route_layer(middleware::from_fn_with_state(app_state.clone(), mw_require_auth));
     let pages_router = core::pages::router();
+    let mail_router = chat::mail::router()
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), mw_require_auth));
 
     // Main App Router
-    let app = Router::new()
+    Router::new()
         // Protocol-Driven Dispatcher (the entrance)
         .route("/{*path}", get(dispatch_get).put(dispatch_put))
         
@@ -130,59 +182,54 @@ pub async fn run() -> anyhow::Result<()> {
         .merge(core_router)
         .merge(chat_router)
         .merge(pages_router)
+        .merge(mail_router)
         
         // Global routes
-        .route("/health", get(health_check))
+        .route("/health", get(core::health::health_check))
         
         // State and Layers
         .with_state(app_state)
         .layer(tower_http::cors::CorsLayer::permissive())
-        .layer(tower_http::trace::TraceLayer::new_for_http());
-
-    // Start server
-    let port = std::env::var("PORT")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(3001);
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    
-    info!("Server starting at http://localhost:{}", port);
-
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
-
-    Ok(())
-}
-
-async fn health_check() -> &'static str {
-    "OK - Braid Server (Modular Architecture)"
+        .layer(tower_http::trace::TraceLayer::new_for_http())
 }
 
 // Braid Protocol Dispatcher
-// 
-// Routes requests based on Merge-Type or extension to Chat or Website services.
+//
+// Routes catch-all requests to Chat or Website services; see
+// `core::dispatch` for how the target is chosen.
 
 async fn dispatch_get(
-    _uri: axum::http::Uri,
     headers: axum::http::HeaderMap,
+    uri: axum::http::Uri,
     Path(path): Path<String>,
     State(state): State<AppState>,
 ) -> axum::response::Response {
-    let merge_type = headers.get("Merge-Type").and_then(|h| h.to_str().ok());
-    let is_simpleton = merge_type == Some("simpleton");
-    let has_extension = std::path::Path::new(&path).extension().is_some();
-    let is_local_org = path.starts_with("local.org/");
-    
-    if is_local_org {
-        crate::core::pages::handlers::get_local_page(Path(path.replace("local.org/", "")), State(state), headers).await
-    } else if is_simpleton || has_extension {
-        crate::core::pages::handlers::get_wiki_page(Path(path), State(state), headers).await
-    } else {
-        crate::chat::handlers::braid_subscribe::braid_subscribe(
-            Path(path),
-            State(state),
-            headers,
-        ).await.into_response()
+    match state.config.routes.resolve(&Method::GET, &path, &headers) {
+        RouteTarget::LocalPage(page) => {
+            crate::core::pages::handlers::get_local_page(Path(page), State(state), headers).await
+        }
+        RouteTarget::Wiki(page) => {
+            // Only pages take query options, so other targets never reject one
+            let query = match Query::try_from_uri(&uri) {
+                Ok(query) => query,
+                Err(e) => return e.into_response(),
+            };
+            crate::core::pages::handlers::get_wiki_page(Path(page), query, State(state), headers).await
+        }
+        RouteTarget::Chat(room_id) => {
+            let ctx = match authenticate(&state, &headers).await {
+                Ok(ctx) => ctx,
+                Err(e) => return e.into_response(),
+            };
+            crate::chat::handlers::braid_subscribe::braid_subscribe(
+                Path(room_id),
+                State(state),
+                headers,
+                ctx,
+            )
+            .await
+            .into_response()
+        }
     }
 }
 
@@ -191,57 +238,54 @@ async fn dispatch_put(
     State(state): State<AppState>,
     request: Request,
 ) -> axum::response::Response {
-    let headers = request.headers().clone();
-    let merge_type = headers.get("Merge-Type").and_then(|h| h.to_str().ok());
-    let is_simpleton = merge_type == Some("simpleton");
-    let has_extension = std::path::Path::new(&path).extension().is_some();
-    let is_local_org = path.starts_with("local.org/");
+    let target = state.config.routes.resolve(&Method::PUT, &path, request.headers());
 
     let (parts, body) = request.into_parts();
-    let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap_or_default();
-    let body_str = String::from_utf8(bytes.to_vec()).unwrap_or_default();
-
-    if is_local_org {
-        return crate::core::pages::handlers::put_local_page(
-            Path(path.replace("local.org/", "")),
-            State(state),
-            parts.headers,
-            body_str
-        ).await.into_response();
-    }
-
-    if is_simpleton || has_extension {
-
-        return crate::core::pages::handlers::put_wiki_page(
-            Path(path),
-            State(state),
-            parts.headers,
-            body_str
-        ).await.into_response();
-    }
+    let bytes = match crate::core::dispatch::read_body(body, state.config.max_request_body).await {
+        Ok(bytes) => bytes,
+        Err(status) => {
+            warn!("Rejected PUT body for {}: {}", path, status);
+            return status.into_response();
+        }
+    };
 
-    // Default: Chat
-    match serde_json::from_slice::<crate::core::models::CreateMessageInput>(&bytes) {
-        Ok(json) => {
+    match target {
+        RouteTarget::LocalPage(page) => {
+            let body_str = String::from_utf8(bytes.to_vec()).unwrap_or_default();
+            crate::core::pages::handlers::put_local_page(Path(page), State(state), parts.headers, body_str)
+                .await
+                .into_response()
+        }
+        RouteTarget::Wiki(page) => {
+            let body_str = String::from_utf8(bytes.to_vec()).unwrap_or_default();
+            crate::core::pages::handlers::put_wiki_page(Path(page), State(state), parts.headers, body_str)
+                .await
+                .into_response()
+        }
+        RouteTarget::Chat(room_id) => {
+            let ctx = match authenticate(&state, &parts.headers).await {
+                Ok(ctx) => ctx,
+                Err(e) => return e.into_response(),
+            };
+            let json = match serde_json::from_slice::<crate::core::models::CreateMessageInput>(&bytes) {
+                Ok(json) => json,
+                Err(e) => {
+                    warn!("Invalid chat message for {}: {}", room_id, e);
+                    return (StatusCode::BAD_REQUEST, format!("Invalid chat message: {}", e))
+                        .into_response();
+                }
+            };
             match crate::chat::handlers::chat::put_message(
-                Path(path),
-                parts.headers,
+                Path(room_id),
                 State(state),
-                axum::Json(json)
-            ).await {
-                Ok((h, s)) => (s, h).into_response(),
+                ctx,
+                axum::Json(json),
+            )
+            .await
+            {
+                Ok(resp) => resp.into_response(),
                 Err(c) => c.into_response(),
             }
-        },
-        Err(e) => {
-            warn!("Failed to parse Chat JSON for {}: {}. Fallback to Wiki?", path, e);
-            let body_str = String::from_utf8(bytes.to_vec()).unwrap_or_default();
-            crate::core::pages::handlers::put_wiki_page(
-               Path(path),
-               State(state),
-               parts.headers,
-               body_str
-            ).await
         }
     }
 }
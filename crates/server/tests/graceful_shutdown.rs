@@ -0,0 +1,54 @@
+use axum::{routing::get, Router};
+use local_link_server::core::shutdown::{serve, Shutdown};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Notify;
+
+#[tokio::test]
+async fn test_in_flight_request_completes_before_shutdown() {
+    let started = Arc::new(Notify::new());
+    let notify = started.clone();
+    let app = Router::new().route(
+        "/slow",
+        get(move || {
+            let notify = notify.clone();
+            async move {
+                notify.notify_one();
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                "done"
+            }
+        }),
+    );
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let shutdown = Shutdown::new();
+    let server = tokio::spawn(serve(listener, app, shutdown.clone()));
+
+    let client = tokio::spawn(async move {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    });
+
+    // Equivalent of SIGTERM arriving mid-request
+    started.notified().await;
+    shutdown.trigger();
+
+    let response = client.await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.ends_with("done"), "{}", response);
+
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("server should exit once drained")
+        .unwrap()
+        .unwrap();
+}
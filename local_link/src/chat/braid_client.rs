@@ -17,6 +17,10 @@ pub use crate::chat::{
     Message, BlobRef, ChatSnapshot, ChatSyncStatus, MessageType,
 };
 
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
 /// Simple auth response type
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
 pub struct AuthResponse {
@@ -25,6 +29,12 @@ pub struct AuthResponse {
     pub username: String,
 }
 
+/// Session token as saved between runs
+#[derive(serde::Deserialize, serde::Serialize)]
+struct StoredSession {
+    token: String,
+}
+
 /// Braid update from subscription stream
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct BraidUpdate {
@@ -61,6 +71,8 @@ pub struct ChatManager {
     client: BraidClient,
     pub base_url: String,
     auth_token: Option<String>,
+    /// Where the token is saved so a restart keeps the session
+    session_path: Option<PathBuf>,
 }
 
 impl ChatManager {
@@ -72,11 +84,85 @@ impl ChatManager {
             client,
             base_url,
             auth_token: None,
+            session_path: None,
         })
     }
+
+    /// Save the session token at `path`, restoring one saved by an earlier run
+    pub fn with_session_file(mut self, path: PathBuf) -> Self {
+        if let Ok(json) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<StoredSession>(&json) {
+                Ok(session) => self.auth_token = Some(session.token),
+                Err(e) => warn!("Ignoring unreadable session file {:?}: {}", path, e),
+            }
+        }
+        self.session_path = Some(path);
+        self
+    }
     
     pub fn set_auth_token(&mut self, token: String) {
         self.auth_token = Some(token);
+        self.save_session();
+    }
+
+    pub fn auth_token(&self) -> Option<&str> {
+        self.auth_token.as_deref()
+    }
+
+    /// Forget the session token, e.g. after logging out
+    pub fn clear_auth_token(&mut self) {
+        self.auth_token = None;
+        self.save_session();
+    }
+
+    /// Keep the token from a signup or login response, if it has one
+    pub fn remember_token(&mut self, auth: &serde_json::Value) {
+        if let Some(token) = auth["token"].as_str() {
+            self.set_auth_token(token.to_string());
+        }
+    }
+
+    /// Write the current token to the session file, or remove the file
+    fn save_session(&self) {
+        let Some(path) = &self.session_path else {
+            return;
+        };
+        let result = match &self.auth_token {
+            Some(token) => write_session(path, token),
+            None => match std::fs::remove_file(path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                other => other,
+            },
+        };
+        if let Err(e) = result {
+            warn!("Failed to update session file {:?}: {}", path, e);
+        }
+    }
+
+    /// Log in and keep the session token
+    ///
+    /// Returns the server's response as-is, so a rejected login comes back
+    /// with its error message.
+    pub async fn login(
+        &mut self,
+        email: &str,
+        password: &str,
+    ) -> anyhow::Result<serde_json::Value> {
+        let url = format!("{}/auth/login", self.base_url);
+        let body = serde_json::json!({
+            "email": email,
+            "password": password,
+        });
+
+        let req = BraidRequest::new()
+            .with_method("POST")
+            .with_content_type("application/json")
+            .with_body(body.to_string());
+
+        let resp = self.client.fetch(&url, req).await?;
+        let auth: serde_json::Value = serde_json::from_slice(&resp.body)?;
+        self.remember_token(&auth);
+        Ok(auth)
     }
     
     pub fn client(&self) -> &BraidClient {
@@ -110,11 +196,10 @@ impl ChatManager {
         let resp = self.client.fetch(&url, req).await?;
         
         // Extract version from Braid response headers
-        let version = resp.headers
-            .get("version")
-            .map(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+        let version = resp
+            .version()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
 
         Ok(crate::models::BraidMessage {
             id: uuid::Uuid::new_v4(),
@@ -132,3 +217,115 @@ impl ChatManager {
         Ok(())
     }
 }
+
+/// Write the token readable only by the current user
+fn write_session(path: &Path, token: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string(&StoredSession {
+        token: token.to_string(),
+    })?;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    // `mode` only applies when the file is created; tighten an existing one
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(json.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderMap;
+    use axum::routing::{post, put};
+    use axum::{Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    /// Serve a login endpoint and a chat room that records Authorization headers
+    async fn mock_server() -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+        let seen: Arc<Mutex<Vec<Option<String>>>> = Arc::default();
+        let log = seen.clone();
+        let app = Router::new()
+            .route(
+                "/auth/login",
+                post(|| async {
+                    Json(serde_json::json!({
+                        "token": "token-1",
+                        "user_id": "user-1",
+                        "username": "alice",
+                    }))
+                }),
+            )
+            .route(
+                "/chat/{room}",
+                put(move |headers: HeaderMap| async move {
+                    let auth = headers
+                        .get("authorization")
+                        .and_then(|h| h.to_str().ok())
+                        .map(str::to_string);
+                    log.lock().unwrap().push(auth);
+                    "{}"
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), seen)
+    }
+
+    #[tokio::test]
+    async fn test_login_token_is_sent_and_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = dir.path().join("session.json");
+        let (base_url, seen) = mock_server().await;
+
+        let mut manager = ChatManager::new(base_url.clone())
+            .unwrap()
+            .with_session_file(session.clone());
+        assert_eq!(manager.auth_token(), None);
+        manager.login("alice@example.com", "pw").await.unwrap();
+        manager
+            .send_message("general".into(), "hi".into(), "alice".into())
+            .await
+            .unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![Some("Bearer token-1".to_string())]);
+
+        // A fresh manager picks the token back up
+        let mut restarted = ChatManager::new(base_url.clone())
+            .unwrap()
+            .with_session_file(session.clone());
+        assert_eq!(restarted.auth_token(), Some("token-1"));
+
+        restarted.clear_auth_token();
+        assert!(!session.exists());
+        let after_logout = ChatManager::new(base_url).unwrap().with_session_file(session);
+        assert_eq!(after_logout.auth_token(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_session_file_is_private_even_if_it_existed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let session = dir.path().join("session.json");
+        std::fs::write(&session, "{}").unwrap();
+        std::fs::set_permissions(&session, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_session(&session, "token-1").unwrap();
+        let mode = std::fs::metadata(&session).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,308 @@
+//! Explorer Tree
+//!
+//! Builds the file tree shown in the explorer sidebar. Directories are
+//! tracked by canonical path, so a symlink back to an ancestor is listed
+//! once instead of recursing forever. Large trees can be listed a level at
+//! a time and expanded on demand with [`scan_subtree`].
+//!
+//! Also creates new pages under `local.org/`.
+
+use crate::models::FileNode;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Recursively list `dir`, with paths relative to `folder_root`'s parent
+///
+/// Symlinked entries are skipped entirely unless `follow_symlinks` is set.
+/// `depth` limits how many levels are listed (`Some(1)` returns only the
+/// entries of `dir`); `None` scans the whole tree.
+pub fn scan_dir_helper(
+    dir: &Path,
+    folder_root: &Path,
+    is_network: bool,
+    follow_symlinks: bool,
+    depth: Option<usize>,
+) -> Result<Vec<FileNode>, String> {
+    let mut visited = HashSet::new();
+    if let Ok(canonical) = dir.canonicalize() {
+        visited.insert(canonical);
+    }
+    scan_dir_inner(
+        dir,
+        folder_root,
+        is_network,
+        follow_symlinks,
+        depth,
+        &mut visited,
+    )
+}
+
+/// List the direct children of `relative_path` under `root`
+///
+/// Relative paths in the result stay rooted at `root`, matching the full tree.
+pub fn scan_subtree(
+    root: &Path,
+    relative_path: &str,
+    is_network: bool,
+) -> Result<Vec<FileNode>, String> {
+    let root = root.canonicalize().map_err(|e| e.to_string())?;
+    let dir = braid_common::safe_join(&root, relative_path).map_err(|e| e.to_string())?;
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", relative_path));
+    }
+
+    let top = dir
+        .strip_prefix(&root)
+        .ok()
+        .and_then(|rel| rel.components().next())
+        .ok_or_else(|| format!("Not a subtree of the explorer root: {}", relative_path))?;
+    let folder_root = root.join(top);
+    scan_dir_helper(&dir, &folder_root, is_network, true, Some(1))
+}
+
+fn scan_dir_inner(
+    dir: &Path,
+    folder_root: &Path,
+    is_network: bool,
+    follow_symlinks: bool,
+    depth: Option<usize>,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<Vec<FileNode>, String> {
+    let descend = !matches!(depth, Some(d) if d <= 1);
+    let mut nodes = Vec::new();
+    let entries = std::fs::read_dir(dir).map_err(|e| e.to_string())?;
+
+    for entry in entries.flatten() {
+        let is_symlink = entry.file_type().is_ok_and(|t| t.is_symlink());
+        if is_symlink && !follow_symlinks {
+            continue;
+        }
+
+        let path = entry.path();
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let is_dir = path.is_dir();
+
+        // A directory already in the tree, e.g. reached again via a symlink
+        if is_dir {
+            let Ok(canonical) = path.canonicalize() else {
+                continue;
+            };
+            if !visited.insert(canonical) {
+                continue;
+            }
+        }
+
+        // Root relative path (e.g. braid.org/Welcome.md)
+        let root = folder_root.parent().unwrap_or(folder_root);
+        let Ok(relative_path) = path.strip_prefix(root) else {
+            continue;
+        };
+        let relative_path_str = relative_path.to_string_lossy().to_string();
+
+        let mut node = FileNode {
+            name,
+            is_dir,
+            is_network,
+            relative_path: relative_path_str,
+            full_path: path.to_string_lossy().to_string(),
+            children: Vec::new(),
+            has_children: false,
+        };
+
+        if is_dir && descend {
+            node.children = scan_dir_inner(
+                &path,
+                folder_root,
+                is_network,
+                follow_symlinks,
+                depth.map(|d| d - 1),
+                visited,
+            )?;
+            node.has_children = !node.children.is_empty();
+        } else if is_dir {
+            node.has_children = has_listable_entries(&path, follow_symlinks);
+        }
+
+        nodes.push(node);
+    }
+
+    // Sort directories first, then by name
+    nodes.sort_by(|a, b| {
+        if a.is_dir != b.is_dir {
+            b.is_dir.cmp(&a.is_dir)
+        } else {
+            a.name.cmp(&b.name)
+        }
+    });
+
+    Ok(nodes)
+}
+
+/// File name for a new local page called `name`
+///
+/// `.md` is matched case-insensitively and kept as typed; any other extension
+/// is treated as part of the name, so `notes.txt` becomes `notes.txt.md`.
+pub fn local_page_filename(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        return Err("Page name is empty".to_string());
+    }
+    if name.contains(['/', '\\']) {
+        return Err(format!(
+            "Page name cannot contain path separators: {}",
+            name
+        ));
+    }
+
+    let has_md = Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+    if has_md {
+        Ok(name.to_string())
+    } else {
+        Ok(format!("{}.md", name))
+    }
+}
+
+/// Create an empty page in `dir`, failing if the file already exists
+pub fn create_local_page_in(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let filename = local_page_filename(name)?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create local directory: {}", e))?;
+    let full_path = braid_common::safe_join(dir, &filename).map_err(|e| e.to_string())?;
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&full_path)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => "File already exists".to_string(),
+            _ => format!("Failed to create file: {}", e),
+        })?;
+
+    Ok(full_path)
+}
+
+/// Whether a directory left unexpanded would list anything
+fn has_listable_entries(dir: &Path, follow_symlinks: bool) -> bool {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return false;
+    };
+    entries
+        .flatten()
+        .any(|entry| follow_symlinks || !entry.file_type().is_ok_and(|t| t.is_symlink()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symlink_to_ancestor_terminates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("braid.org");
+        let nested = root.join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(nested.join("page.md"), "hi").unwrap();
+        std::os::unix::fs::symlink(root.join("a"), nested.join("loop")).unwrap();
+
+        let tree = scan_dir_helper(&root, &root, true, true, None).unwrap();
+        assert_eq!(tree.len(), 1);
+        let b = &tree[0].children[0];
+        assert_eq!(b.name, "b");
+        let names: Vec<_> = b.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["page.md"]);
+        assert_eq!(b.children[0].relative_path, "braid.org/a/b/page.md");
+    }
+
+    #[test]
+    fn test_symlinks_skipped_unless_followed() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("braid.org");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("page.md"), "hi").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("linked")).unwrap();
+
+        assert!(scan_dir_helper(&root, &root, true, false, None)
+            .unwrap()
+            .is_empty());
+        let tree = scan_dir_helper(&root, &root, true, true, None).unwrap();
+        assert_eq!(tree[0].name, "linked");
+        assert_eq!(tree[0].children[0].name, "page.md");
+    }
+
+    #[test]
+    fn test_depth_limited_scan_does_not_descend() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("braid.org");
+        std::fs::create_dir_all(root.join("full").join("deeper")).unwrap();
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        std::fs::write(root.join("full").join("page.md"), "hi").unwrap();
+        std::fs::write(root.join("index.md"), "hi").unwrap();
+
+        let tree = scan_dir_helper(&root, &root, true, true, Some(1)).unwrap();
+        let names: Vec<_> = tree.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["empty", "full", "index.md"]);
+        assert!(tree.iter().all(|n| n.children.is_empty()));
+        assert!(!tree[0].has_children);
+        assert!(tree[1].has_children);
+        assert!(!tree[2].has_children);
+
+        let full = scan_dir_helper(&root, &root, true, true, None).unwrap();
+        assert_eq!(full[1].children.len(), 2);
+        assert!(full[1].has_children);
+        assert!(!full[1].children[0].has_children);
+    }
+
+    #[test]
+    fn test_subtree_lists_one_level_with_root_relative_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("braid.org").join("a").join("b")).unwrap();
+        std::fs::write(root.join("braid.org").join("a").join("b").join("x.md"), "").unwrap();
+
+        let nodes = scan_subtree(root, "braid.org/a", true).unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].relative_path, "braid.org/a/b");
+        assert!(nodes[0].has_children);
+        assert!(nodes[0].children.is_empty());
+
+        assert!(scan_subtree(root, "../outside", true).is_err());
+        assert!(scan_subtree(root, "braid.org/a/b/x.md", true).is_err());
+    }
+
+    #[test]
+    fn test_local_page_filename() {
+        assert_eq!(local_page_filename("notes").unwrap(), "notes.md");
+        assert_eq!(local_page_filename("notes.md").unwrap(), "notes.md");
+        assert_eq!(local_page_filename("Notes.MD").unwrap(), "Notes.MD");
+        assert_eq!(local_page_filename("notes.txt").unwrap(), "notes.txt.md");
+        assert!(local_page_filename("a/b").is_err());
+        assert!(local_page_filename("../escape").is_err());
+        assert!(local_page_filename("a\\b").is_err());
+        assert!(local_page_filename("  ").is_err());
+    }
+
+    #[test]
+    fn test_create_local_page_never_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("local.org");
+
+        let path = create_local_page_in(&local, "Notes.MD").unwrap();
+        assert_eq!(path.file_name().unwrap(), "Notes.MD");
+        assert!(path.starts_with(local.canonicalize().unwrap()));
+
+        std::fs::write(&path, "keep me").unwrap();
+        let err = create_local_page_in(&local, "Notes.MD").unwrap_err();
+        assert_eq!(err, "File already exists");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+
+        assert!(create_local_page_in(&local, "sub/page").is_err());
+        assert!(!local.join("sub").exists());
+    }
+}
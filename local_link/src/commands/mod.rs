@@ -2,14 +2,23 @@
 //!
 //! Includes both legacy HTTP commands and new pure Braid protocol commands.
 
+mod explorer;
+mod progress;
+mod reconnect;
+mod upload;
+mod wiki_sync;
+
 // Braid protocol commands - defined directly in this module for Tauri macro compatibility
-use crate::chat::{parse_braid_update, BraidRequest, ChatBraidExt, ChatManager};
+use crate::chat::{BraidRequest, ChatBraidExt, ChatManager};
 use crate::local_sync;
 use crate::models::FileNode;
+use explorer::{create_local_page_in, scan_dir_helper, scan_subtree};
 use std::sync::Arc;
 use tauri::{Emitter, State};
 use tokio::sync::Mutex;
 use tracing::{error, info};
+use upload::upload_file;
+use wiki_sync::{sync_pages, WikiSyncSummary, WIKI_SYNC_PROGRESS_EVENT};
 
 /// App state with LocalLink client
 pub struct LocalLinkAppState {
@@ -18,7 +27,7 @@ pub struct LocalLinkAppState {
 
 // Helper to build authenticated request
 fn auth_req(client: &ChatManager) -> BraidRequest {
-    client.client().with_auth(None)
+    client.client().with_auth(client.auth_token())
 }
 
 #[tauri::command]
@@ -29,7 +38,7 @@ pub async fn signup_braid(
     avatar_blob_hash: Option<String>,
     state: State<'_, LocalLinkAppState>,
 ) -> Result<serde_json::Value, String> {
-    let manager = state.client.lock().await;
+    let mut manager = state.client.lock().await;
     let client = manager.client();
     let base_url = &manager.base_url;
 
@@ -51,6 +60,7 @@ pub async fn signup_braid(
             let body_str = String::from_utf8_lossy(&resp.body);
             let auth: serde_json::Value =
                 serde_json::from_str(&body_str).map_err(|e| e.to_string())?;
+            manager.remember_token(&auth);
             Ok(auth)
         }
         Err(e) => Err(e.to_string()),
@@ -63,29 +73,35 @@ pub async fn login_braid(
     password: String,
     state: State<'_, LocalLinkAppState>,
 ) -> Result<serde_json::Value, String> {
-    let manager = state.client.lock().await;
-    let client = manager.client();
-    let base_url = &manager.base_url;
+    let mut manager = state.client.lock().await;
+    manager
+        .login(&email, &password)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    let url = format!("{}/auth/login", base_url);
-    let body = serde_json::json!({
-        "email": email,
-        "password": password,
-    });
+/// Invalidate the session on the server and forget the stored token
+///
+/// The token is dropped even if the server can't be reached, and logging
+/// out without a session is a no-op.
+#[tauri::command]
+pub async fn logout_braid(state: State<'_, LocalLinkAppState>) -> Result<(), String> {
+    let mut manager = state.client.lock().await;
+    if manager.auth_token().is_none() {
+        return Ok(());
+    }
 
-    let req = BraidRequest::new()
-        .with_method("POST")
-        .with_content_type("application/json")
-        .with_body(body.to_string());
+    let url = format!("{}/auth/logout", manager.base_url);
+    let req = auth_req(&manager).with_method("POST");
+    let result = manager.client().fetch(&url, req).await;
+    manager.clear_auth_token();
 
-    match client.fetch(&url, req).await {
-        Ok(resp) => {
-            let body_str = String::from_utf8_lossy(&resp.body);
-            let auth: serde_json::Value =
-                serde_json::from_str(&body_str).map_err(|e| e.to_string())?;
-            Ok(auth)
-        }
-        Err(e) => Err(e.to_string()),
+    // The local session is gone either way; report if the server's wasn't ended
+    let resp = result.map_err(|e| e.to_string())?;
+    if resp.is_success() {
+        Ok(())
+    } else {
+        Err(format!("Logout failed: HTTP {}", resp.status))
     }
 }
 
@@ -93,16 +109,21 @@ pub async fn login_braid(
 pub async fn send_message_braid(
     conversation_id: String,
     content: String,
+    client_msg_id: Option<String>,
     state: State<'_, LocalLinkAppState>,
 ) -> Result<serde_json::Value, String> {
     let manager = state.client.lock().await;
     let client = manager.client();
     let base_url = &manager.base_url;
 
+    // The client retries with this same body, so every attempt of this send
+    // carries one key and the server stores the message once
+    let client_msg_id = client_msg_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
     let url = format!("{}/chat/{}", base_url, conversation_id);
     let body = serde_json::json!({
         "content": content,
         "message_type": { "type": "text" },
+        "client_msg_id": client_msg_id,
     });
 
     let req = auth_req(&manager)
@@ -156,6 +177,10 @@ pub async fn get_messages_braid(
     }
 }
 
+/// Subscribe to a chat room, forwarding updates as `braid-update`
+///
+/// A dropped subscription is re-established in the background, resuming
+/// from the last version seen; see `reconnect::run_subscription`.
 #[tauri::command]
 pub async fn start_braid_subscription(
     conversation_id: String,
@@ -175,34 +200,32 @@ pub async fn start_braid_subscription(
 
     let req = auth_req(&manager).subscribe().with_heartbeat(30);
 
-    let mut subscription = client
+    let subscription = client
         .subscribe(&url, req)
         .await
         .map_err(|e| format!("Subscribe failed: {}", e))?;
 
     drop(manager);
 
-    tokio::spawn(async move {
-        loop {
-            match subscription.next().await {
-                Some(Ok(update)) => {
-                    if let Some(body) = &update.body {
-                        if let Some(braid_update) = parse_braid_update(body) {
-                            let _ = app_handle.emit("braid-update", braid_update);
-                        }
-                    }
-                }
-                Some(Err(e)) => {
-                    error!("[BraidCommands] Subscription error: {}", e);
-                    let _ = app_handle.emit("braid-error", e.to_string());
-                }
-                None => {
-                    info!("[BraidCommands] Subscription ended");
-                    break;
-                }
+    let manager = state.client.clone();
+    tokio::spawn(reconnect::run_subscription(
+        subscription,
+        move |parents| {
+            let manager = manager.clone();
+            let url = url.clone();
+            async move {
+                // Pick up a token refreshed since the first subscribe
+                let manager = manager.lock().await;
+                let req = auth_req(&manager)
+                    .subscribe()
+                    .with_heartbeat(30)
+                    .with_parents(parents);
+                manager.client().subscribe(&url, req).await
             }
-        }
-    });
+        },
+        app_handle,
+        reconnect::reconnect_policy(),
+    ));
 
     Ok(())
 }
@@ -235,6 +258,8 @@ pub async fn get_sync_status_braid(
     }
 }
 
+/// Update a profile; with `avatar_path`, the image is uploaded first and
+/// becomes the new avatar
 #[tauri::command]
 pub async fn update_profile_braid(
     user_id: String,
@@ -242,12 +267,23 @@ pub async fn update_profile_braid(
     email: Option<String>,
     password: Option<String>,
     avatar_blob_hash: Option<String>,
+    avatar_path: Option<String>,
     state: State<'_, LocalLinkAppState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<serde_json::Value, String> {
     let manager = state.client.lock().await;
     let client = manager.client();
     let base_url = &manager.base_url;
 
+    let avatar_blob_hash = match avatar_path {
+        Some(path) => {
+            let http_client = reqwest::Client::new();
+            let path = std::path::PathBuf::from(path);
+            Some(upload::upload_avatar(&http_client, base_url, &path, app_handle).await?)
+        }
+        None => avatar_blob_hash,
+    };
+
     let url = format!("{}/auth/profile/{}", base_url, user_id);
     let body = serde_json::json!({
         "username": username,
@@ -423,6 +459,36 @@ pub async fn get_conversations_braid(
     }
 }
 
+/// `POST /chat/rooms` with `body`, returning the room the server stored
+async fn create_room(
+    manager: &ChatManager,
+    body: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let url = format!("{}/chat/rooms", manager.base_url);
+    let req = auth_req(manager)
+        .with_method("POST")
+        .with_content_type("application/json")
+        .with_body(body.to_string());
+
+    let resp = manager
+        .client()
+        .fetch(&url, req)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !(200..300).contains(&resp.status) {
+        return Err(format!(
+            "Failed to create room: {} {}",
+            resp.status,
+            String::from_utf8_lossy(&resp.body)
+        ));
+    }
+    serde_json::from_slice(&resp.body).map_err(|e| e.to_string())
+}
+
+/// Create a room with `participant_emails` as members.
+///
+/// The server records the signed-in user as the creator; `sender` is only
+/// part of the UI's call shape.
 #[tauri::command]
 pub async fn create_conversation_braid(
     name: String,
@@ -431,65 +497,113 @@ pub async fn create_conversation_braid(
     sender: String,
     state: State<'_, LocalLinkAppState>,
 ) -> Result<serde_json::Value, String> {
+    let _ = sender;
     let manager = state.client.lock().await;
-    let client = manager.client();
-    let base_url = &manager.base_url;
-
-    // Use UUID for new room ID
-    let room_id = uuid::Uuid::new_v4().to_string();
-    let url = format!("{}/chat/{}", base_url, room_id);
-
-    // Call GET to create the room implicitly (backend creates if missing)
-    match client.get(&url).await {
-        Ok(_) => {
-            // Return constructed room object for UI
-            Ok(serde_json::json!({
-                "id": room_id,
-                "name": name,
-                "is_direct_message": is_direct_message,
-                "participants": participant_emails,
-                "created_by": sender,
-                "created_at": chrono::Utc::now().to_rfc3339()
-            }))
-        }
-        Err(e) => Err(e.to_string()),
-    }
+    create_room(
+        &manager,
+        serde_json::json!({
+            "name": name,
+            "participants": participant_emails,
+            "is_direct_message": is_direct_message,
+        }),
+    )
+    .await
 }
 
+/// Create a room answered by @BraidBot.
 #[tauri::command]
 pub async fn create_ai_chat_braid(
     name: String,
     sender: String,
     state: State<'_, LocalLinkAppState>,
 ) -> Result<serde_json::Value, String> {
+    let _ = sender;
+    let manager = state.client.lock().await;
+    let conversation = create_room(
+        &manager,
+        serde_json::json!({
+            "name": name,
+            "is_ai_chat": true,
+        }),
+    )
+    .await?;
+
+    Ok(serde_json::json!({
+        "conversation": conversation,
+        "admin_token": "dummy_token" // Backward compat for ai.js
+    }))
+}
+
+/// List the files BraidBot can read with `ai read context <file>`.
+#[tauri::command]
+pub async fn list_ai_context_files(
+    state: State<'_, LocalLinkAppState>,
+) -> Result<Vec<serde_json::Value>, String> {
     let manager = state.client.lock().await;
     let client = manager.client();
-    let base_url = &manager.base_url;
+    let url = format!("{}/ai/context", manager.base_url);
 
-    let room_id = uuid::Uuid::new_v4().to_string();
-    let url = format!("{}/chat/{}", base_url, room_id);
+    let resp = client
+        .fetch(&url, auth_req(&manager))
+        .await
+        .map_err(|e| e.to_string())?;
+    let body_str = String::from_utf8_lossy(&resp.body);
+    serde_json::from_str(&body_str).map_err(|e| e.to_string())
+}
 
-    match client.get(&url).await {
-        Ok(_resp) => {
-            // AI Chat doesn't need special handling on creation in pure Braid protocol
-            // The act of talking to @BraidBot! happens in messages.
-            // But we need to return the expected structure.
-            // ai.js expects: conversation object or wrapper
-
-            let conversation = serde_json::json!({
-                "id": room_id,
-                "name": name,
-                "is_direct_message": false,
-                "participants": [], // AI rooms have no human participants initially
-                "created_by": sender,
-            });
+/// Upload (or replace) an AI context file.
+#[tauri::command]
+pub async fn add_ai_context_file(
+    name: String,
+    content: String,
+    state: State<'_, LocalLinkAppState>,
+) -> Result<(), String> {
+    let manager = state.client.lock().await;
+    let client = manager.client();
+    let url = format!("{}/ai/context", manager.base_url);
+    let body = serde_json::json!({ "name": name, "content": content });
 
-            Ok(serde_json::json!({
-                "conversation": conversation,
-                "admin_token": "dummy_token" // Backward compat for ai.js
-            }))
-        }
-        Err(e) => Err(e.to_string()),
+    let req = auth_req(&manager)
+        .with_method("POST")
+        .with_content_type("application/json")
+        .with_body(body.to_string());
+
+    let resp = client.fetch(&url, req).await.map_err(|e| e.to_string())?;
+    if resp.is_success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Adding AI context file failed: HTTP {}: {}",
+            resp.status,
+            String::from_utf8_lossy(&resp.body)
+        ))
+    }
+}
+
+/// Delete an AI context file.
+#[tauri::command]
+pub async fn remove_ai_context_file(
+    name: String,
+    state: State<'_, LocalLinkAppState>,
+) -> Result<(), String> {
+    let manager = state.client.lock().await;
+    let client = manager.client();
+    let url = format!(
+        "{}/ai/context/{}",
+        manager.base_url,
+        urlencoding::encode(&name)
+    );
+    let req = auth_req(&manager).with_method("DELETE");
+
+    let resp = client.fetch(&url, req).await.map_err(|e| e.to_string())?;
+    if resp.is_success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Removing AI context file failed: HTTP {}: {}",
+            resp.status,
+            String::from_utf8_lossy(&resp.body)
+        ))
     }
 }
 
@@ -546,40 +660,31 @@ pub async fn sync_drafts_braid(
 pub async fn upload_file_braid(
     file_path: String,
     state: State<'_, LocalLinkAppState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<serde_json::Value, String> {
     let manager = state.client.lock().await;
     let base_url = &manager.base_url;
 
-    let url = format!("{}/blobs", base_url);
     let path = std::path::PathBuf::from(file_path);
 
-    let file_content = tokio::fs::read(&path).await.map_err(|e| e.to_string())?;
-    let file_name = path
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unnamed");
-
     let http_client = reqwest::Client::new();
-    let form = reqwest::multipart::Form::new().part(
-        "file",
-        reqwest::multipart::Part::bytes(file_content).file_name(file_name.to_string()),
-    );
-
-    let resp = http_client
-        .post(&url)
-        .header("Merge-Type", "diamond")
-        .multipart(form)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+    let (blob_ref, _, _) = upload_file(&http_client, base_url, &path, app_handle).await?;
 
-    if !resp.status().is_success() {
-        return Err(format!("Upload failed: {}", resp.status()));
-    }
+    Ok(blob_ref)
+}
 
-    let blob_ref: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+/// Upload an image for use as an avatar, returning its blob hash
+#[tauri::command]
+pub async fn upload_avatar(
+    file_path: String,
+    state: State<'_, LocalLinkAppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let manager = state.client.lock().await;
+    let path = std::path::PathBuf::from(file_path);
 
-    Ok(blob_ref)
+    let http_client = reqwest::Client::new();
+    upload::upload_avatar(&http_client, &manager.base_url, &path, app_handle).await
 }
 
 #[tauri::command]
@@ -589,44 +694,17 @@ pub async fn send_message_with_file_braid(
     file_path: String,
     _sender: String, // Kept for frontend compat, unused in pure protocol (server infers from token)
     state: State<'_, LocalLinkAppState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<serde_json::Value, String> {
     let manager = state.client.lock().await;
     let base_url = &manager.base_url;
 
     // 1. Upload File
-    let upload_url = format!("{}/blobs", base_url);
     let path = std::path::PathBuf::from(&file_path);
 
-    let file_content = tokio::fs::read(&path).await.map_err(|e| e.to_string())?;
-    let file_name = path
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unnamed")
-        .to_string();
-    let file_size = file_content.len() as u64;
-
     let http_client = reqwest::Client::new();
-    let form = reqwest::multipart::Form::new().part(
-        "file",
-        reqwest::multipart::Part::bytes(file_content).file_name(file_name.clone()),
-    );
-
-    let resp = http_client
-        .post(&upload_url)
-        .header("Merge-Type", "simpleton")
-        .multipart(form)
-        .send()
-        .await
-        .map_err(|e| format!("Upload request failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        return Err(format!("Upload failed: {}", resp.status()));
-    }
-
-    let blob_ref: serde_json::Value = resp
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse blob ref: {}", e))?;
+    let (blob_ref, file_name, file_size) =
+        upload_file(&http_client, base_url, &path, app_handle).await?;
 
     // 2. Send Message with Link
     let chat_url = format!("{}/chat/{}", base_url, conversation_id);
@@ -669,6 +747,7 @@ pub async fn subscribe_braid_mail(
     let manager = state.client.lock().await;
     let client = manager.client().clone();
     let base_url = manager.base_url.clone();
+    let auth = auth_req(&manager);
     drop(manager);
 
     // 1. Tell server to subscribe to external feed
@@ -677,7 +756,8 @@ pub async fn subscribe_braid_mail(
         "feed_url": "https://mail.braid.org/feed"
     });
 
-    let sub_req = BraidRequest::new()
+    let sub_req = auth
+        .clone()
         .with_method("POST")
         .with_content_type("application/json")
         .with_body(sub_body.to_string());
@@ -689,7 +769,7 @@ pub async fn subscribe_braid_mail(
 
     // 2. Start Braid subscription to server's mail feed
     let feed_url = format!("{}/mail/feed", base_url);
-    let feed_req = BraidRequest::new().subscribe().with_heartbeat(30);
+    let feed_req = auth.subscribe().with_heartbeat(30);
 
     let mut subscription = client
         .subscribe(&feed_url, feed_req)
@@ -727,6 +807,52 @@ pub async fn subscribe_braid_mail(
     Ok(serde_json::json!({"status": "subscribed", "message": "Mail subscription active"}))
 }
 
+#[tauri::command]
+pub async fn unsubscribe_braid_mail(
+    state: State<'_, LocalLinkAppState>,
+    clear_cache: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let manager = state.client.lock().await;
+    let client = manager.client();
+    let base_url = &manager.base_url;
+
+    let url = format!("{}/mail/subscribe", base_url);
+    let body = serde_json::json!({
+        "feed_url": "https://mail.braid.org/feed",
+        "clear_cache": clear_cache.unwrap_or(false)
+    });
+
+    let req = auth_req(&manager)
+        .with_method("DELETE")
+        .with_content_type("application/json")
+        .with_body(body.to_string());
+
+    let resp = client.fetch(&url, req).await.map_err(|e| e.to_string())?;
+    let body_str = String::from_utf8_lossy(&resp.body);
+    serde_json::from_str(&body_str).map_err(|e| e.to_string())
+}
+
+/// Ask the server to sync the mail feed now, retrying posts that failed to load
+#[tauri::command]
+pub async fn refresh_braid_mail(state: State<'_, LocalLinkAppState>) -> Result<(), String> {
+    let manager = state.client.lock().await;
+    let client = manager.client();
+    let url = format!("{}/mail/refresh", manager.base_url);
+    let body = serde_json::json!({ "feed_url": "https://mail.braid.org/feed" });
+
+    let req = auth_req(&manager)
+        .with_method("POST")
+        .with_content_type("application/json")
+        .with_body(body.to_string());
+
+    let resp = client.fetch(&url, req).await.map_err(|e| e.to_string())?;
+    if resp.is_success() {
+        Ok(())
+    } else {
+        Err(format!("Mail refresh failed: HTTP {}", resp.status))
+    }
+}
+
 #[tauri::command]
 pub async fn is_braid_mail_subscribed(state: State<'_, LocalLinkAppState>) -> Result<bool, String> {
     let manager = state.client.lock().await;
@@ -735,7 +861,7 @@ pub async fn is_braid_mail_subscribed(state: State<'_, LocalLinkAppState>) -> Re
 
     let url = format!("{}/mail/subscription", base_url);
 
-    match client.get(&url).await {
+    match client.fetch(&url, auth_req(&manager)).await {
         Ok(resp) => {
             let body_str = String::from_utf8_lossy(&resp.body);
             // API returns boolean true/false
@@ -766,7 +892,7 @@ pub async fn set_mail_auth(
         "email": email
     });
 
-    let req = BraidRequest::new()
+    let req = auth_req(&manager)
         .with_method("POST")
         .with_content_type("application/json")
         .with_body(body.to_string());
@@ -777,22 +903,26 @@ pub async fn set_mail_auth(
     }
 }
 
+/// Returns `{ items, total, has_more }` for the requested slice of the feed.
 #[tauri::command]
 pub async fn get_mail_feed(
     state: State<'_, LocalLinkAppState>,
-) -> Result<Vec<serde_json::Value>, String> {
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<serde_json::Value, String> {
     let manager = state.client.lock().await;
     let client = manager.client();
     let base_url = &manager.base_url;
 
-    let url = format!("{}/mail/feed", base_url);
+    let mut url = format!("{}/mail/feed?offset={}", base_url, offset.unwrap_or(0));
+    if let Some(limit) = limit {
+        url.push_str(&format!("&limit={}", limit));
+    }
 
-    match client.get(&url).await {
+    match client.fetch(&url, auth_req(&manager)).await {
         Ok(resp) => {
             let body_str = String::from_utf8_lossy(&resp.body);
-            let items: Vec<serde_json::Value> =
-                serde_json::from_str(&body_str).map_err(|e| e.to_string())?;
-            Ok(items)
+            serde_json::from_str(&body_str).map_err(|e| e.to_string())
         }
         Err(e) => Err(e.to_string()),
     }
@@ -805,7 +935,29 @@ pub async fn get_mail_feed_braid(
     state: State<'_, LocalLinkAppState>,
 ) -> Result<Vec<serde_json::Value>, String> {
     // Reuse the main get_mail_feed logic to ensure hydration
-    get_mail_feed(state).await
+    let page = get_mail_feed(state, None, None).await?;
+    Ok(page["items"].as_array().cloned().unwrap_or_default())
+}
+
+/// Mark one mail item read, or every item if `id` is omitted.
+#[tauri::command]
+pub async fn mark_mail_read(
+    state: State<'_, LocalLinkAppState>,
+    id: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let manager = state.client.lock().await;
+    let client = manager.client();
+    let base_url = &manager.base_url;
+
+    let url = match id {
+        Some(id) => format!("{}/mail/read/{}", base_url, urlencoding::encode(&id)),
+        None => format!("{}/mail/read", base_url),
+    };
+    let req = auth_req(&manager).with_method("PUT");
+
+    let resp = client.fetch(&url, req).await.map_err(|e| e.to_string())?;
+    let body_str = String::from_utf8_lossy(&resp.body);
+    serde_json::from_str(&body_str).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -826,7 +978,7 @@ pub async fn send_mail(
         "to": ["braid@braid.org"],
     });
 
-    let req = BraidRequest::new()
+    let req = auth_req(&manager)
         .with_method("POST")
         .with_content_type("application/json")
         .with_body(body_json.to_string());
@@ -844,8 +996,15 @@ pub async fn send_mail(
 
 // ========== EXPLORER / SYNC EDITOR COMMANDS ==========
 
+/// List the explorer tree for a section
+///
+/// With `depth` set, only that many levels are returned and unexpanded
+/// directories report `has_children`; fetch them with `read_explorer_subtree`.
 #[tauri::command]
-pub async fn get_braid_explorer_tree(section: Option<String>) -> Result<Vec<FileNode>, String> {
+pub async fn get_braid_explorer_tree(
+    section: Option<String>,
+    depth: Option<usize>,
+) -> Result<Vec<FileNode>, String> {
     // Determine root based on section
     let (scan_root, folder_root) = match section.as_deref() {
         Some("braid.org") => (braid_common::braid_org_dir(), braid_common::braid_org_dir()),
@@ -875,6 +1034,8 @@ pub async fn get_braid_explorer_tree(section: Option<String>) -> Result<Vec<File
         &scan_root,
         &folder_root,
         section.as_deref() == Some("braid.org"),
+        true,
+        depth,
     )?;
 
     // Filter for "LinkedLocal" mode (section="local")
@@ -891,8 +1052,23 @@ pub async fn get_braid_explorer_tree(section: Option<String>) -> Result<Vec<File
     Ok(tree)
 }
 
+/// List the direct children of an explorer directory, relative to the braid root
+#[tauri::command]
+pub async fn read_explorer_subtree(relative_path: String) -> Result<Vec<FileNode>, String> {
+    let network_dir = braid_common::braid_org_dir();
+    let is_network = network_dir
+        .file_name()
+        .is_some_and(|name| std::path::Path::new(&relative_path).starts_with(name));
+
+    scan_subtree(&braid_common::braid_root(), &relative_path, is_network)
+}
+
+/// Sync every page in the braid.org index, emitting `wiki-sync-progress`
+///
+/// Pages the daemon already has up to date are skipped, so rerunning after an
+/// interruption only fetches what's missing.
 #[tauri::command]
-pub async fn download_default_wiki() -> Result<(), String> {
+pub async fn download_default_wiki(app: tauri::AppHandle) -> Result<WikiSyncSummary, String> {
     println!("[Command] download_default_wiki called - CHECK YOUR TERMINAL!");
     let braid_org = braid_common::braid_org_dir();
     println!("[Command] Target dir: {:?}", braid_org);
@@ -949,73 +1125,35 @@ pub async fn download_default_wiki() -> Result<(), String> {
     pages_to_sync.dedup();
 
     info!("[Wiki] Syncing {} pages...", pages_to_sync.len());
-    for page_url in pages_to_sync {
-        info!("[Wiki] Syncing page: {}", page_url);
-        // Ensure we use the proper local_sync
-        if let Err(e) = crate::local_sync::sync_page(&page_url).await {
-            tracing::error!("[Wiki] Failed to sync {}: {}", page_url, e);
-        }
-    }
-
-    Ok(())
-}
-
-fn scan_dir_helper(
-    dir: &std::path::Path,
-    folder_root: &std::path::Path,
-    is_network: bool,
-) -> Result<Vec<FileNode>, String> {
-    let mut nodes = Vec::new();
-    let entries = std::fs::read_dir(dir).map_err(|e| e.to_string())?;
-
-    for entry in entries.flatten() {
-        let path = entry.path();
-        let name = path
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
-        let is_dir = path.is_dir();
-
-        // Root relative path (e.g. braid.org/Welcome.md)
-        let root = folder_root.parent().unwrap_or(folder_root);
-        let Ok(relative_path) = path.strip_prefix(root) else {
-            continue;
-        };
-        let relative_path_str = relative_path.to_string_lossy().to_string();
-
-        let mut node = FileNode {
-            name,
-            is_dir,
-            is_network,
-            relative_path: relative_path_str,
-            full_path: path.to_string_lossy().to_string(),
-            children: Vec::new(),
-        };
-
-        if is_dir {
-            node.children = scan_dir_helper(&path, folder_root, is_network)?;
-        }
-
-        nodes.push(node);
-    }
-
-    // Sort directories first, then by name
-    nodes.sort_by(|a, b| {
-        if a.is_dir != b.is_dir {
-            b.is_dir.cmp(&a.is_dir)
-        } else {
-            a.name.cmp(&b.name)
-        }
-    });
+    let summary = sync_pages(
+        pages_to_sync,
+        |url| async move { local_sync::is_up_to_date(&url).await },
+        |url| async move {
+            info!("[Wiki] Syncing page: {}", url);
+            local_sync::sync_page(&url).await.map_err(|e| {
+                error!("[Wiki] Failed to sync {}: {}", url, e);
+                e.to_string()
+            })
+        },
+        |progress| {
+            let _ = app.emit(WIKI_SYNC_PROGRESS_EVENT, progress);
+        },
+    )
+    .await;
+    info!(
+        "[Wiki] Synced {}, skipped {}, failed {}",
+        summary.succeeded.len(),
+        summary.skipped.len(),
+        summary.failed.len()
+    );
 
-    Ok(nodes)
+    Ok(summary)
 }
 
 #[tauri::command]
 pub async fn read_explorer_file(relative_path: String) -> Result<String, String> {
     let root = braid_common::braid_root();
-    let full_path = root.join(&relative_path);
+    let full_path = braid_common::safe_join(&root, &relative_path).map_err(|e| e.to_string())?;
     tracing::info!(
         "[Explorer] Reading file: {:?} (Root: {:?})",
         full_path,
@@ -1027,7 +1165,7 @@ pub async fn read_explorer_file(relative_path: String) -> Result<String, String>
 #[tauri::command]
 pub async fn write_explorer_file(relative_path: String, content: String) -> Result<(), String> {
     let root = braid_common::braid_root();
-    let full_path = root.join(&relative_path);
+    let full_path = braid_common::safe_join(&root, &relative_path).map_err(|e| e.to_string())?;
 
     // Ensure parent dir exists
     if let Some(parent) = full_path.parent() {
@@ -1050,31 +1188,7 @@ pub async fn write_explorer_file(relative_path: String, content: String) -> Resu
 #[tauri::command]
 pub async fn create_local_page(name: String) -> Result<String, String> {
     let root = braid_common::braid_root().join("local.org");
-
-    // Ensure .md extension
-    let filename = if name.to_lowercase().endsWith(".md") {
-        name
-    } else {
-        format!("{}.md", name)
-    };
-
-    let full_path = root.join(filename);
-
-    // Ensure local dir exists
-    if let Err(e) = std::fs::create_dir_all(&root) {
-        return Err(format!("Failed to create local directory: {}", e));
-    }
-
-    // Check if exists
-    if full_path.exists() {
-        return Err("File already exists".to_string());
-    }
-
-    // Create empty file
-    if let Err(e) = std::fs::write(&full_path, "") {
-        return Err(format!("Failed to create file: {}", e));
-    }
-
+    let full_path = create_local_page_in(&root, &name)?;
     Ok(full_path.to_string_lossy().to_string())
 }
 
@@ -1105,6 +1219,16 @@ pub async fn add_braid_sync_subscription(url: String) -> Result<(), String> {
     local_sync::sync_page(&url).await.map_err(|e| e.to_string())
 }
 
+/// Daemon sync status for `url`, or for every synced URL
+#[tauri::command]
+pub async fn get_braid_sync_status(
+    url: Option<String>,
+) -> Result<Vec<local_sync::SyncStatus>, String> {
+    local_sync::sync_status(url.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_sync_editor_page(url: String) -> Result<crate::models::SyncEditorPage, String> {
     local_sync::load_page(&url).await.map_err(|e| e.to_string())
@@ -1124,7 +1248,11 @@ pub async fn setup_user_storage(
 
     // 1. Create directory structure
     braid_common::set_braid_root(root.clone());
-    braid_common::init_structure().map_err(|e| e.to_string())?;
+    // Preflight errors (read-only folder, low disk space) are shown to the user as-is
+    braid_common::init_structure().map_err(|e| {
+        error!("[Storage] Cannot use {:?}: {}", root, e);
+        e.to_string()
+    })?;
 
     // 2. Re-initialize local sync (restarts watcher)
     crate::local_sync::init(root.clone())
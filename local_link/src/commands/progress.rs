@@ -0,0 +1,104 @@
+//! Upload Progress
+//!
+//! Reports how much of a streamed upload has been read so the UI can show
+//! a progress bar instead of freezing on large files.
+
+use futures::{Stream, TryStreamExt};
+use serde::Serialize;
+use tauri::Emitter;
+
+/// Emitted after each chunk is handed to the HTTP client
+pub const UPLOAD_PROGRESS_EVENT: &str = "upload-progress";
+
+/// Emitted once the server has accepted the upload
+pub const UPLOAD_COMPLETE_EVENT: &str = "upload-complete";
+
+/// Payload for both upload events
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UploadProgress {
+    pub file_name: String,
+    pub bytes_sent: u64,
+    pub total: u64,
+}
+
+/// Where progress events go: the app handle at runtime, a recorder in tests
+pub trait ProgressEmitter: Clone + Send + Sync + 'static {
+    fn emit_progress(&self, event: &str, progress: &UploadProgress);
+}
+
+impl<R: tauri::Runtime> ProgressEmitter for tauri::AppHandle<R> {
+    fn emit_progress(&self, event: &str, progress: &UploadProgress) {
+        let _ = self.emit(event, progress);
+    }
+}
+
+/// Pass `chunks` through unchanged, emitting `upload-progress` as each
+/// one is read
+pub fn with_progress<S, E>(
+    chunks: S,
+    emitter: E,
+    file_name: String,
+    total: u64,
+) -> impl Stream<Item = std::io::Result<Vec<u8>>>
+where
+    S: Stream<Item = std::io::Result<Vec<u8>>>,
+    E: ProgressEmitter,
+{
+    let mut bytes_sent = 0u64;
+    chunks.inspect_ok(move |chunk| {
+        bytes_sent += chunk.len() as u64;
+        emitter.emit_progress(
+            UPLOAD_PROGRESS_EVENT,
+            &UploadProgress {
+                file_name: file_name.clone(),
+                bytes_sent,
+                total,
+            },
+        );
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<(String, UploadProgress)>>>);
+
+    impl ProgressEmitter for Recorder {
+        fn emit_progress(&self, event: &str, progress: &UploadProgress) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((event.to_string(), progress.clone()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multi_chunk_upload_reports_increasing_progress() {
+        let chunks: Vec<std::io::Result<Vec<u8>>> =
+            vec![Ok(vec![0; 4]), Ok(vec![0; 4]), Ok(vec![0; 2])];
+        let recorder = Recorder::default();
+
+        let sent: Vec<_> = with_progress(
+            futures::stream::iter(chunks),
+            recorder.clone(),
+            "photo.png".to_string(),
+            10,
+        )
+        .collect()
+        .await;
+        assert_eq!(sent.len(), 3);
+
+        let events = recorder.0.lock().unwrap();
+        let progress: Vec<u64> = events.iter().map(|(_, p)| p.bytes_sent).collect();
+        assert_eq!(progress, vec![4, 8, 10]);
+        assert!(events
+            .iter()
+            .all(|(event, p)| event == UPLOAD_PROGRESS_EVENT
+                && p.total == 10
+                && p.file_name == "photo.png"));
+    }
+}
@@ -0,0 +1,231 @@
+//! Chat Subscription Reconnects
+//!
+//! Keeps a chat subscription alive across dropped connections. When the
+//! stream ends or fails, it re-subscribes with backoff, sending the last
+//! version seen as `Parents` so the server replays anything missed.
+
+use crate::chat::{parse_braid_update, BraidUpdate};
+use braid_http::client::{RetryConfig, RetryDecision, RetryState, Subscription};
+use braid_http::types::Version;
+use futures::Future;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::Emitter;
+use tracing::{info, warn};
+
+/// Emitted for each update received
+pub const BRAID_UPDATE_EVENT: &str = "braid-update";
+
+/// Emitted before each attempt to re-subscribe
+pub const BRAID_RECONNECTING_EVENT: &str = "braid-reconnecting";
+
+/// Emitted once a dropped subscription is back
+pub const BRAID_RECONNECTED_EVENT: &str = "braid-reconnected";
+
+/// Emitted when reconnecting has been given up
+pub const BRAID_ERROR_EVENT: &str = "braid-error";
+
+/// Reconnect attempts before giving up
+pub const MAX_RECONNECT_ATTEMPTS: u32 = 8;
+
+/// Backoff between attempts: one second more each time, up to this
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Payload for `braid-reconnecting`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Reconnecting {
+    pub attempt: u32,
+    pub delay_ms: u64,
+}
+
+/// Where subscription events go: the app handle at runtime, a recorder in tests
+pub trait SubscriptionEvents: Send + Sync + 'static {
+    fn update(&self, update: BraidUpdate);
+    fn reconnecting(&self, reconnecting: &Reconnecting);
+    fn reconnected(&self);
+    fn error(&self, message: String);
+}
+
+impl<R: tauri::Runtime> SubscriptionEvents for tauri::AppHandle<R> {
+    fn update(&self, update: BraidUpdate) {
+        let _ = self.emit(BRAID_UPDATE_EVENT, update);
+    }
+
+    fn reconnecting(&self, reconnecting: &Reconnecting) {
+        let _ = self.emit(BRAID_RECONNECTING_EVENT, reconnecting);
+    }
+
+    fn reconnected(&self) {
+        let _ = self.emit(BRAID_RECONNECTED_EVENT, ());
+    }
+
+    fn error(&self, message: String) {
+        let _ = self.emit(BRAID_ERROR_EVENT, message);
+    }
+}
+
+/// Backoff used for chat subscriptions
+pub fn reconnect_policy() -> RetryConfig {
+    RetryConfig::new()
+        .with_max_retries(MAX_RECONNECT_ATTEMPTS)
+        .with_max_backoff(MAX_RECONNECT_BACKOFF)
+}
+
+/// Forward updates from `subscription` to `events`, re-subscribing through
+/// `subscribe` whenever the stream drops
+///
+/// `subscribe` is given the last version seen (empty if none yet). Attempts
+/// are counted from the last update received, so a server that accepts and
+/// immediately drops the connection still runs out of retries. Returns after
+/// emitting the terminal error.
+pub async fn run_subscription<S, Fut>(
+    mut subscription: Subscription,
+    subscribe: S,
+    events: impl SubscriptionEvents,
+    policy: RetryConfig,
+) where
+    S: Fn(Vec<Version>) -> Fut,
+    Fut: Future<Output = braid_http::Result<Subscription>>,
+{
+    let mut last_version: Vec<Version> = Vec::new();
+    let mut retry = RetryState::new(policy);
+
+    loop {
+        let mut reason = loop {
+            match subscription.next().await {
+                Some(Ok(update)) => {
+                    retry.reset();
+                    if !update.version.is_empty() {
+                        last_version = update.version.clone();
+                    }
+                    if let Some(braid_update) = update.body.as_deref().and_then(parse_braid_update)
+                    {
+                        events.update(braid_update);
+                    }
+                }
+                Some(Err(e)) => break e.to_string(),
+                None => break "Subscription ended".to_string(),
+            }
+        };
+        warn!("[BraidCommands] Subscription dropped: {}", reason);
+
+        loop {
+            let delay = match retry.should_retry_error(false) {
+                RetryDecision::Retry(delay) => delay,
+                RetryDecision::DontRetry => {
+                    events.error(format!("Subscription lost: {}", reason));
+                    return;
+                }
+            };
+            events.reconnecting(&Reconnecting {
+                attempt: retry.attempts,
+                delay_ms: delay.as_millis() as u64,
+            });
+            tokio::time::sleep(delay).await;
+
+            match subscribe(last_version.clone()).await {
+                Ok(resubscribed) => {
+                    info!("[BraidCommands] Resubscribed after {:?}", last_version);
+                    subscription = resubscribed;
+                    events.reconnected();
+                    break;
+                }
+                Err(e) => reason = e.to_string(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use braid_http::types::Update;
+    use braid_http::BraidError;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl SubscriptionEvents for Recorder {
+        fn update(&self, update: BraidUpdate) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("update {}", update.version));
+        }
+
+        fn reconnecting(&self, reconnecting: &Reconnecting) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("reconnecting {}", reconnecting.attempt));
+        }
+
+        fn reconnected(&self) {
+            self.0.lock().unwrap().push("reconnected".to_string());
+        }
+
+        fn error(&self, _message: String) {
+            self.0.lock().unwrap().push("error".to_string());
+        }
+    }
+
+    /// A subscription that delivers one update at `version` and then drops
+    fn dropping_stream(version: &str) -> Subscription {
+        let (tx, rx) = async_channel::unbounded();
+        let body = serde_json::json!({
+            "version": version,
+            "data": {},
+            "type": "message",
+        });
+        let update = Update::snapshot(Version::new(version), body.to_string());
+        tx.try_send(Ok(update)).unwrap();
+        Subscription::new(rx)
+    }
+
+    #[tokio::test]
+    async fn test_dropped_stream_resumes_from_last_version() {
+        let recorder = Recorder::default();
+        let requested = Mutex::new(Vec::new());
+        let policy = RetryConfig::new()
+            .with_max_retries(2)
+            .with_initial_backoff(Duration::from_millis(1))
+            .with_max_backoff(Duration::from_millis(1));
+
+        run_subscription(
+            dropping_stream("v1"),
+            |parents: Vec<Version>| {
+                let mut requested = requested.lock().unwrap();
+                requested.push(parents);
+                let result = match requested.len() {
+                    1 => Err(BraidError::Timeout),
+                    2 => Ok(dropping_stream("v2")),
+                    _ => Err(BraidError::Timeout),
+                };
+                async move { result }
+            },
+            recorder.clone(),
+            policy,
+        )
+        .await;
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                "update v1",
+                "reconnecting 1",
+                "reconnecting 2",
+                "reconnected",
+                "update v2",
+                "reconnecting 1",
+                "reconnecting 2",
+                "error",
+            ]
+        );
+        let v = |s: &str| vec![Version::new(s)];
+        assert_eq!(
+            *requested.lock().unwrap(),
+            [v("v1"), v("v1"), v("v2"), v("v2")]
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! Blob Uploads
+//!
+//! Everything the commands that POST a file to the server's `/blobs`
+//! endpoint have in common: streaming the file, declaring the merge type,
+//! and skipping the upload when the server already has the content.
+
+use super::progress::{with_progress, ProgressEmitter, UploadProgress, UPLOAD_COMPLETE_EVENT};
+use braid_common::BLOB_MERGE_TYPE;
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Chunk size used when streaming uploads from disk.
+const UPLOAD_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Largest avatar image accepted for upload.
+pub const MAX_AVATAR_SIZE: u64 = 2 * 1024 * 1024;
+
+/// `POST {base_url}/blobs` carrying `form`
+pub fn blob_upload_request(client: &Client, base_url: &str, form: Form) -> RequestBuilder {
+    client
+        .post(format!("{}/blobs", base_url))
+        .header("Merge-Type", BLOB_MERGE_TYPE)
+        .multipart(form)
+}
+
+/// Lowercase hex SHA-256 of the file, the key the server's blob store
+/// files the same bytes under.
+pub async fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; UPLOAD_CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Content type the server stored for `hash`, or `None` if it doesn't
+/// have the blob.
+///
+/// Any answer other than 200 counts as missing, so servers without the
+/// `HEAD` route still get a full upload.
+pub async fn existing_blob(
+    client: &Client,
+    base_url: &str,
+    hash: &str,
+) -> Result<Option<String>, String> {
+    let resp = client
+        .head(format!("{}/blobs/{}", base_url, hash))
+        .send()
+        .await
+        .map_err(|e| format!("Blob lookup failed: {}", e))?;
+    if resp.status() != StatusCode::OK {
+        return Ok(None);
+    }
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream");
+    Ok(Some(content_type.to_string()))
+}
+
+/// Upload `path` to `/blobs` unless the server already has its content,
+/// emitting progress and completion to `emitter`.
+///
+/// Returns the blob ref along with the file name and size. A skipped upload
+/// returns a ref built from the `HEAD` response, shaped like the server's.
+pub async fn upload_file(
+    client: &Client,
+    base_url: &str,
+    path: &Path,
+    emitter: impl ProgressEmitter,
+) -> Result<(serde_json::Value, String, u64), String> {
+    let hash = hash_file(path).await.map_err(|e| e.to_string())?;
+    if let Some(content_type) = existing_blob(client, base_url, &hash).await? {
+        let file_name = file_name(path);
+        let file_size = tokio::fs::metadata(path)
+            .await
+            .map_err(|e| e.to_string())?
+            .len();
+        let blob_ref = serde_json::json!({
+            "hash": hash,
+            "content_type": content_type,
+            "filename": file_name,
+            "size": file_size,
+            "inline_data": null,
+        });
+        emit_upload_complete(&emitter, file_name.clone(), file_size);
+        return Ok((blob_ref, file_name, file_size));
+    }
+
+    let (part, file_name, file_size) = streamed_file_part(path, emitter.clone()).await?;
+    let resp = blob_upload_request(client, base_url, Form::new().part("file", part))
+        .send()
+        .await
+        .map_err(|e| format!("Upload request failed: {}", e))?;
+
+    if !resp.status().is_success() {
+        return Err(format!("Upload failed: {}", resp.status()));
+    }
+
+    let blob_ref: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse blob ref: {}", e))?;
+    emit_upload_complete(&emitter, file_name.clone(), file_size);
+    Ok((blob_ref, file_name, file_size))
+}
+
+/// Upload `path` as an avatar and return its blob hash.
+///
+/// The file must be an image, judged by its magic bytes rather than its
+/// extension, and no larger than `MAX_AVATAR_SIZE`. Nothing is sent
+/// otherwise.
+pub async fn upload_avatar(
+    client: &Client,
+    base_url: &str,
+    path: &Path,
+    emitter: impl ProgressEmitter,
+) -> Result<String, String> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| e.to_string())?;
+    let size = file.metadata().await.map_err(|e| e.to_string())?.len();
+    if size > MAX_AVATAR_SIZE {
+        return Err(format!(
+            "Avatar is {} bytes, the limit is {}",
+            size, MAX_AVATAR_SIZE
+        ));
+    }
+    let mut head = [0u8; 64];
+    let n = file.read(&mut head).await.map_err(|e| e.to_string())?;
+    if !infer::is_image(&head[..n]) {
+        return Err(format!("{} is not an image", file_name(path)));
+    }
+
+    let (blob_ref, _, _) = upload_file(client, base_url, path, emitter).await?;
+    blob_ref["hash"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "Upload response has no hash".to_string())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unnamed")
+        .to_string()
+}
+
+/// Build a multipart part that streams `path` from disk in 1 MiB chunks,
+/// reporting progress to `emitter` as chunks are sent.
+///
+/// Returns the part along with the file name and size.
+async fn streamed_file_part(
+    path: &Path,
+    emitter: impl ProgressEmitter,
+) -> Result<(Part, String, u64), String> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| e.to_string())?;
+    let file_size = file.metadata().await.map_err(|e| e.to_string())?.len();
+    let file_name = file_name(path);
+
+    let chunks = futures::stream::try_unfold(file, |mut file| async move {
+        let mut buf = vec![0u8; UPLOAD_CHUNK_SIZE];
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            return Ok::<_, std::io::Error>(None);
+        }
+        buf.truncate(n);
+        Ok(Some((buf, file)))
+    });
+    let chunks = with_progress(chunks, emitter, file_name.clone(), file_size);
+
+    let part = Part::stream_with_length(reqwest::Body::wrap_stream(chunks), file_size)
+        .file_name(file_name.clone());
+    Ok((part, file_name, file_size))
+}
+
+/// Tell the UI an upload finished
+fn emit_upload_complete(emitter: &impl ProgressEmitter, file_name: String, total: u64) {
+    emitter.emit_progress(
+        UPLOAD_COMPLETE_EVENT,
+        &UploadProgress {
+            file_name,
+            bytes_sent: total,
+            total,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path as UrlPath, State};
+    use axum::http::StatusCode as AxumStatus;
+    use axum::routing::{head, post};
+    use axum::{Json, Router};
+    use std::collections::HashSet;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone)]
+    struct Silent;
+
+    impl ProgressEmitter for Silent {
+        fn emit_progress(&self, _event: &str, _progress: &UploadProgress) {}
+    }
+
+    /// Minimal `/blobs` server that records every request it sees
+    #[derive(Clone)]
+    struct MockBlobServer {
+        hash: String,
+        log: Arc<Mutex<Vec<String>>>,
+        stored: Arc<Mutex<HashSet<String>>>,
+    }
+
+    async fn mock_head(
+        State(mock): State<MockBlobServer>,
+        UrlPath(hash): UrlPath<String>,
+    ) -> AxumStatus {
+        mock.log
+            .lock()
+            .unwrap()
+            .push(format!("HEAD /blobs/{}", hash));
+        if mock.stored.lock().unwrap().contains(&hash) {
+            AxumStatus::OK
+        } else {
+            AxumStatus::NOT_FOUND
+        }
+    }
+
+    async fn mock_post(
+        State(mock): State<MockBlobServer>,
+        headers: axum::http::HeaderMap,
+    ) -> Json<serde_json::Value> {
+        assert_eq!(headers["Merge-Type"], BLOB_MERGE_TYPE);
+        mock.log.lock().unwrap().push("POST /blobs".to_string());
+        mock.stored.lock().unwrap().insert(mock.hash.clone());
+        Json(serde_json::json!({
+            "hash": mock.hash,
+            "content_type": "text/plain",
+            "filename": "note.txt",
+            "size": 5,
+            "inline_data": null,
+        }))
+    }
+
+    /// Serve a mock for a blob whose POSTs store `hash`; returns its base URL
+    async fn mock_blob_server(hash: String) -> (String, Arc<Mutex<Vec<String>>>) {
+        let mock = MockBlobServer {
+            hash,
+            log: Arc::default(),
+            stored: Arc::default(),
+        };
+        let log = mock.log.clone();
+        let app = Router::new()
+            .route("/blobs/{hash}", head(mock_head))
+            .route("/blobs", post(mock_post))
+            .with_state(mock);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), log)
+    }
+
+    #[test]
+    fn test_upload_declares_blob_merge_type() {
+        let client = Client::new();
+        let form = Form::new().text("file", "hello");
+        let req = blob_upload_request(&client, "http://localhost:3001", form)
+            .build()
+            .unwrap();
+
+        assert_eq!(req.method(), reqwest::Method::POST);
+        assert_eq!(req.url().as_str(), "http://localhost:3001/blobs");
+        assert_eq!(req.headers()["Merge-Type"], BLOB_MERGE_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_hash_matches_blob_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("note.txt");
+        let data = vec![7u8; UPLOAD_CHUNK_SIZE + 3];
+        std::fs::write(&path, &data).unwrap();
+
+        let store =
+            braid_blob::BlobStore::new(dir.path().join("blobs"), dir.path().join("meta.sqlite"))
+                .await
+                .unwrap();
+        let stored = store.put_with_type(data.into(), None).await.unwrap();
+        assert_eq!(hash_file(&path).await.unwrap(), stored);
+    }
+
+    #[tokio::test]
+    async fn test_second_upload_of_same_bytes_only_sends_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("note.txt");
+        std::fs::write(&path, "hello").unwrap();
+        let hash = hash_file(&path).await.unwrap();
+        let (base_url, log) = mock_blob_server(hash.clone()).await;
+        let client = Client::new();
+
+        let (first, _, _) = upload_file(&client, &base_url, &path, Silent)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::mem::take(&mut *log.lock().unwrap()),
+            vec![format!("HEAD /blobs/{}", hash), "POST /blobs".to_string()]
+        );
+
+        let (second, name, size) = upload_file(&client, &base_url, &path, Silent)
+            .await
+            .unwrap();
+        assert_eq!(*log.lock().unwrap(), vec![format!("HEAD /blobs/{}", hash)]);
+        assert_eq!((name.as_str(), size), ("note.txt", 5));
+        assert_eq!(second["hash"], first["hash"]);
+        assert_eq!(second["filename"], "note.txt");
+        assert_eq!(second["size"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_avatar_upload_returns_hash_and_rejects_non_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("me.png");
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        png.extend_from_slice(&[0; 24]);
+        std::fs::write(&image, &png).unwrap();
+        let hash = hash_file(&image).await.unwrap();
+        let (base_url, log) = mock_blob_server(hash.clone()).await;
+        let client = Client::new();
+
+        let uploaded = upload_avatar(&client, &base_url, &image, Silent)
+            .await
+            .unwrap();
+        assert_eq!(uploaded, hash);
+        assert!(log.lock().unwrap().contains(&"POST /blobs".to_string()));
+        log.lock().unwrap().clear();
+
+        // Named like an image, but the bytes are text
+        let text = dir.path().join("fake.png");
+        std::fs::write(&text, "just some text").unwrap();
+        let err = upload_avatar(&client, &base_url, &text, Silent)
+            .await
+            .unwrap_err();
+        assert!(err.contains("not an image"), "{}", err);
+
+        let huge = dir.path().join("huge.png");
+        png.resize(MAX_AVATAR_SIZE as usize + 1, 0);
+        std::fs::write(&huge, &png).unwrap();
+        let err = upload_avatar(&client, &base_url, &huge, Silent)
+            .await
+            .unwrap_err();
+        assert!(err.contains("limit"), "{}", err);
+
+        assert!(log.lock().unwrap().is_empty());
+    }
+}
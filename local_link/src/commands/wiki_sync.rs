@@ -0,0 +1,148 @@
+//! Wiki Download
+//!
+//! Syncs the pages listed in a wiki index a few at a time, skipping pages the
+//! daemon already has up to date, so an interrupted download picks up where
+//! it stopped.
+
+use futures::{stream, Future, StreamExt};
+use serde::Serialize;
+
+/// Emitted after each page is synced, skipped or has failed
+pub const WIKI_SYNC_PROGRESS_EVENT: &str = "wiki-sync-progress";
+
+/// Pages synced at once
+pub const WIKI_SYNC_CONCURRENCY: usize = 4;
+
+/// Payload for `wiki-sync-progress`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct WikiSyncProgress {
+    pub done: usize,
+    pub total: usize,
+    /// The page that just finished
+    pub current: String,
+}
+
+/// A page that couldn't be synced
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FailedPage {
+    pub url: String,
+    pub error: String,
+}
+
+/// Outcome of a wiki download, each list sorted by URL
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct WikiSyncSummary {
+    pub succeeded: Vec<String>,
+    /// Already up to date, so not synced again
+    pub skipped: Vec<String>,
+    pub failed: Vec<FailedPage>,
+}
+
+enum Outcome {
+    Synced,
+    Skipped,
+    Failed(String),
+}
+
+/// Sync `pages`, at most `WIKI_SYNC_CONCURRENCY` at a time
+///
+/// Pages for which `is_current` returns true are skipped. `on_progress` is
+/// called once per page, in completion order.
+pub async fn sync_pages<C, CFut, S, SFut>(
+    pages: Vec<String>,
+    is_current: C,
+    sync: S,
+    on_progress: impl Fn(&WikiSyncProgress),
+) -> WikiSyncSummary
+where
+    C: Fn(String) -> CFut,
+    CFut: Future<Output = bool>,
+    S: Fn(String) -> SFut,
+    SFut: Future<Output = Result<(), String>>,
+{
+    let total = pages.len();
+    let mut results = stream::iter(pages)
+        .map(|url| {
+            let is_current = &is_current;
+            let sync = &sync;
+            async move {
+                let outcome = if is_current(url.clone()).await {
+                    Outcome::Skipped
+                } else {
+                    match sync(url.clone()).await {
+                        Ok(()) => Outcome::Synced,
+                        Err(e) => Outcome::Failed(e),
+                    }
+                };
+                (url, outcome)
+            }
+        })
+        .buffer_unordered(WIKI_SYNC_CONCURRENCY);
+
+    let mut summary = WikiSyncSummary::default();
+    let mut done = 0;
+    while let Some((url, outcome)) = results.next().await {
+        done += 1;
+        on_progress(&WikiSyncProgress {
+            done,
+            total,
+            current: url.clone(),
+        });
+        match outcome {
+            Outcome::Synced => summary.succeeded.push(url),
+            Outcome::Skipped => summary.skipped.push(url),
+            Outcome::Failed(error) => summary.failed.push(FailedPage { url, error }),
+        }
+    }
+
+    summary.succeeded.sort();
+    summary.skipped.sort();
+    summary.failed.sort_by(|a, b| a.url.cmp(&b.url));
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_progress_fires_per_page_and_failures_are_reported() {
+        let index: Vec<String> = ["a", "b", "c", "d", "e", "f"]
+            .iter()
+            .map(|p| format!("https://braid.org/{}", p))
+            .collect();
+        let events = Mutex::new(Vec::new());
+
+        let summary = sync_pages(
+            index.clone(),
+            |url| async move { url.ends_with("/a") },
+            |url| async move {
+                if url.ends_with("/c") || url.ends_with("/e") {
+                    Err("HTTP 404".to_string())
+                } else {
+                    Ok(())
+                }
+            },
+            |progress| events.lock().unwrap().push(progress.clone()),
+        )
+        .await;
+
+        assert_eq!(summary.skipped, vec![index[0].clone()]);
+        assert_eq!(
+            summary.succeeded,
+            vec![index[1].clone(), index[3].clone(), index[5].clone()]
+        );
+        let failed: Vec<_> = summary.failed.iter().map(|f| f.url.clone()).collect();
+        assert_eq!(failed, vec![index[2].clone(), index[4].clone()]);
+        assert!(summary.failed.iter().all(|f| f.error == "HTTP 404"));
+
+        let events = events.into_inner().unwrap();
+        let done: Vec<_> = events.iter().map(|e| e.done).collect();
+        assert_eq!(done, vec![1, 2, 3, 4, 5, 6]);
+        assert!(events.iter().all(|e| e.total == 6));
+        let mut current: Vec<_> = events.into_iter().map(|e| e.current).collect();
+        current.sort();
+        assert_eq!(current, index);
+    }
+}
@@ -1,6 +1,5 @@
 pub mod ai;
 pub mod auth;
-pub mod braid_mail;
 pub mod chat;
 pub mod local_sync;
 
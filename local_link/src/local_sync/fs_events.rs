@@ -0,0 +1,214 @@
+//! Filesystem Update Events
+//!
+//! Turns raw `notify` events into the `fs-change` payload sent to the UI,
+//! alongside the original `fs-update` event carrying just the path.
+//! Events are coalesced per path first, so one editor save is one update.
+
+use braid_core::fs::debouncer::{ChangeKind, CoalescedChange, RawChange};
+use notify::event::{CreateKind, EventKind, ModifyKind, RemoveKind, RenameMode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Tauri event carrying an [`FsUpdate`]
+pub const FS_CHANGE_EVENT: &str = "fs-change";
+
+/// Tauri event carrying only the relative path string
+pub const FS_UPDATE_EVENT: &str = "fs-update";
+
+/// Events for a path are merged until it has been quiet this long
+pub const FS_UPDATE_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// What happened to a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FsChangeKind {
+    Create,
+    Modify,
+    Remove,
+}
+
+impl FsChangeKind {
+    /// Classify a `notify` event, or `None` for events the UI doesn't care about
+    ///
+    /// The two halves of a rename are reported as a remove and a create.
+    pub fn from_event_kind(kind: &EventKind) -> Option<Self> {
+        match kind {
+            EventKind::Create(_) => Some(Self::Create),
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => Some(Self::Remove),
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => Some(Self::Create),
+            EventKind::Modify(_) => Some(Self::Modify),
+            EventKind::Remove(_) => Some(Self::Remove),
+            EventKind::Access(_) | EventKind::Any | EventKind::Other => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Modify => "modify",
+            Self::Remove => "remove",
+        }
+    }
+}
+
+/// Payload of the `fs-change` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsUpdate {
+    /// Path relative to the watched root
+    pub path: String,
+    pub kind: FsChangeKind,
+    pub is_dir: bool,
+}
+
+/// Whether `path` is a directory, trusting the event kind when the path is gone
+pub fn event_is_dir(kind: &EventKind, path: &Path) -> bool {
+    match kind {
+        EventKind::Create(CreateKind::Folder) | EventKind::Remove(RemoveKind::Folder) => true,
+        EventKind::Create(CreateKind::File) | EventKind::Remove(RemoveKind::File) => false,
+        _ => path.is_dir(),
+    }
+}
+
+/// Coalescer input for a `notify` event, keeping both sides of a rename
+pub fn raw_change(kind: &EventKind, path: &Path) -> Option<RawChange> {
+    match kind {
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => Some(RawChange::RenameFrom),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => Some(RawChange::RenameTo),
+        EventKind::Modify(ModifyKind::Name(_)) if path.exists() => Some(RawChange::RenameTo),
+        EventKind::Modify(ModifyKind::Name(_)) => Some(RawChange::RenameFrom),
+        _ => match FsChangeKind::from_event_kind(kind)? {
+            FsChangeKind::Create => Some(RawChange::Create),
+            FsChangeKind::Modify => Some(RawChange::Modify),
+            FsChangeKind::Remove => Some(RawChange::Remove),
+        },
+    }
+}
+
+/// UI updates for a coalesced change; a move is a remove and a create
+///
+/// `dir_hints` holds [`event_is_dir`] from the raw events, since a removed
+/// path can no longer be inspected.
+pub fn updates_for(
+    change: CoalescedChange,
+    dir_hints: &mut HashMap<PathBuf, bool>,
+) -> Vec<FsUpdate> {
+    let mut update = |path: PathBuf, kind: FsChangeKind| FsUpdate {
+        is_dir: dir_hints.remove(&path).unwrap_or(false),
+        path: path.to_string_lossy().to_string(),
+        kind,
+    };
+
+    match change.kind {
+        ChangeKind::Created => vec![update(change.path, FsChangeKind::Create)],
+        ChangeKind::Modified => vec![update(change.path, FsChangeKind::Modify)],
+        ChangeKind::Removed => vec![update(change.path, FsChangeKind::Remove)],
+        ChangeKind::Moved { from } => vec![
+            update(from, FsChangeKind::Remove),
+            update(change.path, FsChangeKind::Create),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, DataChange, MetadataKind};
+
+    #[test]
+    fn test_event_kind_mapping() {
+        let cases = [
+            (EventKind::Create(CreateKind::File), Some("create")),
+            (EventKind::Create(CreateKind::Folder), Some("create")),
+            (EventKind::Create(CreateKind::Any), Some("create")),
+            (
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                Some("modify"),
+            ),
+            (
+                EventKind::Modify(ModifyKind::Metadata(MetadataKind::Any)),
+                Some("modify"),
+            ),
+            (EventKind::Modify(ModifyKind::Any), Some("modify")),
+            (
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                Some("modify"),
+            ),
+            (
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)),
+                Some("remove"),
+            ),
+            (
+                EventKind::Modify(ModifyKind::Name(RenameMode::To)),
+                Some("create"),
+            ),
+            (EventKind::Remove(RemoveKind::File), Some("remove")),
+            (EventKind::Remove(RemoveKind::Any), Some("remove")),
+            (EventKind::Access(AccessKind::Any), None),
+            (EventKind::Any, None),
+            (EventKind::Other, None),
+        ];
+
+        for (event_kind, expected) in cases {
+            let kind = FsChangeKind::from_event_kind(&event_kind);
+            assert_eq!(kind.map(|k| k.as_str()), expected, "{:?}", event_kind);
+        }
+    }
+
+    #[test]
+    fn test_payload_serializes_kind_as_string() {
+        let update = FsUpdate {
+            path: "braid.org/index.md".to_string(),
+            kind: FsChangeKind::Remove,
+            is_dir: false,
+        };
+        let json = serde_json::to_value(&update).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "path": "braid.org/index.md", "kind": "remove", "is_dir": false })
+        );
+    }
+
+    #[test]
+    fn test_move_becomes_remove_and_create() {
+        let mut dir_hints = HashMap::from([
+            (PathBuf::from("local.org/old"), true),
+            (PathBuf::from("local.org/new"), true),
+        ]);
+        let change = CoalescedChange {
+            path: PathBuf::from("local.org/new"),
+            kind: ChangeKind::Moved {
+                from: PathBuf::from("local.org/old"),
+            },
+        };
+
+        let updates = updates_for(change, &mut dir_hints);
+        let summary: Vec<_> = updates
+            .iter()
+            .map(|u| (u.path.as_str(), u.kind.as_str(), u.is_dir))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("local.org/old", "remove", true),
+                ("local.org/new", "create", true)
+            ]
+        );
+        assert!(dir_hints.is_empty());
+    }
+
+    #[test]
+    fn test_rename_halves_map_to_rename_changes() {
+        let path = Path::new("/nonexistent/page.md");
+        let from = EventKind::Modify(ModifyKind::Name(RenameMode::From));
+        let to = EventKind::Modify(ModifyKind::Name(RenameMode::To));
+        assert_eq!(raw_change(&from, path), Some(RawChange::RenameFrom));
+        assert_eq!(raw_change(&to, path), Some(RawChange::RenameTo));
+        assert_eq!(
+            raw_change(&EventKind::Modify(ModifyKind::Name(RenameMode::Any)), path),
+            Some(RawChange::RenameFrom)
+        );
+        assert_eq!(raw_change(&EventKind::Access(AccessKind::Any), path), None);
+    }
+}
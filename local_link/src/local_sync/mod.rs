@@ -3,17 +3,24 @@
 //! Uses braid-http directly for Braid protocol operations.
 //! Daemon control API uses reqwest (non-Braid REST API).
 
+mod fs_events;
+
+pub use braid_core::fs::api::SyncStatus;
 pub use braid_http::{BraidClient, BraidRequest};
+pub use fs_events::{FsChangeKind, FsUpdate, FS_CHANGE_EVENT, FS_UPDATE_EVENT};
 
 use anyhow::Result;
+use braid_core::fs::debouncer::DebouncedSyncManager;
 use notify::{RecursiveMode, Watcher};
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::mpsc::RecvTimeoutError;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::{watch, RwLock};
+use tokio::time::Instant;
 use tracing::{error, info};
 
 /// Daemon control URL (non-Braid REST API)
@@ -99,35 +106,65 @@ async fn spawn_filesystem_watcher(
     watcher.watch(&watch_root, RecursiveMode::Recursive)?;
 
     tokio::spawn(async move {
-        for file_event in file_event_rx {
-            match file_event {
-                Ok(event) => {
-                    let is_interesting =
-                        event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove();
-
-                    if !is_interesting {
-                        continue;
-                    }
+        let debouncer = DebouncedSyncManager::events_only(fs_events::FS_UPDATE_DEBOUNCE);
+        let mut dir_hints = HashMap::new();
 
-                    let maybe_handle = app_handle_rx.borrow().clone();
-                    let Some(tauri_handle) = maybe_handle else {
-                        continue;
-                    };
-
-                    for changed_path in event.paths {
+        loop {
+            let received = match debouncer.next_change_deadline() {
+                Some(deadline) => {
+                    file_event_rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => file_event_rx
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match received {
+                Ok(Ok(event)) => {
+                    for changed_path in &event.paths {
                         let Ok(relative_path) = changed_path.strip_prefix(&watch_root) else {
                             continue;
                         };
-
-                        let relative_path_str = relative_path.to_string_lossy().to_string();
-                        info!(path = %relative_path_str, "Filesystem change detected");
-
-                        let _ = tauri_handle.emit("fs-update", relative_path_str);
+                        let Some(raw) = fs_events::raw_change(&event.kind, changed_path) else {
+                            continue;
+                        };
+                        dir_hints.insert(
+                            relative_path.to_path_buf(),
+                            fs_events::event_is_dir(&event.kind, changed_path),
+                        );
+                        debouncer.push_change(relative_path, raw, Instant::now());
                     }
                 }
-                Err(watch_error) => {
+                Ok(Err(watch_error)) => {
                     error!(error = ?watch_error, "Filesystem watcher error");
                 }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            let changes = debouncer.drain_changes(Instant::now());
+            if changes.is_empty() {
+                continue;
+            }
+
+            let maybe_handle = app_handle_rx.borrow().clone();
+            let Some(tauri_handle) = maybe_handle else {
+                dir_hints.clear();
+                continue;
+            };
+
+            for update in changes
+                .into_iter()
+                .flat_map(|change| fs_events::updates_for(change, &mut dir_hints))
+            {
+                info!(
+                    path = %update.path,
+                    kind = update.kind.as_str(),
+                    "Filesystem change detected"
+                );
+
+                let _ = tauri_handle.emit(FS_UPDATE_EVENT, update.path.clone());
+                let _ = tauri_handle.emit(FS_CHANGE_EVENT, update);
             }
         }
         drop(watcher);
@@ -336,8 +373,41 @@ pub async fn sync_page(url: &str) -> Result<()> {
         req = req.header("Cookie", cookie);
     }
 
-    let _ = req.send().await?;
-    Ok(())
+    let status_json: serde_json::Value = req.send().await?.json().await?;
+    match status_json["status"].as_str() {
+        Some("ok") => Ok(()),
+        _ => anyhow::bail!("Sync failed: {}", status_json["message"]),
+    }
+}
+
+/// Whether the daemon already keeps `url` subscribed with nothing outstanding
+pub async fn is_up_to_date(url: &str) -> bool {
+    match sync_status(Some(url)).await {
+        Ok(statuses) => statuses.first().is_some_and(|s| {
+            s.subscribed && s.last_synced.is_some() && !s.pending && s.last_error.is_none()
+        }),
+        Err(_) => false,
+    }
+}
+
+/// Sync status of one URL, or of every synced URL when `url` is `None`
+pub async fn sync_status(url: Option<&str>) -> Result<Vec<SyncStatus>> {
+    let endpoint = match url {
+        Some(url) => format!("{}/api/status?url={}", DAEMON_URL, urlencoding::encode(url)),
+        None => format!("{}/api/status", DAEMON_URL),
+    };
+    let client = reqwest::Client::new();
+
+    let resp = client.get(&endpoint).send().await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Status request failed: {}", resp.status());
+    }
+
+    if url.is_some() {
+        Ok(vec![resp.json().await?])
+    } else {
+        Ok(resp.json().await?)
+    }
 }
 
 /// Probe URL for auth
@@ -50,8 +50,13 @@ fn main() {
         let chat_server_url =
             env::var("CHAT_SERVER_URL").unwrap_or_else(|_| "http://localhost:3001".to_string());
 
-        let chat_manager =
+        let mut chat_manager =
             ChatManager::new(chat_server_url).expect("Failed to initialize ChatManager");
+        // Keep the login across restarts
+        if let Some(config_dir) = dirs::config_dir() {
+            chat_manager =
+                chat_manager.with_session_file(config_dir.join("local_link").join("session.json"));
+        }
 
         // info!("[App] LocalLinkClient initialized - Using PURE BRAID PROTOCOL (NO SSE)");
 
@@ -113,6 +118,7 @@ fn main() {
                 // PURE BRAID COMMANDS (NO SSE!)
                 commands::signup_braid,
                 commands::login_braid,
+                commands::logout_braid,
                 commands::send_friend_request_braid,
                 commands::get_pending_requests_braid,
                 commands::respond_to_request_braid,
@@ -120,6 +126,9 @@ fn main() {
                 commands::get_conversations_braid,
                 commands::create_conversation_braid,
                 commands::create_ai_chat_braid,
+                commands::list_ai_context_files,
+                commands::add_ai_context_file,
+                commands::remove_ai_context_file,
                 commands::send_message_braid,
                 commands::get_messages_braid,
                 commands::start_braid_subscription,
@@ -127,21 +136,27 @@ fn main() {
                 commands::sync_drafts_braid,
                 commands::get_sync_status_braid,
                 commands::upload_file_braid,
+                commands::upload_avatar,
                 commands::send_message_with_file_braid,
                 // MAIL/FEED COMMANDS
                 commands::subscribe_braid_mail,
+                commands::unsubscribe_braid_mail,
                 commands::is_braid_mail_subscribed,
+                commands::refresh_braid_mail,
                 commands::set_mail_auth,
                 commands::get_mail_feed,
                 commands::get_mail_feed_braid,
+                commands::mark_mail_read,
                 commands::send_mail,
                 // EXPLORER / SYNC EDITOR COMMANDS
                 commands::get_braid_explorer_tree,
+                commands::read_explorer_subtree,
                 commands::read_explorer_file,
                 commands::write_explorer_file,
                 commands::read_sync_editor_file,
                 commands::set_sync_editor_cookie,
                 commands::add_braid_sync_subscription,
+                commands::get_braid_sync_status,
                 commands::get_sync_editor_page,
                 commands::setup_user_storage,
                 commands::get_default_storage_base,
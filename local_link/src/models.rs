@@ -28,17 +28,6 @@ pub struct BraidMessage {
     pub version: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MailPost {
-    pub url: String,
-    pub date: Option<u64>,
-    pub from: Option<Vec<String>>,
-    pub to: Option<Vec<String>>,
-    pub subject: Option<String>,
-    pub body: Option<String>,
-    pub version: Option<String>,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncEditorPage {
     pub url: String,
@@ -54,6 +43,9 @@ pub struct FileNode {
     pub relative_path: String,
     pub full_path: String,
     pub children: Vec<FileNode>,
+    /// Whether a directory has entries, even when `children` was not loaded
+    #[serde(default)]
+    pub has_children: bool,
 }
 
 /// Type of real-time event (following xfmail guidance)
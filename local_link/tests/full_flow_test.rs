@@ -179,7 +179,7 @@ async fn setup_test_db() -> anyhow::Result<sqlx::SqlitePool> {
     // Use a temp file database so all connections share the same data
     let temp_dir = std::env::temp_dir();
     let db_path = temp_dir.join(format!("xf_test_{}.db", uuid::Uuid::new_v4()));
-    let pool = sqlx::sqlite::SqlitePool::connect(&format!("sqlite:{}?mode=rwc", db_path.display())).await?;
+    let pool = sqlx::sqlite::SqlitePool::connect(&format!("{}?mode=rwc", braid_common::sqlite_url(&db_path))).await?;
     
     // Create tables
     sqlx::query(
@@ -380,8 +380,13 @@ export function initAuth(onLoginSuccess) {
     }
 
     if (logoutBtn) {
-        logoutBtn.addEventListener('click', () => {
+        logoutBtn.addEventListener('click', async () => {
             showToast("Logging out...", "info");
+            try {
+                await invoke('logout_braid');
+            } catch (e) {
+                console.warn("[Auth] Server logout failed:", e);
+            }
             setTimeout(() => {
                 window.location.reload();
             }, 1000);
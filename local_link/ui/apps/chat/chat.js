@@ -350,10 +350,21 @@ async function startBraidSubscription(conversationId) {
         await invoke('start_braid_subscription', { conversationId });
         
         // Listen for Braid updates from Rust
-        chatSubscriptionUnlisten = await window.__TAURI__.event.listen('braid-update', (event) => {
-            const update = event.payload;
-            handleBraidUpdate(update);
-        });
+        const { listen } = window.__TAURI__.event;
+        const unlisteners = await Promise.all([
+            listen('braid-update', (event) => {
+                const update = event.payload;
+                handleBraidUpdate(update);
+            }),
+            // The Rust side re-subscribes on its own after a drop
+            listen('braid-reconnecting', () => renderSyncStatus({ status: 'syncing' })),
+            listen('braid-reconnected', () => renderSyncStatus({ status: 'connected' })),
+            listen('braid-error', (event) => {
+                console.error('[Chat] Braid subscription lost:', event.payload);
+                renderSyncStatus({ status: 'offline' });
+            }),
+        ]);
+        chatSubscriptionUnlisten = () => unlisteners.forEach((unlisten) => unlisten());
 
         chatReconnectAttempts = 0;
         renderSyncStatus({ status: 'connected' });
@@ -427,7 +438,8 @@ export function renderMessage(msg) {
     if (!msgList) return;
 
     const isBot = msg.sender === "@BraidBot" || msg.sender === "BraidBot";
-    const isSent = msg.sender === window.currentUser?.email || 
+    const isSent = msg.sender === window.currentUser?.user_id ||
+                   msg.sender === window.currentUser?.email || 
                    msg.sender === window.currentUser?.username || 
                    msg.sender === "current_user";
 
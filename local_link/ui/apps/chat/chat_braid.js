@@ -182,7 +182,8 @@ export function renderMessage(msg) {
     if (!msgList) return;
 
     const isBot = msg.sender === "@BraidBot";
-    const isSent = msg.sender === window.currentUser?.email;
+    const isSent = msg.sender === window.currentUser?.user_id ||
+                   msg.sender === window.currentUser?.email;
 
     const bubble = document.createElement('div');
     bubble.className = `chat-bubble ${isSent ? 'sent' : 'received'} ${isBot ? 'ai' : ''}`;
@@ -1,4 +1,4 @@
-import { showToast, invoke } from '../shared/utils.js';
+import { showToast, invoke, listen } from '../shared/utils.js';
 import { setupQuill, setupExplorerResizer } from './editor.js';
 import { simpleton_client } from '../../lib/simpleton-client.js';
 
@@ -86,16 +86,27 @@ export function renderExplorerTree(nodes, container, level = 0, section = null)
                     console.log("[Explorer] Download Wiki button clicked");
                     downloadBtn.disabled = true;
                     downloadBtn.textContent = "Downloading...";
+                    const unlisten = await listen('wiki-sync-progress', (event) => {
+                        const { done, total } = event.payload;
+                        downloadBtn.textContent = `Downloading ${done}/${total}...`;
+                    });
                     try {
                         console.log("[Explorer] Invoking download_default_wiki...");
-                        await invoke('download_default_wiki');
+                        const summary = await invoke('download_default_wiki');
                         console.log("[Explorer] Download command matched. Reloading tree...");
-                        showToast("Wiki downloaded!", "success");
+                        if (summary.failed.length > 0) {
+                            console.warn("[Explorer] Failed wiki pages:", summary.failed);
+                            showToast(`Wiki downloaded, ${summary.failed.length} page(s) failed`, "error");
+                        } else {
+                            showToast("Wiki downloaded!", "success");
+                        }
                         loadExplorerTree(container.id, section);
                     } catch (e) {
                         showToast("Download failed: " + e, "error");
                         downloadBtn.disabled = false;
                         downloadBtn.textContent = "Try Again";
+                    } finally {
+                        unlisten();
                     }
                 });
             }
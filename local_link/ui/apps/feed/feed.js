@@ -177,7 +177,8 @@ export async function loadMailFeed() {
     try {
 
         // We only need one fetch now as both commands route to the local hydrated server
-        const posts = await invoke('get_mail_feed').catch(e => { console.error("Feed error:", e); return []; });
+        const page = await invoke('get_mail_feed').catch(e => { console.error("Feed error:", e); return null; });
+        const posts = page?.items || [];
 
         if (posts.length === 0 && isSubscribed) {
             // If empty but subscribed, it might be syncing. Retry aggressively.
@@ -297,8 +298,16 @@ function renderMailFeed(posts) {
         const subject = post.subject || (post.url ? `Post ${post.url.split('/').pop()}` : '(No Subject)');
 
         item.style.animationDelay = `${index * 0.05}s`;
+        // Posts the server couldn't hydrate carry an error instead of content
+        const failed = post.error
+            ? `<div class="feed-card-error">
+                <span>Failed to load</span>
+                <button class="feed-retry-btn">Retry</button>
+            </div>`
+            : '';
         item.innerHTML = `
             <div class="feed-card-title">${subject}</div>
+            ${failed}
             <div class="feed-card-footer">
                 <div class="feed-author">
                     <div class="profile-icon">
@@ -309,12 +318,27 @@ function renderMailFeed(posts) {
             </div>
         `;
         item.addEventListener('click', () => selectMailItem(index, item));
+        item.querySelector('.feed-retry-btn')?.addEventListener('click', (e) => {
+            e.stopPropagation();
+            retryFailedPosts();
+        });
         feedContent.appendChild(item);
     });
 
     if (posts.length > 0) selectMailItem(0, feedContent.querySelector('.mail-item'));
 }
 
+// Ask the server to sync again; it re-fetches failed posts and the
+// resulting mail-update (or this reload) picks them up
+async function retryFailedPosts() {
+    try {
+        await invoke('refresh_braid_mail');
+        setTimeout(loadMailFeed, 1000);
+    } catch (e) {
+        showToast(`Retry failed: ${e}`, 'error');
+    }
+}
+
 // Helper for robust sender extraction
 function getSafeFrom(post) {
     let from = post.from || 'Anonymous';
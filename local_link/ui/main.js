@@ -361,8 +361,9 @@ function setupRealtimeListeners() {
         }
     });
 
-    listen('fs-update', async (event) => {
-        const changedPath = event.payload;
+    listen('fs-change', async (event) => {
+        const { path: changedPath, kind } = event.payload;
+        if (kind === 'remove') return;
         if (window.activeNode && (window.activeNode.relative_path === changedPath || window.activeNode.relative_path.endsWith(changedPath))) {
             console.log("Active file updated on disk. Fetching...");
             try {